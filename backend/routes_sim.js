@@ -1,6 +1,84 @@
 // Local files
-const { runSimulation } = require('./sim/des/engine');
-const { runMonteCarlo } = require('./sim/monte/engine');
+const { runSimulation, runSimulationSequence, replayAndVerify, previewDemand, sampleDistribution, summarizeScenario, summarizeResults, applyScenarioPatch } = require('./sim/des/engine');
+const { runMonteCarlo, runMonteCarloSequence } = require('./sim/monte/engine');
+const { compileThresholds } = require('./sim/monte/thresholds');
+const { compileInputUncertainty, validateInputSampling } = require('./sim/monte/uncertainty');
+const { compileInputOutputAnalysis } = require('./sim/monte/analysis');
+const { compileExtractors, compileExtremeMetrics } = require('./sim/monte/extractors');
+const { validateDemandBootstrap } = require('./sim/monte/bootstrap');
+const { compileStratifyBy } = require('./sim/monte/strata');
+const { compareResults, validateCompareOptions } = require('./sim/monte/compare');
+const { runSensitivity, compileFactors } = require('./sim/monte/sensitivity');
+const { isLimitError } = require('./sim/des/helpers/limits');
+const { isOutputSizeError } = require('./sim/des/helpers/outputsize');
+const { isScenarioError, scenarioError } = require('./sim/des/helpers/validation');
+const { getEngineStatus, isBusyError } = require('./sim/monte/slots');
+
+// Error response for a failed run; an invalid scenario is reported as 400 with the offending path and
+// a tripped watchdog limit (or a result too large to send even with its bulky parts removed) as 422 with its details
+// Monte Carlo failures also carry the failing iteration or the collected iteration_errors; a run rejected
+// because the engine is busy (onBusy: 'reject') is reported as 503
+function sendRunError(res, error) {
+  const iterationDetails = {
+    iteration: error.iteration,
+    iteration_errors: error.iteration_errors
+  };
+  if (isBusyError(error)) {
+    return res.status(503).json({ ok: false, error: error.message, code: error.code, running: error.running, queued: error.queued });
+  }
+  if (isScenarioError(error)) {
+    return res.status(400).json({ ok: false, error: error.message, code: error.code, path: error.path });
+  }
+  if (isLimitError(error)) {
+    return res.status(422).json({
+      ok: false,
+      error: error.message,
+      limit: error.limit,
+      max: error.max,
+      counts: error.counts,
+      ...iterationDetails
+    });
+  }
+  if (isOutputSizeError(error)) {
+    return res.status(422).json({ ok: false, error: error.message, code: error.code, estimated_bytes: error.estimated_bytes, max_bytes: error.max_bytes });
+  }
+  return res.status(500).json({ ok: false, error: error.message, ...iterationDetails });
+}
+
+// Log the digest of what a run was submitted with, so support can see it at a glance
+// An invalid scenario is left to the run itself to report
+function logRunSummary(label, scenario, state, overrides) {
+  try {
+    console.log(`${label} starting:\n${summarizeScenario(scenario, { state, overrides })}`);
+  } catch (error) {
+    console.log(`${label} starting (scenario summary unavailable: ${error.message})`);
+  }
+}
+
+// DES settings a run request carries, shared by /run_des and /run_des_stream so the routes accept the same fields
+function desSettingsFromBody(body) {
+  return {
+    state: body.state,
+    overrides: (body.overrides && typeof body.overrides === 'object') ? body.overrides : null,
+    missionDetail: body.missionDetail === true,
+    emitResourceLevels: body.emitResourceLevels === true,
+    hourlyProfile: body.hourlyProfile === true,
+    sortedOutput: body.sortedOutput === true,
+    seed: Number.isInteger(body.seed) ? body.seed : undefined,
+    limits: (body.limits && typeof body.limits === 'object') ? body.limits : undefined,
+    resourceHoldModel: typeof body.resourceHoldModel === 'string' ? body.resourceHoldModel : undefined,
+    timelineRejectionSampling: typeof body.timelineRejectionSampling === 'number' ? body.timelineRejectionSampling : undefined,
+    peakWindowHours: typeof body.peakWindowHours === 'number' ? body.peakWindowHours : undefined,
+    rejectionDensityBucketHours: typeof body.rejectionDensityBucketHours === 'number' ? body.rejectionDensityBucketHours : undefined,
+    mode: typeof body.mode === 'string' ? body.mode : undefined,
+    trace: (body.trace !== undefined && body.trace !== null) ? body.trace : undefined,
+    echoConfig: typeof body.echoConfig === 'boolean' ? body.echoConfig : undefined,
+    maxOutputBytes: (typeof body.maxOutputBytes === 'number' || body.maxOutputBytes === null) ? body.maxOutputBytes : undefined,
+    priorityWeights: (body.priorityWeights && typeof body.priorityWeights === 'object') ? body.priorityWeights : undefined,
+    endState: body.endState === true,
+    initialState: (body.initialState && typeof body.initialState === 'object') ? body.initialState : undefined
+  };
+}
 
 module.exports = function registerSimRoutes(app, utils) {
   const { path, fs } = utils;
@@ -23,6 +101,25 @@ module.exports = function registerSimRoutes(app, utils) {
     return n.toLowerCase().endsWith('.json') ? n : `${n}.json`;
   }
 
+  // Scenario a run request names: body.scenario, or the file at body.scenarioPath (default: the example scenario),
+  // with body.scenarioPatch applied on top; a patch that is not an object is an invalid scenario
+  async function loadScenario(body) {
+    let scenario;
+    if (body.scenario && typeof body.scenario === 'object') {
+      scenario = body.scenario;
+    } else {
+      const scenarioPath = body.scenarioPath || path.join(__dirname, 'sim', 'des', 'scenario.example.json');
+      scenario = JSON.parse(await fs.promises.readFile(scenarioPath, 'utf8'));
+    }
+    if (body.scenarioPatch !== undefined && body.scenarioPatch !== null) {
+      if (typeof body.scenarioPatch !== 'object' || Array.isArray(body.scenarioPatch)) {
+        throw scenarioError('scenarioPatch', 'must be an object');
+      }
+      scenario = applyScenarioPatch(scenario, body.scenarioPatch);
+    }
+    return scenario;
+  }
+
   // Get a list of all scenario files with friendly names
   app.get('/api/sim/scenarios', async (req, res) => {
     try {
@@ -64,24 +161,183 @@ module.exports = function registerSimRoutes(app, utils) {
   // Run a DES simulation with provided scenario and state
   app.post('/api/sim/run_des', async (req, res) => {
     try {
-      let scenario = null;
       const body = req.body || {};
       if (!body.state || typeof body.state !== 'object') {
         return res.status(400).json({ ok: false, error: 'Missing required state snapshot in request body.' });
       }
-      const overrides = (body.overrides && typeof body.overrides === 'object') ? body.overrides : null;
-      if (body.scenario && typeof body.scenario === 'object') {
-        scenario = body.scenario;
-      } else {
-        const scenarioPath = body.scenarioPath || path.join(__dirname, 'sim', 'des', 'scenario.example.json');
-        const content = await fs.promises.readFile(scenarioPath, 'utf8');
-        scenario = JSON.parse(content);
-      }
-      const state = body.state;
-      const results = await runSimulation(scenario, { state, overrides });
+      const scenario = await loadScenario(body);
+      const settings = desSettingsFromBody(body);
+      logRunSummary('DES run', scenario, settings.state, settings.overrides);
+      const results = await runSimulation(scenario, settings);
       res.json({ ok: true, results });
     } catch (error) {
       console.error('DES simulation run failed:', error);
+      sendRunError(res, error);
+    }
+  });
+
+  // Preview the mission demand a scenario generates (no state needed, nothing is simulated)
+  app.post('/api/sim/preview_demand', async (req, res) => {
+    try {
+      const body = req.body || {};
+      const scenario = await loadScenario(body);
+      const seed = Number.isInteger(body.seed) ? body.seed : undefined;
+      const maxEvents = Number.isInteger(body.maxEvents) ? body.maxEvents : undefined;
+      const results = previewDemand(scenario, { seed, maxEvents });
+      res.json({ ok: true, results });
+    } catch (error) {
+      console.error('Demand preview failed:', error);
+      if (isScenarioError(error)) {
+        return res.status(400).json({ ok: false, error: error.message, code: error.code, path: error.path });
+      }
+      res.status(500).json({ ok: false, error: error.message });
+    }
+  });
+
+  // Sample one distribution spec for a shape preview (nothing is simulated)
+  app.post('/api/sim/sample_distribution', async (req, res) => {
+    try {
+      const body = req.body || {};
+      const seed = Number.isInteger(body.seed) ? body.seed : undefined;
+      const bins = Number.isInteger(body.bins) ? body.bins : undefined;
+      const results = sampleDistribution(body.dist, body.n ?? 1000, { seed, bins });
+      res.json({ ok: true, results });
+    } catch (error) {
+      console.error('Distribution sampling failed:', error);
+      if (isScenarioError(error)) {
+        return res.status(400).json({ ok: false, error: error.message, code: error.code, path: error.path });
+      }
+      res.status(500).json({ ok: false, error: error.message });
+    }
+  });
+
+  // Run a DES simulation and stream the timeline as newline-delimited JSON
+  // Emits { type: 'chunk', events: [...] } lines as the simulation runs, then a final
+  // { type: 'results', results } line with the summary sections (no timeline)
+  app.post('/api/sim/run_des_stream', async (req, res) => {
+    const body = req.body || {};
+    if (!body.state || typeof body.state !== 'object') {
+      return res.status(400).json({ ok: false, error: 'Missing required state snapshot in request body.' });
+    }
+    let scenario;
+    try {
+      scenario = await loadScenario(body);
+    } catch (error) {
+      console.error('DES streaming run failed:', error);
+      return sendRunError(res, error);
+    }
+
+    res.setHeader('Content-Type', 'application/x-ndjson');
+    try {
+      const settings = desSettingsFromBody(body);
+      const timelineChunkSize = (typeof body.timelineChunkSize === 'number' && body.timelineChunkSize > 0) ? body.timelineChunkSize : undefined;
+      logRunSummary('DES streaming run', scenario, settings.state, settings.overrides);
+      const results = await runSimulation(scenario, {
+        ...settings,
+        timelineChunkSize,
+        onTimelineChunk: events => res.write(JSON.stringify({ type: 'chunk', events }) + '\n')
+      });
+      res.end(JSON.stringify({ type: 'results', results }) + '\n');
+    } catch (error) {
+      console.error('DES streaming run failed:', error);
+      res.end(JSON.stringify({ type: 'error', error: error.message }) + '\n');
+    }
+  });
+
+  // Rerun a stored DES result from its scenario, state and seed and report every field that does not match
+  app.post('/api/sim/replay_verify', async (req, res) => {
+    const body = req.body || {};
+    if (!body.scenario || typeof body.scenario !== 'object') {
+      return res.status(400).json({ ok: false, error: 'Request body must include the scenario the result was run with.' });
+    }
+    if (!body.state || typeof body.state !== 'object') {
+      return res.status(400).json({ ok: false, error: 'Missing required state snapshot in request body.' });
+    }
+    if (!body.results || typeof body.results !== 'object' || Array.isArray(body.results)) {
+      return res.status(400).json({ ok: false, error: 'Request body must include the stored DES results.' });
+    }
+    const settings = (body.settings && typeof body.settings === 'object') ? body.settings : {};
+    const options = (body.options && typeof body.options === 'object') ? body.options : {};
+    try {
+      const verification = await replayAndVerify(body.scenario, {
+        ...settings,
+        state: body.state,
+        overrides: (body.overrides && typeof body.overrides === 'object') ? body.overrides : null,
+        seed: Number.isInteger(body.seed) ? body.seed : settings.seed
+      }, body.results, options);
+      res.json({ ok: true, ...verification });
+    } catch (error) {
+      sendRunError(res, error);
+    }
+  });
+
+  // Run consecutive days back to back, each starting from the previous day's end state (held resources, flight hours);
+  // with iterations, Monte Carlo over the whole sequence
+  app.post('/api/sim/run_sequence', async (req, res) => {
+    const body = req.body || {};
+    if (!Array.isArray(body.scenarios) || body.scenarios.length === 0 || !body.scenarios.every(s => s && typeof s === 'object')) {
+      return res.status(400).json({ ok: false, error: 'Request body must include scenarios, a non-empty array of scenario objects.' });
+    }
+    if (!body.state || typeof body.state !== 'object') {
+      return res.status(400).json({ ok: false, error: 'Missing required state snapshot in request body.' });
+    }
+    const overrides = (body.overrides && typeof body.overrides === 'object') ? body.overrides : null;
+    const seed = Number.isInteger(body.seed) ? body.seed : undefined;
+    try {
+      if (typeof body.iterations === 'number' && body.iterations > 0) {
+        const results = await runMonteCarloSequence(body.scenarios, {
+          state: body.state,
+          overrides: overrides ?? undefined,
+          iterations: body.iterations,
+          seed,
+          onBusy: typeof body.onBusy === 'string' ? body.onBusy : undefined
+        });
+        return res.json({ ok: true, results });
+      }
+      const results = await runSimulationSequence(body.scenarios, {
+        state: body.state,
+        overrides,
+        seed,
+        initialState: (body.initialState && typeof body.initialState === 'object') ? body.initialState : undefined,
+        collectTimeline: typeof body.collectTimeline === 'boolean' ? body.collectTimeline : undefined,
+        priorityWeights: (body.priorityWeights && typeof body.priorityWeights === 'object') ? body.priorityWeights : undefined
+      });
+      res.json({ ok: true, results });
+    } catch (error) {
+      console.error('Sequence run failed:', error);
+      sendRunError(res, error);
+    }
+  });
+
+  // Display-sized copy of a stored DES result: the timeline capped with every rejection kept, plus per-bucket counts
+  app.post('/api/sim/summarize_results', (req, res) => {
+    const body = req.body || {};
+    if (!body.results || typeof body.results !== 'object' || Array.isArray(body.results)) {
+      return res.status(400).json({ ok: false, error: 'Request body must include DES results.' });
+    }
+    const options = (body.options && typeof body.options === 'object') ? body.options : {};
+    try {
+      res.json({ ok: true, summary: summarizeResults(body.results, options) });
+    } catch (error) {
+      res.status(400).json({ ok: false, error: error.message });
+    }
+  });
+
+  // Compare two Monte Carlo results (e.g. before/after) and summarize the significant changes
+  app.post('/api/sim/compare_results', (req, res) => {
+    const body = req.body || {};
+    if (!body.a || typeof body.a !== 'object' || !body.b || typeof body.b !== 'object') {
+      return res.status(400).json({ ok: false, error: 'Request body must include Monte Carlo results a and b.' });
+    }
+    const options = (body.options && typeof body.options === 'object') ? body.options : {};
+    try {
+      validateCompareOptions(options);
+    } catch (error) {
+      return res.status(400).json({ ok: false, error: error.message });
+    }
+    try {
+      res.json({ ok: true, comparison: compareResults(body.a, body.b, options) });
+    } catch (error) {
       res.status(500).json({ ok: false, error: error.message });
     }
   });
@@ -89,7 +345,6 @@ module.exports = function registerSimRoutes(app, utils) {
   // Run a Monte Carlo simulation with provided scenario and state
   app.post('/api/sim/run_monte', async (req, res) => {
     try {
-      let scenario = null;
       const body = req.body || {};
       if (!body.state || typeof body.state !== 'object') {
         return res.status(400).json({ ok: false, error: 'Missing required state snapshot in request body.' });
@@ -97,13 +352,7 @@ module.exports = function registerSimRoutes(app, utils) {
       const overrides = (body.overrides && typeof body.overrides === 'object') ? body.overrides : null;
       const iterations = (typeof body.iterations === 'number' && body.iterations > 0) ? body.iterations : 1000;
       const keepIterations = (typeof body.keepIterations === 'boolean') ? body.keepIterations : false;
-      if (body.scenario && typeof body.scenario === 'object') {
-        scenario = body.scenario;
-      } else {
-        const scenarioPath = body.scenarioPath || path.join(__dirname, 'sim', 'des', 'scenario.example.json');
-        const content = await fs.promises.readFile(scenarioPath, 'utf8');
-        scenario = JSON.parse(content);
-      }
+      const scenario = await loadScenario(body);
       const state = body.state;
       const algorithm = (body.algorithm && ['Step', 'PERT'].includes(body.algorithm)) 
         ? body.algorithm 
@@ -111,18 +360,108 @@ module.exports = function registerSimRoutes(app, utils) {
       const simulateSettings = (body.simulateSettings && Array.isArray(body.simulateSettings))
         ? body.simulateSettings
         : undefined;
+      const thresholds = (body.thresholds !== undefined && body.thresholds !== null) ? body.thresholds : undefined;
+      if (thresholds !== undefined) {
+        try {
+          compileThresholds(thresholds);
+        } catch (error) {
+          return res.status(400).json({ ok: false, error: error.message });
+        }
+      }
+      const inputUncertainty = (body.inputUncertainty !== undefined && body.inputUncertainty !== null) ? body.inputUncertainty : undefined;
+      const inputOutputAnalysis = (body.inputOutputAnalysis !== undefined && body.inputOutputAnalysis !== null) ? body.inputOutputAnalysis : undefined;
+      const extractors = (body.extractors !== undefined && body.extractors !== null) ? body.extractors : undefined;
+      const extremeMetrics = (body.extremeMetrics !== undefined && body.extremeMetrics !== null) ? body.extremeMetrics : undefined;
+      const demandBootstrap = (body.demandBootstrap !== undefined && body.demandBootstrap !== null) ? body.demandBootstrap : undefined;
+      const stratifyBy = (body.stratifyBy !== undefined && body.stratifyBy !== null) ? body.stratifyBy : undefined;
+      const stratifyBuckets = body.stratifyBuckets ?? undefined;
+      const inputSampling = body.inputSampling ?? undefined;
+      try {
+        const compiledInputs = inputUncertainty !== undefined ? compileInputUncertainty(inputUncertainty) : null;
+        validateInputSampling(inputSampling);
+        if (inputOutputAnalysis !== undefined) compileInputOutputAnalysis(inputOutputAnalysis, compiledInputs);
+        if (extractors !== undefined) compileExtractors(extractors);
+        if (extremeMetrics !== undefined) compileExtremeMetrics(extremeMetrics);
+        if (demandBootstrap !== undefined) validateDemandBootstrap(demandBootstrap, scenario);
+        if (stratifyBy !== undefined) compileStratifyBy(stratifyBy, stratifyBuckets, compiledInputs, demandBootstrap ?? null);
+      } catch (error) {
+        return res.status(400).json({ ok: false, error: error.message });
+      }
+      logRunSummary(`Monte Carlo run (${iterations} iterations)`, scenario, state, overrides);
       const results = await runMonteCarlo(scenario, {
         state,
         overrides,
         iterations,
         algorithm,
         keepIterations,
-        simulateSettings
+        keepSampleIterations: Number.isInteger(body.keepSampleIterations) ? body.keepSampleIterations : undefined,
+        simulateSettings,
+        thresholds,
+        inputUncertainty,
+        inputOutputAnalysis,
+        extractors,
+        extremeMetrics,
+        demandBootstrap,
+        stratifyBy,
+        stratifyBuckets,
+        inputSampling,
+        onBusy: typeof body.onBusy === 'string' ? body.onBusy : undefined,
+        seed: Number.isInteger(body.seed) ? body.seed : undefined,
+        limits: (body.limits && typeof body.limits === 'object') ? body.limits : undefined,
+        resourceHoldModel: typeof body.resourceHoldModel === 'string' ? body.resourceHoldModel : undefined,
+        timelineRejectionSampling: typeof body.timelineRejectionSampling === 'number' ? body.timelineRejectionSampling : undefined,
+        peakWindowHours: typeof body.peakWindowHours === 'number' ? body.peakWindowHours : undefined,
+        rejectionDensityBucketHours: typeof body.rejectionDensityBucketHours === 'number' ? body.rejectionDensityBucketHours : undefined,
+        hourlyProfile: body.hourlyProfile === true,
+        priorityWeights: (body.priorityWeights && typeof body.priorityWeights === 'object') ? body.priorityWeights : undefined,
+        collectTimeline: typeof body.collectTimeline === 'boolean' ? body.collectTimeline : undefined,
+        echoConfig: typeof body.echoConfig === 'boolean' ? body.echoConfig : undefined,
+        maxOutputBytes: (typeof body.maxOutputBytes === 'number' || body.maxOutputBytes === null) ? body.maxOutputBytes : undefined,
+        continueOnIterationError: body.continueOnIterationError === true,
+        byTypeMissingAsZero: typeof body.byTypeMissingAsZero === 'boolean' ? body.byTypeMissingAsZero : undefined,
+        maxIterationErrorFraction: typeof body.maxIterationErrorFraction === 'number' ? body.maxIterationErrorFraction : undefined,
+        iterationTimeoutMs: typeof body.iterationTimeoutMs === 'number' ? body.iterationTimeoutMs : undefined
       });
       res.json({ ok: true, results });
     } catch (error) {
       console.error('Monte Carlo simulation run failed:', error);
-      res.status(500).json({ ok: false, error: error.message });
+      sendRunError(res, error);
+    }
+  });
+
+  // Monte Carlo run slots in use and waiting, for the dashboard
+  app.get('/api/sim/engine_status', (req, res) => {
+    res.json({ ok: true, status: getEngineStatus() });
+  });
+
+  // Run a one-factor-at-a-time sensitivity sweep (tornado chart data)
+  app.post('/api/sim/run_sensitivity', async (req, res) => {
+    try {
+      const body = req.body || {};
+      if (!body.state || typeof body.state !== 'object') {
+        return res.status(400).json({ ok: false, error: 'Missing required state snapshot in request body.' });
+      }
+      const overrides = (body.overrides && typeof body.overrides === 'object') ? body.overrides : null;
+      const scenario = await loadScenario(body);
+      try {
+        compileFactors(body.factors, scenario, overrides);
+      } catch (error) {
+        return res.status(400).json({ ok: false, error: error.message });
+      }
+      const results = await runSensitivity(scenario, {
+        state: body.state,
+        overrides: overrides ?? undefined,
+        iterations: (typeof body.iterations === 'number' && body.iterations > 0) ? body.iterations : 200,
+        metric: body.metric ?? undefined,
+        statistic: typeof body.statistic === 'string' ? body.statistic : undefined,
+        onBusy: typeof body.onBusy === 'string' ? body.onBusy : undefined,
+        seed: Number.isInteger(body.seed) ? body.seed : undefined,
+        limits: (body.limits && typeof body.limits === 'object') ? body.limits : undefined
+      }, body.factors);
+      res.json({ ok: true, results });
+    } catch (error) {
+      console.error('Sensitivity run failed:', error);
+      sendRunError(res, error);
     }
   });
 };
@@ -7,10 +7,66 @@ const { applySettings } = require('./stages/stage2-settings');
 const { processPersonnel } = require('./stages/stage3-personnel');
 const { processOperations } = require('./stages/stage4-operations');
 const { runSimulation: runSimulationStage } = require('./stages/stage5-simulation');
-const { generateResults } = require('./stages/stage6-results');
+const { generateResults, sortResultKeys } = require('./stages/stage6-results');
+
+// Demand generation, for previews without a full run
+const { generateDemand, expectedDemandCounts } = require('./helpers/demand');
+
+// Unit-assignment policies (runSimulationWithPolicy)
+const { validatePolicy } = require('./helpers/assignment');
+
+// State derivation (re-exported for callers that inspect snapshots directly)
+const { loadState } = require('./helpers/state');
+
+// Scenario merge-patch (what-if scenarios as baseline + changes)
+const { applyScenarioPatch } = require('./helpers/patch');
+
+// Unit-tagged scenario values (minutes/days) converted to hours
+const { normalizeScenarioUnits, distributionToHours } = require('./helpers/units');
+const { canonicalizeDistribution } = require('./helpers/aliases');
+
+// Settings resolved once with their defaults (results.resolved_config)
+const { resolveSettings, echoResolvedConfig } = require('./helpers/config');
+
+// Scenario validation (structured INVALID_SCENARIO errors) and run warnings
+const { validateScenario, validateDistribution, scenarioWarnings } = require('./helpers/validation');
+
+// Mission types no unit can ever fly (results.infeasible_by_type)
+const { findInfeasibleMissionTypes, infeasibilityWarnings } = require('./helpers/feasibility');
+
+// End-of-run holds carried into the next run (results.end_state, runSimulationSequence)
+const { buildEndState, seedCarriedHolds, summarizeSequence } = require('./helpers/carryover');
+
+// Plain-text scenario digest (summarizeScenario)
+const { formatScenarioSummary } = require('./helpers/summary');
+const { summarizeResults } = require('./helpers/downsample');
+
+// Serialized size cap (results.truncated)
+const { limitOutputSize } = require('./helpers/outputsize');
+
+// Field-by-field result diff, shared with Monte Carlo result comparison (replayAndVerify)
+const { diffFields } = require('../monte/compare');
+
+// Watchdog limits for runaway runs
+const { createWatchdog } = require('./helpers/limits');
+
+// Seedable random source shared by all stages
+const { setSeed, restoreRng, isValidSeed, makeRng, deriveSeed } = require('./helpers/random');
+const { sampleDist, setExpectedValueMode } = require('./helpers/distributions');
+
+// deriveSeed stream index for timeline rejection sampling
+const REJECTION_SAMPLING_STREAM = 0x5245;
+// deriveSeed stream index for crew absences (crew_policy.availability_rate, stochastic mode)
+const CREW_ABSENCE_STREAM = 0x4142;
+
+// Events previewDemand returns by default
+const DEFAULT_PREVIEW_MAX_EVENTS = 1000;
+// sampleDistribution: most samples per call and default histogram bins
+const MAX_DISTRIBUTION_SAMPLES = 100000;
+const DEFAULT_HISTOGRAM_BINS = 20;
 
 // Utility for log level management
-const { setLogLevel, getLogLevel } = require('../../utils');
+const { setLogLevel, getLogLevel, logWithLocation } = require('../../utils');
 
 /**
  * Run a discrete event simulation for squadron operations
@@ -20,32 +76,95 @@ const { setLogLevel, getLogLevel } = require('../../utils');
  * @param {Object} settings.state - State snapshot with resource data
  * @param {Object} settings.overrides - Optional resource overrides by unit
  * @param {string} settings.logLevel - Optional log level ('silent', 'error', 'warn', 'info', 'verbose', 'debug'). Defaults to 'verbose'
+ * @param {number} settings.seed - Optional integer seed; the same seed and inputs reproduce the same results. Defaults to Math.random()
+ * @param {boolean} settings.missionDetail - Optional flag to include results.missions_detail (one row per demand). Defaults to false
+ * @param {Function} settings.onTimelineChunk - Optional callback receiving timeline events in chunks as they are produced;
+ *                                              when set, results omit timeline and report timeline_events instead
+ * @param {number} settings.timelineChunkSize - Optional events per chunk for onTimelineChunk. Defaults to 1000
+ * @param {boolean} settings.collectTimeline - Optional; false builds no timeline at all (results omit timeline, counters and
+ *                                           completions are unaffected). Defaults to true
+ * @param {string} settings.resourceHoldModel - Optional 'legacy' (every resource held through turnaround) or 'per_resource'
+ *                                            (crew released after postflight, payloads after transit out). Defaults to 'legacy'
+ * @param {number} settings.timelineRejectionSampling - Optional fraction (0, 1] of rejection timeline entries to keep;
+ *                                                    counters are unaffected and the rate is echoed in results
+ * @param {number} settings.peakWindowHours - Optional sliding window length for results.peak_window. Defaults to 24
+ * @param {number} settings.rejectionDensityBucketHours - Optional bucket length for results.rejection_density. Defaults to 1
+ * @param {boolean} settings.emitResourceLevels - Optional flag to add resource_level timeline events (available count of
+ *                                               every pool at t=0 and at each change). Defaults to false
+ * @param {Object} settings.limits - Optional { maxEvents, maxTimelineEvents, maxWallMs }; exceeding one throws an
+ *                                   error with code 'SIMULATION_LIMIT', the limit that tripped and the counts at the time
+ * @param {string} settings.mode - Optional 'stochastic' or 'expected_value' (every distribution returns its mean and
+ *                                 Poisson demand arrives every 1 / rate hours; an approximation, not the Monte Carlo mean).
+ *                                 Defaults to 'stochastic'
+ * @param {boolean} settings.hourlyProfile - Optional flag to add results.hourly_profile (mission arrivals, starts and final
+ *                                          rejections per hour, ceil(horizon) entries each). Defaults to false
+ * @param {boolean} settings.sortedOutput - Optional flag to return results with every object's keys sorted, so the JSON of
+ *                                         two identical runs is identical byte for byte. Defaults to false
+ * @param {Object} settings.trace - Optional { missionTypes, timeRange: [start, end] }; every mission demand matching both
+ *                                  (either may be left out) gets a decision log in results.trace. Defaults to no trace
+ * @param {number|null} settings.maxOutputBytes - Optional cap on the results' estimated JSON size; over it, timeline,
+ *                                             missions_detail and trace are dropped in that order (results.truncated
+ *                                             lists them, results.output_size has the sizes). null for no cap.
+ *                                             Defaults to 256 MiB
+ * @param {Object} settings.priorityWeights - Optional { priority: weight } for results.weighted_completion_score; a priority
+ *                                         left out weighs 1 / priority. Defaults to 1 / priority for every type
+ * @param {boolean} settings.endState - Optional flag to add results.end_state (per unit, the hours each hold still running
+ *                                    at the horizon has left, and accumulated flight hours). Defaults to false
+ * @param {Object} settings.initialState - Optional end_state of a previous run; its holds are seeded from t=0 for their
+ *                                        remaining hours and its flight hours carried on. Defaults to none
+ * @param {boolean} settings.echoConfig - Optional; false leaves out results.resolved_config (the scenario and settings the run
+ *                                      used after defaults and canonicalization). Defaults to true
+ * @param {Object} assignmentPolicy - Optional unit-assignment policy; pass it through runSimulationWithPolicy
  * @returns {Promise<Object>} Simulation results with missions, rejections, utilization, timeline
  */
-async function runSimulation(scenario, settings = {}) {
+async function runSimulation(scenario, settings = {}, assignmentPolicy = null) {
+  const resolved = resolveSettings(settings);
+  const { seed, mode } = resolved;
+  const expectedValue = mode === 'expected_value';
+
+  // All stages work in hours; convert { value, unit } horizons and unit-tagged distributions up front
+  scenario = normalizeScenarioUnits(scenario);
+
   // Set log level if provided (save current level to restore later)
   // This allows callers to control logging verbosity
   const previousLogLevel = getLogLevel();
-  if (settings.logLevel !== undefined && settings.logLevel !== null) {
-    setLogLevel(settings.logLevel);
+  if (resolved.logLevel !== null) {
+    setLogLevel(resolved.logLevel);
   }
 
+  // Install the seeded generator for this run (restored afterwards)
+  const previousRng = setSeed(seed);
+  const previousExpectedValue = setExpectedValueMode(expectedValue);
+
   try {
     // Stage 1: Process scenario configuration
     const config = processScenario(scenario);
 
     // Stage 2: Apply settings (load state and overrides)
     const initial = applySettings(settings, scenario);
+    const warnings = scenarioWarnings(scenario, initial);
+    for (const warning of warnings) logWithLocation(`WARNING: ${warning.message}`, undefined, 'warn');
 
     // Stage 3: Process personnel configuration
     const personnel = processPersonnel(scenario, initial);
 
     // Stage 4: Process operations (demand generation and resource pools)
-    const operations = processOperations(scenario, initial, personnel);
+    const watchdog = resolved.limits ? createWatchdog(resolved.limits) : null;
+    const crewAbsenceRng = isValidSeed(seed) ? makeRng(deriveSeed(seed, CREW_ABSENCE_STREAM)) : null;
+    const operations = processOperations(scenario, initial, personnel, watchdog, assignmentPolicy, crewAbsenceRng);
+    const infeasibleTypes = findInfeasibleMissionTypes(scenario, config.missionTypes, operations.pools);
+    for (const warning of infeasibilityWarnings(infeasibleTypes)) {
+      logWithLocation(`WARNING: ${warning.message}`, undefined, 'warn');
+      warnings.push(warning);
+    }
+    // Holds still running at the end of the previous run (settings.initialState)
+    const carriedHolds = seedCarriedHolds(resolved.initialState, operations.pools);
 
     // Stage 5: Run simulation (process all events)
     const context = {
       events: operations.events,
+      demandStats: operations.demandStats,
+      activeMissions: operations.activeMissions,
       pools: operations.pools,
       unitList: operations.unitList,
       horizon: config.horizon,
@@ -55,9 +174,25 @@ async function runSimulation(scenario, settings = {}) {
       turnSpec: config.turnSpec,
       scenario,
       initial,
-      overrides: settings.overrides && settings.overrides.units ? settings.overrides.units : null
+      overrides: resolved.overrides,
+      missionDetail: resolved.missionDetail,
+      onTimelineChunk: resolved.onTimelineChunk,
+      timelineChunkSize: resolved.timelineChunkSize,
+      collectTimeline: resolved.collectTimeline,
+      watchdog,
+      resourceHoldModel: resolved.resourceHoldModel,
+      timelineRejectionSampling: resolved.timelineRejectionSampling,
+      rejectionDensityBucketHours: resolved.rejectionDensityBucketHours,
+      assignmentPolicy,
+      emitResourceLevels: resolved.emitResourceLevels,
+      hourlyProfile: resolved.hourlyProfile,
+      trace: resolved.trace,
+      infeasibleTypes,
+      // Sub-stream of the run seed reserved for rejection sampling
+      rejectionSamplingRng: isValidSeed(seed) ? makeRng(deriveSeed(seed, REJECTION_SAMPLING_STREAM)) : null
     };
     const partialResults = runSimulationStage(context);
+    if (carriedHolds > 0) partialResults.initial_resources.carried_holds = carriedHolds;
 
     // Stage 6: Generate final results
     const resultsContext = {
@@ -65,16 +200,251 @@ async function runSimulation(scenario, settings = {}) {
       horizon: config.horizon,
       initial,
       availability: personnel,
-      scenario
+      scenario,
+      peakWindowHours: resolved.peakWindowHours,
+      priorityWeights: resolved.priorityWeights
     };
-    return generateResults(partialResults, resultsContext);
+    const results = generateResults(partialResults, resultsContext);
+    if (expectedValue) results.mode = mode;
+    if (resolved.endState) results.end_state = buildEndState(operations.pools, config.horizon, resolved.initialState);
+    if (warnings.length > 0) results.warnings = warnings;
+    // What the run actually used, after defaults, unit normalization and alias canonicalization
+    if (resolved.echoConfig) results.resolved_config = echoResolvedConfig(resolved, scenario, config.horizon);
+    // Over the size cap, drop the bulky parts rather than fail later in JSON.stringify
+    const limited = limitOutputSize(results, resolved.maxOutputBytes, ['timeline', 'missions_detail', 'trace']);
+    if (limited) {
+      results.truncated = limited.truncated;
+      results.output_size = limited.sizes;
+    }
+    return resolved.sortedOutput ? sortResultKeys(results) : results;
 
   } finally {
+    restoreRng(previousRng);
+    setExpectedValueMode(previousExpectedValue);
+
     // Restore previous log level
-    if (settings.logLevel) {
+    if (resolved.logLevel !== null) {
       setLogLevel(previousLogLevel);
     }
   }
 }
 
-module.exports = { runSimulation };
+/**
+ * Run a simulation with a custom unit-assignment policy (JavaScript API only; the routes do not accept one)
+ * The policy's selectUnit(ctx) is called at each non-pinned mission demand's time with
+ * { time, mission_type, demand_source, units, availability(unit) } and returns a unit name, or null to leave
+ * the demand unassigned. Built-ins and the snapshot shape are in helpers/assignment.js
+ *
+ * Demands are assigned as they arrive, so ODO lookahead only sees missions already accepted
+ *
+ * @param {Object} scenario - Scenario configuration
+ * @param {Object} settings - Simulation settings (as runSimulation)
+ * @param {Object} policy - Assignment policy ({ selectUnit(ctx) })
+ * @returns {Promise<Object>} Simulation results
+ */
+async function runSimulationWithPolicy(scenario, settings, policy) {
+  validatePolicy(policy);
+  return runSimulation(scenario, settings, policy);
+}
+
+/**
+ * Rerun a stored result from the scenario, settings and seed it claims and check that it is reproduced
+ * The seed is settings.seed or, when unset, the one recorded in the stored result's resolved_config. A difference
+ * (numbers are compared within options.tolerance) means the engine changed since the result was stored, the
+ * inputs are not the ones it was run with, or the result was edited
+ *
+ * @param {Object} scenario - Scenario the result was run with
+ * @param {Object} settings - Settings it was run with (state, overrides, ...; as runSimulation)
+ * @param {Object} storedResults - The stored result (as runSimulation returned it, or its JSON)
+ * @param {Object} options - Optional settings
+ * @param {number} options.tolerance - Relative tolerance for numbers. Defaults to 1e-9
+ * @param {number} options.maxDifferences - Differences to report at most. Defaults to 100
+ * @returns {Promise<Object>} { match, seed, differences: [{ path, kind, expected (stored), actual (replayed) }] }
+ * @throws {Error} If there is no seed to replay with
+ */
+async function replayAndVerify(scenario, settings, storedResults, options = {}) {
+  if (!storedResults || typeof storedResults !== 'object' || Array.isArray(storedResults)) {
+    throw new Error('replayAndVerify requires a stored DES result object');
+  }
+  const seed = settings.seed ?? storedResults.resolved_config?.settings?.seed ?? (settings.mode === 'expected_value' ? 0 : null);
+  if (!isValidSeed(seed)) {
+    throw new Error('replayAndVerify needs the seed the result was run with: pass settings.seed or store results with resolved_config');
+  }
+  const replayed = await runSimulation(scenario, { ...settings, seed, logLevel: settings.logLevel ?? 'silent' });
+  // Compare what was stored as JSON, whichever form it was handed in
+  const differences = diffFields(JSON.parse(JSON.stringify(storedResults)), JSON.parse(JSON.stringify(replayed)), {
+    tolerance: options.tolerance,
+    maxDifferences: options.maxDifferences ?? 100
+  });
+  return { match: differences.length === 0, seed, differences };
+}
+
+/**
+ * Run consecutive segments (e.g. the days of a campaign) back to back, each seeded with the previous one's end state:
+ * holds still running at a segment's horizon (missions in progress, turnarounds, crew recovery) carry into the next
+ * from t=0, and flight hours accumulate per unit. Every segment is a full run of its own scenario with the same state
+ *
+ * @param {Array<Object>} scenarios - One scenario per segment, in order
+ * @param {Object} settings - Simulation settings for every segment (as runSimulation); settings.initialState seeds the
+ *                            first one. With settings.seed, segment i runs with a sub-seed derived from (seed, i)
+ * @returns {Promise<Object>} { days: [results with end_state], summary: { days, horizon_hours, missions, rejections,
+ *                              by_type, flight_hours } }; a mission carried into the next segment counts as completed
+ * @throws {Error} If scenarios is empty, or a segment's carried holds do not fit the next one's pools
+ */
+async function runSimulationSequence(scenarios, settings = {}) {
+  if (!Array.isArray(scenarios) || scenarios.length === 0) {
+    throw new Error('runSimulationSequence requires a non-empty array of scenarios');
+  }
+  if (settings.seed !== undefined && settings.seed !== null && !isValidSeed(settings.seed)) {
+    throw new Error(`settings.seed must be an integer, got ${settings.seed}`);
+  }
+  const days = [];
+  let initialState = settings.initialState ?? null;
+  for (let i = 0; i < scenarios.length; i++) {
+    const seed = isValidSeed(settings.seed) ? deriveSeed(settings.seed, i) : settings.seed;
+    const results = await runSimulation(scenarios[i], { ...settings, seed, initialState, endState: true });
+    days.push(results);
+    initialState = results.end_state;
+  }
+  return { days, summary: summarizeSequence(days) };
+}
+
+/**
+ * Generate a scenario's mission demand without building state or pools, to check a
+ * configuration before running it
+ *
+ * @param {Object} scenario - Scenario configuration
+ * @param {Object} options - Optional settings
+ * @param {number} options.seed - Optional integer seed (the same seed gives the demand a run with that seed sees)
+ * @param {number} options.maxEvents - Optional number of events to return. Defaults to 1000; counts cover every event
+ * @returns {Object} { horizon_hours, events: [{ time, mission_type, demand_source }], events_truncated,
+ *                     counts_by_type, expected_rate_check: [{ demand_source, mission_type, type, expected, generated }] }
+ */
+function previewDemand(scenario, options = {}) {
+  const { seed, maxEvents = DEFAULT_PREVIEW_MAX_EVENTS } = options;
+  if (seed !== undefined && seed !== null && !isValidSeed(seed)) {
+    throw new Error(`seed must be an integer, got ${seed}`);
+  }
+  if (!Number.isInteger(maxEvents) || maxEvents < 0) {
+    throw new Error(`maxEvents must be a non-negative integer, got ${maxEvents}`);
+  }
+  scenario = normalizeScenarioUnits(scenario);
+  validateScenario(scenario);
+
+  const previousRng = setSeed(seed);
+  const previousExpectedValue = setExpectedValueMode(false);
+  let missions;
+  try {
+    missions = generateDemand(scenario).filter(e => e.type === 'mission_demand');
+  } finally {
+    restoreRng(previousRng);
+    setExpectedValueMode(previousExpectedValue);
+  }
+
+  const countsByType = {};
+  const countsBySource = {};
+  for (const e of missions) {
+    countsByType[e.mission_type] = (countsByType[e.mission_type] || 0) + 1;
+    countsBySource[e.demand_source] = (countsBySource[e.demand_source] || 0) + 1;
+  }
+  return {
+    horizon_hours: scenario.horizon_hours,
+    events: missions.slice(0, maxEvents).map(e => ({ time: e.time, mission_type: e.mission_type, demand_source: e.demand_source })),
+    events_truncated: missions.length > maxEvents,
+    counts_by_type: countsByType,
+    expected_rate_check: expectedDemandCounts(scenario).map(row => ({ ...row, generated: countsBySource[row.demand_source] || 0 }))
+  };
+}
+
+/**
+ * Draw samples from one distribution spec, to show its shape while a scenario is being edited
+ * The spec goes through the same alias renaming, unit conversion and checks as a scenario's specs,
+ * so the preview fails exactly when a run would
+ *
+ * @param {Object} dist - Distribution spec (as in mission_types[].flight_time; may carry a unit)
+ * @param {number} n - Number of samples, 1 to 100000
+ * @param {Object} options - Optional settings
+ * @param {number} options.seed - Optional integer seed (samples are then reproducible)
+ * @param {number} options.bins - Optional histogram bin count. Defaults to 20
+ * @returns {Object} { dist (in hours), samples, mean, stddev, min, max,
+ *                     histogram: { bin_width, edges (bins + 1), counts (bins) } }
+ */
+function sampleDistribution(dist, n, options = {}) {
+  const { seed, bins = DEFAULT_HISTOGRAM_BINS } = options;
+  if (seed !== undefined && seed !== null && !isValidSeed(seed)) {
+    throw new Error(`seed must be an integer, got ${seed}`);
+  }
+  if (!Number.isInteger(n) || n < 1 || n > MAX_DISTRIBUTION_SAMPLES) {
+    throw new Error(`n must be an integer from 1 to ${MAX_DISTRIBUTION_SAMPLES}, got ${n}`);
+  }
+  if (!Number.isInteger(bins) || bins < 1) {
+    throw new Error(`bins must be a positive integer, got ${bins}`);
+  }
+  if (dist && typeof dist === 'object' && !Array.isArray(dist)) {
+    dist = distributionToHours(canonicalizeDistribution(dist, 'dist'), 'dist');
+  }
+  validateDistribution(dist, 'dist');
+
+  const previousRng = setSeed(seed);
+  const previousExpectedValue = setExpectedValueMode(false);
+  const samples = [];
+  try {
+    for (let i = 0; i < n; i++) samples.push(sampleDist(dist));
+  } finally {
+    restoreRng(previousRng);
+    setExpectedValueMode(previousExpectedValue);
+  }
+
+  const mean = samples.reduce((sum, v) => sum + v, 0) / n;
+  const stddev = Math.sqrt(samples.reduce((sum, v) => sum + (v - mean) ** 2, 0) / n);
+  const min = samples.reduce((a, b) => Math.min(a, b));
+  const max = samples.reduce((a, b) => Math.max(a, b));
+  // Equal-width bins over [min, max]; the last bin includes max, a constant sample fills one bin
+  const binWidth = max > min ? (max - min) / bins : 0;
+  const counts = new Array(binWidth > 0 ? bins : 1).fill(0);
+  for (const v of samples) {
+    counts[binWidth > 0 ? Math.min(Math.floor((v - min) / binWidth), bins - 1) : 0]++;
+  }
+  return {
+    dist,
+    samples,
+    mean,
+    stddev,
+    min,
+    max,
+    histogram: {
+      bin_width: binWidth,
+      edges: counts.map((_, k) => min + k * binWidth).concat(max),
+      counts
+    }
+  };
+}
+
+/**
+ * Concise human-readable digest of a scenario, for logs and support: horizon, expected demand per entry,
+ * mission types with mean flight times, resources per unit after overrides, active policies and warnings
+ * Analytic only; nothing is simulated
+ *
+ * @param {Object} scenario - Scenario configuration
+ * @param {Object} options - Optional { state, overrides }; without a state snapshot the unit section is skipped
+ * @returns {string} Multi-line summary
+ */
+function summarizeScenario(scenario, options = {}) {
+  scenario = normalizeScenarioUnits(scenario);
+  validateScenario(scenario);
+  const initial = options.state ? applySettings({ state: options.state, overrides: options.overrides }, scenario) : null;
+  return formatScenarioSummary(scenario, initial, initial ? scenarioWarnings(scenario, initial) : []);
+}
+
+module.exports = {
+  runSimulation,
+  runSimulationWithPolicy,
+  replayAndVerify,
+  runSimulationSequence,
+  previewDemand,
+  sampleDistribution,
+  summarizeScenario,
+  summarizeResults,
+  loadState,
+  applyScenarioPatch
+};
@@ -0,0 +1,70 @@
+// Example: a custom unit-assignment policy
+// Sends each mission to the unit with the most free pilots at demand time, falling back to round robin on ties
+//
+//   node sim/des/examples/assignment-policy.js
+//
+// A policy is any object with selectUnit(ctx); see helpers/assignment.js for the context and the built-ins
+
+const { runSimulation, runSimulationWithPolicy } = require('../engine');
+const { roundRobinPolicy } = require('../helpers/assignment');
+
+const state = {
+  tables: {
+    v_aircraft: { rows: [{ Unit: 'VMU-1', Status: 'FMC' }, { Unit: 'VMU-1', Status: 'FMC' }, { Unit: 'VMU-2', Status: 'FMC' }, { Unit: 'VMU-2', Status: 'FMC' }] },
+    v_payload: { rows: [{ Unit: 'VMU-1', Type: 'SkyTower II' }, { Unit: 'VMU-1', Type: 'SkyTower II' }, { Unit: 'VMU-2', Type: 'SkyTower II' }, { Unit: 'VMU-2', Type: 'SkyTower II' }] },
+    v_staffing: {
+      rows: [
+        ...Array.from({ length: 4 }, () => ({ 'Unit Name': 'VMU-1', 'MOS Number': '7318' })),
+        ...Array.from({ length: 2 }, () => ({ 'Unit Name': 'VMU-1', 'MOS Number': '7314' })),
+        ...Array.from({ length: 2 }, () => ({ 'Unit Name': 'VMU-2', 'MOS Number': '7318' })),
+        ...Array.from({ length: 2 }, () => ({ 'Unit Name': 'VMU-2', 'MOS Number': '7314' }))
+      ]
+    },
+    v_unit: { rows: [{ Unit: 'VMU-1' }, { Unit: 'VMU-2' }] }
+  }
+};
+
+const scenario = {
+  horizon_hours: 72,
+  mission_types: [{
+    name: 'ISR',
+    flight_time: { type: 'triangular', a: 2, m: 3, b: 5, transit_in_hours: 0.5, transit_out_hours: 0.5 },
+    required_aircrew: { pilot: 1, so: 1 },
+    required_payload_types: ['SkyTower II']
+  }],
+  demand: [{ type: 'poisson', mission_type: 'ISR', rate_per_hour: 0.5 }],
+  process_times: {
+    preflight: { type: 'deterministic', value_hours: 0.5 },
+    postflight: { type: 'deterministic', value_hours: 0.25 },
+    turnaround: { type: 'deterministic', value_hours: 0.25 },
+    mount_times: { 'SkyTower II': { type: 'deterministic', value_hours: 0.5 } }
+  }
+};
+
+function mostFreePilotsPolicy() {
+  const fallback = roundRobinPolicy(['VMU-1', 'VMU-2']);
+  return {
+    name: 'most_free_pilots',
+    selectUnit(ctx) {
+      const free = ctx.units.map(unit => ({ unit, pilots: ctx.availability(unit).pilot.available }));
+      const best = Math.max(...free.map(f => f.pilots));
+      const leaders = free.filter(f => f.pilots === best);
+      return leaders.length === 1 ? leaders[0].unit : fallback.selectUnit(ctx);
+    }
+  };
+}
+
+async function main() {
+  const settings = { state, seed: 42, logLevel: 'silent' };
+  const baseline = await runSimulation(scenario, settings);
+  const custom = await runSimulationWithPolicy(scenario, settings, mostFreePilotsPolicy());
+  for (const [label, results] of [['round robin', baseline], ['most free pilots', custom]]) {
+    const { requested, completed, rejected } = results.missions;
+    console.log(`${label}: ${completed}/${requested} completed, ${rejected} rejected, by reason ${JSON.stringify(results.rejections)}`);
+  }
+}
+
+main().catch(error => {
+  console.error(error);
+  process.exit(1);
+});
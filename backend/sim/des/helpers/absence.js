@@ -0,0 +1,106 @@
+// Crew Absence Module
+// crew_policy.availability_rate: the fraction of each crew pool present on a given day (sickness, short-notice absence)
+// 'deterministic' mode scales pool totals once (see Stage 4); 'stochastic' mode draws each member's presence per day
+// from the run seed, so the crew on hand varies from day to day and between Monte Carlo iterations
+
+const { random } = require('./random');
+const { isExpectedValueMode } = require('./distributions');
+const { unitNames } = require('./state');
+
+const CREW_AVAILABILITY_MODES = ['deterministic', 'stochastic'];
+
+/**
+ * Availability mode in effect (expected-value runs always scale deterministically)
+ * @param {Object} scenario - Scenario configuration
+ * @returns {string|null} 'deterministic' or 'stochastic', or null when crew_policy.availability_rate is unset
+ */
+function crewAvailabilityMode(scenario) {
+  if (!scenario.crew_policy?.availability_rate) return null;
+  if (isExpectedValueMode()) return 'deterministic';
+  return scenario.crew_policy.availability_mode || 'deterministic';
+}
+
+/**
+ * Factor a crew pool's total is scaled by
+ * @param {Object} scenario - Scenario configuration
+ * @param {string} role - Crew role (pilot, so, intel or a mos_roles role)
+ * @returns {number} The role's rate in deterministic mode, otherwise 1
+ */
+function deterministicCrewRate(scenario, role) {
+  if (crewAvailabilityMode(scenario) !== 'deterministic') return 1;
+  return scenario.crew_policy.availability_rate[role] ?? 1;
+}
+
+/**
+ * Draw which members of each crew pool are absent on each day (stochastic mode only)
+ * Every member is absent on a day with probability 1 - rate, independently, so the absent
+ * count per day is binomial; members flying at the day boundary keep flying
+ * @param {Object} pools - Map of unit → resource pools (Stage 4)
+ * @param {Object} scenario - Scenario configuration
+ * @param {number} horizon - Simulation horizon in hours
+ * @param {Function|null} rng - Generator returning floats in [0, 1), or null for the run's random()
+ */
+function drawCrewAbsences(pools, scenario, horizon, rng = null) {
+  if (crewAvailabilityMode(scenario) !== 'stochastic') return;
+  const draw = rng || random;
+  const days = Math.ceil(horizon / 24);
+  for (const unit of unitNames(pools)) {
+    for (const [role, crewPool] of Object.entries(crewPoolsOf(pools[unit]))) {
+      const rate = scenario.crew_policy.availability_rate[role];
+      if (rate === undefined || rate >= 1) continue;
+      const absentDays = [];
+      for (let day = 0; day < days; day++) {
+        absentDays.push(new Set(crewPool.crew.filter(() => draw() >= rate).map(c => c.id)));
+      }
+      crewPool.setAbsences(absentDays);
+    }
+  }
+}
+
+/**
+ * Every crew pool of a unit by role
+ * @param {Object} pool - One unit's resource pools
+ * @returns {Object} role → CrewQueue
+ */
+function crewPoolsOf(pool) {
+  return { pilot: pool.pilot, so: pool.so, intel: pool.intel, ...pool.roles };
+}
+
+/**
+ * Realized crew availability per unit and role, for results.crew_availability
+ * @param {Object} pools - Map of unit → resource pools
+ * @param {Object} scenario - Scenario configuration
+ * @param {number} horizon - Simulation horizon in hours
+ * @returns {Object|null} { mode, units: { unit: { role: { rate, headcount, mean_available, realized_rate } } } },
+ *                        or null when crew_policy.availability_rate is unset
+ */
+function crewAvailabilityResults(pools, scenario, horizon) {
+  const mode = crewAvailabilityMode(scenario);
+  if (!mode) return null;
+  const units = {};
+  for (const unit of unitNames(pools)) {
+    const pool = pools[unit];
+    units[unit] = {};
+    for (const [role, rate] of Object.entries(scenario.crew_policy.availability_rate)) {
+      const crewPool = crewPoolsOf(pool)[role];
+      const headcount = pool.crew_headcount[role];
+      const meanAvailable = crewPool.total * crewPool.presentFraction(horizon);
+      units[unit][role] = {
+        rate,
+        headcount,
+        mean_available: Number(meanAvailable.toFixed(3)),
+        realized_rate: headcount > 0 ? Number((meanAvailable / headcount).toFixed(3)) : null
+      };
+    }
+  }
+  return { mode, units };
+}
+
+module.exports = {
+  CREW_AVAILABILITY_MODES,
+  crewAvailabilityMode,
+  deterministicCrewRate,
+  drawCrewAbsences,
+  crewPoolsOf,
+  crewAvailabilityResults
+};
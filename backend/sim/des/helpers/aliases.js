@@ -0,0 +1,194 @@
+// Field Aliases Module
+// Older scenario files spell some fields differently (rate vs rate_per_hour, value vs value_hours,
+// interval_hours vs every_hours); this rewrites them to the canonical names before any stage runs,
+// so the stages, fingerprints and anything echoing the scenario only ever see one name per concept
+
+// Alias → canonical name, for distribution specs and for demand entries
+const DISTRIBUTION_ALIASES = { value: 'value_hours', rate: 'rate_per_hour' };
+const DEMAND_ALIASES = { interval_hours: 'every_hours', rate: 'rate_per_hour' };
+
+// Type-specific fields; one set on an entry of another type is ignored, so it is warned about
+const DISTRIBUTION_FIELDS = {
+  deterministic: ['value_hours'],
+  exponential: ['rate_per_hour'],
+  triangular: ['a', 'm', 'b'],
+  lognormal: ['mu', 'sigma']
+};
+const DEMAND_FIELDS = {
+  poisson: ['rate_per_hour', 'rate_uncertainty'],
+  deterministic: ['every_hours'],
+  renewal: ['interarrival'],
+  scheduled: ['at_hours']
+};
+// start_at_hours is shared by deterministic and renewal entries
+const SHARED_DEMAND_FIELDS = { start_at_hours: ['deterministic', 'renewal'] };
+
+/**
+ * Rename an object's alias fields to their canonical names
+ * @param {Object} obj     - Distribution spec or demand entry
+ * @param {Object} aliases - Alias → canonical name
+ * @param {string} where   - Scenario path, for error messages
+ * @returns {Object} The object with canonical names (the same object when it uses none of the aliases)
+ * @throws {Error} If both an alias and its canonical field are set
+ */
+function renameAliases(obj, aliases, where) {
+  if (!obj || typeof obj !== 'object' || Array.isArray(obj)) return obj;
+  const present = Object.keys(aliases).filter(alias => obj[alias] !== undefined);
+  if (present.length === 0) return obj;
+  const out = { ...obj };
+  for (const alias of present) {
+    const canonical = aliases[alias];
+    if (out[canonical] !== undefined) {
+      throw new Error(`${where} sets both ${alias} and ${canonical}; ${alias} is an old name for ${canonical}, keep only ${canonical}`);
+    }
+    out[canonical] = out[alias];
+    delete out[alias];
+  }
+  return out;
+}
+
+/**
+ * Return a distribution spec with its alias fields renamed (value → value_hours, rate → rate_per_hour)
+ * @param {Object} spec  - Distribution spec
+ * @param {string} where - Scenario path, for error messages
+ * @returns {Object} The spec with canonical names (the same object when it uses none of the aliases)
+ * @throws {Error} If both an alias and its canonical field are set
+ */
+function canonicalizeDistribution(spec, where) {
+  return renameAliases(spec, DISTRIBUTION_ALIASES, where);
+}
+
+/**
+ * Each distribution spec in the scenario with its path (flight times, process and mount times, demand gaps and rate multipliers)
+ * @param {Object} scenario - Scenario configuration
+ * @returns {Array<Array>} [path, spec] pairs for the specs that are set
+ */
+function scenarioDistributions(scenario) {
+  const specs = [];
+  (Array.isArray(scenario.mission_types) ? scenario.mission_types : []).forEach((mt, i) => {
+    specs.push([`mission_types[${i}].flight_time`, mt?.flight_time]);
+  });
+  const pt = scenario.process_times;
+  if (pt && typeof pt === 'object') {
+    for (const step of ['preflight', 'postflight', 'turnaround']) specs.push([`process_times.${step}`, pt[step]]);
+    for (const key of ['mount_times', 'dismount_times']) {
+      for (const [ptype, spec] of Object.entries(pt[key] && typeof pt[key] === 'object' ? pt[key] : {})) {
+        specs.push([`process_times.${key}['${ptype}']`, spec]);
+      }
+    }
+  }
+  (Array.isArray(scenario.demand) ? scenario.demand : []).forEach((d, i) => {
+    specs.push([`demand[${i}].interarrival`, d?.interarrival], [`demand[${i}].rate_uncertainty`, d?.rate_uncertainty]);
+  });
+  return specs.filter(([, spec]) => spec && typeof spec === 'object' && !Array.isArray(spec));
+}
+
+/**
+ * Return the scenario with every aliased field under its canonical name
+ * The input is not modified; scenarios already using canonical names come back as the same object.
+ * @param {Object} scenario - Scenario configuration
+ * @returns {Object} Scenario with canonical field names
+ * @throws {Error} If a spec or demand entry sets both an alias and its canonical field
+ */
+function canonicalizeFieldNames(scenario) {
+  if (!scenario || typeof scenario !== 'object') return scenario;
+  const out = { ...scenario };
+  let changed = false;
+  const dist = (spec, where) => {
+    const renamed = canonicalizeDistribution(spec, where);
+    if (renamed !== spec) changed = true;
+    return renamed;
+  };
+
+  if (Array.isArray(scenario.mission_types)) {
+    out.mission_types = scenario.mission_types.map((mt, i) => {
+      const flightTime = dist(mt?.flight_time, `mission_types[${i}].flight_time`);
+      return flightTime === mt?.flight_time ? mt : { ...mt, flight_time: flightTime };
+    });
+  }
+
+  if (scenario.process_times && typeof scenario.process_times === 'object') {
+    const pt = { ...scenario.process_times };
+    for (const step of ['preflight', 'postflight', 'turnaround']) {
+      if (pt[step] !== undefined) pt[step] = dist(pt[step], `process_times.${step}`);
+    }
+    for (const key of ['mount_times', 'dismount_times']) {
+      if (pt[key] && typeof pt[key] === 'object') {
+        pt[key] = Object.fromEntries(Object.entries(pt[key])
+          .map(([ptype, spec]) => [ptype, dist(spec, `process_times.${key}['${ptype}']`)]));
+      }
+    }
+    out.process_times = pt;
+  }
+
+  if (Array.isArray(scenario.demand)) {
+    out.demand = scenario.demand.map((d, i) => {
+      const where = `demand[${i}]`;
+      const entry = renameAliases(d, DEMAND_ALIASES, where);
+      if (entry !== d) changed = true;
+      if (!entry || typeof entry !== 'object') return entry;
+      const interarrival = dist(entry.interarrival, `${where}.interarrival`);
+      const rateUncertainty = dist(entry.rate_uncertainty, `${where}.rate_uncertainty`);
+      if (interarrival === entry.interarrival && rateUncertainty === entry.rate_uncertainty) return entry;
+      return {
+        ...entry,
+        ...(interarrival !== undefined ? { interarrival } : {}),
+        ...(rateUncertainty !== undefined ? { rate_uncertainty: rateUncertainty } : {})
+      };
+    });
+  }
+
+  return changed ? out : scenario;
+}
+
+/**
+ * Warnings for type-specific fields set on a distribution or demand entry of another type (after canonicalization)
+ * @param {Object} scenario - Scenario configuration with canonical field names
+ * @returns {Array<Object>} [{ code: 'IRRELEVANT_FIELD', path, message }]
+ */
+function irrelevantFieldWarnings(scenario) {
+  const warnings = [];
+  const check = (obj, type, fields, where) => {
+    for (const [owner, names] of Object.entries(fields)) {
+      if (owner === type) continue;
+      for (const name of names) {
+        if (obj[name] === undefined || (fields[type] || []).includes(name)) continue;
+        warnings.push({
+          code: 'IRRELEVANT_FIELD',
+          path: `${where}.${name}`,
+          message: `${where}.${name} is only used by ${owner} entries and is ignored for type '${type}'`
+        });
+      }
+    }
+  };
+
+  for (const [where, spec] of scenarioDistributions(scenario)) {
+    const type = spec.type || 'deterministic';
+    if (DISTRIBUTION_FIELDS[type]) check(spec, type, DISTRIBUTION_FIELDS, where);
+  }
+  (Array.isArray(scenario.demand) ? scenario.demand : []).forEach((d, i) => {
+    if (!d || typeof d !== 'object') return;
+    const type = d.type || 'poisson';
+    if (!DEMAND_FIELDS[type]) return;
+    check(d, type, DEMAND_FIELDS, `demand[${i}]`);
+    for (const [name, owners] of Object.entries(SHARED_DEMAND_FIELDS)) {
+      if (d[name] !== undefined && !owners.includes(type)) {
+        warnings.push({
+          code: 'IRRELEVANT_FIELD',
+          path: `demand[${i}].${name}`,
+          message: `demand[${i}].${name} is only used by ${owners.join(' and ')} entries and is ignored for type '${type}'`
+        });
+      }
+    }
+  });
+  return warnings;
+}
+
+module.exports = {
+  canonicalizeFieldNames,
+  canonicalizeDistribution,
+  scenarioDistributions,
+  irrelevantFieldWarnings,
+  DISTRIBUTION_ALIASES,
+  DEMAND_ALIASES
+};
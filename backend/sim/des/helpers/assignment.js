@@ -0,0 +1,218 @@
+// Assignment Module
+// Unit-assignment policies: which unit a (non-pinned) mission demand goes to
+// A policy is any object with selectUnit(ctx) returning a unit name, or null to leave the demand unassigned
+// ctx = { time, mission_type, demand_source, units, availability(unit) }; availability returns a frozen
+// snapshot of that unit's free resources at ctx.time
+
+const { unitNames } = require('./state');
+
+/**
+ * Check that a value can be used as an assignment policy
+ * @param {*} policy - Candidate policy
+ * @throws {Error} If policy has no selectUnit function
+ */
+function validatePolicy(policy) {
+  if (!policy || typeof policy.selectUnit !== 'function') {
+    throw new Error('assignment policy must be an object with a selectUnit(ctx) function');
+  }
+}
+
+/**
+ * Round robin over the units in state order (sorted unit names)
+ * @param {Array<string>} unitList - Unit names
+ * @returns {Object} Policy
+ */
+function roundRobinPolicy(unitList) {
+  let idx = 0;
+  return {
+    name: 'round_robin',
+    selectUnit() {
+      if (!unitList.length) return null;
+      return unitList[idx++ % unitList.length];
+    }
+  };
+}
+
+/**
+ * Deterministic interleaved sequence following unit_policy.mission_split ratios
+ * Missions per unit are fixed up front from the demand count, so the split is exact
+ * @param {Object} split - unit → ratio
+ * @param {number} missionDemandCount - Mission demands the split applies to
+ * @param {Function} log - Optional logger for the resulting counts
+ * @returns {Object} Policy
+ */
+function weightedSplitPolicy(split, missionDemandCount, log = null) {
+  // Normalize split ratios to percentages
+  const totalSplit = Object.values(split).reduce((sum, val) => sum + val, 0);
+  const normalized = {};
+  for (const [unit, ratio] of Object.entries(split)) {
+    normalized[unit] = ratio / totalSplit;
+  }
+
+  // Calculate exact number of missions per unit based on actual demand count
+  const unitCounts = {};
+  let allocated = 0;
+  const units = Object.keys(normalized);
+
+  // Allocate missions proportionally
+  for (let i = 0; i < units.length - 1; i++) {
+    const unit = units[i];
+    const count = Math.round(normalized[unit] * missionDemandCount);
+    unitCounts[unit] = count;
+    allocated += count;
+  }
+  // Last unit gets remainder to ensure exact total
+  unitCounts[units[units.length - 1]] = missionDemandCount - allocated;
+
+  // Create interleaved sequence for even distribution
+  const unitSequence = new Array(missionDemandCount);
+  const counters = {};
+  units.forEach(u => counters[u] = 0);
+
+  for (let i = 0; i < missionDemandCount; i++) {
+    // Pick unit with highest remaining ratio
+    let bestUnit = null;
+    let bestScore = -1;
+    for (const unit of units) {
+      const remaining = unitCounts[unit] - counters[unit];
+      const slotsLeft = missionDemandCount - i;
+      const score = remaining / slotsLeft;
+      if (score > bestScore) {
+        bestScore = score;
+        bestUnit = unit;
+      }
+    }
+    if (bestUnit) {
+      unitSequence[i] = bestUnit;
+      counters[bestUnit]++;
+    }
+  }
+
+  if (log) {
+    log(`Mission split sequence (${missionDemandCount} missions):`);
+    for (const [unit, count] of Object.entries(unitCounts)) {
+      const pct = (count / missionDemandCount * 100).toFixed(1);
+      log(`  ${unit}: ${count} missions (${pct}%)`);
+    }
+  }
+
+  let idx = 0;
+  return {
+    name: 'weighted_split',
+    unitCounts,
+    selectUnit() {
+      // Use sequence with wrapping (shouldn't wrap if sequence matches demand count)
+      return unitSequence.length ? unitSequence[idx++ % unitSequence.length] : null;
+    }
+  };
+}
+
+/**
+ * Send each mission to the unit with the largest share of its aircraft free at demand time
+ * Ties go to the unit listed first; units without aircraft are never chosen
+ * @returns {Object} Policy (needs event-time availability, so only usable through runSimulationWithPolicy)
+ */
+function leastUtilizedPolicy() {
+  return {
+    name: 'least_utilized',
+    selectUnit(ctx) {
+      let best = null;
+      let bestShare = -1;
+      for (const unit of ctx.units) {
+        const { aircraft } = ctx.availability(unit);
+        if (aircraft.total <= 0) continue;
+        const share = aircraft.available / aircraft.total;
+        if (share > bestShare) {
+          bestShare = share;
+          best = unit;
+        }
+      }
+      return best;
+    }
+  };
+}
+
+/**
+ * unit_policy.mission_split when it can split anything: a split whose ratios sum to 0 gives no unit a share,
+ * so runs fall back to round robin (scenarioWarnings reports it) instead of leaving every demand unassigned
+ * @param {Object} scenario - Scenario configuration
+ * @returns {Object|null} unit → ratio, or null when unset, empty or zero in total
+ */
+function missionSplit(scenario) {
+  const split = scenario.unit_policy?.mission_split;
+  if (!split || Object.keys(split).length === 0) return null;
+  return Object.values(split).reduce((sum, ratio) => sum + ratio, 0) > 0 ? split : null;
+}
+
+/**
+ * The policy runs use without a custom one: mission_split when set (and not zero in total), round robin otherwise
+ * @param {Object} scenario - Scenario configuration
+ * @param {Array<string>} unitList - Unit names
+ * @param {number} missionDemandCount - Non-pinned mission demands
+ * @param {Function} log - Optional logger
+ * @returns {Object} Policy
+ */
+function defaultPolicy(scenario, unitList, missionDemandCount, log = null) {
+  const split = missionSplit(scenario);
+  if (split && missionDemandCount > 0) {
+    return weightedSplitPolicy(split, missionDemandCount, log);
+  }
+  return roundRobinPolicy(unitList);
+}
+
+/**
+ * Read-only snapshot of a unit's free resources
+ * @param {Object} pool - The unit's resource pools
+ * @param {number} time - Simulation time in hours
+ * @returns {Object} Frozen { aircraft, pilot, so, intel, control_station?, launch_recovery?, payloads } of { total, available }
+ */
+function availabilitySnapshot(pool, time) {
+  const entry = resource => Object.freeze({ total: resource.total, available: resource.availableAt(time) });
+  return Object.freeze({
+    aircraft: entry(pool.aircraft),
+    pilot: entry(pool.pilot),
+    so: entry(pool.so),
+    intel: entry(pool.intel),
+    ...(pool.controlStations ? { control_station: entry(pool.controlStations) } : {}),
+    ...(pool.launchRecovery ? { launch_recovery: entry(pool.launchRecovery) } : {}),
+    payloads: Object.freeze(Object.fromEntries(Object.entries(pool.payloads).map(([ptype, p]) => [ptype, entry(p)])))
+  });
+}
+
+/**
+ * Assign a mission demand through a policy at its demand time
+ * @param {Object} policy - Assignment policy
+ * @param {Object} ev - Mission demand event
+ * @param {Object} pools - Map of unit → resource pools
+ * @returns {string|null} Chosen unit
+ * @throws {Error} If the policy returns a unit that does not exist
+ */
+function assignUnit(policy, ev, pools) {
+  const units = unitNames(pools);
+  const unit = policy.selectUnit(Object.freeze({
+    time: ev.time,
+    mission_type: ev.mission_type,
+    demand_source: ev.demand_source,
+    units,
+    availability: name => {
+      if (!pools[name]) throw new Error(`availability: unknown unit '${name}'`);
+      return availabilitySnapshot(pools[name], ev.time);
+    }
+  }));
+  if (unit === null || unit === undefined) return null;
+  if (!pools[unit]) {
+    throw new Error(`assignment policy${policy.name ? ` '${policy.name}'` : ''} chose unknown unit '${unit}' (units: ${units.join(', ')})`);
+  }
+  return unit;
+}
+
+module.exports = {
+  validatePolicy,
+  roundRobinPolicy,
+  weightedSplitPolicy,
+  leastUtilizedPolicy,
+  missionSplit,
+  defaultPolicy,
+  availabilitySnapshot,
+  assignUnit
+};
@@ -0,0 +1,186 @@
+// Carry-over Module
+// A campaign run as consecutive days (runSimulationSequence) hands each day's end state to the next. The end state
+// (results.end_state, settings.endState) lists every hold still running at the horizon, per unit and pool, as the
+// hours it has left: a mission in progress, an aircraft in turnaround, a crew member in recovery after a shift.
+// settings.initialState seeds those holds into the next run's pools from t=0, and flight hours add up per unit
+
+const { unitNames } = require('./state');
+
+/**
+ * Hours each hold of an equipment pool (general and reserved) runs past the horizon
+ * @param {Object} pool - EquipmentPool
+ * @param {number} horizon - Horizon in hours
+ * @returns {Array<number>} Remaining hours, ascending
+ */
+function equipmentRemaining(pool, horizon) {
+  return [...pool.held, ...pool.heldReserved].filter(t => t > horizon).map(t => t - horizon).sort((a, b) => a - b);
+}
+
+/**
+ * Hours each booking of a window pool runs past the horizon (a later window counts from the horizon)
+ * @param {Object} pool - WindowPool
+ * @param {number} horizon - Horizon in hours
+ * @returns {Array<number>} Remaining hours, ascending
+ */
+function windowRemaining(pool, horizon) {
+  return pool.active.filter(b => b.end > horizon).map(b => b.end - horizon).sort((a, b) => a - b);
+}
+
+/**
+ * Hours each crew member stays unavailable past the horizon (shift plus recovery)
+ * @param {Object} queue - CrewQueue
+ * @param {number} horizon - Horizon in hours
+ * @returns {Array<number>} Remaining hours, ascending
+ */
+function crewRemaining(queue, horizon) {
+  return queue.crew.map(c => c.availableAt).filter(t => t > horizon).map(t => t - horizon).sort((a, b) => a - b);
+}
+
+/**
+ * End-of-run state for results.end_state
+ * @param {Object} pools - Map of unit → resource pools after Stage 5
+ * @param {number} horizon - Horizon in hours
+ * @param {Object|null} initialState - The state the run started from (settings.initialState), for flight hours
+ * @returns {Object} { horizon_hours, units: { unit: { holds, flight_hours } } }; holds has aircraft, pilot, so,
+ *                   intel, payloads: { type: [...] } and, when the unit has them, control_station, launch_recovery
+ *                   and roles: { role: [...] }, each a list of remaining hours
+ */
+function buildEndState(pools, horizon, initialState) {
+  const units = {};
+  for (const unit of unitNames(pools)) {
+    const pool = pools[unit];
+    const holds = {
+      aircraft: equipmentRemaining(pool.aircraft, horizon),
+      pilot: crewRemaining(pool.pilot, horizon),
+      so: crewRemaining(pool.so, horizon),
+      intel: crewRemaining(pool.intel, horizon),
+      payloads: Object.fromEntries(Object.entries(pool.payloads).map(([ptype, p]) => [ptype, equipmentRemaining(p, horizon)]))
+    };
+    if (pool.controlStations) holds.control_station = equipmentRemaining(pool.controlStations, horizon);
+    if (pool.launchRecovery) holds.launch_recovery = windowRemaining(pool.launchRecovery, horizon);
+    if (pool.roles && Object.keys(pool.roles).length > 0) {
+      holds.roles = Object.fromEntries(Object.entries(pool.roles).map(([role, q]) => [role, crewRemaining(q, horizon)]));
+    }
+    const carried = initialState?.units?.[unit]?.flight_hours || 0;
+    units[unit] = { holds, flight_hours: carried + pool.flightHours };
+  }
+  return { horizon_hours: horizon, units };
+}
+
+/**
+ * Check a settings.initialState value (a previous run's results.end_state)
+ * @param {*} value - Setting value
+ * @param {string} name - Setting name for error messages
+ * @returns {Object|null} The state, or null when unset
+ * @throws {Error} If it is not an end state or a hold is not a positive number
+ */
+function resolveInitialState(value, name) {
+  if (value === undefined || value === null) return null;
+  if (typeof value !== 'object' || Array.isArray(value) || !value.units || typeof value.units !== 'object') {
+    throw new Error(`${name} must be a previous run's results.end_state ({ units: { unit: { holds, flight_hours } } })`);
+  }
+  const checkList = (list, where) => {
+    if (!Array.isArray(list) || !list.every(h => typeof h === 'number' && Number.isFinite(h) && h > 0)) {
+      throw new Error(`${where} must be an array of positive hours`);
+    }
+  };
+  for (const [unit, entry] of Object.entries(value.units)) {
+    const where = `${name}.units['${unit}']`;
+    const flightHours = entry?.flight_hours ?? 0;
+    if (!(typeof flightHours === 'number' && flightHours >= 0)) {
+      throw new Error(`${where}.flight_hours must be a non-negative number, got ${flightHours}`);
+    }
+    for (const [resource, list] of Object.entries(entry?.holds || {})) {
+      if (resource === 'payloads' || resource === 'roles') {
+        for (const [key, sub] of Object.entries(list || {})) checkList(sub, `${where}.holds.${resource}['${key}']`);
+      } else {
+        checkList(list, `${where}.holds.${resource}`);
+      }
+    }
+  }
+  return value;
+}
+
+/**
+ * Seed a run's pools with the holds of settings.initialState, each from t=0 for its remaining hours
+ * @param {Object|null} initialState - Output of resolveInitialState
+ * @param {Object} pools - Map of unit → resource pools (Stage 4)
+ * @returns {number} Holds seeded
+ * @throws {Error} If the state names an unknown unit or pool, or holds more than the pool has free
+ */
+function seedCarriedHolds(initialState, pools) {
+  if (!initialState) return 0;
+  let seeded = 0;
+  for (const [unit, entry] of Object.entries(initialState.units)) {
+    const pool = pools[unit];
+    if (!pool) throw new Error(`settings.initialState: unknown unit '${unit}'`);
+    const seed = (resource, target, hours) => {
+      for (const until of hours || []) {
+        if (!target || !target.seedHold(until, 1)) {
+          throw new Error(`settings.initialState: ${unit} has too few free ${resource} for the carried-over holds`);
+        }
+        seeded++;
+      }
+    };
+    const holds = entry.holds || {};
+    seed('aircraft', pool.aircraft, holds.aircraft);
+    seed('control_station', pool.controlStations, holds.control_station);
+    seed('launch_recovery', pool.launchRecovery, holds.launch_recovery);
+    for (const [ptype, hours] of Object.entries(holds.payloads || {})) seed(`payload '${ptype}'`, pool.payloads[ptype], hours);
+    for (const role of ['pilot', 'so', 'intel']) seed(role, pool[role], holds[role]);
+    for (const [role, hours] of Object.entries(holds.roles || {})) seed(role, pool.roles?.[role], hours);
+  }
+  return seeded;
+}
+
+/**
+ * Add the numeric fields of counter objects, key by key
+ * @param {Array<Object>} objects - Counter objects (missing ones are skipped)
+ * @returns {Object} key → sum
+ */
+function sumCounters(objects) {
+  const sums = {};
+  for (const obj of objects) {
+    for (const [key, value] of Object.entries(obj || {})) {
+      if (typeof value === 'number') sums[key] = (sums[key] || 0) + value;
+    }
+  }
+  return sums;
+}
+
+/**
+ * Mission counters over a whole sequence: a mission still running at a day's end finishes during the next day as a
+ * carried hold, so it counts as completed there and only the last day's in_progress_at_horizon remains in progress
+ * @param {Array<Object>} counters - One missions (or by_type entry) object per day, in order
+ * @returns {Object} Summed counters
+ */
+function sequenceMissionCounters(counters) {
+  const sums = sumCounters(counters);
+  const last = counters[counters.length - 1];
+  if (sums.in_progress_at_horizon !== undefined) {
+    const carried = sums.in_progress_at_horizon - (last?.in_progress_at_horizon || 0);
+    sums.completed = (sums.completed || 0) + carried;
+    sums.in_progress_at_horizon = last?.in_progress_at_horizon || 0;
+  }
+  return sums;
+}
+
+/**
+ * Combined summary of a runSimulationSequence run
+ * @param {Array<Object>} days - Each day's results (with end_state), in order
+ * @returns {Object} { days, horizon_hours, missions, rejections, by_type, flight_hours: { unit: hours } }
+ */
+function summarizeSequence(days) {
+  const types = new Set(days.flatMap(day => Object.keys(day.by_type || {})));
+  return {
+    days: days.length,
+    horizon_hours: days.reduce((sum, day) => sum + day.horizon_hours, 0),
+    missions: sequenceMissionCounters(days.map(day => day.missions)),
+    rejections: sumCounters(days.map(day => day.rejections)),
+    by_type: Object.fromEntries([...types].map(type => [type, sequenceMissionCounters(days.map(day => day.by_type?.[type]))])),
+    flight_hours: Object.fromEntries(Object.entries(days[days.length - 1].end_state.units)
+      .map(([unit, entry]) => [unit, entry.flight_hours]))
+  };
+}
+
+module.exports = { buildEndState, resolveInitialState, seedCarriedHolds, summarizeSequence };
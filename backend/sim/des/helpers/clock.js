@@ -0,0 +1,52 @@
+// Clock Module
+// Simulation time resolution and horizon comparisons that tolerate float drift
+// Repeated `t += dt` leaves times like 0.9999999999999999 for a boundary that should be exactly 1
+
+// Event times on a fixed grid are snapped to this resolution; 1e-6 h is under 4 ms
+const TICKS_PER_HOUR = 1e6;
+
+// Two times closer than this are treated as equal in horizon comparisons
+const TIME_EPSILON_HOURS = 1e-9;
+
+/**
+ * Snap a time to the clock resolution
+ * @param {number} t - Time in hours
+ * @returns {number} Nearest whole tick (1 / TICKS_PER_HOUR hours)
+ */
+function snapTime(t) {
+  // Divide rather than multiply by the tick size so decimal grids (0.1 h) come out exact
+  return Math.round(t * TICKS_PER_HOUR) / TICKS_PER_HOUR;
+}
+
+/**
+ * Time of the k-th point on a fixed-interval grid, computed without accumulating error
+ * @param {number} start - Grid start in hours
+ * @param {number} step  - Interval in hours
+ * @param {number} k     - Zero-based index
+ * @returns {number} start + k * step, snapped to the clock resolution
+ */
+function gridTime(start, step, k) {
+  return snapTime(start + k * step);
+}
+
+/**
+ * True when t is strictly before the horizon (an event at the horizon itself is excluded)
+ * @param {number} t       - Time in hours
+ * @param {number} horizon - Horizon in hours
+ * @returns {boolean}
+ */
+function beforeHorizon(t, horizon) {
+  return t < horizon - TIME_EPSILON_HOURS;
+}
+
+/**
+ * True when t is at or before the horizon (a mission finishing at the horizon counts)
+ * @param {number} t       - Time in hours
+ * @param {number} horizon - Horizon in hours
+ * @returns {boolean}
+ */
+function withinHorizon(t, horizon) {
+  return t <= horizon + TIME_EPSILON_HOURS;
+}
+
+module.exports = { snapTime, gridTime, beforeHorizon, withinHorizon, TICKS_PER_HOUR, TIME_EPSILON_HOURS };
@@ -0,0 +1,111 @@
+// Config Module
+// Resolves runSimulation settings once, up front: every default is applied and every value checked here,
+// and the rest of the run reads the resolved object instead of settings. The same object is echoed in
+// results.resolved_config so a caller can see exactly what the engine ran with
+
+const { isValidSeed } = require('./random');
+const { validateLimits } = require('./limits');
+const { validateTrace } = require('./trace');
+const { resolveMaxOutputBytes } = require('./outputsize');
+const { resolvePriorityWeights } = require('./priority');
+const { resolveInitialState } = require('./carryover');
+const { DEFAULT_PEAK_WINDOW_HOURS, DEFAULT_REJECTION_DENSITY_BUCKET_HOURS } = require('./throughput');
+
+// How long each resource type is held per mission (see settings.resourceHoldModel)
+const RESOURCE_HOLD_MODELS = ['legacy', 'per_resource'];
+
+// 'expected_value' replaces every distribution with its mean (see settings.mode)
+const MODES = ['stochastic', 'expected_value'];
+
+/**
+ * Apply defaults to runSimulation settings and check them
+ * @param {Object} settings - Simulation settings (see runSimulation)
+ * @returns {Object} Resolved settings; seed is null for an unseeded run, optional features are null when off
+ * @throws {Error} On the first invalid setting
+ */
+function resolveSettings(settings) {
+  if (settings.seed !== undefined && settings.seed !== null && !isValidSeed(settings.seed)) {
+    throw new Error(`settings.seed must be an integer, got ${settings.seed}`);
+  }
+  validateLimits(settings.limits);
+  const mode = settings.mode ?? 'stochastic';
+  if (!MODES.includes(mode)) {
+    throw new Error(`settings.mode must be one of ${MODES.join(', ')}, got ${mode}`);
+  }
+  const resourceHoldModel = settings.resourceHoldModel ?? 'legacy';
+  if (!RESOURCE_HOLD_MODELS.includes(resourceHoldModel)) {
+    throw new Error(`settings.resourceHoldModel must be one of ${RESOURCE_HOLD_MODELS.join(', ')}, got ${resourceHoldModel}`);
+  }
+
+  const peakWindowHours = settings.peakWindowHours ?? DEFAULT_PEAK_WINDOW_HOURS;
+  if (!(typeof peakWindowHours === 'number' && peakWindowHours > 0)) {
+    throw new Error(`settings.peakWindowHours must be a positive number, got ${peakWindowHours}`);
+  }
+
+  const rejectionDensityBucketHours = settings.rejectionDensityBucketHours ?? DEFAULT_REJECTION_DENSITY_BUCKET_HOURS;
+  if (!(typeof rejectionDensityBucketHours === 'number' && rejectionDensityBucketHours > 0)) {
+    throw new Error(`settings.rejectionDensityBucketHours must be a positive number, got ${rejectionDensityBucketHours}`);
+  }
+
+  const sampling = settings.timelineRejectionSampling ?? null;
+  if (sampling !== null && !(typeof sampling === 'number' && sampling > 0 && sampling <= 1)) {
+    throw new Error(`settings.timelineRejectionSampling must be a number in (0, 1], got ${sampling}`);
+  }
+
+  for (const key of ['collectTimeline', 'echoConfig', 'endState']) {
+    if (settings[key] !== undefined && typeof settings[key] !== 'boolean') {
+      throw new Error(`settings.${key} must be a boolean, got ${settings[key]}`);
+    }
+  }
+
+  const streaming = typeof settings.onTimelineChunk === 'function';
+  return {
+    // Expected-value runs stay reproducible without a seed (crew_distribution 'random' still shuffles)
+    seed: mode === 'expected_value' ? (settings.seed ?? 0) : (settings.seed ?? null),
+    mode,
+    resourceHoldModel,
+    peakWindowHours,
+    rejectionDensityBucketHours,
+    timelineRejectionSampling: sampling,
+    collectTimeline: settings.collectTimeline ?? true,
+    onTimelineChunk: streaming ? settings.onTimelineChunk : null,
+    timelineChunkSize: streaming ? settings.timelineChunkSize ?? null : null,
+    limits: settings.limits ?? null,
+    maxOutputBytes: resolveMaxOutputBytes(settings.maxOutputBytes, 'settings.maxOutputBytes'),
+    trace: validateTrace(settings.trace),
+    priorityWeights: resolvePriorityWeights(settings.priorityWeights, 'settings.priorityWeights'),
+    initialState: resolveInitialState(settings.initialState, 'settings.initialState'),
+    endState: settings.endState === true,
+    overrides: settings.overrides && settings.overrides.units ? settings.overrides.units : null,
+    missionDetail: settings.missionDetail === true,
+    emitResourceLevels: settings.emitResourceLevels === true,
+    hourlyProfile: settings.hourlyProfile === true,
+    sortedOutput: settings.sortedOutput === true,
+    echoConfig: settings.echoConfig ?? true,
+    logLevel: settings.logLevel ?? null
+  };
+}
+
+/**
+ * The resolved configuration as results.resolved_config reports it (plain JSON)
+ * @param {Object} resolved - Output of resolveSettings
+ * @param {Object} scenario - Scenario after unit normalization and field canonicalization
+ * @param {number} horizon - Horizon the run used (Stage 1)
+ * @returns {Object} { scenario, settings }
+ */
+function echoResolvedConfig(resolved, scenario, horizon) {
+  const { onTimelineChunk, trace, echoConfig, ...plain } = resolved;
+  return {
+    scenario: { ...scenario, horizon_hours: horizon },
+    settings: {
+      ...plain,
+      timelineStreaming: onTimelineChunk !== null,
+      trace: trace && {
+        missionTypes: trace.missionTypes ? [...trace.missionTypes] : null,
+        timeRange: trace.timeRange
+      }
+    }
+  };
+}
+
+module.exports = { resolveSettings, echoResolvedConfig, RESOURCE_HOLD_MODELS, MODES };
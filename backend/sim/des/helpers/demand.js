@@ -1,7 +1,12 @@
 // Demand Generation Module
 // Creates mission and duty demand events
 
-const { sampleDist } = require('./distributions');
+const { sampleDist, distMean, isExpectedValueMode } = require('./distributions');
+const { gridTime, beforeHorizon, withinHorizon } = require('./clock');
+const { scenarioError } = require('./validation');
+
+// Smallest gap (hours) between successive renewal demands; shorter sampled gaps are clamped up to it
+const RENEWAL_MIN_GAP_HOURS = 0.001;
 
 /**
  * Build a Map of mission types indexed by name for fast lookup
@@ -11,34 +16,312 @@ const { sampleDist } = require('./distributions');
  */
 function buildMissionMap(scenario) {
   const map = new Map();
-  for (const mt of scenario.mission_types) {
+  for (const [i, mt] of scenario.mission_types.entries()) {
+    const where = `mission_types[${i}]`;
+    validateCrewFlex(mt, where);
+    validateFlightTimeModifiers(mt, where);
+    validateCrewScaling(mt, where);
+    validateLaunchWindows(mt, where);
+    if (mt.max_concurrent !== undefined && !(Number.isInteger(mt.max_concurrent) && mt.max_concurrent >= 0)) {
+      throw scenarioError(`${where}.max_concurrent`, `must be a non-negative integer, got ${mt.max_concurrent}`);
+    }
+    if (mt.priority !== undefined && !(Number.isInteger(mt.priority) && mt.priority >= 1)) {
+      throw scenarioError(`${where}.priority`, `must be a positive integer, got ${mt.priority}`);
+    }
+    if (mt.requires_launch_recovery !== undefined && typeof mt.requires_launch_recovery !== 'boolean') {
+      throw scenarioError(`${where}.requires_launch_recovery`, `must be a boolean, got ${mt.requires_launch_recovery}`);
+    }
     map.set(mt.name, mt);
   }
   return map;
 }
 
+/**
+ * Validate a mission type's optional crew_flex spec
+ * Each allowed composition must fill exactly total_seats pilot + SO seats (when total_seats is given)
+ * @param {Object} mt - Mission type definition
+ * @param {string} where - Scenario path of the mission type, e.g. 'mission_types[0]'
+ * @throws {Error} With code 'INVALID_SCENARIO' if the spec is malformed
+ */
+function validateCrewFlex(mt, where) {
+  const flex = mt.crew_flex;
+  if (flex === undefined || flex === null) return;
+  const compositions = flex.allowed_compositions;
+  if (!Array.isArray(compositions) || compositions.length === 0) {
+    throw scenarioError(`${where}.crew_flex.allowed_compositions`, 'must be a non-empty array');
+  }
+  compositions.forEach((c, i) => {
+    const pilot = c?.pilot || 0;
+    const so = c?.so || 0;
+    if (!Number.isInteger(pilot) || !Number.isInteger(so) || pilot < 0 || so < 0) {
+      throw scenarioError(`${where}.crew_flex.allowed_compositions[${i}]`, 'must have non-negative integer pilot/so counts');
+    }
+    if (flex.total_seats !== undefined && pilot + so !== flex.total_seats) {
+      throw scenarioError(`${where}.crew_flex.allowed_compositions[${i}]`, `fills ${pilot + so} seats, expected total_seats ${flex.total_seats}`);
+    }
+  });
+}
+
+/**
+ * Validate a mission type's optional flight_time_modifiers map
+ * Each entry is a multiplier (number) or { multiplier, add_hours }
+ * @param {Object} mt - Mission type definition
+ * @param {string} where - Scenario path of the mission type
+ * @throws {Error} With code 'INVALID_SCENARIO' if an entry is malformed
+ */
+function validateFlightTimeModifiers(mt, where) {
+  const modifiers = mt.flight_time_modifiers;
+  if (modifiers === undefined || modifiers === null) return;
+  if (typeof modifiers !== 'object' || Array.isArray(modifiers)) {
+    throw scenarioError(`${where}.flight_time_modifiers`, 'must be an object keyed by payload type');
+  }
+  for (const [ptype, mod] of Object.entries(modifiers)) {
+    const { multiplier = 1, add_hours: addHours = 0 } = typeof mod === 'number' ? { multiplier: mod } : (mod || {});
+    if (typeof multiplier !== 'number' || !Number.isFinite(multiplier) || multiplier < 0) {
+      throw scenarioError(`${where}.flight_time_modifiers['${ptype}']`, 'multiplier must be a non-negative number');
+    }
+    if (typeof addHours !== 'number' || !Number.isFinite(addHours)) {
+      throw scenarioError(`${where}.flight_time_modifiers['${ptype}'].add_hours`, 'must be a finite number');
+    }
+  }
+}
+
+/**
+ * Validate a mission type's optional required_aircrew.per_flight_hours (crew swaps on long sorties)
+ * @param {Object} mt - Mission type definition
+ * @param {string} where - Scenario path of the mission type
+ * @throws {Error} With code 'INVALID_SCENARIO' if it is not a positive number or is combined with crew_rotation
+ */
+function validateCrewScaling(mt, where) {
+  const perFlightHours = mt.required_aircrew?.per_flight_hours;
+  if (perFlightHours === undefined || perFlightHours === null) return;
+  if (typeof perFlightHours !== 'number' || !(perFlightHours > 0) || !Number.isFinite(perFlightHours)) {
+    throw scenarioError(`${where}.required_aircrew.per_flight_hours`, `must be a positive number, got ${perFlightHours}`);
+  }
+  if (mt.crew_rotation?.enabled) {
+    throw scenarioError(`${where}.required_aircrew.per_flight_hours`, 'is set with crew_rotation; give either crew_rotation or per_flight_hours, not both');
+  }
+}
+
+// What happens to a demand arriving outside every launch window
+const LAUNCH_WINDOW_POLICIES = ['defer', 'reject'];
+
+/**
+ * Validate a mission type's optional launch_windows ([{ start_hour_of_day, end_hour_of_day }]) and launch_window_policy
+ * A window with start > end wraps past midnight (22 → 4); start and end must differ
+ * @param {Object} mt - Mission type definition
+ * @param {string} where - Scenario path of the mission type, e.g. 'mission_types[0]'
+ * @throws {Error} With code 'INVALID_SCENARIO' if a window is malformed
+ */
+function validateLaunchWindows(mt, where) {
+  const windows = mt.launch_windows;
+  if (windows === undefined || windows === null) {
+    if (mt.launch_window_policy !== undefined) {
+      throw scenarioError(`${where}.launch_window_policy`, 'needs launch_windows');
+    }
+    return;
+  }
+  if (!Array.isArray(windows) || windows.length === 0) {
+    throw scenarioError(`${where}.launch_windows`, 'must be a non-empty array of { start_hour_of_day, end_hour_of_day }');
+  }
+  const isHour = h => typeof h === 'number' && Number.isFinite(h) && h >= 0 && h <= 24;
+  windows.forEach((w, i) => {
+    if (!isHour(w?.start_hour_of_day) || !isHour(w?.end_hour_of_day)) {
+      throw scenarioError(`${where}.launch_windows[${i}]`, 'hours must be numbers in [0, 24]');
+    }
+    if (w.start_hour_of_day % 24 === w.end_hour_of_day % 24) {
+      throw scenarioError(`${where}.launch_windows[${i}]`, 'must not start and end at the same hour');
+    }
+  });
+  const policy = mt.launch_window_policy ?? 'defer';
+  if (!LAUNCH_WINDOW_POLICIES.includes(policy)) {
+    throw scenarioError(`${where}.launch_window_policy`, `must be one of ${LAUNCH_WINDOW_POLICIES.join(', ')}, got ${policy}`);
+  }
+}
+
+/**
+ * True when a mission type may launch at t (always, without launch_windows)
+ * Hours of day count from t=0 at 0000; a window includes its start and excludes its end
+ * @param {Object} mt - Mission type definition
+ * @param {number} t - Time in hours
+ * @returns {boolean}
+ */
+function inLaunchWindow(mt, t) {
+  if (!mt.launch_windows) return true;
+  const hour = ((t % 24) + 24) % 24;
+  return mt.launch_windows.some(({ start_hour_of_day: start, end_hour_of_day: end }) => (
+    start < end ? hour >= start && hour < end : hour >= start || hour < end % 24
+  ));
+}
+
+/**
+ * Earliest time at or after t when one of the mission type's launch windows opens
+ * @param {Object} mt - Mission type definition (with launch_windows)
+ * @param {number} t - Time in hours
+ * @returns {number} Opening time in hours
+ */
+function nextLaunchWindowOpening(mt, t) {
+  const day = Math.floor(t / 24) * 24;
+  return Math.min(...mt.launch_windows.map(({ start_hour_of_day: start }) => {
+    const opening = day + (start % 24);
+    return opening >= t ? opening : opening + 24;
+  }));
+}
+
+/**
+ * Number of pilot/SO crews a sortie needs: one per started per_flight_hours of flight time
+ * @param {Object} mt - Mission type definition
+ * @param {number} flight - Flight time in hours (after flight_time_modifiers)
+ * @returns {number} Crews (1 without required_aircrew.per_flight_hours)
+ */
+function crewShiftCount(mt, flight) {
+  const perFlightHours = mt.required_aircrew?.per_flight_hours;
+  if (!perFlightHours) return 1;
+  // Tolerate float noise so a 16h flight with 8h shifts needs 2 crews, not 3
+  return Math.max(1, Math.ceil(flight / perFlightHours - 1e-9));
+}
+
+/**
+ * Apply a mission type's flight_time_modifiers for the payloads it mounts
+ * Multipliers compound; additive hours are applied after all multipliers
+ * @param {Object} mt - Mission type definition
+ * @param {number} flight - Sampled (or average) flight time in hours
+ * @returns {number} Modified flight time in hours
+ * @throws {Error} If the modified flight time is negative
+ */
+function applyFlightTimeModifiers(mt, flight) {
+  const modifiers = mt.flight_time_modifiers;
+  if (!modifiers) return flight;
+  let multiplier = 1;
+  let addHours = 0;
+  for (const ptype of mt.required_payload_types || []) {
+    const mod = modifiers[ptype];
+    if (mod === undefined || mod === null) continue;
+    if (typeof mod === 'number') {
+      multiplier *= mod;
+    } else {
+      multiplier *= mod.multiplier ?? 1;
+      addHours += mod.add_hours || 0;
+    }
+  }
+  const modified = flight * multiplier + addHours;
+  if (modified < 0) {
+    throw scenarioError(`mission_types['${mt.name}'].flight_time_modifiers`, `give a negative flight time (${modified.toFixed(3)}h from ${flight.toFixed(3)}h)`);
+  }
+  return modified;
+}
+
+/**
+ * Units a demand entry is pinned to (`unit: 'VMU-3'` or `units: ['VMU-3', ...]`)
+ * @param {Object} d - Demand entry
+ * @param {string} where - Scenario path, for error messages
+ * @returns {Array<string>|null} Pinned unit names, or null when the entry is not pinned
+ * @throws {Error} If the pin is malformed
+ */
+function demandUnits(d, where) {
+  if (d.units === undefined && d.unit === undefined) return null;
+  if (d.units !== undefined && d.unit !== undefined) {
+    throw scenarioError(where, 'gives both unit and units; give either unit or units, not both');
+  }
+  const units = d.units !== undefined ? d.units : [d.unit];
+  if (!Array.isArray(units) || units.length === 0 || units.some(u => typeof u !== 'string' || u === '')) {
+    throw scenarioError(`${where}.units`, 'must be a non-empty array of unit names');
+  }
+  return [...new Set(units)];
+}
+
+/**
+ * Read a demand entry's optional retry policy
+ * @param {Object} d - Demand entry
+ * @param {string} where - Entry label for error messages
+ * @returns {Object|null} { max_attempts, delay }, or null when rejected demand is not resubmitted
+ * @throws {Error} If max_attempts is not a positive integer or delay is not a distribution
+ */
+function demandRetry(d, where) {
+  if (d.retry === undefined || d.retry === null) return null;
+  const { max_attempts: maxAttempts, delay } = d.retry;
+  if (!Number.isInteger(maxAttempts) || maxAttempts < 1) {
+    throw scenarioError(`${where}.retry.max_attempts`, `must be a positive integer, got ${maxAttempts}`);
+  }
+  if (!delay || typeof delay !== 'object' || typeof delay.type !== 'string') {
+    throw scenarioError(`${where}.retry.delay`, 'must be a distribution ({ type, ... })');
+  }
+  return { max_attempts: maxAttempts, delay };
+}
+
+/**
+ * Read a scheduled demand entry's demand times (e.g. one historical day of actual demand)
+ * @param {Object} d - Demand entry with type 'scheduled'
+ * @param {string} where - Entry label for error messages
+ * @returns {Array<number>} at_hours in ascending order
+ * @throws {Error} If at_hours is not an array of non-negative numbers
+ */
+function demandSchedule(d, where) {
+  if (!Array.isArray(d.at_hours) || d.at_hours.some(t => !(typeof t === 'number' && Number.isFinite(t) && t >= 0))) {
+    throw scenarioError(`${where}.at_hours`, 'must be an array of non-negative demand times in hours');
+  }
+  return d.at_hours.slice().sort((a, b) => a - b);
+}
+
+/**
+ * Read a demand entry's optional rate_uncertainty (a multiplier on rate_per_hour, sampled once per run)
+ * @param {Object} d - Demand entry
+ * @param {string} where - Entry label for error messages
+ * @returns {Object|null} Multiplier distribution, or null when the rate is fixed
+ * @throws {Error} If it is not a distribution or the entry is not Poisson
+ */
+function demandRateUncertainty(d, where) {
+  if (d.rate_uncertainty === undefined || d.rate_uncertainty === null) return null;
+  if (typeof d.rate_uncertainty !== 'object' || typeof d.rate_uncertainty.type !== 'string') {
+    throw scenarioError(`${where}.rate_uncertainty`, 'must be a distribution ({ type, ... })');
+  }
+  if ((d.type || 'poisson') !== 'poisson') {
+    throw scenarioError(`${where}.rate_uncertainty`, `scales rate_per_hour, so it needs a poisson entry (got ${d.type})`);
+  }
+  return d.rate_uncertainty;
+}
+
 /**
  * Generate mission and duty demand events over the simulation horizon
  * Supports deterministic (fixed interval) and Poisson (random) demand patterns
  * @param {Object} scenario - Scenario configuration
  * @param {number} scenario.horizon_hours - Simulation duration in hours
- * @param {Array} scenario.demand - Mission demand specifications
+ * @param {Array} scenario.demand - Mission demand specifications (optional `name` labels each entry's demand_source;
+ *   optional `unit` / `units` pins the entry's missions to those units, carried as pinned_units;
+ *   optional `retry` resubmits rejected missions, carried as retry with attempt 1;
+ *   optional `rate_uncertainty` on Poisson entries scales rate_per_hour by one sample per run)
  * @param {Array} scenario.mission_types - Mission type definitions with crew requirements
  * @param {Object} scenario.duty_requirements - Duty shift requirements (ODO, SDO, etc.)
+ * @param {Object} watchdog - Optional limits watchdog (see helpers/limits.js), checked as events are generated
+ * @param {Object} demandStats - Optional counters updated during generation ({ renewal_gap_clamps }, plus
+ *   realized_rates: demand_source → sampled rate_per_hour for entries with rate_uncertainty)
  * @returns {Array<Object>} Sorted array of demand events with time and type
  */
-function generateDemand(scenario) {
+function generateDemand(scenario, watchdog = null, demandStats = null) {
   const horizon = scenario.horizon_hours;
   const events = [];
+  // Add an event, tripping the watchdog before a runaway scenario exhausts memory
+  const addEvent = event => {
+    events.push(event);
+    if (watchdog) watchdog.checkGenerated(events.length);
+  };
   const demandList = scenario.demand || [];
 
   // Build mission type lookup for crew requirements
   const missionTypeMap = buildMissionMap(scenario);
 
   // Generate mission demand events
-  for (const d of demandList) {
+  for (let demandIndex = 0; demandIndex < demandList.length; demandIndex++) {
+    const d = demandList[demandIndex];
     const typ = d.type || 'poisson';
+    // Name carried through to results so entries for the same mission type stay distinguishable
+    const demandSource = d.name || `demand[${demandIndex}]`;
     const missionType = missionTypeMap.get(d.mission_type);
+    const pinnedUnits = demandUnits(d, `demand[${demandIndex}]`);
+    const pin = pinnedUnits ? { pinned_units: pinnedUnits } : {};
+    const retry = demandRetry(d, `demand[${demandIndex}]`);
+    const resubmit = retry ? { retry, attempt: 1 } : {};
+    const rateUncertainty = demandRateUncertainty(d, `demand[${demandIndex}]`);
     const requiredAircrew = missionType?.required_aircrew || {};
     const requiresPilot = requiredAircrew.pilot || 0;
     const requiresSO = requiredAircrew.so || 0;
@@ -46,33 +329,88 @@ function generateDemand(scenario) {
 
     // Deterministic demand: fixed intervals
     if (typ === 'deterministic') {
-      const every = d.every_hours || d.interval_hours || 1;
+      const every = d.every_hours || 1;
       if (every <= 0) continue;
+      const start = (d.start_at_hours != null) ? d.start_at_hours : 0;
+      // k-th demand at start + k * every, so long horizons do not drift off the grid
+      for (let k = 0, t = start; beforeHorizon(t, horizon); t = gridTime(start, every, ++k)) {
+        addEvent({
+          time: t,
+          type: 'mission_demand',
+          mission_type: d.mission_type,
+          demand_source: demandSource,
+          ...pin,
+          ...resubmit,
+          requires_pilot: requiresPilot,
+          requires_so: requiresSO,
+          requires_intel: requiresIntel
+        });
+      }
+      // Renewal demand: gaps drawn from an arbitrary distribution (e.g. fitted to empirical inter-arrivals)
+    } else if (typ === 'renewal') {
+      if (!d.interarrival) continue;
       let t = (d.start_at_hours != null) ? d.start_at_hours : 0;
-      while (t < horizon) {
-        events.push({
+      while (beforeHorizon(t, horizon)) {
+        addEvent({
           time: t,
           type: 'mission_demand',
           mission_type: d.mission_type,
+          demand_source: demandSource,
+          ...pin,
+          ...resubmit,
+          requires_pilot: requiresPilot,
+          requires_so: requiresSO,
+          requires_intel: requiresIntel
+        });
+        let gap = sampleDist(d.interarrival);
+        if (!(gap >= RENEWAL_MIN_GAP_HOURS)) {
+          gap = RENEWAL_MIN_GAP_HOURS;
+          if (demandStats) demandStats.renewal_gap_clamps++;
+        }
+        t += gap;
+      }
+      // Scheduled demand: given demand times, e.g. a replayed historical day
+    } else if (typ === 'scheduled') {
+      for (const t of demandSchedule(d, `demand[${demandIndex}]`)) {
+        if (!beforeHorizon(t, horizon)) break;
+        addEvent({
+          time: t,
+          type: 'mission_demand',
+          mission_type: d.mission_type,
+          demand_source: demandSource,
+          ...pin,
+          ...resubmit,
           requires_pilot: requiresPilot,
           requires_so: requiresSO,
           requires_intel: requiresIntel
         });
-        t += every;
       }
       // Poisson demand: random intervals based on exponential distribution
+      // (expected-value mode: exactly every 1 / rate hours, first at 1 / rate)
     } else {
-      const rate = d.rate_per_hour || 0;
+      let rate = d.rate_per_hour || 0;
+      // Demand uncertainty: one multiplier for the whole run, negative samples meaning no demand
+      if (rateUncertainty) {
+        rate = Math.max(0, rate * sampleDist(rateUncertainty));
+        if (demandStats) {
+          if (!demandStats.realized_rates) demandStats.realized_rates = {};
+          demandStats.realized_rates[demandSource] = rate;
+        }
+      }
       if (rate <= 0) continue;
+      const expected = isExpectedValueMode();
       let t = 0;
-      while (t < horizon) {
-        const dt = sampleDist({ type: 'exponential', rate_per_hour: rate });
-        t += dt; // Advance to next demand time
-        if (t <= horizon) {
-          events.push({
+      for (let k = 1; beforeHorizon(t, horizon); k++) {
+        // Advance to next demand time
+        t = expected ? gridTime(0, 1 / rate, k) : t + sampleDist({ type: 'exponential', rate_per_hour: rate });
+        if (withinHorizon(t, horizon)) {
+          addEvent({
             time: t,
             type: 'mission_demand',
             mission_type: d.mission_type,
+            demand_source: demandSource,
+            ...pin,
+            ...resubmit,
             requires_pilot: requiresPilot,
             requires_so: requiresSO
           });
@@ -107,7 +445,7 @@ function generateDemand(scenario) {
 
       const preAvg = preSpec?.mean || preSpec?.min || 0;
       const transitIn = mt.flight_time?.transit_in_hours || 0;
-      const flightAvg = mt.flight_time?.mean || mt.flight_time?.min || 0;
+      const flightAvg = applyFlightTimeModifiers(mt, mt.flight_time?.mean || mt.flight_time?.min || 0);
       const transitOut = mt.flight_time?.transit_out_hours || 0;
       const postAvg = postSpec?.mean || postSpec?.min || 0;
 
@@ -151,8 +489,7 @@ function generateDemand(scenario) {
     const isODO = dutyType.toLowerCase() === 'odo';
 
     // Start at the configured start hour (e.g., if start_hour=8, first shift is at t=8)
-    let t = startHour;
-    while (t < horizon) {
+    for (let k = 0, t = startHour; beforeHorizon(t, horizon); t = gridTime(startHour, shiftInterval, ++k)) {
       // For ODO, only create duty demand if there's an active mission during this shift
       if (isODO) {
         const shiftEnd = t + hoursPerShift;
@@ -162,12 +499,11 @@ function generateDemand(scenario) {
         });
 
         if (!hasActiveMission) {
-          t += shiftInterval;
           continue; // Skip this ODO shift - no mission activity
         }
       }
 
-      events.push({
+      addEvent({
         time: t,
         type: 'duty_demand',
         duty_type: dutyType,
@@ -178,7 +514,6 @@ function generateDemand(scenario) {
         duty_recovery_hours: config.duty_recovery_hours || 0,
         respect_work_schedule: config.respect_work_schedule ?? false
       });
-      t += shiftInterval;
     }
   }
 
@@ -195,4 +530,49 @@ function generateDemand(scenario) {
   return events;
 }
 
-module.exports = { buildMissionMap, generateDemand };
+/**
+ * Analytic expected mission count for each demand entry over the horizon
+ * Deterministic and scheduled entries are counted exactly; Poisson entries give rate × horizon (times the mean
+ * rate_uncertainty multiplier) and renewal
+ * entries (horizon − start) / mean inter-arrival, so a misconfigured rate stands out without a run
+ * @param {Object} scenario - Scenario configuration (horizon in hours)
+ * @returns {Array<Object>} One row per demand entry ({ demand_source, mission_type, type, expected });
+ *   expected is null when the entry has no finite mean inter-arrival
+ */
+function expectedDemandCounts(scenario) {
+  const horizon = scenario.horizon_hours;
+  return (scenario.demand || []).map((d, demandIndex) => {
+    const typ = d.type || 'poisson';
+    const start = (d.start_at_hours != null) ? d.start_at_hours : 0;
+    const window = Math.max(0, horizon - start);
+    let expected;
+    if (typ === 'deterministic') {
+      const every = d.every_hours || 1;
+      expected = every <= 0 || !beforeHorizon(start, horizon) ? 0 : Math.ceil((horizon - start) / every - 1e-9);
+    } else if (typ === 'scheduled') {
+      expected = demandSchedule(d, `demand[${demandIndex}]`).filter(t => beforeHorizon(t, horizon)).length;
+    } else if (typ === 'renewal') {
+      const mean = d.interarrival ? distMean(d.interarrival) : 0;
+      expected = mean > 0 && Number.isFinite(mean) ? window / mean : null;
+    } else {
+      const multiplier = d.rate_uncertainty ? distMean(d.rate_uncertainty) : 1;
+      expected = Math.max(0, (d.rate_per_hour || 0) * multiplier) * Math.max(0, horizon);
+    }
+    return {
+      demand_source: d.name || `demand[${demandIndex}]`,
+      mission_type: d.mission_type,
+      type: typ,
+      expected: expected === null ? null : Number(expected.toFixed(2))
+    };
+  });
+}
+
+module.exports = {
+  buildMissionMap,
+  generateDemand,
+  applyFlightTimeModifiers,
+  crewShiftCount,
+  expectedDemandCounts,
+  inLaunchWindow,
+  nextLaunchWindowOpening
+};
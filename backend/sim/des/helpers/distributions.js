@@ -3,6 +3,105 @@
 // "Sampling" means generating random values based on defined statistical distributions
 
 const { logWithLocation } = require('../../../utils');
+const { random } = require('./random');
+
+// Distribution types sampleDist understands ('deterministic' when type is left out)
+const DISTRIBUTION_TYPES = ['deterministic', 'exponential', 'triangular', 'lognormal'];
+
+// Expected-value mode (settings.mode = 'expected_value'): sampleDist returns each distribution's mean
+let expectedValueMode = false;
+
+/**
+ * Switch expected-value mode on or off
+ * @param {boolean} on - True to return means instead of samples
+ * @returns {boolean} The previous setting (pass back to restore it)
+ */
+function setExpectedValueMode(on) {
+  const previous = expectedValueMode;
+  expectedValueMode = on === true;
+  return previous;
+}
+
+/**
+ * True while expected-value mode is on
+ * @returns {boolean}
+ */
+function isExpectedValueMode() {
+  return expectedValueMode;
+}
+
+/**
+ * Analytic mean of a distribution, with the same parameter defaults as sampleDist
+ * @param {Object} spec - Distribution specification (see sampleDist)
+ * @returns {number} Mean in hours
+ */
+function distMean(spec) {
+  if (!spec) return 0;
+  const t = spec.type || 'deterministic';
+  if (t === 'deterministic') {
+    if (typeof spec.value_hours === 'number') return spec.value_hours;
+    if (typeof spec.value === 'number') return spec.value;
+    return 0;
+  }
+  // Mean of an exponential gap is 1 / λ
+  if (t === 'exponential') return 1 / (spec.rate_per_hour || spec.rate || 1);
+  // Mean of a triangular distribution is (a + m + b) / 3
+  if (t === 'triangular') return (spec.a + spec.m + spec.b) / 3;
+  // Mean of a lognormal is exp(μ + σ² / 2)
+  if (t === 'lognormal') {
+    const mu = spec.mu || 0;
+    const sigma = spec.sigma || 1;
+    return Math.exp(mu + (sigma * sigma) / 2);
+  }
+  return 0;
+}
+
+/**
+ * Inverse of the standard normal CDF (Acklam's rational approximation, relative error < 1.2e-9)
+ * @param {number} p - Probability in (0, 1)
+ * @returns {number} z with Φ(z) = p
+ */
+function normalQuantile(p) {
+  const a = [-3.969683028665376e+01, 2.209460984245205e+02, -2.759285104469687e+02, 1.383577518672690e+02, -3.066479806614716e+01, 2.506628277459239e+00];
+  const b = [-5.447609879822406e+01, 1.615858368580409e+02, -1.556989798598866e+02, 6.680131188771972e+01, -1.328068155288572e+01];
+  const c = [-7.784894002430293e-03, -3.223964580411365e-01, -2.400758277161838e+00, -2.549732539343734e+00, 4.374664141464968e+00, 2.938163982698783e+00];
+  const d = [7.784695709041462e-03, 3.224671290700398e-01, 2.445134137142996e+00, 3.754408661907416e+00];
+  const low = 0.02425;
+  if (p < low) {
+    const q = Math.sqrt(-2 * Math.log(p));
+    return (((((c[0] * q + c[1]) * q + c[2]) * q + c[3]) * q + c[4]) * q + c[5]) / ((((d[0] * q + d[1]) * q + d[2]) * q + d[3]) * q + 1);
+  }
+  if (p > 1 - low) return -normalQuantile(1 - p);
+  const q = p - 0.5;
+  const r = q * q;
+  return (((((a[0] * r + a[1]) * r + a[2]) * r + a[3]) * r + a[4]) * r + a[5]) * q /
+    (((((b[0] * r + b[1]) * r + b[2]) * r + b[3]) * r + b[4]) * r + 1);
+}
+
+/**
+ * Value of a distribution at cumulative probability u (inverse CDF), with the same parameter defaults as sampleDist
+ * Used for stratified (Latin hypercube) draws, where u comes from a stratum rather than straight from random()
+ * @param {Object} spec - Distribution specification (see sampleDist)
+ * @param {number} u    - Cumulative probability in [0, 1)
+ * @returns {number} Value in hours
+ */
+function distQuantile(spec, u) {
+  if (!spec) return 0;
+  const t = spec.type || 'deterministic';
+  if (t === 'exponential') return -Math.log(1 - u) / (spec.rate_per_hour || spec.rate || 1);
+  if (t === 'triangular') {
+    const { a, m, b } = spec;
+    const c = (m - a) / (b - a);
+    if (u < c) return a + Math.sqrt(u * (b - a) * (m - a));
+    return b - Math.sqrt((1 - u) * (b - a) * (b - m));
+  }
+  if (t === 'lognormal') {
+    // u = 0 has no finite normal quantile; nudge it inside (0, 1)
+    const p = Math.min(Math.max(u, Number.EPSILON), 1 - Number.EPSILON);
+    return Math.exp((spec.mu || 0) + (spec.sigma || 1) * normalQuantile(p));
+  }
+  return distMean(spec);
+}
 
 /**
  * Sample a value from a specified distribution
@@ -26,6 +125,7 @@ const { logWithLocation } = require('../../../utils');
  */
 function sampleDist(spec) {
   if (!spec) return 0;
+  if (expectedValueMode) return distMean(spec);
   const t = spec.type || 'deterministic';
 
   // deterministic distribution - fixed value
@@ -40,7 +140,7 @@ function sampleDist(spec) {
   // plain english: returns a random value where shorter times are more likely, based on rate (λ)
   if (t === 'exponential') {
     const rate = spec.rate_per_hour || spec.rate || 1; // lambda
-    const u = Math.random(); // random number between 0 and 1
+    const u = random(); // random number between 0 and 1
     const poisson = -Math.log(1 - u) / rate; // hours
     logWithLocation('sampleDist - exponential', { rate, u, poisson });
     return poisson;
@@ -50,7 +150,7 @@ function sampleDist(spec) {
   // plain english: returns a random value most likely near the mode, but within min and max
   if (t === 'triangular') {
     const { a, m, b } = spec; // hours
-    const u = Math.random();
+    const u = random();
     const c = (m - a) / (b - a);
     if (u < c) return a + Math.sqrt(u * (b - a) * (m - a));
     return b - Math.sqrt((1 - u) * (b - a) * (b - m));
@@ -62,8 +162,8 @@ function sampleDist(spec) {
     const mu = spec.mu || 0; // in log-hours
     const sigma = spec.sigma || 1;
     // Box-Muller transform for normal distribution
-    const u1 = Math.random();
-    const u2 = Math.random();
+    const u1 = random();
+    const u2 = random();
     const z = Math.sqrt(-2 * Math.log(u1)) * Math.cos(2 * Math.PI * u2);
     return Math.exp(mu + sigma * z);
   }
@@ -71,4 +171,4 @@ function sampleDist(spec) {
   return 0;
 }
 
-module.exports = { sampleDist };
+module.exports = { sampleDist, distMean, distQuantile, setExpectedValueMode, isExpectedValueMode, DISTRIBUTION_TYPES };
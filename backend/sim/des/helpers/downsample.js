@@ -0,0 +1,113 @@
+// Downsample Module
+// Display-sized copy of a stored DES result for the UI: counters and utilization as they are, the timeline
+// cut down to a cap that keeps every rejection, with per-bucket counts so density survives where detail is dropped
+
+const DEFAULT_MAX_TIMELINE_EVENTS = 2000;
+// Without options.bucketHours the horizon is split into this many buckets
+const DEFAULT_BUCKET_COUNT = 100;
+
+/**
+ * Time a timeline entry is placed at (missions by demand time, duties by start)
+ * @param {Object} entry - Timeline entry
+ * @returns {number} Time in hours
+ */
+function entryTime(entry) {
+  return entry.time ?? entry.demand_time ?? entry.start ?? 0;
+}
+
+/**
+ * Split a sample budget over buckets in proportion to their sizes (largest remainder, so quotas sum to the budget)
+ * @param {Array<number>} sizes - Sampleable entries per bucket
+ * @param {number} budget - Entries to keep in total (at most the sum of sizes)
+ * @returns {Array<number>} Quota per bucket
+ */
+function bucketQuotas(sizes, budget) {
+  const total = sizes.reduce((a, b) => a + b, 0);
+  if (total === 0) return sizes.map(() => 0);
+  const exact = sizes.map(n => (budget * n) / total);
+  const quotas = exact.map(Math.floor);
+  let left = budget - quotas.reduce((a, b) => a + b, 0);
+  const byRemainder = exact.map((q, b) => [q - quotas[b], b]).sort((x, y) => y[0] - x[0] || x[1] - y[1]);
+  for (const [, b] of byRemainder) {
+    if (left <= 0) break;
+    quotas[b]++;
+    left--;
+  }
+  return quotas;
+}
+
+/**
+ * Summarize a DES result for display
+ * Every rejection is kept (even past the cap); the remaining budget is a stratified sample of the other entries:
+ * each bucket keeps its proportional share, evenly spaced, so the sample is deterministic and keeps the shape
+ * @param {Object} results - DES result (as returned by runSimulation or stored as JSON)
+ * @param {Object} options - Optional settings
+ * @param {number} options.maxTimelineEvents - Timeline entries to keep. Defaults to 2000
+ * @param {number} options.bucketHours - Bucket length for sampling and counts. Defaults to horizon / 100
+ * @returns {Object} Copy of results without missions_detail, with the sampled timeline and timeline_summary
+ *                   ({ original_events, kept_events, bucket_hours, counts, kept }, counts and kept by entry type per bucket)
+ */
+function summarizeResults(results, options = {}) {
+  if (!results || typeof results !== 'object' || Array.isArray(results)) {
+    throw new Error('summarizeResults requires a DES result object');
+  }
+  const maxEvents = options.maxTimelineEvents ?? DEFAULT_MAX_TIMELINE_EVENTS;
+  if (!Number.isInteger(maxEvents) || maxEvents < 0) {
+    throw new Error(`maxTimelineEvents must be a non-negative integer, got ${maxEvents}`);
+  }
+  const horizon = results.horizon_hours || 24;
+  const bucketHours = options.bucketHours ?? horizon / DEFAULT_BUCKET_COUNT;
+  if (typeof bucketHours !== 'number' || !Number.isFinite(bucketHours) || bucketHours <= 0) {
+    throw new Error(`bucketHours must be a positive number, got ${bucketHours}`);
+  }
+
+  const { timeline, missions_detail: _detail, ...summary } = results;
+  if (!Array.isArray(timeline)) return summary;
+
+  const bucketCount = Math.max(1, Math.ceil(horizon / bucketHours));
+  const bucketOf = entry => Math.min(bucketCount - 1, Math.max(0, Math.floor(entryTime(entry) / bucketHours)));
+  const counts = {};
+  const kept = {};
+  const tally = (table, entry, b) => {
+    if (!table[entry.type]) table[entry.type] = new Array(bucketCount).fill(0);
+    table[entry.type][b]++;
+  };
+
+  // Rejections always stay; everything else is sampled per bucket
+  const keep = new Array(timeline.length).fill(false);
+  const sampleable = Array.from({ length: bucketCount }, () => []);
+  let rejections = 0;
+  timeline.forEach((entry, i) => {
+    const b = bucketOf(entry);
+    tally(counts, entry, b);
+    if (entry.type === 'rejection') {
+      keep[i] = true;
+      rejections++;
+    } else {
+      sampleable[b].push(i);
+    }
+  });
+  const budget = Math.min(Math.max(0, maxEvents - rejections), timeline.length - rejections);
+  const quotas = bucketQuotas(sampleable.map(s => s.length), budget);
+  sampleable.forEach((indices, b) => {
+    for (let k = 0; k < quotas[b]; k++) keep[indices[Math.floor(((k + 0.5) * indices.length) / quotas[b])]] = true;
+  });
+
+  const sampled = timeline.filter((entry, i) => {
+    if (keep[i]) tally(kept, entry, bucketOf(entry));
+    return keep[i];
+  });
+  return {
+    ...summary,
+    timeline: sampled,
+    timeline_summary: {
+      original_events: timeline.length,
+      kept_events: sampled.length,
+      bucket_hours: bucketHours,
+      counts,
+      kept
+    }
+  };
+}
+
+module.exports = { summarizeResults, DEFAULT_MAX_TIMELINE_EVENTS };
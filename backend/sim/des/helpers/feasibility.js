@@ -0,0 +1,91 @@
+// Feasibility Module
+// A mission type no unit could ever fly (no aircraft after overrides, more crew than any unit has, a payload type
+// nobody holds) would otherwise show up as ordinary rejections, indistinguishable from contention. Checked once
+// against the pool totals before the event loop; demands of such types are counted as infeasible instead
+
+const { CORE_CREW_ROLES, unitNames } = require('./state');
+
+/**
+ * Requirements a unit can never meet for a mission type, in the order Stage 5a checks them
+ * Totals are the pools' sizes at the start of the run (reserved resources count only for standby types)
+ * @param {Object} mt - Mission type definition
+ * @param {Object} pool - One unit's resource pools (Stage 4)
+ * @param {Object} scenario - Scenario configuration
+ * @param {boolean} aircraftAnywhere - Some unit has aircraft and scenario.reallocation may move them
+ * @returns {Array<string>} Rejection reasons, empty when the unit could fly the type
+ */
+function unitBlockers(mt, pool, scenario, aircraftAnywhere) {
+  const useReserve = Boolean(pool.reservation?.forMissionTypes.has(mt.name));
+  const usable = resource => (resource ? resource.total - (useReserve ? 0 : resource.reserved || 0) : 0);
+  const blockers = [];
+
+  const payloadsNeeded = {};
+  for (const ptype of mt.required_payload_types || []) payloadsNeeded[ptype] = (payloadsNeeded[ptype] || 0) + 1;
+  if (Object.entries(payloadsNeeded).some(([ptype, count]) => usable(pool.payloads[ptype]) < count)) blockers.push('payload');
+  const dailyLimits = scenario.payload_policy?.daily_limits || {};
+  if (Object.entries(payloadsNeeded).some(([ptype, count]) => dailyLimits[ptype] !== undefined && dailyLimits[ptype] < count)) {
+    blockers.push('payload_budget');
+  }
+
+  if (usable(pool.aircraft) < 1 && !aircraftAnywhere) blockers.push('aircraft');
+  if (pool.controlStations && pool.controlStations.total < 1) blockers.push('control_station');
+  if (mt.max_concurrent === 0) blockers.push('concurrency_cap');
+
+  // Crew: some allowed pilot/SO composition must fit, then intel and any additional roles
+  const flex = mt.crew_flex?.allowed_compositions;
+  const compositions = Array.isArray(flex) && flex.length > 0
+    ? flex.map(c => ({ pilot: c.pilot || 0, so: c.so || 0 }))
+    : [{ pilot: mt.required_aircrew?.pilot || 0, so: mt.required_aircrew?.so || 0 }];
+  const fitting = compositions.filter(c => c.pilot <= usable(pool.pilot));
+  if (fitting.length === 0) blockers.push('pilot');
+  else if (!fitting.some(c => c.so <= usable(pool.so))) blockers.push('so');
+  for (const [role, needed] of Object.entries(mt.required_aircrew || {})) {
+    if (role === 'pilot' || role === 'so' || role === 'per_flight_hours' || !(needed > 0)) continue;
+    const crewPool = CORE_CREW_ROLES.includes(role) ? pool[role] : pool.roles?.[role];
+    if (usable(crewPool) < needed) blockers.push(role);
+  }
+  return blockers;
+}
+
+/**
+ * Mission types with demand that no unit could ever fly
+ * @param {Object} scenario - Scenario configuration
+ * @param {Map<string, Object>} missionTypes - Mission types by name (Stage 1)
+ * @param {Object} pools - Map of unit → resource pools (Stage 4)
+ * @returns {Map<string, Object>} mission type → { unit: [reasons] } for every infeasible type (empty when all are feasible)
+ */
+function findInfeasibleMissionTypes(scenario, missionTypes, pools) {
+  const units = unitNames(pools);
+  const aircraftAnywhere = Boolean(scenario.reallocation) && units.some(unit => pools[unit].aircraft.total > 0);
+  const demanded = new Set((scenario.demand || []).map(d => d.mission_type));
+  const infeasible = new Map();
+  for (const name of demanded) {
+    const mt = missionTypes.get(name);
+    if (!mt) continue;
+    const reasonsByUnit = {};
+    for (const unit of units) {
+      const blockers = unitBlockers(mt, pools[unit], scenario, aircraftAnywhere);
+      if (blockers.length === 0) break;
+      reasonsByUnit[unit] = blockers;
+    }
+    if (units.length > 0 && Object.keys(reasonsByUnit).length === units.length) infeasible.set(name, reasonsByUnit);
+  }
+  return infeasible;
+}
+
+/**
+ * One warning per infeasible mission type, for results.warnings
+ * @param {Map<string, Object>} infeasible - Output of findInfeasibleMissionTypes
+ * @returns {Array<Object>} [{ code: 'INFEASIBLE_MISSION_TYPE', mission_type, message }]
+ */
+function infeasibilityWarnings(infeasible) {
+  return [...infeasible].map(([name, reasonsByUnit]) => ({
+    code: 'INFEASIBLE_MISSION_TYPE',
+    mission_type: name,
+    message: `No unit can ever fly mission type '${name}' (` +
+      Object.entries(reasonsByUnit).map(([unit, reasons]) => `${unit}: ${reasons.join(', ')}`).join('; ') +
+      '); its demands are counted as infeasible, not rejected'
+  }));
+}
+
+module.exports = { findInfeasibleMissionTypes, infeasibilityWarnings };
@@ -0,0 +1,89 @@
+// Ledger Module
+// Mission counters and their breakdowns (by_type, by_demand_source, rejection reasons by type and unit, infeasible demands)
+// are only updated here, one call per outcome touching every breakdown, so breakdowns always sum to the totals
+
+const MISSION_COUNTERS = ['requested', 'started', 'completed', 'rejected'];
+const BASE_REJECTION_REASONS = ['aircraft', 'control_station', 'pilot', 'so', 'intel', 'payload'];
+
+/**
+ * Zeroed mission counters
+ * @returns {Object} { requested, started, completed, rejected }
+ */
+function emptyMissionCounts() {
+  return Object.fromEntries(MISSION_COUNTERS.map(key => [key, 0]));
+}
+
+/**
+ * Zeroed rejection counters for the base reasons (additional crew roles are added when first used)
+ * @returns {Object} reason → 0
+ */
+function emptyRejectionCounts() {
+  return Object.fromEntries(BASE_REJECTION_REASONS.map(reason => [reason, 0]));
+}
+
+/**
+ * Counter objects a mission outcome updates: the global totals, its type and its demand source
+ * @param {Object} results - Results being accumulated
+ * @param {string} missionType - Mission type name
+ * @param {string} demandSource - Demand entry label
+ * @returns {Array<Object>} Counter objects
+ */
+function missionBreakdowns(results, missionType, demandSource) {
+  if (!results.by_type[missionType]) results.by_type[missionType] = emptyMissionCounts();
+  if (!results.by_demand_source[demandSource]) results.by_demand_source[demandSource] = emptyMissionCounts();
+  return [results.missions, results.by_type[missionType], results.by_demand_source[demandSource]];
+}
+
+/**
+ * Count a mission outcome in the totals and every mission breakdown
+ * @param {Object} results - Results being accumulated
+ * @param {string} counter - requested, started or completed (rejections go through recordRejected)
+ * @param {string} missionType - Mission type name
+ * @param {string} demandSource - Demand entry label
+ * @param {number} n - How many (default 1)
+ */
+function recordMission(results, counter, missionType, demandSource, n = 1) {
+  for (const counts of missionBreakdowns(results, missionType, demandSource)) {
+    counts[counter] += n;
+  }
+}
+
+/**
+ * Count a final rejection in the totals, every mission breakdown and every reason breakdown
+ * @param {Object} results - Results being accumulated
+ * @param {string} missionType - Mission type name
+ * @param {string} demandSource - Demand entry label
+ * @param {string} unit - Unit the demand was assigned to
+ * @param {string} reason - Rejection reason
+ */
+function recordRejected(results, missionType, demandSource, unit, reason) {
+  recordMission(results, 'rejected', missionType, demandSource);
+  if (!results.rejections_by_type[missionType]) results.rejections_by_type[missionType] = emptyRejectionCounts();
+  if (!results.rejections_by_unit[unit]) results.rejections_by_unit[unit] = emptyRejectionCounts();
+  for (const counts of [results.rejections, results.rejections_by_type[missionType], results.rejections_by_unit[unit]]) {
+    counts[reason] = (counts[reason] || 0) + 1;
+  }
+}
+
+/**
+ * Count a demand of a mission type no unit can fly (see feasibility.js) in the totals and every mission breakdown
+ * Infeasible demands are requested but never started or rejected; the counter is created on first use
+ * @param {Object} results - Results being accumulated
+ * @param {string} missionType - Mission type name
+ * @param {string} demandSource - Demand entry label
+ */
+function recordInfeasible(results, missionType, demandSource) {
+  for (const counts of missionBreakdowns(results, missionType, demandSource)) {
+    counts.infeasible = (counts.infeasible || 0) + 1;
+  }
+  results.infeasible_by_type[missionType].missions++;
+}
+
+module.exports = {
+  emptyMissionCounts,
+  emptyRejectionCounts,
+  recordMission,
+  recordRejected,
+  recordInfeasible,
+  MISSION_COUNTERS
+};
@@ -0,0 +1,118 @@
+// Limits Module
+// Watchdog limits that abort runaway simulations (tiny demand intervals, huge horizons)
+// Used when runSimulation is given settings.limits
+
+const LIMIT_ERROR_CODE = 'SIMULATION_LIMIT';
+
+// Settings key → human-readable name used in error messages
+const LIMIT_NAMES = {
+  maxEvents: 'max events',
+  maxTimelineEvents: 'max timeline events',
+  maxWallMs: 'max wall time (ms)'
+};
+
+/**
+ * Build the structured error thrown when a limit trips
+ * @param {string} limit  - Limit key (maxEvents, maxTimelineEvents, maxWallMs)
+ * @param {number} max    - Configured limit
+ * @param {Object} counts - Counters at the time it tripped ({ events, processedEvents, timelineEvents, wallMs })
+ * @returns {Error} Error with code, limit, max and counts
+ */
+function limitError(limit, max, counts) {
+  const value = limit === 'maxEvents' ? counts.events
+    : limit === 'maxTimelineEvents' ? counts.timelineEvents
+      : counts.wallMs;
+  const error = new Error(
+    `Simulation limit exceeded: ${LIMIT_NAMES[limit]} ${max} (reached ${value}; ` +
+    `${counts.events} events generated, ${counts.processedEvents} processed, ` +
+    `${counts.timelineEvents} timeline events, ${counts.wallMs}ms elapsed)`
+  );
+  error.code = LIMIT_ERROR_CODE;
+  error.limit = limit;
+  error.max = max;
+  error.counts = counts;
+  return error;
+}
+
+/**
+ * Check that settings.limits is well formed
+ * @param {*} limits - Candidate limits object
+ * @throws {Error} If a limit is not a positive number
+ */
+function validateLimits(limits) {
+  if (limits === undefined || limits === null) return;
+  if (typeof limits !== 'object' || Array.isArray(limits)) {
+    throw new Error('settings.limits must be an object');
+  }
+  for (const [key, value] of Object.entries(limits)) {
+    if (!(key in LIMIT_NAMES)) {
+      throw new Error(`settings.limits.${key} is not a known limit (expected ${Object.keys(LIMIT_NAMES).join(', ')})`);
+    }
+    if (value !== undefined && value !== null && !(typeof value === 'number' && value > 0)) {
+      throw new Error(`settings.limits.${key} must be a positive number, got ${value}`);
+    }
+  }
+}
+
+/**
+ * Create a watchdog that trips when any configured limit is exceeded
+ * Without limits every check is a no-op
+ * @param {Object} limits - { maxEvents, maxTimelineEvents, maxWallMs } (all optional)
+ * @returns {Object} Watchdog with checkGenerated(), checkLoop() and the running counts
+ */
+function createWatchdog(limits) {
+  const { maxEvents, maxTimelineEvents, maxWallMs } = limits || {};
+  const startedAt = Date.now();
+
+  const watchdog = {
+    counts: { events: 0, processedEvents: 0, timelineEvents: 0, wallMs: 0 },
+
+    /**
+     * Called while demand is generated, before the event loop starts
+     * @param {number} generated - Demand events generated so far
+     */
+    checkGenerated(generated) {
+      watchdog.counts.events = generated;
+      if (maxEvents && generated > maxEvents) {
+        watchdog.trip('maxEvents', maxEvents);
+      }
+      if (maxWallMs && (generated & 1023) === 0 && Date.now() - startedAt > maxWallMs) {
+        watchdog.trip('maxWallMs', maxWallMs);
+      }
+    },
+
+    /**
+     * Called after each event in the main loop
+     * @param {number} processed      - Events processed so far
+     * @param {number} timelineEvents - Timeline entries produced so far
+     */
+    checkLoop(processed, timelineEvents) {
+      watchdog.counts.processedEvents = processed;
+      watchdog.counts.timelineEvents = timelineEvents;
+      if (maxTimelineEvents && timelineEvents > maxTimelineEvents) {
+        watchdog.trip('maxTimelineEvents', maxTimelineEvents);
+      }
+      if (maxWallMs && Date.now() - startedAt > maxWallMs) {
+        watchdog.trip('maxWallMs', maxWallMs);
+      }
+    },
+
+    trip(limit, max) {
+      watchdog.counts.wallMs = Date.now() - startedAt;
+      throw limitError(limit, max, { ...watchdog.counts });
+    }
+  };
+
+  return watchdog;
+}
+
+/**
+ * True for errors thrown by a tripped watchdog
+ * @param {*} error - Caught error (or a serialized copy with a code field)
+ * @returns {boolean}
+ */
+function isLimitError(error) {
+  return Boolean(error) && error.code === LIMIT_ERROR_CODE;
+}
+
+module.exports = { createWatchdog, validateLimits, isLimitError, LIMIT_ERROR_CODE };
@@ -0,0 +1,111 @@
+// Output Size Module
+// A result whose JSON passes V8's string limit (about 512 MB) fails in JSON.stringify or res.json with an opaque
+// error, after all the simulation work is done. limitOutputSize estimates the serialized size first and, over the
+// cap, drops the large optional parts of the result (timeline, per-iteration data) so the caller still gets the
+// counters and statistics, with `truncated` listing what was removed
+
+const DEFAULT_MAX_OUTPUT_BYTES = 256 * 1024 * 1024;
+// Arrays longer than this are estimated from this many evenly spaced entries
+const SAMPLE_SIZE = 64;
+
+/**
+ * Check a maxOutputBytes setting
+ * @param {*} value - Setting value; undefined for the default, null for no cap
+ * @param {string} name - Setting name for the error message
+ * @returns {number|null} Cap in bytes, or null when there is none
+ */
+function resolveMaxOutputBytes(value, name) {
+  if (value === undefined) return DEFAULT_MAX_OUTPUT_BYTES;
+  if (value === null) return null;
+  if (!(typeof value === 'number' && value > 0)) {
+    throw new Error(`${name} must be a positive number or null, got ${value}`);
+  }
+  return value;
+}
+
+/**
+ * Approximate length of JSON.stringify(value) without building the string
+ * Long arrays are sampled, so the estimate is exact for small values and close for uniform large ones
+ * @param {*} value - Any JSON-compatible value
+ * @returns {number} Estimated bytes
+ */
+function estimateJsonBytes(value) {
+  if (value === null) return 4;
+  switch (typeof value) {
+    case 'undefined':
+    case 'function':
+    case 'symbol':
+      return 0;
+    case 'string':
+      return Buffer.byteLength(value, 'utf8') + 2;
+    case 'number':
+    case 'boolean':
+      return String(value).length;
+    default:
+      break;
+  }
+  if (Array.isArray(value)) {
+    const n = value.length;
+    if (n === 0) return 2;
+    if (n <= SAMPLE_SIZE) return 1 + value.reduce((sum, item) => sum + (estimateJsonBytes(item) || 4) + 1, 0);
+    let sampled = 0;
+    for (let k = 0; k < SAMPLE_SIZE; k++) sampled += estimateJsonBytes(value[Math.floor(((k + 0.5) * n) / SAMPLE_SIZE)]) || 4;
+    return 1 + Math.round((sampled / SAMPLE_SIZE + 1) * n);
+  }
+  if (typeof value.toJSON === 'function') return estimateJsonBytes(value.toJSON());
+  let bytes = 2;
+  for (const [key, item] of Object.entries(value)) {
+    const itemBytes = estimateJsonBytes(item);
+    if (itemBytes > 0) bytes += Buffer.byteLength(key, 'utf8') + 4 + itemBytes;
+  }
+  return bytes;
+}
+
+/**
+ * Drop optional top-level fields of a result, in order, until its estimated JSON size fits the cap
+ * @param {Object} results - Result object (modified in place)
+ * @param {number|null} maxBytes - Cap in bytes; null returns the results untouched
+ * @param {Array<string>} droppable - Field names that may be removed, least needed first
+ * @returns {Object|null} null when nothing was removed, otherwise
+ *                        { truncated: [fields removed], sizes: { max_bytes, estimated_bytes, actual_bytes } }
+ *                        (estimated before removal, actual after)
+ * @throws {Error} With code 'OUTPUT_TOO_LARGE' when the result is still over the cap after every removal
+ */
+function limitOutputSize(results, maxBytes, droppable) {
+  if (maxBytes === null) return null;
+  const estimated = estimateJsonBytes(results);
+  if (estimated <= maxBytes) return null;
+
+  const truncated = [];
+  let remaining = estimated;
+  for (const field of droppable) {
+    if (remaining <= maxBytes) break;
+    if (results[field] === undefined) continue;
+    remaining -= Buffer.byteLength(field, 'utf8') + 4 + estimateJsonBytes(results[field]);
+    delete results[field];
+    truncated.push(field);
+  }
+  if (remaining > maxBytes) {
+    const error = new Error(`Result too large to serialize: about ${estimated} bytes (cap ${maxBytes}), ` +
+      `still about ${remaining} after removing ${truncated.join(', ') || 'nothing'}`);
+    error.code = 'OUTPUT_TOO_LARGE';
+    error.estimated_bytes = estimated;
+    error.max_bytes = maxBytes;
+    throw error;
+  }
+  return {
+    truncated,
+    sizes: { max_bytes: maxBytes, estimated_bytes: estimated, actual_bytes: Buffer.byteLength(JSON.stringify(results), 'utf8') }
+  };
+}
+
+/**
+ * Whether an error is limitOutputSize giving up
+ * @param {*} error - Caught error
+ * @returns {boolean}
+ */
+function isOutputSizeError(error) {
+  return Boolean(error) && error.code === 'OUTPUT_TOO_LARGE';
+}
+
+module.exports = { resolveMaxOutputBytes, estimateJsonBytes, limitOutputSize, isOutputSizeError, DEFAULT_MAX_OUTPUT_BYTES };
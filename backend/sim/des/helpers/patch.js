@@ -0,0 +1,115 @@
+// Scenario Patch Module
+// Expresses what-if scenarios as "baseline + changes" instead of full copies
+// Implements RFC 7386 JSON merge-patch, plus by-name merging for scenario arrays
+
+// Top-level scenario arrays merged element-by-element on a key instead of replaced
+const KEYED_ARRAYS = {
+  mission_types: 'name',
+  demand: 'mission_type'
+};
+
+// Marker on a keyed array element that removes the matching base element
+const REMOVE_FLAG = '_remove';
+
+// True for JSON objects (not arrays or null)
+function isPlainObject(value) {
+  return value !== null && typeof value === 'object' && !Array.isArray(value);
+}
+
+// Deep copy of a JSON value
+function clone(value) {
+  return value === undefined ? undefined : JSON.parse(JSON.stringify(value));
+}
+
+/**
+ * Apply an RFC 7386 merge-patch
+ * Objects merge recursively, null removes a key, anything else (including arrays) replaces
+ * @param {*} base  - Original value (not modified)
+ * @param {*} patch - Merge-patch
+ * @returns {*} Patched value
+ */
+function mergePatch(base, patch) {
+  if (!isPlainObject(patch)) return clone(patch);
+
+  const result = isPlainObject(base) ? clone(base) : {};
+  for (const [key, value] of Object.entries(patch)) {
+    if (value === null) {
+      delete result[key];
+    } else {
+      result[key] = mergePatch(result[key], value);
+    }
+  }
+  return result;
+}
+
+/**
+ * Merge a patch array into a base array by matching elements on a key
+ * Matched elements are merge-patched, unmatched ones appended, and elements
+ * flagged with `_remove: true` delete their match. Repeated keys (e.g. two demand
+ * entries for one mission type) match base elements in order.
+ * @param {Array} base    - Base array
+ * @param {Array} patch   - Patch array
+ * @param {string} keyField - Field used to match elements
+ * @returns {Array} Merged array
+ */
+function mergeByKey(base, patch, keyField) {
+  const result = Array.isArray(base) ? clone(base) : [];
+  const used = new Set();
+
+  for (const item of patch) {
+    if (!isPlainObject(item) || item[keyField] === undefined) {
+      throw new Error(`Scenario patch entries in this array must be objects with '${keyField}'`);
+    }
+
+    const index = result.findIndex((el, i) => !used.has(i) && isPlainObject(el) && el[keyField] === item[keyField]);
+
+    if (item[REMOVE_FLAG] === true) {
+      if (index >= 0) {
+        result[index] = undefined;
+        used.add(index);
+      }
+      continue;
+    }
+
+    if (index >= 0) {
+      result[index] = mergePatch(result[index], item);
+      used.add(index);
+    } else {
+      result.push(mergePatch({}, item));
+      used.add(result.length - 1);
+    }
+  }
+
+  return result.filter(el => el !== undefined);
+}
+
+/**
+ * Apply a patch to a baseline scenario
+ * Same as RFC 7386 except top-level mission_types (matched on name) and
+ * demand (matched on mission_type) are merged element-by-element
+ * @param {Object} base  - Baseline scenario (not modified)
+ * @param {Object} patch - Scenario patch
+ * @returns {Object} Patched scenario
+ */
+function applyScenarioPatch(base, patch) {
+  if (patch === undefined || patch === null) return clone(base);
+  if (!isPlainObject(patch)) {
+    throw new Error('Scenario patch must be an object');
+  }
+
+  const plain = {};
+  for (const [key, value] of Object.entries(patch)) {
+    if (!(key in KEYED_ARRAYS) || !Array.isArray(value)) plain[key] = value;
+  }
+  const result = mergePatch(base, plain);
+
+  for (const [key, keyField] of Object.entries(KEYED_ARRAYS)) {
+    if (Array.isArray(patch[key])) {
+      result[key] = mergeByKey(base ? base[key] : undefined, patch[key], keyField);
+    }
+  }
+
+  return result;
+}
+
+module.exports = { applyScenarioPatch, mergePatch, KEYED_ARRAYS };
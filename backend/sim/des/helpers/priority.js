@@ -0,0 +1,60 @@
+// Priority Module
+// weighted_completion_score: one headline number that counts a completed priority-1 mission for more than a
+// priority-5 one. Each mission type weighs by its priority (mission_types[].priority, 1 is most important):
+// settings.priorityWeights[priority] when given, otherwise 1 / priority; a type without a priority weighs 1
+
+/**
+ * Check a priorityWeights setting
+ * @param {*} value - Setting value: { priority: weight }, e.g. { 1: 10, 2: 3 }, or undefined
+ * @param {string} name - Setting name for the error message
+ * @returns {Object|null} priority (string key) → weight, or null for the 1 / priority default
+ */
+function resolvePriorityWeights(value, name) {
+  if (value === undefined || value === null) return null;
+  if (typeof value !== 'object' || Array.isArray(value)) {
+    throw new Error(`${name} must be an object mapping priorities to weights`);
+  }
+  for (const [priority, weight] of Object.entries(value)) {
+    if (!/^[1-9]\d*$/.test(priority)) {
+      throw new Error(`${name}: priority '${priority}' must be a positive integer`);
+    }
+    if (!(typeof weight === 'number' && Number.isFinite(weight) && weight >= 0)) {
+      throw new Error(`${name}['${priority}'] must be a non-negative number, got ${weight}`);
+    }
+  }
+  return { ...value };
+}
+
+/**
+ * Weight of one mission type in the score
+ * @param {Object} missionType - Mission type definition
+ * @param {Object|null} weights - Output of resolvePriorityWeights
+ * @returns {number} Weight
+ */
+function priorityWeight(missionType, weights) {
+  const priority = missionType?.priority;
+  if (priority === undefined) return 1;
+  if (weights && weights[priority] !== undefined) return weights[priority];
+  return 1 / priority;
+}
+
+/**
+ * Σ weight × completed over Σ weight × requested, across mission types
+ * @param {Object} byType - results.by_type (mission type → counters)
+ * @param {Array<Object>} missionTypes - scenario.mission_types
+ * @param {Object|null} weights - Output of resolvePriorityWeights
+ * @returns {number|null} Score in [0, 1] (3 decimals), null when nothing weighted was requested
+ */
+function weightedCompletionScore(byType, missionTypes, weights) {
+  const definitions = new Map((missionTypes || []).map(mt => [mt.name, mt]));
+  let completed = 0;
+  let requested = 0;
+  for (const [name, counts] of Object.entries(byType)) {
+    const weight = priorityWeight(definitions.get(name), weights);
+    completed += weight * counts.completed;
+    requested += weight * counts.requested;
+  }
+  return requested > 0 ? Number((completed / requested).toFixed(3)) : null;
+}
+
+module.exports = { resolvePriorityWeights, priorityWeight, weightedCompletionScore };
@@ -0,0 +1,48 @@
+// Quality Module
+// completion_quality: a mission flown with a fallback the engine allows still counts as completed, but operators
+// treat it as a partial success. With scenario.quality_penalties, each started mission gets a quality of 1 minus
+// the penalties of the fallbacks it used (never below 0), and results report the mean over completed missions.
+// crew_flex: flown with a crew_flex composition other than the first (preferred) one
+
+// Fallbacks that carry a penalty
+const QUALITY_PENALTY_KEYS = ['crew_flex'];
+
+/**
+ * Quality of one started mission
+ * @param {Object} penalties - scenario.quality_penalties
+ * @param {Object} used - { crew_flex: boolean } fallbacks the mission used
+ * @returns {number} Quality in [0, 1]
+ */
+function missionQuality(penalties, used) {
+  let quality = 1;
+  for (const key of QUALITY_PENALTY_KEYS) {
+    if (used[key]) quality -= penalties[key] || 0;
+  }
+  return Math.max(0, quality);
+}
+
+/**
+ * results.completion_quality from every started mission's quality
+ * @param {Array<Object>} records - { mission_type, finish_time, quality } per started mission
+ * @param {Function} completed - finish_time → true when the mission completed within the horizon
+ * @returns {Object} { mean, degraded, by_type: { type: { completed, mean, degraded } } }; mean (3 decimals) is null
+ *                   when nothing completed, degraded counts completions with quality below 1
+ */
+function completionQuality(records, completed) {
+  const summarize = list => ({
+    completed: list.length,
+    mean: list.length > 0 ? Number((list.reduce((sum, r) => sum + r.quality, 0) / list.length).toFixed(3)) : null,
+    degraded: list.filter(r => r.quality < 1).length
+  });
+  const done = records.filter(r => completed(r.finish_time));
+  const byType = {};
+  for (const record of done) (byType[record.mission_type] = byType[record.mission_type] || []).push(record);
+  const { mean, degraded } = summarize(done);
+  return {
+    mean,
+    degraded,
+    by_type: Object.fromEntries(Object.entries(byType).map(([type, list]) => [type, summarize(list)]))
+  };
+}
+
+module.exports = { missionQuality, completionQuality, QUALITY_PENALTY_KEYS };
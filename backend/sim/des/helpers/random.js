@@ -0,0 +1,87 @@
+// Random Number Module
+// Single source of randomness for the simulation so runs can be reproduced from a seed
+// Unseeded runs use Math.random(); seeded runs use a mulberry32 generator
+
+let current = Math.random;
+
+// Mixed into the base seed of a named stream so stream seeds never share deriveSeed's index space (iterations)
+const STREAM_DOMAIN = 0x5EED57A3;
+
+/**
+ * Create a seeded pseudo-random generator (mulberry32)
+ * @param {number} seed - Integer seed
+ * @returns {Function} Generator returning floats in [0, 1)
+ */
+function makeRng(seed) {
+  let a = seed >>> 0;
+  return function () {
+    a = (a + 0x6D2B79F5) >>> 0;
+    let t = a;
+    t = Math.imul(t ^ (t >>> 15), t | 1);
+    t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+    return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+  };
+}
+
+/**
+ * Derive an independent sub-seed from a base seed and an index (e.g. iteration number)
+ * Uses a splitmix32-style mix so neighbouring indices give unrelated streams
+ * @param {number} seed  - Base seed
+ * @param {number} index - Stream index
+ * @returns {number} Unsigned 32-bit sub-seed
+ */
+function deriveSeed(seed, index) {
+  let z = ((seed >>> 0) + Math.imul((index + 1) >>> 0, 0x9E3779B9)) >>> 0;
+  z = Math.imul(z ^ (z >>> 16), 0x85EBCA6B) >>> 0;
+  z = Math.imul(z ^ (z >>> 13), 0xC2B2AE35) >>> 0;
+  return (z ^ (z >>> 16)) >>> 0;
+}
+
+/**
+ * Derive the seed of a named side stream (e.g. Latin hypercube shuffle) of a base seed
+ * Kept apart from deriveSeed(seed, index) so a stream never reuses the seed of an iteration with the same index
+ * @param {number} seed   - Base seed
+ * @param {number} stream - Stream constant
+ * @returns {number} Unsigned 32-bit sub-seed
+ */
+function deriveStreamSeed(seed, stream) {
+  return deriveSeed(deriveSeed(((seed >>> 0) ^ STREAM_DOMAIN) >>> 0, stream), 0);
+}
+
+/**
+ * Check that a seed is usable (finite integer)
+ * @param {*} seed - Candidate seed
+ * @returns {boolean} True if the seed can be passed to setSeed
+ */
+function isValidSeed(seed) {
+  return typeof seed === 'number' && Number.isInteger(seed);
+}
+
+/**
+ * Install the generator used by random()
+ * @param {number|null} seed - Integer seed, or null/undefined to fall back to Math.random
+ * @returns {Function} The previously installed generator (pass to restoreRng)
+ */
+function setSeed(seed) {
+  const previous = current;
+  current = (seed === null || seed === undefined) ? Math.random : makeRng(seed);
+  return previous;
+}
+
+/**
+ * Reinstall a generator returned by setSeed
+ * @param {Function} rng - Generator to restore
+ */
+function restoreRng(rng) {
+  current = rng || Math.random;
+}
+
+/**
+ * Draw a uniform random number from the active generator
+ * @returns {number} Float in [0, 1)
+ */
+function random() {
+  return current();
+}
+
+module.exports = { random, setSeed, restoreRng, makeRng, deriveSeed, deriveStreamSeed, isValidSeed };
@@ -0,0 +1,110 @@
+// Reallocation Module
+// Moves aircraft between units (and the bases grouping them) during a run
+// First increment: transfers are scheduled in scenario.reallocation.transfers; each one moves
+// free aircraft out of one unit's pool and into another's after a transit delay
+
+const { scenarioError } = require('./validation');
+
+/**
+ * Map each unit to its base (scenario.bases = { "<base>": ["<unit>", ...] })
+ * @param {Object} scenario - Scenario configuration
+ * @param {Object} pools - Map of unit → resource pools
+ * @returns {Object} unit → base name (units without a base are absent)
+ * @throws {Error} If a base names an unknown unit or a unit sits in two bases
+ */
+function resolveBases(scenario, pools) {
+  const baseOf = {};
+  for (const [base, units] of Object.entries(scenario.bases || {})) {
+    if (!Array.isArray(units)) throw scenarioError(`bases['${base}']`, 'must be an array of unit names');
+    for (const unit of units) {
+      if (!pools[unit]) throw scenarioError(`bases['${base}']`, `names unknown unit '${unit}'`);
+      if (baseOf[unit] !== undefined) throw scenarioError(`bases['${base}']`, `names unit '${unit}', which is already in base '${baseOf[unit]}'`);
+      baseOf[unit] = base;
+    }
+  }
+  return baseOf;
+}
+
+/**
+ * Build aircraft_transfer events from scenario.reallocation.transfers
+ * Transit defaults to reallocation.transit_hours between bases and 0 within one base
+ * @param {Object} scenario - Scenario configuration
+ * @param {Object} pools - Map of unit → resource pools
+ * @returns {Array<Object>} Transfer events ({ time, type: 'aircraft_transfer', from_unit, to_unit, aircraft, transit_hours, ... })
+ * @throws {Error} If a transfer is malformed
+ */
+function buildTransferEvents(scenario, pools) {
+  const reallocation = scenario.reallocation;
+  if (reallocation === undefined || reallocation === null) return [];
+  const baseOf = resolveBases(scenario, pools);
+  const defaultTransit = reallocation.transit_hours ?? 0;
+  if (typeof defaultTransit !== 'number' || !(defaultTransit >= 0)) {
+    throw scenarioError('reallocation.transit_hours', `must be a non-negative number, got ${defaultTransit}`);
+  }
+  const transfers = reallocation.transfers || [];
+  if (!Array.isArray(transfers)) throw scenarioError('reallocation.transfers', 'must be an array');
+
+  return transfers.map((t, i) => {
+    const where = `reallocation.transfers[${i}]`;
+    const { from, to, aircraft, at_hours: atHours } = t || {};
+    for (const [key, unit] of [['from', from], ['to', to]]) {
+      if (!pools[unit]) throw scenarioError(`${where}.${key}`, `names unknown unit '${unit}'`);
+    }
+    if (from === to) throw scenarioError(where, `moves aircraft from and to the same unit '${from}'`);
+    if (!Number.isInteger(aircraft) || aircraft <= 0) {
+      throw scenarioError(`${where}.aircraft`, `must be a positive integer, got ${aircraft}`);
+    }
+    if (typeof atHours !== 'number' || !(atHours >= 0)) {
+      throw scenarioError(`${where}.at_hours`, `must be a non-negative number, got ${atHours}`);
+    }
+    const fromBase = baseOf[from] ?? null;
+    const toBase = baseOf[to] ?? null;
+    const sameBase = fromBase !== null && fromBase === toBase;
+    const transit = t.transit_hours ?? (sameBase ? 0 : defaultTransit);
+    if (typeof transit !== 'number' || !(transit >= 0)) {
+      throw scenarioError(`${where}.transit_hours`, `must be a non-negative number, got ${transit}`);
+    }
+    return {
+      time: atHours,
+      type: 'aircraft_transfer',
+      from_unit: from,
+      to_unit: to,
+      from_base: fromBase,
+      to_base: toBase,
+      aircraft,
+      transit_hours: transit
+    };
+  });
+}
+
+/**
+ * Apply one transfer: free aircraft leave now and are usable at the destination after transit
+ * Records a 'reallocation' timeline entry and updates results.reallocations
+ * @param {Object} ev - aircraft_transfer event
+ * @param {Object} pools - Map of unit → resource pools
+ * @param {Object} results - Results object being populated
+ * @returns {number} Aircraft moved (may be fewer than requested when the source has too few free)
+ */
+function processTransfer(ev, pools, results) {
+  const moved = pools[ev.from_unit].aircraft.transferOut(ev.time, ev.aircraft);
+  const arrival = ev.time + ev.transit_hours;
+  pools[ev.to_unit].aircraft.transferIn(arrival, moved);
+
+  results.reallocations.aircraft_requested += ev.aircraft;
+  results.reallocations.aircraft_moved += moved;
+  if (moved > 0) results.reallocations.count++;
+  results.timeline.push({
+    type: 'reallocation',
+    time: ev.time,
+    arrival_time: arrival,
+    from_unit: ev.from_unit,
+    to_unit: ev.to_unit,
+    from_base: ev.from_base,
+    to_base: ev.to_base,
+    requested: ev.aircraft,
+    aircraft: moved
+  });
+  return moved;
+}
+
+module.exports = { buildTransferEvents, processTransfer, resolveBases };
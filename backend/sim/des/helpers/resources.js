@@ -1,57 +1,282 @@
 // Resource Management Module
 // Manages resource pools and crew queues with scheduling logic
 
+const { random } = require('./random');
+
+/**
+ * Booked hours of busy intervals that fall outside [0, horizon]
+ * Acquisitions are booked in full when made; this is the part a horizon-bounded figure must drop
+ * @param {Array<Object>} intervals - Busy intervals ({ start, end, count })
+ * @param {number} horizonHours     - Total simulation horizon
+ * @returns {number} Resource-hours outside the horizon
+ */
+function hoursOutside(intervals, horizonHours) {
+  let outside = 0;
+  for (const { start, end, count } of intervals) {
+    const inside = Math.max(0, Math.min(end, horizonHours) - Math.max(start, 0));
+    outside += ((end - start) - inside) * count;
+  }
+  return outside;
+}
+
+/**
+ * Number of sorted release times later than a time (holds still running then)
+ * @param {Array<number>} releases - Release times in ascending order
+ * @param {number} time            - Query time in hours
+ * @returns {number} Releases after time
+ */
+function countReleasedAfter(releases, time) {
+  let lo = 0;
+  let hi = releases.length;
+  while (lo < hi) {
+    const mid = (lo + hi) >>> 1;
+    if (releases[mid] <= time) lo = mid + 1;
+    else hi = mid;
+  }
+  return releases.length - lo;
+}
+
+/**
+ * Insert a release time, keeping the array in ascending order
+ * @param {Array<number>} releases - Release times in ascending order
+ * @param {number} release         - Release time to add
+ */
+function insertRelease(releases, release) {
+  let i = releases.length;
+  while (i > 0 && releases[i - 1] > release) i--;
+  releases.splice(i, 0, release);
+}
+
+/**
+ * Most [start, end) intervals overlapping at any instant of [start, end)
+ * @param {Array<Object>} intervals - Booked intervals ({ start, end })
+ * @param {number} start - Query start in hours
+ * @param {number} end   - Query end in hours
+ * @returns {number} Peak overlap
+ */
+function peakOverlap(intervals, start, end) {
+  const changes = [];
+  for (const a of intervals) {
+    if (a.start < end && a.end > start) changes.push([Math.max(a.start, start), 1], [a.end, -1]);
+  }
+  // Releases before bookings at the same instant: [start, end) intervals that touch do not overlap
+  changes.sort((x, y) => x[0] - y[0] || x[1] - y[1]);
+  let level = 0;
+  let peak = 0;
+  for (const [, delta] of changes) {
+    level += delta;
+    peak = Math.max(peak, level);
+  }
+  return peak;
+}
+
+/**
+ * Summarize how much of a standby reservation was used
+ * @param {number} reserved     - Units or crew members reserved
+ * @param {number} allocations  - Acquisitions served from the reservation
+ * @param {number} busyHours    - Hours reserved resources were held
+ * @param {number} peakInUse    - Most reserved resources held at once
+ * @param {number} horizonHours - Total simulation horizon
+ * @returns {Object} { reserved, allocations, busy_hours, efficiency, peak_in_use }
+ */
+function reservationStats(reserved, allocations, busyHours, peakInUse, horizonHours) {
+  const capacity = reserved * horizonHours;
+  return {
+    reserved,
+    allocations,
+    busy_hours: busyHours,
+    efficiency: capacity > 0 ? Number(Math.min(1, busyHours / capacity).toFixed(3)) : 0,
+    peak_in_use: peakInUse
+  };
+}
+
 /**
  * EquipmentPool manages a fixed pool of equipment resources
  * Tracks availability, allocations, denials, and peak concurrent usage
+ *
+ * Holds are kept until the end of the run, so availability may be queried in any time order:
+ * a query at an earlier time than a previous one (a later process step booked first, diagnostics)
+ * still sees every hold running then
  */
 class EquipmentPool {
   constructor(name, total) {
     this.name = name;
     this.total = total;
+    // Release times of general and reserved holds, ascending
     this.held = [];
+    // Booked hours (duration × count) and the intervals behind them, for busyHoursWithin
     this.busyTime = 0;
+    this.busyIntervals = [];
     this.allocations = 0;
     this.denials = 0;
     this.usedCount = 0;
+    // Standby reservation: units only acquisitions with useReserve may take
+    this.reserved = 0;
+    this.heldReserved = [];
+    this.reservedBusyTime = 0;
+    this.reservedBusyIntervals = [];
+    this.reservedAllocations = 0;
+    this.peakReservedInUse = 0;
+    // Aircraft away on transfer: [{ start, end, count }], unusable and not counted as available hours
+    this.offline = [];
   }
 
   /**
-   * Get number of resources available at a given time
+   * Units offline (transferred out, or not yet arrived) at a given time
    * @param {number} time - Current simulation time in hours
+   * @returns {number} Offline unit count
+   */
+  offlineAt(time) {
+    let count = 0;
+    for (const o of this.offline) {
+      if (o.start <= time && time < o.end) count += o.count;
+    }
+    return count;
+  }
+
+  /**
+   * Send free general-pool units away for the rest of the run (scenario.reallocation)
+   * Units in use or reserved stay; fewer than count may leave
+   * @param {number} time - Departure time in hours
+   * @param {number} count - Units requested
+   * @returns {number} Units that actually left
+   */
+  transferOut(time, count) {
+    const moved = Math.max(0, Math.min(count, this.availableAt(time)));
+    if (moved > 0) this.offline.push({ start: time, end: Infinity, count: moved });
+    return moved;
+  }
+
+  /**
+   * Add units that arrive from another pool, usable from the arrival time
+   * @param {number} arrival - Arrival time in hours (departure + transit)
+   * @param {number} count - Units arriving
+   */
+  transferIn(arrival, count) {
+    if (count <= 0) return;
+    this.total += count;
+    if (arrival > 0) this.offline.push({ start: 0, end: arrival, count });
+  }
+
+  /**
+   * Hold back units for standby commitments (unit_policy.reservations)
+   * @param {number} count - Units reserved (capped at the pool size)
+   */
+  setReserved(count) {
+    this.reserved = Math.max(0, Math.min(this.total, Math.floor(count)));
+  }
+
+  /**
+   * Units held (general and reserved) at a given time
+   * @param {number} time - Simulation time in hours
+   * @returns {Object} { general, reserved } hold counts
+   */
+  heldAt(time) {
+    return { general: countReleasedAfter(this.held, time), reserved: countReleasedAfter(this.heldReserved, time) };
+  }
+
+  /**
+   * Get number of resources available at a given time
+   * Does not change the pool, so times may be queried in any order
+   * @param {number} time - Simulation time in hours
+   * @param {boolean} useReserve - Include free reserved units (standby missions only)
    * @returns {number} Number of available resources
    */
-  availableAt(time) {
-    this.held = this.held.filter(t => t > time);
-    return this.total - this.held.length;
+  availableAt(time, useReserve = false) {
+    const held = this.heldAt(time);
+    const general = this.total - this.reserved - held.general - this.offlineAt(time);
+    return useReserve ? general + (this.reserved - held.reserved) : general;
+  }
+
+  /**
+   * Earliest time a held unit (general or reserved) comes free after a given time
+   * @param {number} time - Simulation time in hours
+   * @returns {number|null} Release time in hours, or null when nothing is held
+   */
+  nextReleaseAfter(time) {
+    const next = [this.held, this.heldReserved]
+      .map(releases => releases[releases.length - countReleasedAfter(releases, time)])
+      .filter(release => release !== undefined);
+    return next.length > 0 ? Math.min(...next) : null;
   }
 
   /**
    * Try to acquire resources for a duration
+   * With useReserve, free reserved units are taken before general ones
    * @param {number} time - Start time in hours
    * @param {number} durationHours - Duration to hold resources
    * @param {number} count - Number of resources to acquire
+   * @param {boolean} useReserve - Allow reserved units (standby missions only)
    * @returns {boolean} True if acquisition successful, false otherwise
    */
-  tryAcquire(time, durationHours, count = 1) {
-    const avail = this.availableAt(time);
+  tryAcquire(time, durationHours, count = 1, useReserve = false) {
+    const avail = this.availableAt(time, useReserve);
     if (avail >= count) {
-      const currentUsage = this.held.length;
-      const newUsage = currentUsage + count;
+      const held = this.heldAt(time);
+      const newUsage = held.general + held.reserved + count;
       if (newUsage > this.usedCount) {
         this.usedCount = newUsage;
       }
       for (let i = 0; i < count; i++) {
-        this.held.push(time + durationHours);
+        if (useReserve && held.reserved < this.reserved) {
+          insertRelease(this.heldReserved, time + durationHours);
+          held.reserved++;
+          this.reservedAllocations++;
+          this.reservedBusyTime += durationHours;
+          this.reservedBusyIntervals.push({ start: time, end: time + durationHours, count: 1 });
+        } else {
+          insertRelease(this.held, time + durationHours);
+        }
       }
+      this.peakReservedInUse = Math.max(this.peakReservedInUse, held.reserved);
       this.allocations += count;
       this.busyTime += durationHours * count;
+      this.busyIntervals.push({ start: time, end: time + durationHours, count });
       return true;
     }
     this.denials += count;
     return false;
   }
 
+  /**
+   * Hold general-pool units from t=0 for a mission already in progress (v_active_missions)
+   * Only the time from t=0 onward counts as busy
+   * @param {number} untilHours - Time the units are released
+   * @param {number} count - Units held
+   * @returns {boolean} True if enough units were free
+   */
+  seedHold(untilHours, count = 1) {
+    if (this.availableAt(0) < count) return false;
+    for (let i = 0; i < count; i++) insertRelease(this.held, untilHours);
+    const held = this.heldAt(0);
+    this.usedCount = Math.max(this.usedCount, held.general + held.reserved);
+    this.allocations += count;
+    this.busyTime += untilHours * count;
+    this.busyIntervals.push({ start: 0, end: untilHours, count });
+    return true;
+  }
+
+  /**
+   * Reserved-versus-used statistics for a pool with a standby reservation
+   * @param {number} horizonHours - Total simulation horizon
+   * @returns {Object} { reserved, allocations, busy_hours, efficiency, peak_in_use }
+   */
+  reservationStats(horizonHours) {
+    const busyHours = Math.max(0, this.reservedBusyTime - hoursOutside(this.reservedBusyIntervals, horizonHours));
+    return reservationStats(this.reserved, this.reservedAllocations, busyHours, this.peakReservedInUse, horizonHours);
+  }
+
+  /**
+   * Busy resource-hours inside [0, horizon]
+   * Each acquisition contributes count × the overlap of [start, release] with the horizon,
+   * so holds running past the end (or seeded before t=0) only count their in-horizon part
+   * @param {number} horizonHours - Total simulation horizon
+   * @returns {number} Busy resource-hours within the horizon
+   */
+  busyHoursWithin(horizonHours) {
+    return Math.max(0, this.busyTime - hoursOutside(this.busyIntervals, horizonHours));
+  }
+
   /**
    * Calculate utilization as percentage of equipment used at least once
    * @returns {number} Utilization ratio (0-1) based on how many units were used
@@ -68,7 +293,34 @@ class EquipmentPool {
    */
   efficiency(horizonHours) {
     if (this.total <= 0 || horizonHours <= 0) return 0;
-    return Math.min(1, this.busyTime / (this.total * horizonHours));
+    return Math.min(1, this.busyHoursWithin(horizonHours) / (this.total * horizonHours));
+  }
+
+  /**
+   * Integral of available capacity over the horizon (resource-hours)
+   * total * horizon, less the hours units spent transferred out or in transit
+   * @param {number} horizonHours - Total simulation horizon
+   * @returns {number} Available resource-hours
+   */
+  availableHours(horizonHours) {
+    if (this.total <= 0 || horizonHours <= 0) return 0;
+    let offlineHours = 0;
+    for (const o of this.offline) {
+      offlineHours += o.count * Math.max(0, Math.min(o.end, horizonHours) - Math.max(o.start, 0));
+    }
+    return this.total * horizonHours - offlineHours;
+  }
+
+  /**
+   * Calculate efficiency against available capacity rather than total * horizon
+   * Equal to efficiency() unless units were transferred
+   * @param {number} horizonHours - Total simulation horizon
+   * @returns {number} Efficiency ratio (0-1)
+   */
+  efficiencyOfAvailable(horizonHours) {
+    const available = this.availableHours(horizonHours);
+    if (available <= 0) return 0;
+    return Math.min(1, this.busyHoursWithin(horizonHours) / available);
   }
 
   /**
@@ -79,6 +331,7 @@ class EquipmentPool {
   getStats(horizonHours) {
     const utilizationRatio = this.utilization();
     const efficiencyRatio = this.efficiency(horizonHours);
+    const efficiencyOfAvailableRatio = this.efficiencyOfAvailable(horizonHours);
     const used = Math.round(this.total * utilizationRatio);
     const unused = this.total - used;
     return {
@@ -89,7 +342,208 @@ class EquipmentPool {
       utilizationPercent: utilizationRatio * 100,
       efficiency: efficiencyRatio,
       efficiencyPercent: efficiencyRatio * 100,
-      busyHours: this.busyTime,
+      efficiencyOfAvailable: efficiencyOfAvailableRatio,
+      availableHours: this.availableHours(horizonHours),
+      busyHours: this.busyHoursWithin(horizonHours),
+      allocations: this.allocations,
+      denials: this.denials
+    };
+  }
+}
+
+/**
+ * ConcurrencyPool caps how many instances of one mission type are airborne at once across all units
+ * (mission_types[].max_concurrent, e.g. airspace deconfliction); holds are explicit [start, end) intervals because
+ * a mission books its airborne stretch ahead of time, after its preflight and mount
+ */
+class ConcurrencyPool {
+  constructor(name, limit) {
+    this.name = name;
+    this.limit = limit;
+    // Booked airborne intervals that may still overlap a future booking
+    this.active = [];
+    this.allocations = 0;
+    this.denials = 0;
+    this.peak = 0;
+  }
+
+  /**
+   * Most booked intervals overlapping at any instant of [start, end)
+   * @param {number} start - Interval start in hours
+   * @param {number} end   - Interval end in hours
+   * @returns {number} Peak overlap
+   */
+  peakDuring(start, end) {
+    return peakOverlap(this.active, start, end);
+  }
+
+  /**
+   * True when one more instance fits in [start, end); a miss counts as a time the cap bound
+   * @param {number} start - Airborne start in hours
+   * @param {number} end   - Airborne end in hours
+   * @returns {boolean}
+   */
+  fits(start, end) {
+    if (this.peakDuring(start, end) + 1 <= this.limit) return true;
+    this.denials++;
+    return false;
+  }
+
+  /**
+   * Book [start, end); call after fits() (bookings never start before now, so older intervals are dropped)
+   * @param {number} now   - Current simulation time in hours
+   * @param {number} start - Airborne start in hours
+   * @param {number} end   - Airborne end in hours
+   */
+  acquire(now, start, end) {
+    this.active = this.active.filter(a => a.end > now);
+    this.active.push({ start, end });
+    this.allocations++;
+    this.peak = Math.max(this.peak, this.peakDuring(start, end));
+  }
+
+  /**
+   * Result summary
+   * @returns {Object} { max_concurrent, peak_concurrent, allocations, times_bound }
+   */
+  stats() {
+    return { max_concurrent: this.limit, peak_concurrent: this.peak, allocations: this.allocations, times_bound: this.denials };
+  }
+}
+
+/**
+ * WindowPool is unit equipment booked for explicit [start, end) windows instead of from acquisition to release
+ * (launch/recovery equipment: a sortie needs it for launch and again for recovery, not while airborne). A recovery
+ * window lies in the future when the mission is accepted, so every window is checked before any is booked
+ */
+class WindowPool {
+  constructor(name, total) {
+    this.name = name;
+    this.total = total;
+    // Bookings that may still overlap a future window
+    this.active = [];
+    // Every booking ({ start, end, count }), for busyHoursWithin and resource_level events
+    this.busyIntervals = [];
+    this.busyTime = 0;
+    this.allocations = 0;
+    this.denials = 0;
+    this.usedCount = 0;
+  }
+
+  /**
+   * Units free at an instant
+   * @param {number} time - Simulation time in hours
+   * @returns {number} Available units
+   */
+  availableAt(time) {
+    return this.total - this.active.filter(b => b.start <= time && time < b.end).length;
+  }
+
+  /**
+   * Units free throughout a window
+   * @param {number} start - Window start in hours
+   * @param {number} end   - Window end in hours
+   * @returns {number} Available units
+   */
+  availableDuring(start, end) {
+    return this.total - peakOverlap(this.active, start, end);
+  }
+
+  /**
+   * Earliest end of a booking running at a time
+   * @param {number} time - Simulation time in hours
+   * @returns {number|null} Release time in hours, or null when nothing is booked then
+   */
+  nextReleaseAfter(time) {
+    const running = this.active.filter(b => b.start <= time && time < b.end).map(b => b.end);
+    return running.length > 0 ? Math.min(...running) : null;
+  }
+
+  /**
+   * True when one unit is free in every window; a miss counts one denial
+   * @param {Array<Object>} windows - [{ start, end }] (empty windows always fit)
+   * @returns {boolean}
+   */
+  fitsAll(windows) {
+    if (windows.every(w => w.end <= w.start || this.availableDuring(w.start, w.end) >= 1)) return true;
+    this.denials++;
+    return false;
+  }
+
+  /**
+   * Book one unit for every window; call after fitsAll() (windows never start before now, so older bookings are dropped)
+   * @param {number} now - Current simulation time in hours
+   * @param {Array<Object>} windows - [{ start, end }]
+   */
+  acquire(now, windows) {
+    this.active = this.active.filter(b => b.end > now);
+    for (const w of windows.filter(w => w.end > w.start)) {
+      this.usedCount = Math.max(this.usedCount, peakOverlap(this.active, w.start, w.end) + 1);
+      this.active.push({ start: w.start, end: w.end });
+      this.busyIntervals.push({ start: w.start, end: w.end, count: 1 });
+      this.busyTime += w.end - w.start;
+    }
+    this.allocations++;
+  }
+
+  /**
+   * Book units from t=0 for holds carried over from a previous run (settings.initialState)
+   * @param {number} untilHours - Time the units are released
+   * @param {number} count - Units held
+   * @returns {boolean} True if enough units were free
+   */
+  seedHold(untilHours, count = 1) {
+    if (this.availableDuring(0, untilHours) < count) return false;
+    for (let i = 0; i < count; i++) this.active.push({ start: 0, end: untilHours });
+    this.usedCount = Math.max(this.usedCount, peakOverlap(this.active, 0, untilHours));
+    this.busyIntervals.push({ start: 0, end: untilHours, count });
+    this.busyTime += untilHours * count;
+    this.allocations += count;
+    return true;
+  }
+
+  /**
+   * Booked unit-hours inside [0, horizon]
+   * @param {number} horizonHours - Total simulation horizon
+   * @returns {number} Busy unit-hours within the horizon
+   */
+  busyHoursWithin(horizonHours) {
+    return Math.max(0, this.busyTime - hoursOutside(this.busyIntervals, horizonHours));
+  }
+
+  /**
+   * Most units booked at once over the pool size
+   * @returns {number} Utilization ratio (0-1)
+   */
+  utilization() {
+    if (this.total <= 0) return 0;
+    return Math.min(1, this.usedCount / this.total);
+  }
+
+  /**
+   * Booked unit-hours over total * horizon
+   * @param {number} horizonHours - Total simulation horizon
+   * @returns {number} Efficiency ratio (0-1)
+   */
+  efficiency(horizonHours) {
+    if (this.total <= 0 || horizonHours <= 0) return 0;
+    return Math.min(1, this.busyHoursWithin(horizonHours) / (this.total * horizonHours));
+  }
+
+  /**
+   * Statistics for display (allocations count missions, each booking two windows)
+   * @param {number} horizonHours - Total simulation horizon
+   * @returns {Object} { total, used, unused, utilization, efficiency, busyHours, allocations, denials }
+   */
+  getStats(horizonHours) {
+    const used = Math.min(this.total, this.usedCount);
+    return {
+      total: this.total,
+      used,
+      unused: this.total - used,
+      utilization: this.utilization(),
+      efficiency: this.efficiency(horizonHours),
+      busyHours: this.busyHoursWithin(horizonHours),
       allocations: this.allocations,
       denials: this.denials
     };
@@ -105,7 +559,9 @@ class CrewQueue {
     this.name = name;
     this.total = total;
     this.crew = [];
+    // Booked hours (shift + recovery per member) and the intervals behind them, for busyHoursWithin
     this.busyTime = 0;
+    this.busyIntervals = [];
     this.allocations = 0;
     this.denials = 0;
     this.dutyAssignmentCount = {};      // Track how many duty assignments each crew member has
@@ -113,6 +569,17 @@ class CrewQueue {
     this.dutyRotationPoolSize = null;   // Limit rotation pool to only crew needed for duty coverage
     this.crewRestHours = crewRestHours; // Crew rest period to add after each shift
     this.usedCrewIds = new Set();       // Track which crew members have been used at least once
+    this.reserved = 0;                  // Standby crew only missions with useReserve may take
+    this.reservedBusyTime = 0;
+    this.reservedBusyIntervals = [];
+    this.reservedAllocations = 0;
+    this.peakReservedInUse = 0;
+    // Minimum gap between a member's consecutive sorties (crew_policy.min_crew_turn_hours), tracked apart from busy time
+    this.turnHours = 0;
+    this.turnTime = 0;
+    this.turnIntervals = [];
+    // Members absent per day (crew_policy.availability_rate, stochastic mode): absentDays[d] is a Set of ids
+    this.absentDays = null;
 
     // Work schedule configuration
     this.workSchedule = workSchedule || {
@@ -231,6 +698,40 @@ class CrewQueue {
     return true;
   }
 
+  /**
+   * Set which members are absent on each day (day d covers [24d, 24d + 24) hours)
+   * @param {Array<Set<number>>} absentDays - Absent member ids per day
+   */
+  setAbsences(absentDays) {
+    this.absentDays = absentDays;
+  }
+
+  /**
+   * Check if a crew member is absent at a given time (off on the day, whatever their schedule)
+   * @param {Object} crewMember - Crew member object
+   * @param {number} time - Current simulation time in hours
+   * @returns {boolean} True if absent
+   */
+  isAbsent(crewMember, time) {
+    if (!this.absentDays) return false;
+    const day = this.absentDays[Math.floor(time / 24)];
+    return Boolean(day && day.has(crewMember.id));
+  }
+
+  /**
+   * Fraction of member-hours within the horizon the crew was present (not absent)
+   * @param {number} horizonHours - Total simulation horizon
+   * @returns {number} Present fraction (0-1); 1 without absences
+   */
+  presentFraction(horizonHours) {
+    if (!this.absentDays || this.total <= 0 || horizonHours <= 0) return 1;
+    let absentHours = 0;
+    this.absentDays.forEach((ids, day) => {
+      absentHours += ids.size * Math.max(0, Math.min(24, horizonHours - day * 24));
+    });
+    return 1 - absentHours / (this.total * horizonHours);
+  }
+
   /**
    * Set the duty rotation pool size to limit which crew members are used for rotating duties
    * This prevents unnecessary rotation through entire crew when only subset is needed
@@ -251,31 +752,198 @@ class CrewQueue {
     this.dutyCycleResetTime = currentTime;
   }
 
+  /**
+   * Hold back crew members for standby commitments (unit_policy.reservations)
+   * The highest-numbered members are reserved, away from the low-numbered duty rotation pool
+   * @param {number} count - Crew members reserved (capped at the pool size)
+   */
+  setReserved(count) {
+    this.reserved = Math.max(0, Math.min(this.total, Math.floor(count)));
+    for (const c of this.crew) {
+      c.reserved = c.id >= this.total - this.reserved;
+    }
+  }
+
+  /**
+   * Keep each member unavailable for this long after every flight assignment ends (duties are unaffected)
+   * @param {number} hours - Crew turn in hours
+   */
+  setTurnHours(hours) {
+    this.turnHours = Math.max(0, hours);
+  }
+
+  /**
+   * Start a member's crew turn when their flight assignment ends
+   * @param {Object} crewMember - Assigned crew member
+   * @param {number} shiftEnd   - End of the assignment
+   */
+  startTurn(crewMember, shiftEnd) {
+    crewMember.availableAt = shiftEnd + this.turnHours;
+    if (this.turnHours <= 0) return;
+    this.turnTime += this.turnHours;
+    this.turnIntervals.push({ start: shiftEnd, end: crewMember.availableAt, count: 1 });
+  }
+
+  /**
+   * Crew-turn resource-hours inside [0, horizon]
+   * @param {number} horizonHours - Total simulation horizon
+   * @returns {number} Turn resource-hours within the horizon
+   */
+  turnHoursWithin(horizonHours) {
+    return Math.max(0, this.turnTime - hoursOutside(this.turnIntervals, horizonHours));
+  }
+
+  /**
+   * Fraction of crew capacity spent in crew turn (unavailable but not flying)
+   * @param {number} horizonHours - Total simulation horizon
+   * @returns {number} Turn ratio (0-1)
+   */
+  turnFraction(horizonHours) {
+    if (this.total <= 0 || horizonHours <= 0) return 0;
+    return Math.min(1, this.turnHoursWithin(horizonHours) / (this.total * horizonHours));
+  }
+
+  /**
+   * Hold crew from t=0 for a mission already in progress (v_active_missions)
+   * Takes the lowest-numbered free, unreserved members regardless of work schedule
+   * @param {number} untilHours - Time the crew are released
+   * @param {number} count - Crew members held
+   * @returns {Array<number>|null} Held crew ids, or null if too few were free
+   */
+  seedHold(untilHours, count = 1) {
+    const free = this.crew.filter(c => !c.reserved && c.availableAt <= 0).slice(0, count);
+    if (free.length < count) return null;
+    for (const c of free) {
+      c.availableAt = untilHours;
+      c.missionCount = (c.missionCount || 0) + 1;
+      this.usedCrewIds.add(c.id);
+    }
+    this.allocations += count;
+    this.busyTime += untilHours * count;
+    this.busyIntervals.push({ start: 0, end: untilHours, count });
+    return free.map(c => c.id);
+  }
+
+  /**
+   * Capture the assignment state, so acquisitions made after it can be undone with rollback
+   * Denials are not captured: a denied request stays counted
+   * @returns {Object} Opaque checkpoint
+   */
+  checkpoint() {
+    return {
+      crew: this.crew.map(c => ({ availableAt: c.availableAt, missionCount: c.missionCount })),
+      busyTime: this.busyTime,
+      busyIntervals: this.busyIntervals.length,
+      allocations: this.allocations,
+      usedCrewIds: new Set(this.usedCrewIds),
+      dutyAssignmentCount: { ...this.dutyAssignmentCount },
+      reservedBusyTime: this.reservedBusyTime,
+      reservedBusyIntervals: this.reservedBusyIntervals.length,
+      reservedAllocations: this.reservedAllocations,
+      peakReservedInUse: this.peakReservedInUse,
+      turnTime: this.turnTime,
+      turnIntervals: this.turnIntervals.length
+    };
+  }
+
+  /**
+   * Undo every acquisition made since a checkpoint
+   * @param {Object} checkpoint - Output of checkpoint()
+   */
+  rollback(checkpoint) {
+    checkpoint.crew.forEach((saved, i) => Object.assign(this.crew[i], saved));
+    this.busyTime = checkpoint.busyTime;
+    this.busyIntervals.length = checkpoint.busyIntervals;
+    this.allocations = checkpoint.allocations;
+    this.usedCrewIds = checkpoint.usedCrewIds;
+    this.dutyAssignmentCount = checkpoint.dutyAssignmentCount;
+    this.reservedBusyTime = checkpoint.reservedBusyTime;
+    this.reservedBusyIntervals.length = checkpoint.reservedBusyIntervals;
+    this.reservedAllocations = checkpoint.reservedAllocations;
+    this.peakReservedInUse = checkpoint.peakReservedInUse;
+    this.turnTime = checkpoint.turnTime;
+    this.turnIntervals.length = checkpoint.turnIntervals;
+  }
+
+  /**
+   * Reserved-versus-used statistics for a pool with standby crew
+   * @param {number} horizonHours - Total simulation horizon
+   * @returns {Object} { reserved, allocations, busy_hours, efficiency, peak_in_use }
+   */
+  reservationStats(horizonHours) {
+    const busyHours = Math.max(0, this.reservedBusyTime - hoursOutside(this.reservedBusyIntervals, horizonHours));
+    return reservationStats(this.reserved, this.reservedAllocations, busyHours, this.peakReservedInUse, horizonHours);
+  }
+
+  /**
+   * Busy resource-hours inside [0, horizon]
+   * Each acquisition contributes count × the overlap of [start, release] with the horizon,
+   * so holds running past the end (or seeded before t=0) only count their in-horizon part
+   * @param {number} horizonHours - Total simulation horizon
+   * @returns {number} Busy resource-hours within the horizon
+   */
+  busyHoursWithin(horizonHours) {
+    return Math.max(0, this.busyTime - hoursOutside(this.busyIntervals, horizonHours));
+  }
+
+  /**
+   * Record an assignment of a reserved crew member
+   * @param {Object} crewMember - Assigned crew member
+   * @param {number} time       - Assignment start
+   * @param {number} hours      - Hours held
+   */
+  trackReservedUse(crewMember, time, hours) {
+    if (!crewMember.reserved) return;
+    this.reservedAllocations++;
+    this.reservedBusyTime += hours;
+    this.reservedBusyIntervals.push({ start: time, end: time + hours, count: 1 });
+    const inUse = this.crew.filter(c => c.reserved && c.availableAt > time).length;
+    this.peakReservedInUse = Math.max(this.peakReservedInUse, inUse);
+  }
+
   /**
    * Get number of crew members available at a given time
    * Considers both assignment availability and work schedule (days on/off, working hours)
    * @param {number} time - Current simulation time in hours
+   * @param {boolean} useReserve - Include free standby crew (standby missions only)
    * @returns {number} Number of available crew members
    */
-  availableAt(time) {
+  availableAt(time, useReserve = false) {
     return this.crew.filter(c => {
+      // Standby crew are invisible to ordinary acquisitions
+      if (c.reserved && !useReserve) return false;
+
       // Must not be currently assigned
       if (c.availableAt > time) return false;
 
+      // Must not be absent that day
+      if (this.isAbsent(c, time)) return false;
+
       // Must be in work schedule (days on cycle and working hours)
       return this.isScheduleAvailable(c, time);
     }).length;
   }
 
+  /**
+   * Earliest time an assigned crew member comes free (shift plus recovery) after a given time
+   * @param {number} time - Simulation time in hours
+   * @returns {number|null} Release time in hours, or null when no one is assigned past time
+   */
+  nextReleaseAfter(time) {
+    const busy = this.crew.map(c => c.availableAt).filter(t => t > time);
+    return busy.length > 0 ? Math.min(...busy) : null;
+  }
+
   /**
    * Get number of crew members available by shift at a given time
    * Only applicable when shift split is enabled
    * @param {number} time - Current simulation time in hours
+   * @param {boolean} useReserve - Include free standby crew (standby missions only)
    * @returns {Object} Object with shift1, shift2, and total counts
    */
-  availableByShift(time) {
+  availableByShift(time, useReserve = false) {
     if (!this.workSchedule.shift_split_enabled) {
-      const total = this.availableAt(time);
+      const total = this.availableAt(time, useReserve);
       return { shift1: total, shift2: 0, total };
     }
 
@@ -283,9 +951,14 @@ class CrewQueue {
     let shift2Count = 0;
 
     for (const c of this.crew) {
+      if (c.reserved && !useReserve) continue;
+
       // Must not be currently assigned
       if (c.availableAt > time) continue;
 
+      // Must not be absent that day
+      if (this.isAbsent(c, time)) continue;
+
       // Must be in work schedule (days on cycle and working hours)
       if (!this.isScheduleAvailable(c, time)) continue;
 
@@ -322,6 +995,7 @@ class CrewQueue {
       let onMission = 0;
       let daysOff = 0;
       let crewRest = 0;
+      let absent = 0;
 
       for (const c of shiftCrew) {
         // Check if on mission
@@ -330,6 +1004,12 @@ class CrewQueue {
           continue;
         }
 
+        // Check if absent that day (crew_policy.availability_rate)
+        if (this.isAbsent(c, time)) {
+          absent++;
+          continue;
+        }
+
         // Check if in days off period
         if (!this.isInDaysOnCycle(time, c.id)) {
           daysOff++;
@@ -346,7 +1026,7 @@ class CrewQueue {
         available++;
       }
 
-      return { available, onMission, daysOff, crewRest, total: shiftCrew.length };
+      return { available, onMission, daysOff, crewRest, ...(this.absentDays ? { absent } : {}), total: shiftCrew.length };
     };
 
     return {
@@ -367,7 +1047,7 @@ class CrewQueue {
    * @param {string} crewDistribution   - How to distribute missions: 'concentrate', 'even', 'random'
    * @returns {Array|null} Array of crew assignments or null if insufficient crew
    */
-  tryAcquireShifts(time, shifts, isDuty = false, isContinuousDuty = false, forceSequential = false, ignoreWorkSchedule = false, dutyRecoveryHours = 0, crewDistribution = 'concentrate') {
+  tryAcquireShifts(time, shifts, isDuty = false, isContinuousDuty = false, forceSequential = false, ignoreWorkSchedule = false, dutyRecoveryHours = 0, crewDistribution = 'concentrate', useReserve = false) {
     // shifts is array of durations [4, 4, 4, 4, 5] for sequential shifts
     // or [5, 5, 5, 5, 5, 5, 5] for concurrent crew (all same duration = concurrent)
     // isDuty flag indicates if this is a duty assignment (uses different rotation logic)
@@ -382,9 +1062,15 @@ class CrewQueue {
 
     // Filter crew based on assignment availability AND work schedule
     const available = this.crew.filter(c => {
+      // Standby crew only fly missions allowed to use the reservation
+      if (c.reserved && !useReserve) return false;
+
       // Must not be currently assigned
       if (c.availableAt > time) return false;
 
+      // Absent crew stand no duties either
+      if (this.isAbsent(c, time)) return false;
+
       // For 24/7 duties (SDO, SDNCO), ignore work schedule restrictions
       // For missions and ODO, respect work schedule (days on/off, working hours)
       if (ignoreWorkSchedule) return true;
@@ -457,7 +1143,7 @@ class CrewQueue {
       } else if (crewDistribution === 'random') {
         // Random distribution: shuffle available crew
         for (let i = available.length - 1; i > 0; i--) {
-          const j = Math.floor(Math.random() * (i + 1));
+          const j = Math.floor(random() * (i + 1));
           [available[i], available[j]] = [available[j], available[i]];
         }
       } else {
@@ -480,6 +1166,11 @@ class CrewQueue {
       }
     }
 
+    // Standby missions draw on the reservation first (stable, so the distribution order is kept within each group)
+    if (useReserve) {
+      available.sort((a, b) => (b.reserved ? 1 : 0) - (a.reserved ? 1 : 0));
+    }
+
     // Determine if shifts are concurrent (all same duration) or sequential
     const allSameDuration = !forceSequential && shifts.every(d => d === shifts[0]);
 
@@ -500,7 +1191,10 @@ class CrewQueue {
           crewMember.availableAt = shiftEnd;
           this.busyTime += duration;
         }
+        this.busyIntervals.push({ start: time, end: crewMember.availableAt, count: 1 });
+        if (!isDuty) this.startTurn(crewMember, shiftEnd);
         this.allocations++;
+        this.trackReservedUse(crewMember, time, duration);
 
         // Track that this crew member has been used
         this.usedCrewIds.add(crewMember.id);
@@ -535,12 +1229,17 @@ class CrewQueue {
         // For each shift, find ANY crew member available at that shift's start time
         // Re-evaluate available crew at this shift's start time (like a new mission demand)
         const availableAtShiftStart = this.crew.filter(c => {
+          if (c.reserved && !useReserve) return false;
+
           // Must not be currently assigned at shift start
           if (c.availableAt > currentShiftStart) return false;
 
           // Must not have already been used in this mission
           if (usedCrewInThisMission.has(c.id)) return false;
 
+          // Must not be absent on the shift's day
+          if (this.isAbsent(c, currentShiftStart)) return false;
+
           // For 24/7 duties (SDO, SDNCO), ignore work schedule restrictions
           if (ignoreWorkSchedule) return true;
 
@@ -569,7 +1268,7 @@ class CrewQueue {
           if (crewDistribution === 'random') {
             // Random distribution: shuffle the available crew
             for (let i = availableAtShiftStart.length - 1; i > 0; i--) {
-              const j = Math.floor(Math.random() * (i + 1));
+              const j = Math.floor(random() * (i + 1));
               [availableAtShiftStart[i], availableAtShiftStart[j]] = [availableAtShiftStart[j], availableAtShiftStart[i]];
             }
           } else if (crewDistribution === 'rotate') {
@@ -593,6 +1292,9 @@ class CrewQueue {
           }
         }
 
+        if (useReserve) {
+          availableAtShiftStart.sort((a, b) => (b.reserved ? 1 : 0) - (a.reserved ? 1 : 0));
+        }
         const crewMember = availableAtShiftStart[0];
 
         // Mark this crew member as used in this mission
@@ -606,7 +1308,10 @@ class CrewQueue {
           crewMember.availableAt = shiftEnd;
           this.busyTime += shiftDuration;
         }
+        this.busyIntervals.push({ start: currentShiftStart, end: crewMember.availableAt, count: 1 });
+        if (!isDuty) this.startTurn(crewMember, shiftEnd);
         this.allocations++;
+        this.trackReservedUse(crewMember, currentShiftStart, shiftDuration);
 
         // Track that this crew member has been used
         this.usedCrewIds.add(crewMember.id);
@@ -652,7 +1357,71 @@ class CrewQueue {
    */
   efficiency(horizonHours) {
     if (this.total <= 0 || horizonHours <= 0) return 0;
-    return Math.min(1, this.busyTime / (this.total * horizonHours));
+    return Math.min(1, this.busyHoursWithin(horizonHours) / (this.total * horizonHours));
+  }
+
+  /**
+   * Integral of scheduled availability over the horizon (crew-hours)
+   * Counts the hours each crew member is in their days-on cycle and working hours and not absent;
+   * the schedule only changes at cycle and shift boundaries (and absences at day boundaries),
+   * so it is evaluated once per interval between boundaries
+   * @param {number} horizonHours - Total simulation horizon
+   * @returns {number} Available crew-hours
+   */
+  availableHours(horizonHours) {
+    if (this.total <= 0 || horizonHours <= 0) return 0;
+
+    const daysOn = this.workSchedule.days_on || 365;
+    const daysOff = this.workSchedule.days_off || 0;
+    const cycleHours = (daysOn + daysOff) * 24;
+    const dailyStartHour = this.workSchedule.daily_start_hour || 0;
+    const staggerDays = this.workSchedule.stagger_days_off || 0;
+    const workingHours = Math.max(0, 24 - (this.workSchedule.daily_crew_rest_hours || 0));
+
+    // Add every t0 + k * period within [0, horizon]
+    const addPeriodic = (points, t0, period) => {
+      const first = t0 - Math.floor(t0 / period) * period;
+      for (let t = first; t < horizonHours; t += period) points.push(t);
+    };
+
+    let total = 0;
+    for (const member of this.crew) {
+      const points = [0, horizonHours];
+      if (daysOff > 0) {
+        const cycleStart = staggerDays * member.id * 24 + dailyStartHour;
+        addPeriodic(points, cycleStart, cycleHours);
+        addPeriodic(points, cycleStart + daysOn * 24, cycleHours);
+      }
+      if (workingHours < 24) {
+        const shiftStart = (this.workSchedule.shift_split_enabled && member.shift === 2)
+          ? (dailyStartHour + 12) % 24
+          : dailyStartHour;
+        addPeriodic(points, shiftStart, 24);
+        addPeriodic(points, shiftStart + workingHours, 24);
+      }
+      if (this.absentDays) addPeriodic(points, 0, 24);
+
+      points.sort((a, b) => a - b);
+      for (let i = 1; i < points.length; i++) {
+        const width = points[i] - points[i - 1];
+        if (width <= 0) continue;
+        const mid = points[i - 1] + width / 2;
+        if (this.isScheduleAvailable(member, mid) && !this.isAbsent(member, mid)) total += width;
+      }
+    }
+    return total;
+  }
+
+  /**
+   * Calculate efficiency against scheduled availability rather than total * horizon
+   * Busy time includes duties and rest outside working hours, so this is capped at 1
+   * @param {number} horizonHours - Total simulation horizon
+   * @returns {number} Efficiency ratio (0-1)
+   */
+  efficiencyOfAvailable(horizonHours) {
+    const available = this.availableHours(horizonHours);
+    if (available <= 0) return 0;
+    return Math.min(1, this.busyHoursWithin(horizonHours) / available);
   }
 
   /**
@@ -665,6 +1434,8 @@ class CrewQueue {
   getStats(horizonHours, effectiveTotal, rawTotal) {
     const utilizationRatio = this.utilization();
     const efficiencyRatio = this.efficiency(horizonHours);
+    const availableHours = this.availableHours(horizonHours);
+    const efficiencyOfAvailableRatio = availableHours > 0 ? Math.min(1, this.busyHoursWithin(horizonHours) / availableHours) : 0;
     const used = this.usedCrewIds.size;
     const busy = Math.min(used, effectiveTotal);
     const idle = Math.max(0, effectiveTotal - busy);
@@ -681,15 +1452,17 @@ class CrewQueue {
       utilizationPercent: utilizationRatio * 100,
       efficiency: efficiencyRatio,
       efficiencyPercent: efficiencyRatio * 100,
+      efficiencyOfAvailable: efficiencyOfAvailableRatio,
+      availableHours,
       busyPercent: rawTotal > 0 ? (busy / rawTotal) * 100 : 0,
       idlePercent: rawTotal > 0 ? (idle / rawTotal) * 100 : 0,
       unavailablePercent: rawTotal > 0 ? (unavailable / rawTotal) * 100 : 0,
       trueForceUtilization: rawTotal > 0 ? (busy + unavailable) / rawTotal : 0,
-      busyHours: this.busyTime,
+      busyHours: this.busyHoursWithin(horizonHours),
       allocations: this.allocations,
       denials: this.denials
     };
   }
 }
 
-module.exports = { EquipmentPool, CrewQueue };
+module.exports = { EquipmentPool, ConcurrencyPool, WindowPool, CrewQueue };
@@ -1,40 +1,112 @@
 // State Management Module
 // Derives initial resource counts from database state snapshot
 
+// MOS number → crew role recognized without scenario.mos_roles
+const DEFAULT_MOS_ROLES = { '7318': 'pilot', '7314': 'so', '0231': 'intel' };
+
+// Roles with dedicated pools, duty handling and result fields; every unit reports these
+const CORE_CREW_ROLES = ['pilot', 'so', 'intel'];
+
+// Names used by non-crew resources, so they cannot be crew roles
+const RESERVED_ROLE_NAMES = new Set(['aircraft', 'payload', 'payloads', 'control_station']);
+
+/**
+ * Merge scenario.mos_roles over the default MOS → role mapping
+ * @param {Object} mosRoles - Optional { mos: role } additions or remappings
+ * @returns {Object} Complete MOS → role mapping
+ * @throws {Error} If the mapping is malformed or uses a reserved role name
+ */
+function resolveMosRoles(mosRoles) {
+  if (mosRoles === undefined || mosRoles === null) return { ...DEFAULT_MOS_ROLES };
+  if (typeof mosRoles !== 'object' || Array.isArray(mosRoles)) {
+    throw new Error('scenario.mos_roles must be an object mapping MOS numbers to role names');
+  }
+  for (const [mos, role] of Object.entries(mosRoles)) {
+    if (typeof role !== 'string' || !/^[a-z][a-z0-9_]*$/.test(role)) {
+      throw new Error(`scenario.mos_roles['${mos}'] must be a lowercase role name (letters, digits, underscores), got ${role}`);
+    }
+    if (RESERVED_ROLE_NAMES.has(role)) {
+      throw new Error(`scenario.mos_roles['${mos}']: '${role}' is reserved and cannot be a crew role`);
+    }
+  }
+  return { ...DEFAULT_MOS_ROLES, ...mosRoles };
+}
+
+/**
+ * Read access to one state table, stored as rows ({ rows: [{ column: value }] }) or
+ * column-wise ({ columns: { column: [values] } }); only the columns asked for are touched
+ * @param {Object} state - State snapshot
+ * @param {string} key - Table name
+ * @returns {Object} { length, column(name) } where column(name) returns i → value; empty when the table is missing
+ * @throws {Error} If a table gives both formats or its columns differ in length
+ */
+function stateTable(state, key) {
+  const t = state.tables[key];
+  if (t && Array.isArray(t.rows) && t.columns) {
+    throw new Error(`state table ${key}: give either rows or columns, not both`);
+  }
+  if (t && t.columns && typeof t.columns === 'object') {
+    const lengths = new Set(Object.values(t.columns).map(c => (Array.isArray(c) ? c.length : -1)));
+    if (lengths.has(-1) || lengths.size > 1) {
+      throw new Error(`state table ${key}: columns must be arrays of the same length`);
+    }
+    const columns = t.columns;
+    return {
+      length: lengths.size ? [...lengths][0] : 0,
+      column: name => {
+        const values = columns[name] || [];
+        return i => values[i];
+      }
+    };
+  }
+  const rows = t && Array.isArray(t.rows) ? t.rows : [];
+  return { length: rows.length, column: name => i => rows[i][name] };
+}
+
 /**
  * Loads the state snapshot
  * @param {Object} state - State snapshot with tables property
- * @param {Object} state.tables - Database tables (v_aircraft, v_payload, v_staffing, v_unit)
+ * @param {Object} state.tables - Database tables (v_aircraft, v_payload, v_staffing, v_unit; optional v_active_missions,
+ *   v_launch_recovery),
+ *   each as { rows } or { columns } (see stateTable)
+ * @param {Object} mosRoles - Optional scenario.mos_roles ({ mos: role }) merged over the defaults
  * @returns {Object|null} Initial resources configuration or null if invalid
  * 
- * @returns {Array<string>} returns.units   - List of unit names
+ * @returns {Array<string>} returns.units   - List of unit names, sorted
  * @returns {Object} returns.aircraftByUnit - FMC aircraft count per unit
  * @returns {Object} returns.payloadByUnit  - Payload counts by type and unit
- * @returns {Object} returns.staffingByUnit - Crew counts by role (pilot/so) and unit
+ * @returns {Object} returns.staffingByUnit - Crew counts by role and unit (pilot/so/intel always present)
+ * @returns {Array<string>} returns.crewRoles - Roles beyond pilot/so/intel named by the MOS mapping
+ * @returns {Array<Object>} returns.activeMissions - Missions already flying at t=0 (optional v_active_missions table)
+ * @returns {Object} returns.launchRecoveryByUnit - Serviceable launch/recovery sets per unit, for units listed in the
+ *   optional v_launch_recovery table
  */
-function loadState(state) {
+function loadState(state, mosRoles = null) {
   if (!state || !state.tables) return null;
+  const roleByMos = resolveMosRoles(mosRoles);
+  const crewRoles = Array.from(new Set(Object.values(roleByMos))).filter(r => !CORE_CREW_ROLES.includes(r));
 
-  function getRows(key) {
-    const t = state.tables[key];
-    return t && Array.isArray(t.rows) ? t.rows : [];
-  }
-
-  const unitRows = getRows('v_unit');
-  const aircraftRows = getRows('v_aircraft');
-  const payloadRows = getRows('v_payload');
-  const staffingRows = getRows('v_staffing');
+  const unitTable = stateTable(state, 'v_unit');
+  const aircraftTable = stateTable(state, 'v_aircraft');
+  const payloadTable = stateTable(state, 'v_payload');
+  const staffingTable = stateTable(state, 'v_staffing');
 
   // Units (from v_unit)
-  const units = Array.from(new Set(
-    unitRows.map(r => r['Unit']).filter(Boolean)
-  ));
+  const unitSet = new Set();
+  const unitColumn = unitTable.column('Unit');
+  for (let i = 0; i < unitTable.length; i++) {
+    const unit = unitColumn(i);
+    if (unit) unitSet.add(unit);
+  }
+  const units = Array.from(unitSet);
 
   // FMC aircraft by unit (from v_aircraft)
   const aircraftByUnit = {};
-  for (const r of aircraftRows) {
-    const status = r['Status'];
-    const unit = r['Unit'];
+  const aircraftStatus = aircraftTable.column('Status');
+  const aircraftUnit = aircraftTable.column('Unit');
+  for (let i = 0; i < aircraftTable.length; i++) {
+    const status = aircraftStatus(i);
+    const unit = aircraftUnit(i);
     if (status === 'FMC' && unit) {
       aircraftByUnit[unit] = (aircraftByUnit[unit] || 0) + 1;
     }
@@ -42,24 +114,63 @@ function loadState(state) {
 
   // Payload counts by type and unit (from v_payload)
   const payloadByUnit = {};
-  for (const r of payloadRows) {
-    const unit = r['Unit'] || 'UNKNOWN';
-    const type = r['Type'];
+  const payloadUnit = payloadTable.column('Unit');
+  const payloadType = payloadTable.column('Type');
+  for (let i = 0; i < payloadTable.length; i++) {
+    const unit = payloadUnit(i) || 'UNKNOWN';
+    const type = payloadType(i);
     if (!type) continue;
     if (!payloadByUnit[unit]) payloadByUnit[unit] = {};
     payloadByUnit[unit][type] = (payloadByUnit[unit][type] || 0) + 1;
   }
 
-  // Staffing by MOS and unit (from v_staffing)
+  // Staffing by role and unit (from v_staffing, MOS mapped to role)
   const staffingByUnit = {};
-  for (const r of staffingRows) {
-    const unitName = r['Unit Name'];
-    const mos = r['MOS Number'];
+  const staffingUnit = staffingTable.column('Unit Name');
+  const staffingMos = staffingTable.column('MOS Number');
+  for (let i = 0; i < staffingTable.length; i++) {
+    const unitName = staffingUnit(i);
+    const mos = staffingMos(i);
     if (!unitName || !mos) continue;
-    if (!staffingByUnit[unitName]) staffingByUnit[unitName] = { pilot: 0, so: 0, intel: 0 };
-    if (mos === '7318') staffingByUnit[unitName].pilot += 1;
-    if (mos === '7314') staffingByUnit[unitName].so += 1;
-    if (mos === '0231') staffingByUnit[unitName].intel += 1;
+    if (!staffingByUnit[unitName]) staffingByUnit[unitName] = emptyStaffing(crewRoles);
+    const role = roleByMos[mos];
+    if (role) staffingByUnit[unitName][role] += 1;
+  }
+
+  // Missions already in progress at t=0 (optional v_active_missions)
+  // Missing resource columns are null and default to the mission type's requirements in Stage 4
+  const count = v => (v === undefined || v === null || v === '' ? null : Math.max(0, Math.floor(Number(v)) || 0));
+  const activeMissions = [];
+  const activeTable = stateTable(state, 'v_active_missions');
+  for (let i = 0; i < activeTable.length; i++) {
+    const cell = name => activeTable.column(name)(i);
+    const unit = cell('Unit');
+    const remaining = Number(cell('Remaining Hours'));
+    if (!unit || !(remaining > 0)) continue;
+    const payload = cell('Payload Type');
+    activeMissions.push({
+      unit,
+      mission_type: cell('Mission Type') || null,
+      remaining_hours: remaining,
+      elapsed_hours: Math.max(0, Number(cell('Elapsed Hours')) || 0),
+      aircraft: count(cell('Aircraft')),
+      pilot: count(cell('Pilot')),
+      so: count(cell('SO')),
+      intel: count(cell('Intel')),
+      payload_types: payload ? String(payload).split(',').map(t => t.trim()).filter(Boolean) : null
+    });
+  }
+
+  // Launch/recovery equipment sets (optional v_launch_recovery, one row per set; a Status other than FMC is unserviceable)
+  const launchRecoveryByUnit = {};
+  const launchRecoveryTable = stateTable(state, 'v_launch_recovery');
+  const launchRecoveryUnit = launchRecoveryTable.column('Unit');
+  const launchRecoveryStatus = launchRecoveryTable.column('Status');
+  for (let i = 0; i < launchRecoveryTable.length; i++) {
+    const unit = launchRecoveryUnit(i);
+    if (!unit) continue;
+    const status = launchRecoveryStatus(i);
+    launchRecoveryByUnit[unit] = (launchRecoveryByUnit[unit] || 0) + (status === undefined || status === null || status === 'FMC' ? 1 : 0);
   }
 
   // Ensure all units seen in resources are included
@@ -69,11 +180,36 @@ function loadState(state) {
   Object.keys(staffingByUnit).forEach(u => allUnits.add(u));
 
   return {
-    units: Array.from(allUnits),
+    units: Array.from(allUnits).sort(),
     aircraftByUnit,
     payloadByUnit,
     staffingByUnit,
+    crewRoles,
+    activeMissions,
+    launchRecoveryByUnit,
   };
 }
 
-module.exports = { loadState };
+/**
+ * Zeroed staffing counts for the core roles plus any additional roles
+ * @param {Array<string>} crewRoles - Additional roles
+ * @returns {Object} role → 0
+ */
+function emptyStaffing(crewRoles = []) {
+  const staffing = { pilot: 0, so: 0, intel: 0 };
+  for (const role of crewRoles) staffing[role] = 0;
+  return staffing;
+}
+
+/**
+ * Unit names of a pools map in state order (sorted, as loadState lists them)
+ * Object.keys alone would put integer-like names such as '267' first, in numeric order, and so change which
+ * unit round robin starts with
+ * @param {Object} pools - Map of unit → resource pools
+ * @returns {Array<string>} Unit names
+ */
+function unitNames(pools) {
+  return Object.keys(pools).sort();
+}
+
+module.exports = { loadState, resolveMosRoles, emptyStaffing, unitNames, DEFAULT_MOS_ROLES, CORE_CREW_ROLES };
@@ -0,0 +1,116 @@
+// Summary Module
+// Concise plain-text digest of what a scenario asks for, for logs and support
+// Everything is analytic (distribution means, expected demand counts); nothing is simulated
+
+const { expectedDemandCounts, applyFlightTimeModifiers } = require('./demand');
+const { distMean } = require('./distributions');
+
+/**
+ * Format hours compactly (at most two decimals, no trailing zeros)
+ * @param {number} hours - Hours
+ * @returns {string} e.g. '4.5h'
+ */
+function formatHours(hours) {
+  return `${Number(hours.toFixed(2))}h`;
+}
+
+/**
+ * Format a counts object as 'key n, key n' (zero counts included)
+ * @param {Object} counts - name → count
+ * @returns {string}
+ */
+function formatCounts(counts) {
+  const entries = Object.entries(counts || {});
+  return entries.length > 0 ? entries.map(([name, n]) => `${name} ${n}`).join(', ') : 'none';
+}
+
+/**
+ * Policies the scenario turns on, as short labels
+ * @param {Object} scenario - Scenario configuration
+ * @returns {Array<string>} Labels, e.g. 'unit_policy.mission_split', 'duty_requirements: SDO, ODO'
+ */
+function activePolicies(scenario) {
+  const policies = Object.keys(scenario.unit_policy || {}).map(key => `unit_policy.${key}`);
+  if (scenario.payload_policy?.daily_limits) {
+    policies.push(`payload_policy.daily_limits (${formatCounts(scenario.payload_policy.daily_limits)})`);
+  }
+  if (scenario.crew_policy?.min_crew_turn_hours) {
+    policies.push(`crew_policy.min_crew_turn_hours = ${scenario.crew_policy.min_crew_turn_hours}`);
+  }
+  if (scenario.crew_policy?.availability_rate) {
+    policies.push(`crew_policy.availability_rate (${formatCounts(scenario.crew_policy.availability_rate)}, ` +
+      `${scenario.crew_policy.availability_mode || 'deterministic'})`);
+  }
+  if (scenario.reallocation) {
+    policies.push(`reallocation (${(scenario.reallocation.transfers || []).length} transfers)`);
+  }
+  const duties = Object.entries(scenario.duty_requirements || {})
+    .filter(([, spec]) => spec && typeof spec === 'object' && spec.enabled)
+    .map(([duty]) => duty);
+  if (duties.length > 0) policies.push(`duty_requirements: ${duties.join(', ')}`);
+  const pt = scenario.process_times || {};
+  if (pt.hold_crew_during_process_times === false) policies.push('process_times.hold_crew_during_process_times = false');
+  if (pt.role_requirements) policies.push(`process_times.role_requirements (${Object.keys(pt.role_requirements).join(', ')})`);
+  if (pt.dismount_times) policies.push('process_times.dismount_times');
+  const demand = scenario.demand || [];
+  for (const key of ['units', 'retry', 'rate_uncertainty']) {
+    const n = demand.filter(d => d[key] !== undefined && d[key] !== null).length;
+    if (n > 0) policies.push(`demand ${key} (${n} of ${demand.length} entries)`);
+  }
+  return policies;
+}
+
+/**
+ * Render the scenario digest
+ * @param {Object} scenario - Scenario configuration (hours, validated)
+ * @param {Object|null} initial - Stage 2 output (resources after overrides), or null without a state snapshot
+ * @param {Array<Object>} warnings - Run warnings ({ code, message })
+ * @returns {string} Multi-line summary
+ */
+function formatScenarioSummary(scenario, initial, warnings = []) {
+  const horizon = scenario.horizon_hours || 24;
+  const lines = [`Scenario: ${formatHours(horizon)} horizon`];
+
+  const demand = expectedDemandCounts({ ...scenario, horizon_hours: horizon });
+  lines.push(`Demand entries (${demand.length}), expected events over the horizon:`);
+  for (const row of demand) {
+    lines.push(`  ${row.demand_source}: ${row.mission_type}, ${row.type}, ${row.expected === null ? 'unbounded' : row.expected}`);
+  }
+
+  const missionTypes = scenario.mission_types || [];
+  lines.push(`Mission types (${missionTypes.length}):`);
+  for (const mt of missionTypes) {
+    const flight = applyFlightTimeModifiers(mt, distMean(mt.flight_time));
+    const transit = (mt.flight_time?.transit_in_hours || 0) + (mt.flight_time?.transit_out_hours || 0);
+    const crew = Object.fromEntries(Object.entries(mt.required_aircrew || {}).filter(([role]) => role !== 'per_flight_hours'));
+    const payloads = mt.required_payload_types || [];
+    const windows = (mt.launch_windows || []).map(w => `${w.start_hour_of_day}-${w.end_hour_of_day}h`);
+    lines.push(
+      `  ${mt.name}: flight ${formatHours(flight)} mean${transit > 0 ? ` + ${formatHours(transit)} transit` : ''}; ` +
+      `crew ${formatCounts(crew)}; payloads ${payloads.length > 0 ? payloads.join(', ') : 'none'}` +
+      (windows.length > 0 ? `; launch windows ${windows.join(', ')} (${mt.launch_window_policy || 'defer'})` : '') +
+      (mt.max_concurrent !== undefined ? `; at most ${mt.max_concurrent} airborne` : '') +
+      (mt.requires_launch_recovery ? '; needs launch/recovery' : '')
+    );
+  }
+
+  if (initial) {
+    lines.push(`Units (${initial.units.length}, after overrides):`);
+    for (const unit of initial.units) {
+      lines.push(
+        `  ${unit}: aircraft ${initial.aircraftByUnit[unit] || 0}; crew ${formatCounts(initial.staffingByUnit[unit])}; ` +
+        `payloads ${formatCounts(initial.payloadByUnit[unit])}` +
+        (initial.launchRecoveryByUnit?.[unit] !== undefined ? `; launch/recovery ${initial.launchRecoveryByUnit[unit]}` : '')
+      );
+    }
+  } else {
+    lines.push('Units: no state snapshot');
+  }
+
+  const policies = activePolicies(scenario);
+  lines.push(`Policies: ${policies.length > 0 ? policies.join('; ') : 'none'}`);
+  lines.push(`Warnings: ${warnings.length > 0 ? warnings.map(w => `${w.code}: ${w.message}`).join('; ') : 'none'}`);
+  return lines.join('\n');
+}
+
+module.exports = { formatScenarioSummary };
@@ -0,0 +1,61 @@
+// Throughput Module
+// Rolling-window sortie counts for surge questions ("best 24 hours", not the average),
+// fixed-bucket rejection counts for when rejections cluster and per-hour demand versus starts
+
+const DEFAULT_PEAK_WINDOW_HOURS = 24;
+const DEFAULT_REJECTION_DENSITY_BUCKET_HOURS = 1;
+
+/**
+ * Find the sliding window holding the most mission starts
+ * A window covers [window_start, window_start + windowHours); two pointers over the sorted starts
+ * @param {Array<number>} startTimes - Mission start times in hours (any order)
+ * @param {number} windowHours       - Window length in hours
+ * @returns {Object} { max_starts, window_start } (window_start is the first start in the best window, null without starts)
+ */
+function peakWindow(startTimes, windowHours) {
+  const times = startTimes.slice().sort((a, b) => a - b);
+  let best = 0;
+  let bestStart = null;
+  let i = 0;
+  for (let j = 0; j < times.length; j++) {
+    while (times[j] - times[i] >= windowHours) i++;
+    if (j - i + 1 > best) {
+      best = j - i + 1;
+      bestStart = times[i];
+    }
+  }
+  return { max_starts: best, window_start: bestStart };
+}
+
+/**
+ * Count one rejection in its fixed-length time bucket; buckets cover [0, horizon)
+ * A reason's array is created, all zeros, on its first rejection
+ * @param {Object} density - { bucket_hours, buckets: reason → Array<number> }
+ * @param {number} horizon - Horizon in hours
+ * @param {string} reason  - Rejection reason
+ * @param {number} time    - Demand time in hours
+ */
+function addRejection(density, horizon, reason, time) {
+  let counts = density.buckets[reason];
+  if (!counts) {
+    counts = density.buckets[reason] = new Array(Math.max(1, Math.ceil(horizon / density.bucket_hours))).fill(0);
+  }
+  counts[Math.min(counts.length - 1, Math.max(0, Math.floor(time / density.bucket_hours)))]++;
+}
+
+/**
+ * Count one event in its hour of the run (results.hourly_profile arrays cover [0, ceil(horizon)))
+ * @param {Array<number>} counts - Per-hour counts
+ * @param {number} time          - Event time in hours
+ */
+function countInHour(counts, time) {
+  counts[Math.min(counts.length - 1, Math.max(0, Math.floor(time)))]++;
+}
+
+module.exports = {
+  peakWindow,
+  addRejection,
+  countInHour,
+  DEFAULT_PEAK_WINDOW_HOURS,
+  DEFAULT_REJECTION_DENSITY_BUCKET_HOURS
+};
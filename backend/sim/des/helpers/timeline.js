@@ -0,0 +1,149 @@
+// Timeline Sink Module
+// Streams timeline events to a callback in fixed-size chunks instead of keeping them in memory
+// Used when runSimulation is given settings.onTimelineChunk (or settings.collectTimeline false, which keeps nothing)
+// Also derives resource_level events (pool occupancy over time) for settings.emitResourceLevels
+
+const { withinHorizon } = require('./clock');
+const { unitNames } = require('./state');
+
+const DEFAULT_CHUNK_SIZE = 1000;
+
+/**
+ * Create a timeline sink that stands in for the results.timeline array
+ * Stages push events with sink.push(item) exactly as they would onto the array;
+ * every chunkSize events are handed to onChunk and released
+ * @param {Function} onChunk  - Called with each array of timeline events
+ * @param {number} chunkSize  - Events per chunk (default 1000)
+ * @param {number} horizon    - Simulation horizon in hours (for per-type completion tallies)
+ * @returns {Object} Sink with push(), flush(), completed and count
+ */
+function createTimelineSink(onChunk, chunkSize, horizon) {
+  const size = Number.isInteger(chunkSize) && chunkSize > 0 ? chunkSize : DEFAULT_CHUNK_SIZE;
+  let buffer = [];
+
+  const sink = {
+    streaming: true,
+    count: 0,
+    // Missions finishing within the horizon, by type then demand source
+    // (Stage 6 can no longer scan the timeline)
+    completed: {},
+
+    push(item) {
+      if (item.type === 'mission' && withinHorizon(item.finish_time, horizon)) {
+        countCompleted(sink.completed, item.mission_type, item.demand_source);
+      } else if (item.type === 'mission') {
+        // Same flag Stage 6 sets on an in-memory timeline
+        item.truncated = true;
+      }
+      buffer.push(item);
+      sink.count++;
+      if (buffer.length >= size) sink.flush();
+    },
+
+    flush() {
+      if (buffer.length === 0) return;
+      const chunk = buffer;
+      buffer = [];
+      onChunk(chunk);
+    }
+  };
+
+  return sink;
+}
+
+/**
+ * Create a sink for runs that keep no timeline at all (settings.collectTimeline false)
+ * Stages check sink.discarding and skip building events; missions are only counted, through countMission,
+ * so Stage 6 gets the same per-type completion tallies as from a streaming sink
+ * @param {number} horizon - Simulation horizon in hours
+ * @returns {Object} Sink with push() (drops the event), countMission(), flush(), completed and count (always 0)
+ */
+function createDiscardingSink(horizon) {
+  const sink = {
+    discarding: true,
+    count: 0,
+    completed: {},
+
+    push() {},
+
+    countMission(missionType, demandSource, finishTime) {
+      if (withinHorizon(finishTime, horizon)) countCompleted(sink.completed, missionType, demandSource);
+    },
+
+    flush() {}
+  };
+
+  return sink;
+}
+
+/**
+ * Add one completed mission to a sink's tallies
+ * @param {Object} completed - mission type → demand source → count
+ * @param {string} missionType - Mission type name
+ * @param {string} demandSource - Demand source
+ */
+function countCompleted(completed, missionType, demandSource) {
+  const bySource = completed[missionType] || (completed[missionType] = {});
+  bySource[demandSource] = (bySource[demandSource] || 0) + 1;
+}
+
+/**
+ * Occupancy changes of one pool as resource_level events
+ * Derived from the pool's acquisition intervals (plus crew turns and offline transferred aircraft) after the run, so releases
+ * need no bookkeeping; changes at the same instant are coalesced and only actual level changes are emitted
+ * @param {Object} pool - EquipmentPool or CrewQueue
+ * @param {number} horizon - Simulation horizon in hours
+ * @param {Object} fields - Fields identifying the pool ({ unit, resource, payload_type? })
+ * @returns {Array<Object>} Events { type: 'resource_level', time, ...fields, available, total }, the first at t=0
+ */
+function poolLevelEvents(pool, horizon, fields) {
+  const deltas = new Map();
+  const change = (time, delta) => {
+    const t = Math.max(0, time);
+    if (withinHorizon(t, horizon)) deltas.set(t, (deltas.get(t) || 0) + delta);
+  };
+  for (const interval of [...pool.busyIntervals, ...(pool.turnIntervals || []), ...(pool.offline || [])]) {
+    change(interval.start, -interval.count);
+    change(interval.end, interval.count);
+  }
+  if (!deltas.has(0)) deltas.set(0, 0);
+
+  const events = [];
+  let available = pool.total;
+  for (const time of [...deltas.keys()].sort((a, b) => a - b)) {
+    available += deltas.get(time);
+    if (events.length === 0 || available !== events[events.length - 1].available) {
+      events.push({ type: 'resource_level', time, ...fields, available, total: pool.total });
+    }
+  }
+  return events;
+}
+
+/**
+ * resource_level events for every pool of every unit, ordered by time
+ * @param {Object} pools - Map of unit → resource pools
+ * @param {number} horizon - Simulation horizon in hours
+ * @returns {Array<Object>} Events (see poolLevelEvents); payload pools carry payload_type
+ */
+function resourceLevelEvents(pools, horizon) {
+  const events = [];
+  for (const unit of unitNames(pools)) {
+    const pool = pools[unit];
+    events.push(...poolLevelEvents(pool.aircraft, horizon, { unit, resource: 'aircraft' }));
+    if (pool.controlStations) events.push(...poolLevelEvents(pool.controlStations, horizon, { unit, resource: 'control_station' }));
+    if (pool.launchRecovery) events.push(...poolLevelEvents(pool.launchRecovery, horizon, { unit, resource: 'launch_recovery' }));
+    for (const [ptype, payload] of Object.entries(pool.payloads)) {
+      events.push(...poolLevelEvents(payload, horizon, { unit, resource: 'payload', payload_type: ptype }));
+    }
+    for (const role of ['pilot', 'so', 'intel']) {
+      events.push(...poolLevelEvents(pool[role], horizon, { unit, resource: role }));
+    }
+    for (const [role, crew] of Object.entries(pool.roles || {})) {
+      events.push(...poolLevelEvents(crew, horizon, { unit, resource: role }));
+    }
+  }
+  // Stable sort keeps each pool's own order at equal times
+  return events.sort((a, b) => a.time - b.time);
+}
+
+module.exports = { createTimelineSink, createDiscardingSink, resourceLevelEvents, DEFAULT_CHUNK_SIZE };
@@ -0,0 +1,142 @@
+// Trace Module
+// Decision log for selected mission demands (settings.trace), for support and for checking new policies:
+// which unit the demand went to and why, every pool at that instant, the sampled durations, each check in
+// the order the engine made it and the outcome. Tracing only reads engine state, so a seeded run traces the same
+
+const { CORE_CREW_ROLES } = require('./state');
+const { missionSplit } = require('./assignment');
+
+/**
+ * Check settings.trace
+ * @param {Object} trace - { missionTypes, timeRange } (both optional)
+ * @returns {Object|null} Filter { missionTypes: Set|null, timeRange: [start, end]|null }, or null when tracing is off
+ * @throws {Error} If the filter is malformed
+ */
+function validateTrace(trace) {
+  if (trace === undefined || trace === null) return null;
+  if (typeof trace !== 'object' || Array.isArray(trace)) {
+    throw new Error('settings.trace must be { missionTypes, timeRange }');
+  }
+  const { missionTypes, timeRange } = trace;
+  if (missionTypes !== undefined && !(Array.isArray(missionTypes) && missionTypes.every(t => typeof t === 'string'))) {
+    throw new Error('settings.trace.missionTypes must be an array of mission type names');
+  }
+  if (timeRange !== undefined && !(Array.isArray(timeRange) && timeRange.length === 2 &&
+    timeRange.every(t => typeof t === 'number' && !Number.isNaN(t)) && timeRange[0] <= timeRange[1])) {
+    throw new Error(`settings.trace.timeRange must be [start, end] in hours with start <= end, got ${JSON.stringify(timeRange)}`);
+  }
+  return {
+    missionTypes: missionTypes ? new Set(missionTypes) : null,
+    timeRange: timeRange ? timeRange.slice() : null
+  };
+}
+
+/**
+ * True when a mission demand (at this attempt's time) passes the trace filter
+ * @param {Object} filter - Output of validateTrace
+ * @param {Object} ev - Mission demand event
+ * @returns {boolean}
+ */
+function traceMatches(filter, ev) {
+  if (filter.missionTypes && !filter.missionTypes.has(ev.mission_type)) return false;
+  return !filter.timeRange || (filter.timeRange[0] <= ev.time && ev.time <= filter.timeRange[1]);
+}
+
+/**
+ * Count, free count and next release of one pool
+ * @param {Object} resource - EquipmentPool or CrewQueue
+ * @param {number} time - Simulation time in hours
+ * @param {boolean} useReserve - Count free reserved units too (standby missions)
+ * @returns {Object} { total, available, next_release }
+ */
+function poolState(resource, time, useReserve) {
+  return { total: resource.total, available: resource.availableAt(time, useReserve), next_release: resource.nextReleaseAfter(time) };
+}
+
+/**
+ * How the demand's unit was chosen
+ * @param {Object} ev - Mission demand event (after assignment)
+ * @param {Object} scenario - Scenario configuration
+ * @param {Object|null} assignmentPolicy - Caller-supplied policy, if any
+ * @returns {Object} { by, ... }: pinned (with the pinned units), assignment_policy (with its name),
+ *                   mission_split (with the split) or round_robin
+ */
+function assignmentReason(ev, scenario, assignmentPolicy) {
+  if (ev.pinned_units) return { by: 'pinned', units: ev.pinned_units.slice() };
+  if (assignmentPolicy) return { by: 'assignment_policy', policy: assignmentPolicy.name || null };
+  const split = missionSplit(scenario);
+  if (split) return { by: 'mission_split', split: { ...split } };
+  return { by: 'round_robin' };
+}
+
+/**
+ * Start a trace entry for a mission demand, before the engine looks at it
+ * @param {Object} ev - Mission demand event (after assignment)
+ * @param {Object} pools - Map of unit → resource pools
+ * @param {Object} scenario - Scenario configuration
+ * @param {Object|null} assignmentPolicy - Caller-supplied policy, if any
+ * @returns {Object} Entry; processMissionDemand adds durations, checks and crew, the caller sets outcome and reason
+ */
+function startTrace(ev, pools, scenario, assignmentPolicy) {
+  const pool = ev.assignedUnit ? pools[ev.assignedUnit] : null;
+  const useReserve = Boolean(pool?.reservation?.forMissionTypes.has(ev.mission_type));
+  const availability = pool ? {
+    aircraft: poolState(pool.aircraft, ev.time, useReserve),
+    ...(pool.controlStations ? { control_station: poolState(pool.controlStations, ev.time, false) } : {}),
+    ...(pool.launchRecovery ? { launch_recovery: poolState(pool.launchRecovery, ev.time, false) } : {}),
+    ...Object.fromEntries(CORE_CREW_ROLES.map(role => [role, poolState(pool[role], ev.time, useReserve)])),
+    ...Object.fromEntries(Object.entries(pool.roles || {}).map(([role, q]) => [role, poolState(q, ev.time, false)])),
+    payloads: Object.fromEntries(Object.entries(pool.payloads).map(([ptype, p]) => [ptype, poolState(p, ev.time, false)]))
+  } : null;
+  return {
+    mission_id: ev.mission_id,
+    time: ev.time,
+    mission_type: ev.mission_type,
+    demand_source: ev.demand_source,
+    ...(ev.retry ? { attempt: ev.attempt } : {}),
+    ...(ev.deferred_from !== undefined ? { deferred_from: ev.deferred_from } : {}),
+    unit: ev.assignedUnit || null,
+    assignment: assignmentReason(ev, scenario, assignmentPolicy),
+    availability,
+    durations: null,
+    checks: [],
+    outcome: null,
+    reason: null
+  };
+}
+
+/**
+ * Record one availability check on a trace entry
+ * @param {Object|null} trace - Trace entry, or null when the demand is not traced
+ * @param {string} check - What was checked (aircraft, control_station, launch_recovery, concurrency_cap, pilot, so,
+ *                         intel, a role, payload:<type>, payload_budget, window)
+ * @param {number} needed - Units needed
+ * @param {number} available - Units the check could use
+ * @param {Object} extra - Optional fields to add (e.g. reserved_for_duty)
+ */
+function traceCheck(trace, check, needed, available, extra = {}) {
+  if (trace) trace.checks.push({ check, needed, available, ok: available >= needed, ...extra });
+}
+
+/**
+ * Close a trace entry once processMissionDemand returns
+ * @param {Object} trace - Trace entry
+ * @param {Object} ev - Mission demand event after processing
+ * @param {boolean} success - Whether the mission started
+ */
+function finishTrace(trace, ev, success) {
+  if (trace.outcome !== null) return;
+  const failed = trace.checks.find(c => !c.ok);
+  if (success) {
+    trace.outcome = 'started';
+  } else if (ev.deferred_until !== undefined) {
+    trace.outcome = 'deferred';
+    trace.deferred_until = ev.deferred_until;
+  } else {
+    trace.outcome = ev.next_attempt_time !== undefined ? 'retry' : 'rejected';
+    if (ev.next_attempt_time !== undefined) trace.next_attempt_time = ev.next_attempt_time;
+  }
+  if (!success && failed) trace.reason = failed.check.startsWith('payload:') ? 'payload' : failed.check;
+}
+
+module.exports = { validateTrace, traceMatches, startTrace, traceCheck, finishTrace };
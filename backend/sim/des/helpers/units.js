@@ -0,0 +1,141 @@
+// Time Units Module
+// Converts unit-tagged scenario values (minutes, hours, days) to hours before any stage runs
+// Internal math is always in hours; this only rewrites the scenario the stages see
+
+const { canonicalizeFieldNames } = require('./aliases');
+
+// Unit → [numerator, denominator] of its length in hours; kept as a ratio so 30 minutes is exactly 0.5 h
+const HOURS_PER_UNIT = {
+  minutes: [1, 60],
+  hours: [1, 1],
+  days: [24, 1]
+};
+
+/**
+ * Converters between a unit and hours
+ * @param {string} unit  - 'minutes', 'hours' or 'days'
+ * @param {string} where - Scenario path, for the error message
+ * @returns {Object} { toHours(duration), rateToHours(rate), logShift } for the unit
+ * @throws {Error} If the unit is unknown
+ */
+function converterFor(unit, where) {
+  const ratio = HOURS_PER_UNIT[unit];
+  if (ratio === undefined) {
+    throw new Error(`${where}.unit must be one of ${Object.keys(HOURS_PER_UNIT).join(', ')}, got ${unit}`);
+  }
+  const [num, den] = ratio;
+  return {
+    toHours: v => (v * num) / den,
+    rateToHours: r => (r * den) / num,
+    logShift: Math.log(num / den)
+  };
+}
+
+/**
+ * Convert a unit-tagged distribution spec to hours
+ * Durations (value_hours, value, a/m/b, transit times) scale by the unit; rates scale inversely;
+ * lognormal mu shifts by ln(hours per unit). Specs without a unit are returned unchanged.
+ * @param {Object} spec  - Distribution spec, optionally with unit
+ * @param {string} where - Scenario path, for error messages
+ * @returns {Object} Spec in hours (without unit)
+ * @throws {Error} If the unit is unknown or a converted duration is negative
+ */
+function distributionToHours(spec, where) {
+  if (!spec || typeof spec !== 'object' || spec.unit === undefined) return spec;
+  const convert = converterFor(spec.unit, where);
+  const { unit, ...out } = spec;
+
+  for (const key of ['value_hours', 'value', 'a', 'm', 'b', 'transit_in_hours', 'transit_out_hours']) {
+    if (typeof out[key] !== 'number') continue;
+    out[key] = convert.toHours(out[key]);
+    if (out[key] < 0) {
+      throw new Error(`${where}.${key} must not be negative: ${spec[key]} ${unit} resolves to ${out[key]} hours`);
+    }
+  }
+  for (const key of ['rate_per_hour', 'rate']) {
+    if (typeof out[key] === 'number') out[key] = convert.rateToHours(out[key]);
+  }
+  if (out.type === 'lognormal' && convert.logShift !== 0) {
+    out.mu = (out.mu || 0) + convert.logShift;
+  }
+  return out;
+}
+
+/**
+ * Resolve scenario.horizon ({ value, unit }) or scenario.horizon_hours to hours
+ * @param {Object} scenario - Scenario configuration
+ * @returns {number|undefined} Horizon in hours, or undefined when neither is given
+ * @throws {Error} If the horizon is not positive or the two forms disagree
+ */
+function resolveHorizonHours(scenario) {
+  const { horizon, horizon_hours: horizonHours } = scenario;
+  if (horizon === undefined || horizon === null) return horizonHours;
+  if (typeof horizon !== 'object' || typeof horizon.value !== 'number') {
+    throw new Error('scenario.horizon must be { value, unit }');
+  }
+  const hours = converterFor(horizon.unit ?? 'hours', 'scenario.horizon').toHours(horizon.value);
+  if (!(hours > 0)) {
+    throw new Error(`scenario.horizon must be positive: ${horizon.value} ${horizon.unit ?? 'hours'} resolves to ${hours} hours`);
+  }
+  if (horizonHours !== undefined && horizonHours !== null && horizonHours !== hours) {
+    throw new Error(`scenario.horizon (${horizon.value} ${horizon.unit ?? 'hours'} = ${hours} hours) conflicts with horizon_hours ${horizonHours}`);
+  }
+  return hours;
+}
+
+/**
+ * Return the scenario with aliased field names made canonical (see aliases.js) and every unit-tagged value converted to hours
+ * Covers horizon, mission flight times, process times (including mount times) and renewal inter-arrival gaps.
+ * The input is not modified; scenarios without aliases or units come back as the same object.
+ * @param {Object} scenario - Scenario configuration
+ * @returns {Object} Scenario in hours
+ */
+function normalizeScenarioUnits(scenario) {
+  if (!scenario || typeof scenario !== 'object') return scenario;
+  scenario = canonicalizeFieldNames(scenario);
+  const out = { ...scenario };
+  let changed = false;
+  const convert = (spec, where) => {
+    const converted = distributionToHours(spec, where);
+    if (converted !== spec) changed = true;
+    return converted;
+  };
+
+  if (scenario.horizon !== undefined && scenario.horizon !== null) {
+    out.horizon_hours = resolveHorizonHours(scenario);
+    delete out.horizon;
+    changed = true;
+  }
+
+  if (Array.isArray(scenario.mission_types)) {
+    out.mission_types = scenario.mission_types.map((mt, i) => {
+      const flightTime = convert(mt?.flight_time, `mission_types[${i}].flight_time`);
+      return flightTime === mt?.flight_time ? mt : { ...mt, flight_time: flightTime };
+    });
+  }
+
+  if (scenario.process_times && typeof scenario.process_times === 'object') {
+    const pt = { ...scenario.process_times };
+    for (const step of ['preflight', 'postflight', 'turnaround']) {
+      if (pt[step] !== undefined) pt[step] = convert(pt[step], `process_times.${step}`);
+    }
+    for (const key of ['mount_times', 'dismount_times']) {
+      if (pt[key] && typeof pt[key] === 'object') {
+        pt[key] = Object.fromEntries(Object.entries(pt[key])
+          .map(([ptype, spec]) => [ptype, convert(spec, `process_times.${key}['${ptype}']`)]));
+      }
+    }
+    out.process_times = pt;
+  }
+
+  if (Array.isArray(scenario.demand)) {
+    out.demand = scenario.demand.map((d, i) => {
+      const interarrival = convert(d?.interarrival, `demand[${i}].interarrival`);
+      return interarrival === d?.interarrival ? d : { ...d, interarrival };
+    });
+  }
+
+  return changed ? out : scenario;
+}
+
+module.exports = { normalizeScenarioUnits, resolveHorizonHours, distributionToHours, HOURS_PER_UNIT };
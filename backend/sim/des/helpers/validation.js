@@ -0,0 +1,195 @@
+// Validation Module
+// Scenario checks that run before any stage, so a scenario that cannot mean anything fails loudly
+// instead of producing a run that "succeeds" with all-zero stats
+
+const { irrelevantFieldWarnings, scenarioDistributions } = require('./aliases');
+const { DISTRIBUTION_TYPES } = require('./distributions');
+const { missionSplit } = require('./assignment');
+const { QUALITY_PENALTY_KEYS } = require('./quality');
+const { CREW_AVAILABILITY_MODES } = require('./absence');
+
+const SCENARIO_ERROR_CODE = 'INVALID_SCENARIO';
+
+/**
+ * Build the structured error thrown for an invalid scenario
+ * @param {string} path    - Scenario path of the offending value, e.g. 'demand[2].mission_type'
+ * @param {string} message - What is wrong with it
+ * @returns {Error} Error with code and path
+ */
+function scenarioError(path, message) {
+  const error = new Error(`Invalid scenario: ${path} ${message}`);
+  error.code = SCENARIO_ERROR_CODE;
+  error.path = path;
+  return error;
+}
+
+/**
+ * Check the scenario's horizon, mission types and demand references (after unit normalization)
+ * @param {Object} scenario - Scenario configuration
+ * @throws {Error} With code 'INVALID_SCENARIO' and the offending path
+ */
+function validateScenario(scenario) {
+  const horizon = scenario.horizon_hours;
+  if (horizon !== undefined && horizon !== null && !(typeof horizon === 'number' && Number.isFinite(horizon) && horizon > 0)) {
+    throw scenarioError('horizon_hours', `must be a finite number greater than 0, got ${horizon}`);
+  }
+
+  const missionTypes = scenario.mission_types;
+  if (missionTypes !== undefined && !Array.isArray(missionTypes)) {
+    throw scenarioError('mission_types', 'must be an array');
+  }
+  const demand = scenario.demand || [];
+  if (!Array.isArray(demand)) {
+    throw scenarioError('demand', 'must be an array');
+  }
+  if (demand.length > 0 && (missionTypes || []).length === 0) {
+    throw scenarioError('mission_types', `must not be empty when demand has ${demand.length} entries`);
+  }
+
+  const names = new Set((missionTypes || []).map(mt => mt?.name));
+  demand.forEach((d, i) => {
+    if (!names.has(d?.mission_type)) {
+      throw scenarioError(`demand[${i}].mission_type`, `'${d?.mission_type}' does not match any mission_types[].name`);
+    }
+  });
+
+  const split = scenario.unit_policy?.mission_split;
+  if (split !== undefined && split !== null) {
+    if (typeof split !== 'object' || Array.isArray(split)) {
+      throw scenarioError('unit_policy.mission_split', 'must be an object mapping units to ratios');
+    }
+    for (const [unit, ratio] of Object.entries(split)) {
+      if (!(typeof ratio === 'number' && Number.isFinite(ratio) && ratio >= 0)) {
+        throw scenarioError(`unit_policy.mission_split.${unit}`, `must be a non-negative number, got ${ratio}`);
+      }
+    }
+  }
+
+  // payload_policy.daily_limits: { payloadType: acquisitions per day across all units }
+  for (const [ptype, limit] of Object.entries(scenario.payload_policy?.daily_limits || {})) {
+    if (!Number.isInteger(limit) || limit < 0) {
+      throw scenarioError(`payload_policy.daily_limits['${ptype}']`, `must be a non-negative integer, got ${limit}`);
+    }
+  }
+
+  // crew_policy.min_crew_turn_hours: gap between a crew member's consecutive sorties
+  const minCrewTurn = scenario.crew_policy?.min_crew_turn_hours;
+  if (minCrewTurn !== undefined && !(typeof minCrewTurn === 'number' && Number.isFinite(minCrewTurn) && minCrewTurn >= 0)) {
+    throw scenarioError('crew_policy.min_crew_turn_hours', `must be a non-negative number, got ${minCrewTurn}`);
+  }
+
+  // crew_policy.availability_rate: { role: fraction of the crew present on a day }, applied per availability_mode
+  // (role names are checked against the unit's crew in Stage 4)
+  const availabilityRate = scenario.crew_policy?.availability_rate;
+  if (availabilityRate !== undefined && (!availabilityRate || typeof availabilityRate !== 'object' || Array.isArray(availabilityRate))) {
+    throw scenarioError('crew_policy.availability_rate', 'must be an object mapping crew roles to rates');
+  }
+  for (const [role, rate] of Object.entries(availabilityRate || {})) {
+    if (!(typeof rate === 'number' && rate >= 0 && rate <= 1)) {
+      throw scenarioError(`crew_policy.availability_rate.${role}`, `must be a number between 0 and 1, got ${rate}`);
+    }
+  }
+  const availabilityMode = scenario.crew_policy?.availability_mode;
+  if (availabilityMode !== undefined && !CREW_AVAILABILITY_MODES.includes(availabilityMode)) {
+    throw scenarioError('crew_policy.availability_mode', `must be one of ${CREW_AVAILABILITY_MODES.join(', ')}, got ${availabilityMode}`);
+  }
+
+  const penalties = scenario.quality_penalties;
+  if (penalties !== undefined && penalties !== null) {
+    if (typeof penalties !== 'object' || Array.isArray(penalties)) {
+      throw scenarioError('quality_penalties', 'must be an object mapping fallbacks to penalties');
+    }
+    for (const [key, penalty] of Object.entries(penalties)) {
+      if (!QUALITY_PENALTY_KEYS.includes(key)) {
+        throw scenarioError(`quality_penalties.${key}`, `is not a known fallback (expected ${QUALITY_PENALTY_KEYS.join(', ')})`);
+      }
+      if (!(typeof penalty === 'number' && penalty >= 0 && penalty <= 1)) {
+        throw scenarioError(`quality_penalties.${key}`, `must be a number between 0 and 1, got ${penalty}`);
+      }
+    }
+  }
+
+  for (const [path, spec] of scenarioDistributions(scenario)) validateDistribution(spec, path);
+}
+
+/**
+ * Check one distribution spec (canonical field names, in hours): a known type and parameters it can sample from
+ * Parameters left out keep sampleDist's defaults
+ * @param {Object} spec - Distribution spec
+ * @param {string} path - Scenario path of the spec, e.g. 'mission_types[0].flight_time'
+ * @throws {Error} With code 'INVALID_SCENARIO' and the offending path
+ */
+function validateDistribution(spec, path) {
+  if (!spec || typeof spec !== 'object' || Array.isArray(spec)) {
+    throw scenarioError(path, 'must be a distribution spec object');
+  }
+  const type = spec.type || 'deterministic';
+  if (!DISTRIBUTION_TYPES.includes(type)) {
+    throw scenarioError(`${path}.type`, `must be one of ${DISTRIBUTION_TYPES.join(', ')}, got '${type}'`);
+  }
+  const number = (key, check, expected) => {
+    const value = spec[key];
+    if (value === undefined) return;
+    if (!(typeof value === 'number' && Number.isFinite(value) && check(value))) {
+      throw scenarioError(`${path}.${key}`, `must be ${expected}, got ${value}`);
+    }
+  };
+  if (type === 'exponential') number('rate_per_hour', v => v > 0, 'a positive number');
+  if (type === 'lognormal') {
+    number('mu', () => true, 'a finite number');
+    number('sigma', v => v > 0, 'a positive number');
+  }
+  if (type === 'triangular') {
+    for (const key of ['a', 'm', 'b']) {
+      if (spec[key] === undefined) throw scenarioError(`${path}.${key}`, 'is required for triangular distributions');
+      number(key, () => true, 'a finite number');
+    }
+    if (!(spec.a <= spec.m && spec.m <= spec.b && spec.a < spec.b)) {
+      throw scenarioError(path, `triangular needs a <= m <= b with a < b, got a=${spec.a} m=${spec.m} b=${spec.b}`);
+    }
+  }
+}
+
+/**
+ * Warnings about a scenario that runs but cannot fly anything (after state and overrides are applied),
+ * or sets fields its entries' types ignore
+ * @param {Object} scenario - Scenario configuration
+ * @param {Object} initial  - Stage 2 output ({ units, aircraftByUnit })
+ * @returns {Array<Object>} [{ code, message }] (IRRELEVANT_FIELD warnings add path), empty when nothing looks wrong
+ */
+function scenarioWarnings(scenario, initial) {
+  const warnings = irrelevantFieldWarnings(scenario);
+  const flyable = initial.units.filter(unit => (initial.aircraftByUnit[unit] || 0) > 0);
+  if ((scenario.demand || []).length > 0 && flyable.length === 0) {
+    warnings.push({
+      code: 'NO_AIRCRAFT',
+      message: `No unit has FMC aircraft after overrides (${initial.units.length} units); no mission can fly`
+    });
+  }
+  const split = scenario.unit_policy?.mission_split;
+  if (split && Object.keys(split).length > 0 && !missionSplit(scenario)) {
+    warnings.push({
+      code: 'MISSION_SPLIT_ZERO_TOTAL',
+      message: `unit_policy.mission_split ratios sum to 0 (${Object.keys(split).join(', ')}); missions are assigned round robin over all units`
+    });
+  }
+  return warnings;
+}
+
+/**
+ * True for the error an invalid scenario throws
+ * @param {Error} error - Error to check
+ * @returns {boolean}
+ */
+function isScenarioError(error) {
+  return Boolean(error) && error.code === SCENARIO_ERROR_CODE;
+}
+
+module.exports = {
+  scenarioError,
+  validateScenario,
+  validateDistribution,
+  scenarioWarnings,
+  isScenarioError,
+  SCENARIO_ERROR_CODE
+};
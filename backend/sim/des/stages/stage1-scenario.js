@@ -3,6 +3,10 @@
 
 const { logWithLocation } = require('../../../utils');
 const { buildMissionMap } = require('../helpers/demand');
+const { validateScenario, scenarioError } = require('../helpers/validation');
+
+// Process steps that process_times.role_requirements may hold crew for
+const ROLE_REQUIREMENT_STEPS = ['preflight', 'mount', 'postflight', 'dismount', 'turnaround'];
 
 /**
  * Process scenario configuration and extract simulation parameters
@@ -15,12 +19,35 @@ function processScenario(scenario) {
   logWithLocation(` Simulation Start `);
   logWithLocation(`******************\n`);
 
+  validateScenario(scenario);
   const horizon = scenario.horizon_hours || 24;
   const missionTypes = buildMissionMap(scenario);
   const preSpec = scenario.process_times?.preflight;
   const postSpec = scenario.process_times?.postflight;
   const turnSpec = scenario.process_times?.turnaround;
 
+  // dismount_times: { payloadType: Distribution }, sampled per required payload after postflight
+  const dismountTimes = scenario.process_times?.dismount_times;
+  if (dismountTimes !== undefined && (!dismountTimes || typeof dismountTimes !== 'object' || Array.isArray(dismountTimes))) {
+    throw scenarioError('process_times.dismount_times', 'must be an object mapping payload types to distributions');
+  }
+  const dismountAfterTurnaround = scenario.process_times?.dismount_after_turnaround;
+  if (dismountAfterTurnaround !== undefined && typeof dismountAfterTurnaround !== 'boolean') {
+    throw scenarioError('process_times.dismount_after_turnaround', `must be a boolean, got ${dismountAfterTurnaround}`);
+  }
+
+  // role_requirements: { step: { role: count } }
+  for (const [step, roles] of Object.entries(scenario.process_times?.role_requirements || {})) {
+    if (!ROLE_REQUIREMENT_STEPS.includes(step)) {
+      throw scenarioError(`process_times.role_requirements.${step}`, `is not a known step (expected ${ROLE_REQUIREMENT_STEPS.join(', ')})`);
+    }
+    for (const [role, count] of Object.entries(roles || {})) {
+      if (!Number.isInteger(count) || count < 0) {
+        throw scenarioError(`process_times.role_requirements.${step}.${role}`, `must be a non-negative integer, got ${count}`);
+      }
+    }
+  }
+
   return {
     horizon,
     missionTypes,
@@ -1,7 +1,7 @@
 // Stage 2: Settings
 // Load initial state and apply resource overrides
 
-const { loadState } = require('../helpers/state');
+const { loadState, emptyStaffing, CORE_CREW_ROLES } = require('../helpers/state');
 
 /**
  * Apply settings: load initial state and apply overrides
@@ -14,7 +14,7 @@ function applySettings(settings, scenario) {
   // Load state from database snapshot
   let initial;
   if (settings.state) {
-    initial = loadState(settings.state);
+    initial = loadState(settings.state, scenario.mos_roles);
   }
   if (!initial || !initial.units || initial.units.length === 0) {
     throw new Error('Simulation requires a valid state snapshot with tables: v_aircraft, v_payload, v_staffing, v_unit');
@@ -33,11 +33,21 @@ function applySettings(settings, scenario) {
       if (!initial.units.includes(unit)) initial.units.push(unit);
       if (o && typeof o === 'object') {
         if (Number.isFinite(o.aircraft)) initial.aircraftByUnit[unit] = Math.max(0, Math.floor(o.aircraft));
+        if (Number.isFinite(o.launch_recovery)) initial.launchRecoveryByUnit[unit] = Math.max(0, Math.floor(o.launch_recovery));
         // Ensure crew object exists
-        if (!initial.staffingByUnit[unit]) initial.staffingByUnit[unit] = { pilot: 0, so: 0, intel: 0 };
+        if (!initial.staffingByUnit[unit]) initial.staffingByUnit[unit] = emptyStaffing(initial.crewRoles);
         if (Number.isFinite(o.pilot)) initial.staffingByUnit[unit].pilot = Math.max(0, Math.floor(o.pilot));
         if (Number.isFinite(o.so)) initial.staffingByUnit[unit].so = Math.max(0, Math.floor(o.so));
         if (Number.isFinite(o.intel)) initial.staffingByUnit[unit].intel = Math.max(0, Math.floor(o.intel));
+        // Per-role counts for any role (crew_by_role = { maintainer: 12, ... }); wins over pilot/so/intel
+        if (o.crew_by_role && typeof o.crew_by_role === 'object') {
+          for (const [role, valRaw] of Object.entries(o.crew_by_role)) {
+            if (!CORE_CREW_ROLES.includes(role) && !initial.crewRoles.includes(role)) {
+              throw new Error(`overrides.units['${unit}'].crew_by_role: unknown role '${role}' (add its MOS to scenario.mos_roles)`);
+            }
+            initial.staffingByUnit[unit][role] = Number.isFinite(valRaw) ? Math.max(0, Math.floor(valRaw)) : 0;
+          }
+        }
         // Payload overrides
         // 1) Per-type mapping (preferred): payload_by_type = { 'SkyTower II': 6, ... }
         if (o.payload_by_type && typeof o.payload_by_type === 'object') {
@@ -61,6 +71,9 @@ function applySettings(settings, scenario) {
     }
   }
 
+  // Fixed unit order whatever the snapshot's row order or override key order, so runs and their JSON are reproducible
+  initial.units.sort();
+
   return initial;
 }
 
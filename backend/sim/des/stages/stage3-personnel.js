@@ -3,6 +3,7 @@
 
 const { logPersonnelAvailability } = require('../../../utils');
 const { calculateAvailabilityFactor } = require('../helpers/availability');
+const { resolveMosRoles } = require('../helpers/state');
 
 /**
  * Process personnel configuration: availability factors and work schedules
@@ -38,6 +39,19 @@ function processPersonnel(scenario, initial) {
     ...(intelConfig.work_schedule), daily_crew_rest_hours: intelCrewRestHours
   };
 
+  // Additional crew roles (scenario.mos_roles) take availability from the first of their MOS
+  // numbers with a personnel_availability entry
+  const roleByMos = resolveMosRoles(scenario.mos_roles);
+  const crewRoleConfig = {};
+  for (const role of initial.crewRoles || []) {
+    const mos = Object.keys(roleByMos).find(m => roleByMos[m] === role && personnelAvailability[m]);
+    const config = mos ? personnelAvailability[mos] : {};
+    crewRoleConfig[role] = {
+      availability: calculateAvailabilityFactor(config),
+      workSchedule: { ...(config.work_schedule), daily_crew_rest_hours: config.daily_crew_rest_hours || 0 }
+    };
+  }
+
   // Calculate total and effective personnel across all units for logging
   let totalPilots = 0;
   let totalSOs = 0;
@@ -94,6 +108,7 @@ function processPersonnel(scenario, initial) {
     pilotWorkSchedule,
     soWorkSchedule,
     intelWorkSchedule,
+    crewRoleConfig,
     personnelAvailability
   };
 }
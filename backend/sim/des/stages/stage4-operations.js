@@ -4,7 +4,53 @@
 
 const { logWithLocation } = require('../../../utils');
 const { generateDemand } = require('../helpers/demand');
-const { EquipmentPool, CrewQueue } = require('../helpers/resources');
+const { EquipmentPool, WindowPool, CrewQueue } = require('../helpers/resources');
+const { buildTransferEvents } = require('../helpers/reallocation');
+const { defaultPolicy } = require('../helpers/assignment');
+const { deterministicCrewRate, drawCrewAbsences } = require('../helpers/absence');
+const { unitNames } = require('../helpers/state');
+const { scenarioError } = require('../helpers/validation');
+
+// Pools unit_policy.reservations may hold back for standby missions
+const RESERVABLE_RESOURCES = ['aircraft', 'pilot', 'so', 'intel'];
+
+/**
+ * Seed pools with missions already in progress at t=0 (state table v_active_missions)
+ * Resource counts missing from a row default to the row's mission type requirements
+ * @param {Object} scenario - Scenario configuration
+ * @param {Array<Object>} activeMissions - Rows parsed by loadState
+ * @param {Object} pools - Map of unit → resource pools
+ * @returns {Array<Object>} Seeded missions with the crew ids they hold
+ * @throws {Error} With code 'INVALID_SCENARIO' if a row names an unknown unit or holds more than the unit has free
+ */
+function seedActiveMissions(scenario, activeMissions, pools) {
+  const missionTypes = new Map((scenario.mission_types || []).map(mt => [mt.name, mt]));
+  return activeMissions.map((m, i) => {
+    const where = `v_active_missions[${i}]`;
+    const pool = pools[m.unit];
+    if (!pool) throw scenarioError(`${where}.Unit`, `names unknown unit '${m.unit}'`);
+    const mt = missionTypes.get(m.mission_type);
+    const aircrew = mt?.required_aircrew || {};
+    const until = m.remaining_hours;
+    const hold = (resource, ok) => {
+      if (!ok) throw scenarioError(where, `holds more ${resource} than ${m.unit} has free (${m.unit} has too few free ${resource} for a mission in progress)`);
+    };
+
+    hold('aircraft', pool.aircraft.seedHold(until, m.aircraft ?? 1));
+    if (pool.controlStations) hold('control_station', pool.controlStations.seedHold(until, 1));
+    const payloadTypes = m.payload_types ?? mt?.required_payload_types ?? [];
+    for (const ptype of payloadTypes) {
+      hold(`payload '${ptype}'`, pool.payloads[ptype]?.seedHold(until, 1));
+    }
+    const crew = {};
+    for (const [role, key] of [['pilot', 'pilots'], ['so', 'sos'], ['intel', 'intel']]) {
+      const ids = pool[role].seedHold(until, m[role] ?? aircrew[role] ?? 0);
+      hold(role, ids);
+      crew[key] = ids;
+    }
+    return { ...m, payload_types: payloadTypes, crew };
+  });
+}
 
 /**
  * Process operations: generate demand and initialize resource pools
@@ -12,9 +58,14 @@ const { EquipmentPool, CrewQueue } = require('../helpers/resources');
  * @param {Object} scenario - Scenario configuration
  * @param {Object} initial - Initial state with resource counts
  * @param {Object} personnel - Personnel configuration from stage 3
- * @returns {Object} Events, pools, and unit selection data
+ * @param {Object} watchdog - Optional limits watchdog for demand generation
+ * @param {Object} assignmentPolicy - Optional caller-supplied policy (helpers/assignment.js); non-pinned demands
+ *                                    are then left unassigned here and assigned at demand time in stage 5
+ * @param {Function} crewAbsenceRng - Optional generator for crew_policy.availability_rate absences (stochastic mode);
+ *                                    defaults to the run's random()
+ * @returns {Object} Events, demand generation counters, pools, unit selection data and seeded active missions
  */
-function processOperations(scenario, initial, personnel) {
+function processOperations(scenario, initial, personnel, watchdog = null, assignmentPolicy = null, crewAbsenceRng = null) {
   const {
     pilotAvailability,
     soAvailability,
@@ -24,11 +75,13 @@ function processOperations(scenario, initial, personnel) {
     intelCrewRestHours,
     pilotWorkSchedule,
     soWorkSchedule,
-    intelWorkSchedule
+    intelWorkSchedule,
+    crewRoleConfig = {}
   } = personnel;
 
   // Generate demand events first
-  const events = generateDemand(scenario);
+  const demandStats = { renewal_gap_clamps: 0 };
+  const events = generateDemand(scenario, watchdog, demandStats);
 
   const pools = {};
 
@@ -44,9 +97,15 @@ function processOperations(scenario, initial, personnel) {
 
     // Apply availability factors to get effective crew sizes
     // Only the effective crew can be used in simulation
-    const effectivePilots = Math.floor(fullPilots * pilotAvailability);
-    const effectiveSOs = Math.floor(fullSOs * soAvailability);
-    const effectiveIntel = Math.floor(fullIntel * intelAvailability);
+    const headcount = {
+      pilot: Math.floor(fullPilots * pilotAvailability),
+      so: Math.floor(fullSOs * soAvailability),
+      intel: Math.floor(fullIntel * intelAvailability)
+    };
+    // crew_policy.availability_rate in deterministic mode scales them again (absence on an average day)
+    const effectivePilots = Math.floor(headcount.pilot * deterministicCrewRate(scenario, 'pilot'));
+    const effectiveSOs = Math.floor(headcount.so * deterministicCrewRate(scenario, 'so'));
+    const effectiveIntel = Math.floor(headcount.intel * deterministicCrewRate(scenario, 'intel'));
 
     pools[unit] = {
       aircraft: new EquipmentPool(`aircraft:${unit}`, acTotal),
@@ -57,6 +116,11 @@ function processOperations(scenario, initial, personnel) {
       intel: new CrewQueue(`intel:${unit}`, effectiveIntel, 0, intelWorkSchedule),
       payloads: {},
       missionFinishes: [],
+      missionStarts: [],
+      // Airborne hours (transit in, on station, transit out) of missions started, for results.end_state
+      flightHours: 0,
+      // { mission_type, finish_time, quality } per started mission, with scenario.quality_penalties
+      missionQualities: [],
       availability_factors: {
         pilot: pilotAvailability,
         so: soAvailability,
@@ -71,14 +135,84 @@ function processOperations(scenario, initial, personnel) {
         pilot: effectivePilots,
         so: effectiveSOs,
         intel: effectiveIntel
-      }
+      },
+      // Crew before crew_policy.availability_rate, for results.crew_availability
+      crew_headcount: headcount
     };
+    // Additional crew roles (scenario.mos_roles), e.g. maintainers
+    pools[unit].roles = {};
+    for (const [role, config] of Object.entries(crewRoleConfig)) {
+      const fullCount = crew[role] || 0;
+      const roleHeadcount = Math.floor(fullCount * config.availability);
+      const effectiveCount = Math.floor(roleHeadcount * deterministicCrewRate(scenario, role));
+      pools[unit].roles[role] = new CrewQueue(`${role}:${unit}`, effectiveCount, 0, config.workSchedule);
+      pools[unit].crew_headcount[role] = roleHeadcount;
+      pools[unit].availability_factors[role] = config.availability;
+      pools[unit].initial_crew[role] = fullCount;
+      pools[unit].effective_crew[role] = effectiveCount;
+    }
+    // Crew turn (crew_policy.min_crew_turn_hours): every flight crew pool rests this long after each sortie
+    const crewTurn = scenario.crew_policy?.min_crew_turn_hours || 0;
+    for (const crewPool of [pools[unit].pilot, pools[unit].so, pools[unit].intel, ...Object.values(pools[unit].roles)]) {
+      crewPool.setTurnHours(crewTurn);
+    }
+    // Optional cap on simultaneous missions (e.g. ground control stations), held for the full mission
+    const maxConcurrent = scenario.unit_policy?.max_concurrent_missions?.[unit];
+    pools[unit].controlStations = typeof maxConcurrent === 'number' && maxConcurrent >= 0
+      ? new EquipmentPool(`control_station:${unit}`, Math.floor(maxConcurrent))
+      : null;
+    // Launch/recovery equipment (v_launch_recovery or overrides launch_recovery), booked for launch and recovery only
+    const launchRecovery = initial.launchRecoveryByUnit?.[unit];
+    pools[unit].launchRecovery = launchRecovery !== undefined ? new WindowPool(`launch_recovery:${unit}`, launchRecovery) : null;
+    // Standby reservation (unit_policy.reservations): held back for the listed mission types only
+    const reservation = scenario.unit_policy?.reservations?.[unit];
+    pools[unit].reservation = null;
+    if (reservation) {
+      const forTypes = reservation.for_mission_types;
+      if (!Array.isArray(forTypes) || forTypes.length === 0) {
+        throw scenarioError(`unit_policy.reservations['${unit}'].for_mission_types`, 'must be a non-empty array of mission type names');
+      }
+      for (const resource of RESERVABLE_RESOURCES) {
+        const count = reservation[resource];
+        if (count === undefined) continue;
+        if (!Number.isInteger(count) || count < 0) {
+          throw scenarioError(`unit_policy.reservations['${unit}'].${resource}`, `must be a non-negative integer, got ${count}`);
+        }
+        pools[unit][resource].setReserved(count);
+      }
+      pools[unit].reservation = { forMissionTypes: new Set(forTypes) };
+    }
+    // Crew proficiency (unit_policy.crew_experience): scales this unit's sampled preflight/postflight
+    const experience = scenario.unit_policy?.crew_experience?.[unit];
+    if (experience !== undefined && !(typeof experience === 'number' && Number.isFinite(experience) && experience > 0)) {
+      throw scenarioError(`unit_policy.crew_experience['${unit}']`, `must be a positive number, got ${experience}`);
+    }
+    pools[unit].crewExperience = experience === undefined ? 1 : experience;
     const payloads = initial.payloadByUnit[unit] || {};
     for (const [ptype, count] of Object.entries(payloads)) {
       pools[unit].payloads[ptype] = new EquipmentPool(`payload:${unit}:${ptype}`, count || 0);
     }
   }
 
+  for (const unit of Object.keys(scenario.unit_policy?.crew_experience || {})) {
+    if (!pools[unit]) throw scenarioError(`unit_policy.crew_experience['${unit}']`, `names unknown unit '${unit}'`);
+  }
+
+  // Crew absences (crew_policy.availability_rate, stochastic mode): who is off each day, drawn once per run
+  const crewRoles = ['pilot', 'so', 'intel', ...Object.keys(crewRoleConfig)];
+  for (const role of Object.keys(scenario.crew_policy?.availability_rate || {})) {
+    if (!crewRoles.includes(role)) {
+      throw scenarioError('crew_policy.availability_rate', `names unknown crew role '${role}' (expected ${crewRoles.join(', ')})`);
+    }
+  }
+  drawCrewAbsences(pools, scenario, scenario.horizon_hours || 24, crewAbsenceRng);
+
+  // Scheduled aircraft moves between units and bases (scenario.reallocation)
+  events.push(...buildTransferEvents(scenario, pools));
+
+  // Missions already airborne when the run starts hold their resources from t=0
+  const activeMissions = seedActiveMissions(scenario, initial.activeMissions || [], pools);
+
   // Calculate duty rotation pool sizes
   const dutyCycleDays = 30;
   const dutyCycleHours = dutyCycleDays * 24; // 720 hours
@@ -117,100 +251,68 @@ function processOperations(scenario, initial, personnel) {
     const soPoolSize = Math.ceil(soDutySlots * 1.2);
 
     // Don't exceed total crew size
-    const effectivePilotPoolSize = Math.min(pilotPoolSize, pool.pilot.total);
-    const effectiveSOPoolSize = Math.min(soPoolSize, pool.so.total);
+    // Standby crew never stand duty
+    const effectivePilotPoolSize = Math.min(pilotPoolSize, pool.pilot.total - pool.pilot.reserved);
+    const effectiveSOPoolSize = Math.min(soPoolSize, pool.so.total - pool.so.reserved);
 
     pool.pilot.setDutyRotationPoolSize(effectivePilotPoolSize);
     pool.so.setDutyRotationPoolSize(effectiveSOPoolSize);
   }
 
-  // Build unit selection policy
-  const split = scenario.unit_policy?.mission_split || {};
-  const unitList = Object.keys(pools);
-
-  // Count total mission demands to build accurate distribution sequence
-  const missionDemandCount = events.filter(e => e.type === 'mission_demand').length;
-
-  // Build deterministic round-robin sequence based on split ratios and actual demand count
-  let unitSequence = [];
-  if (Object.keys(split).length > 0 && missionDemandCount > 0) {
-    // Normalize split ratios to percentages
-    const totalSplit = Object.values(split).reduce((sum, val) => sum + val, 0);
-    const normalized = {};
-    for (const [unit, ratio] of Object.entries(split)) {
-      normalized[unit] = ratio / totalSplit;
-    }
+  const unitList = unitNames(pools);
 
-    // Calculate exact number of missions per unit based on actual demand count
-    const unitCounts = {};
-    let allocated = 0;
-    const units = Object.keys(normalized);
-
-    // Allocate missions proportionally
-    for (let i = 0; i < units.length - 1; i++) {
-      const unit = units[i];
-      const count = Math.round(normalized[unit] * missionDemandCount);
-      unitCounts[unit] = count;
-      allocated += count;
-    }
-    // Last unit gets remainder to ensure exact total
-    unitCounts[units[units.length - 1]] = missionDemandCount - allocated;
-
-    // Create interleaved sequence for even distribution
-    unitSequence = new Array(missionDemandCount);
-    const counters = {};
-    units.forEach(u => counters[u] = 0);
-
-    for (let i = 0; i < missionDemandCount; i++) {
-      // Pick unit with highest remaining ratio
-      let bestUnit = null;
-      let bestScore = -1;
-      for (const unit of units) {
-        const remaining = unitCounts[unit] - counters[unit];
-        const slotsLeft = missionDemandCount - i;
-        const score = remaining / slotsLeft;
-        if (score > bestScore) {
-          bestScore = score;
-          bestUnit = unit;
-        }
-      }
-      if (bestUnit) {
-        unitSequence[i] = bestUnit;
-        counters[bestUnit]++;
-      }
-    }
-
-    logWithLocation(`Mission split sequence (${missionDemandCount} missions):`);
-    for (const [unit, count] of Object.entries(unitCounts)) {
-      const pct = (count / missionDemandCount * 100).toFixed(1);
-      logWithLocation(`  ${unit}: ${count} missions (${pct}%)`);
-    }
+  // mission_split may only name units that exist after overrides
+  const splitMissing = Object.keys(scenario.unit_policy?.mission_split || {}).find(u => !pools[u]);
+  if (splitMissing !== undefined) {
+    throw scenarioError(`unit_policy.mission_split.${splitMissing}`, `names unknown unit '${splitMissing}' (units: ${unitList.join(', ')})`);
   }
 
-  function pickUnit(idx) {
-    if (!unitList.length) return null;
-    if (unitSequence.length === 0) {
-      // No split defined, use simple round-robin
-      return unitList[idx % unitList.length];
+  // Demands pinned to units (demand[].unit / units) must name units that exist after overrides
+  (scenario.demand || []).forEach((d, i) => {
+    const pinned = d.units !== undefined ? d.units : (d.unit !== undefined ? [d.unit] : []);
+    const missing = pinned.find(u => !pools[u]);
+    if (missing !== undefined) {
+      throw scenarioError(`demand[${i}].${d.units !== undefined ? 'units' : 'unit'}`, `is pinned to unknown unit '${missing}' (units: ${unitList.join(', ')})`);
     }
-    // Use sequence with wrapping (shouldn't wrap if sequence matches demand count)
-    return unitSequence[idx % unitSequence.length];
+  });
+
+  // Count mission demands the split applies to (pinned demands bypass it) to build an accurate sequence
+  const missionDemandCount = events.filter(e => e.type === 'mission_demand' && !e.pinned_units).length;
+
+  // Build unit selection policy: mission_split sequence or round robin, unless the caller supplied one
+  const policy = assignmentPolicy ? null : defaultPolicy(scenario, unitList, missionDemandCount, logWithLocation);
+
+  // Pinned demands round-robin over their own units, one counter per demand entry
+  const pinnedCounters = {};
+  function pickPinnedUnit(ev) {
+    const idx = pinnedCounters[ev.demand_source] || 0;
+    pinnedCounters[ev.demand_source] = idx + 1;
+    return ev.pinned_units[idx % ev.pinned_units.length];
   }
 
   // Pre-assign units to mission demands for ODO duty planning
+  // A caller-supplied policy instead assigns in stage 5 at demand time, when availability is known
+  // Each demand also gets a stable mission_id (1-based, in demand-time order)
   let missionIdx = 0;
   for (const ev of events) {
     if (ev.type === 'mission_demand') {
-      ev.assignedUnit = pickUnit(missionIdx);
+      if (ev.pinned_units) {
+        ev.assignedUnit = pickPinnedUnit(ev);
+      } else if (policy) {
+        ev.assignedUnit = unitList.length ? policy.selectUnit({ time: ev.time, mission_type: ev.mission_type, demand_source: ev.demand_source, units: unitList }) : null;
+      }
+      ev.mission_id = missionIdx + 1;
       missionIdx++;
     }
   }
 
   return {
     events,
+    demandStats,
     pools,
     unitList,
-    pickUnit
+    assignmentPolicy,
+    activeMissions
   };
 }
 
@@ -2,8 +2,16 @@
 // Run the core simulation by processing duty and mission demands
 
 const { logWithLocation } = require('../../../utils');
+const { ConcurrencyPool } = require('../helpers/resources');
 const { processMissionDemand } = require('./stage5a-mission-processing');
+const { traceMatches, startTrace, finishTrace } = require('../helpers/trace');
 const { processDutyDemand } = require('./stage5b-duty-processing');
+const { createTimelineSink, createDiscardingSink, resourceLevelEvents } = require('../helpers/timeline');
+const { withinHorizon } = require('../helpers/clock');
+const { processTransfer } = require('../helpers/reallocation');
+const { DEFAULT_REJECTION_DENSITY_BUCKET_HOURS } = require('../helpers/throughput');
+const { assignUnit } = require('../helpers/assignment');
+const { emptyMissionCounts } = require('../helpers/ledger');
 
 /**
  * Run simulation by processing all demand events (duty and mission)
@@ -14,6 +22,8 @@ const { processDutyDemand } = require('./stage5b-duty-processing');
 function runSimulation(context) {
   const {
     events,
+    demandStats,
+    activeMissions = [],
     pools,
     unitList,
     horizon,
@@ -23,27 +33,148 @@ function runSimulation(context) {
     turnSpec,
     scenario,
     initial,
-    overrides
+    overrides,
+    missionDetail,
+    onTimelineChunk,
+    timelineChunkSize,
+    collectTimeline = true,
+    watchdog,
+    resourceHoldModel,
+    timelineRejectionSampling,
+    rejectionSamplingRng,
+    rejectionDensityBucketHours = DEFAULT_REJECTION_DENSITY_BUCKET_HOURS,
+    assignmentPolicy = null,
+    emitResourceLevels = false,
+    hourlyProfile = false,
+    trace = null,
+    infeasibleTypes = new Map()
   } = context;
 
   // Initialize results tracking
   const results = {
     horizon_hours: horizon,
-    missions: { requested: 0, started: 0, completed: 0, rejected: 0 },
-    rejections: { aircraft: 0, pilot: 0, so: 0, intel: 0, payload: 0 },
+    missions: emptyMissionCounts(),
+    rejections: {
+      aircraft: 0, control_station: 0, pilot: 0, so: 0, intel: 0, payload: 0,
+      ...Object.fromEntries((initial.crewRoles || []).map(role => [role, 0]))
+    },
+    rejections_by_type: {},
+    rejections_by_unit: {},
+    // Rejections per reason in fixed time buckets, kept whether or not the timeline is
+    rejection_density: { bucket_hours: rejectionDensityBucketHours, buckets: {} },
     duties: { requested: 0, filled: 0, unfilled: 0 },
     utilization: {},
     by_type: {},
+    by_demand_source: {},
+    demand_stats: { ...demandStats },
     timeline: [],
     initial_resources: {
       units: initial.units.slice(),
       aircraftByUnit: initial.aircraftByUnit,
       staffingByUnit: initial.staffingByUnit,
       payloadByUnit: initial.payloadByUnit,
-      overrides_applied: Boolean(overrides)
+      overrides_applied: Boolean(overrides),
+      // Preflight/postflight multiplier per unit, only when unit_policy.crew_experience is set
+      ...(scenario.unit_policy?.crew_experience
+        ? { crew_experience: Object.fromEntries(Object.entries(pools).map(([unit, pool]) => [unit, pool.crewExperience])) }
+        : {}),
+      // Launch/recovery sets per unit, only when some unit has them
+      ...(Object.keys(initial.launchRecoveryByUnit || {}).length > 0 ? { launchRecoveryByUnit: initial.launchRecoveryByUnit } : {})
     }
   };
 
+  // Stream timeline events to the caller in chunks instead of accumulating them
+  if (!collectTimeline) {
+    // No timeline wanted at all (Monte Carlo iterations): nothing is built, completions are only counted
+    results.timeline = createDiscardingSink(horizon);
+  } else if (typeof onTimelineChunk === 'function') {
+    results.timeline = createTimelineSink(onTimelineChunk, timelineChunkSize, horizon);
+  }
+
+  // Keep only a sample of rejection timeline entries (counters stay exact)
+  // Drawn from a separate generator so sampling never shifts the simulation's own random stream
+  let keepRejectionEvent = null;
+  if (timelineRejectionSampling !== undefined && timelineRejectionSampling !== null) {
+    const rng = rejectionSamplingRng || Math.random;
+    keepRejectionEvent = () => rng() < timelineRejectionSampling;
+    results.timeline_rejection_sampling = timelineRejectionSampling;
+  }
+
+  // Missions already in progress at t=0 (v_active_missions), started before the run
+  if (activeMissions.length > 0) {
+    results.initial_resources.active_missions = activeMissions.length;
+    for (const m of activeMissions) {
+      results.timeline.push({
+        type: 'active_mission',
+        unit: m.unit,
+        mission_type: m.mission_type,
+        demand_time: -m.elapsed_hours,
+        finish_time: m.remaining_hours,
+        aircraft: m.aircraft ?? 1,
+        payload_types: m.payload_types,
+        crew: m.crew
+      });
+    }
+  }
+
+  // Demands of mission types no unit can fly, only when there are such types (see helpers/feasibility.js)
+  if (infeasibleTypes.size > 0) {
+    results.missions.infeasible = 0;
+    results.infeasible_by_type = Object.fromEntries([...infeasibleTypes]
+      .map(([name, reasonsByUnit]) => [name, { missions: 0, reasons_by_unit: reasonsByUnit }]));
+  }
+
+  // Aircraft transfer counters, only when the scenario schedules transfers
+  if (scenario.reallocation) {
+    results.reallocations = { count: 0, aircraft_requested: 0, aircraft_moved: 0 };
+  }
+
+  // Force-wide daily payload issue (payload_policy.daily_limits), only when the scenario sets limits
+  const dailyLimits = scenario.payload_policy?.daily_limits;
+  if (dailyLimits) {
+    const days = Math.max(1, Math.ceil(horizon / 24 - 1e-9));
+    results.payload_consumption = Object.fromEntries(Object.entries(dailyLimits)
+      .map(([ptype, limit]) => [ptype, { daily_limit: limit, by_day: new Array(days).fill(0) }]));
+    results.rejections.payload_budget = 0;
+  }
+
+  // Cross-unit airborne caps (mission_types[].max_concurrent), one pool per capped type
+  const concurrencyPools = new Map();
+  for (const mt of missionTypes.values()) {
+    if (mt.max_concurrent !== undefined) concurrencyPools.set(mt.name, new ConcurrencyPool(`concurrency:${mt.name}`, mt.max_concurrent));
+  }
+  if (concurrencyPools.size > 0) results.rejections.concurrency_cap = 0;
+  if (Object.values(pools).some(pool => pool.launchRecovery)) results.rejections.launch_recovery = 0;
+
+  // Launch-window deferrals and rejections, only when some mission type has launch_windows
+  if ((scenario.mission_types || []).some(mt => mt.launch_windows)) {
+    results.launch_deferrals = { count: 0, total_hours: 0, max_hours: 0 };
+    results.rejections.window = 0;
+  }
+
+  // Mission arrivals, starts and final rejections per hour of the run (settings.hourlyProfile)
+  if (hourlyProfile) {
+    const hours = Math.max(1, Math.ceil(horizon - 1e-9));
+    results.hourly_profile = {
+      arrivals: new Array(hours).fill(0),
+      starts: new Array(hours).fill(0),
+      rejections: new Array(hours).fill(0)
+    };
+  }
+
+  // Decision logs of the mission demands settings.trace selects
+  if (trace) results.trace = [];
+
+  // Resubmitted-demand counter, only when some demand entry has a retry policy
+  if ((scenario.demand || []).some(d => d.retry)) {
+    results.retries = 0;
+  }
+
+  // Optional flat per-demand table (one row per mission demand)
+  if (missionDetail) {
+    results.missions_detail = [];
+  }
+
   // Duty lookahead helper
   function getUpcomingDutyRequirements(unit, fromTime, lookaheadHours) {
     logWithLocation(`********** Duty Lookahead Enabled **********`)
@@ -113,13 +244,12 @@ function runSimulation(context) {
   // Key: unit:duty_type, Value: { dayCounter, shiftsPerDay, currentDayShiftCount }
   const dutyIDTracking = {};
 
-  // Sort events: missions before duties at the same time
+  // Sort events: transfers, then missions, then duties at the same time
+  const typeOrder = type => (type === 'aircraft_transfer' ? -1 : type === 'mission_demand' ? 0 : 1);
   events.sort((a, b) => {
     if (a.time !== b.time) return a.time - b.time;
-    // At same time: missions (0) before duties (1)
-    const aOrder = a.type === 'mission_demand' ? 0 : 1;
-    const bOrder = b.type === 'mission_demand' ? 0 : 1;
-    return aOrder - bOrder;
+    // At same time: transfers (-1) change capacity before missions (0) and duties (1) see it
+    return typeOrder(a.type) - typeOrder(b.type);
   });
 
   // Log event queue summary
@@ -150,21 +280,43 @@ function runSimulation(context) {
     timeCount++;
   }
 
+  // Insert a re-queued mission demand after event i, behind anything already queued at its time
+  function requeue(i, demand) {
+    let j = i + 1;
+    while (j < events.length && (events[j].time < demand.time || (events[j].time === demand.time && typeOrder(events[j].type) <= 0))) j++;
+    events.splice(j, 0, demand);
+  }
+
   // Main event processing loop
   let missionIndex = 0;
   for (let i = 0; i < events.length; i++) {
     const ev = events[i];
-    if (ev.time > horizon) break;
+    if (!withinHorizon(ev.time, horizon)) break;
     logWithLocation(`\n`);
     logWithLocation(`===========================================`);
     logWithLocation(`Event ${i + 1}/${events.length} at t=${ev.time.toFixed(1)}h: type=${ev.type}`);
     logWithLocation(`===========================================`);
 
+    // Abort runaway runs (settings.limits) before handling the next event
+    if (watchdog) {
+      watchdog.checkLoop(i, results.timeline.count ?? results.timeline.length);
+    }
+
+    // Scheduled aircraft transfer (scenario.reallocation)
+    if (ev.type === 'aircraft_transfer') {
+      processTransfer(ev, pools, results);
+      continue;
+    }
+
     // ---------------------------------------------------------------------------
     // MISSION DEMAND PROCESSING (Process missions FIRST)
     // ---------------------------------------------------------------------------
     if (ev.type === 'mission_demand') {
-      processMissionDemand({
+      // Caller-supplied assignment policy: choose the unit now, seeing current availability
+      if (assignmentPolicy && !ev.pinned_units) ev.assignedUnit = assignUnit(assignmentPolicy, ev, pools);
+      // Decision log for demands matching settings.trace, opened before the engine touches any pool
+      const traced = trace && traceMatches(trace, ev) ? startTrace(ev, pools, scenario, assignmentPolicy) : null;
+      const outcome = processMissionDemand({
         ev,
         pools,
         missionTypes,
@@ -175,8 +327,32 @@ function runSimulation(context) {
         results,
         missionIndex,
         acceptedMissions,
-        getUpcomingDutyRequirements
+        getUpcomingDutyRequirements,
+        resourceHoldModel,
+        keepRejectionEvent,
+        concurrencyPools,
+        trace: traced,
+        infeasibleTypes
       });
+      if (traced) {
+        finishTrace(traced, ev, outcome.success);
+        results.trace.push(traced);
+      }
+      // Rejected with attempts left: resubmit after the sampled delay, behind anything already queued then
+      if (ev.next_attempt_time !== undefined) {
+        const { next_attempt_time: time, ...rest } = ev;
+        requeue(i, { ...rest, time, attempt: ev.attempt + 1 });
+      }
+      // Outside its launch windows: the same demand again when the next window opens
+      if (ev.deferred_until !== undefined) {
+        const { deferred_until: time, ...rest } = ev;
+        requeue(i, {
+          ...rest,
+          time,
+          deferred_from: ev.deferred_from ?? ev.time,
+          launch_deferral_hours: (ev.launch_deferral_hours || 0) + (time - ev.time)
+        });
+      }
       continue;
     }
 
@@ -204,6 +380,19 @@ function runSimulation(context) {
     }
   }
 
+  if (concurrencyPools.size > 0) {
+    results.concurrency_caps = Object.fromEntries([...concurrencyPools].map(([name, cap]) => [name, cap.stats()]));
+  }
+
+  // Pool occupancy over time, appended once every acquisition is known (counts toward maxTimelineEvents)
+  if (emitResourceLevels && !results.timeline.discarding) {
+    for (const item of resourceLevelEvents(pools, horizon)) results.timeline.push(item);
+  }
+
+  if (watchdog) {
+    watchdog.checkLoop(events.length, results.timeline.count ?? results.timeline.length);
+  }
+
   return results;
 }
 
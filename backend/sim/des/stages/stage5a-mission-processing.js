@@ -2,8 +2,15 @@
 // Process mission demands and allocate resources
 
 const { logWithLocation } = require('../../../utils');
+const { missionQuality } = require('../helpers/quality');
 const { sampleDist } = require('../helpers/distributions');
+const { applyFlightTimeModifiers, crewShiftCount, inLaunchWindow, nextLaunchWindowOpening } = require('../helpers/demand');
 const { EquipmentPool } = require('../helpers/resources');
+const { CORE_CREW_ROLES } = require('../helpers/state');
+const { addRejection, countInHour } = require('../helpers/throughput');
+const { withinHorizon } = require('../helpers/clock');
+const { recordMission, recordRejected, recordInfeasible } = require('../helpers/ledger');
+const { traceCheck } = require('../helpers/trace');
 
 /**
  * Check crew availability and log status
@@ -41,12 +48,14 @@ function allocateCrewForMOS(params) {
     hasRotation,
     sequential,
     crewDistribution,
-    ev
+    ev,
+    useReserve = false,
+    handoverAssignments = null
   } = params;
 
   if (needed === 0) return [];
 
-  const availByShift = pool.availableByShift(ev.time);
+  const availByShift = pool.availableByShift(ev.time, useReserve);
   let assignments = [];
 
   if (hasRotation && rotationShifts && rotationShifts.length > 0) {
@@ -62,7 +71,7 @@ function allocateCrewForMOS(params) {
       shifts.push(s < rotationShifts.length ? rotationShifts[s] : rotationShifts[rotationShifts.length - 1]);
     }
 
-    assignments = pool.tryAcquireShifts(crewStartTime, shifts, false, false, sequential, false, 0, crewDistribution) || [];
+    assignments = pool.tryAcquireShifts(crewStartTime, shifts, false, false, sequential, false, 0, crewDistribution, useReserve) || [];
   } else {
     // Full mission mode
     if (pool.workSchedule.shift_split_enabled) {
@@ -81,12 +90,17 @@ function allocateCrewForMOS(params) {
       logWithLocation(`  ${label}${hasRotation ? ' (full mission)' : ''}: Need ${needed}, Available ${availByShift.total}/${pool.total}`);
     }
 
-    const shifts = new Array(needed).fill(crewHoldDuration);
-    assignments = pool.tryAcquireShifts(crewStartTime, shifts, false, false, false, false, 0, crewDistribution) || [];
+    if (handoverAssignments) {
+      // Crew swaps (required_aircrew.per_flight_hours): the chains were taken by acquireHandoverChains
+      assignments = handoverAssignments;
+    } else {
+      const shifts = new Array(needed).fill(crewHoldDuration);
+      assignments = pool.tryAcquireShifts(crewStartTime, shifts, false, false, false, false, 0, crewDistribution, useReserve) || [];
+    }
   }
 
   if (assignments.length > 0) {
-    const availAfterByShift = pool.availableByShift(ev.time);
+    const availAfterByShift = pool.availableByShift(ev.time, useReserve);
     const crewIds = assignments.map(a => a.id).join(', ');
     if (pool.workSchedule.shift_split_enabled) {
       logWithLocation(`  ✓ ${label} allocated: IDs [${crewIds}], Remaining ${availAfterByShift.total}/${pool.total} (Shift 1: ${availAfterByShift.shift1}, Shift 2: ${availAfterByShift.shift2})`);
@@ -98,6 +112,173 @@ function allocateCrewForMOS(params) {
   return assignments;
 }
 
+/**
+ * Take the handover chains of every seat of one role (required_aircrew.per_flight_hours, per_resource)
+ * Each seat is a chain of crews handing over in flight; a chain can run out of crew at a later
+ * handover even when enough crew are free at the start
+ * 
+ * @param {Object} crewPool - CrewQueue for the role
+ * @param {number} needed - Crew needed (seats × crews per chain)
+ * @param {Array<number>} handoverShifts - Hours each crew in a chain holds the seat
+ * @param {number} crewStartTime - Crew hold start
+ * @param {string} crewDistribution - Crew distribution strategy
+ * @param {boolean} useReserve - Whether standby crew may be taken
+ * @returns {Array<Object>|null} Assignments, or null as soon as a seat's chain cannot be filled
+ */
+function acquireHandoverChains(crewPool, needed, handoverShifts, crewStartTime, crewDistribution, useReserve) {
+  const assignments = [];
+  for (let seat = 0; seat < needed / handoverShifts.length; seat++) {
+    const chain = crewPool.tryAcquireShifts(crewStartTime, handoverShifts, false, false, true, false, 0, crewDistribution, useReserve);
+    if (!chain) return null;
+    assignments.push(...chain);
+  }
+  return assignments;
+}
+
+/**
+ * List the pilot/SO compositions a mission type may fly with, in preference order
+ * Without crew_flex this is the single required_aircrew composition
+ * 
+ * @param {Object} mt - Mission type definition
+ * @returns {Array<Object>} Compositions ({ pilot, so })
+ */
+function crewCompositions(mt) {
+  const flex = mt.crew_flex?.allowed_compositions;
+  if (Array.isArray(flex) && flex.length > 0) {
+    return flex.map(c => ({ pilot: c.pilot || 0, so: c.so || 0 }));
+  }
+  return [{ pilot: mt.required_aircrew?.pilot || 0, so: mt.required_aircrew?.so || 0 }];
+}
+
+/**
+ * Crew pool for a role: the dedicated pilot/so/intel pools or an additional role pool
+ * 
+ * @param {Object} pool - Unit resource pools
+ * @param {string} role - Crew role
+ * @returns {Object|null} CrewQueue, or null when the unit has no pool for the role
+ */
+function crewPoolFor(pool, role) {
+  return CORE_CREW_ROLES.includes(role) ? pool[role] : (pool.roles?.[role] || null);
+}
+
+/**
+ * List a mission's requirements against additional crew roles, with their hold windows
+ * Roles beyond pilot/so/intel in required_aircrew are held with the rest of the crew;
+ * process_times.role_requirements[step] holds any role for that step only
+ * 
+ * @param {Object} mt - Mission type definition
+ * @param {Object} scenario - Scenario configuration
+ * @param {Object} crewHold - Mission crew hold window ({ start, duration })
+ * @param {Object} steps - Step windows (step → { start, duration })
+ * @returns {Array<Object>} Requirements ({ role, needed, start, duration, step })
+ */
+function roleRequirements(mt, scenario, crewHold, steps) {
+  const requirements = [];
+  for (const [role, needed] of Object.entries(mt.required_aircrew || {})) {
+    if (CORE_CREW_ROLES.includes(role) || role === 'per_flight_hours' || !(needed > 0)) continue;
+    requirements.push({ role, needed, start: crewHold.start, duration: crewHold.duration, step: null });
+  }
+  for (const [step, roles] of Object.entries(scenario.process_times?.role_requirements || {})) {
+    const window = steps[step];
+    if (!window || window.duration <= 0) continue;
+    for (const [role, needed] of Object.entries(roles || {})) {
+      if (needed > 0) requirements.push({ role, needed, start: window.start, duration: window.duration, step });
+    }
+  }
+  return requirements;
+}
+
+/**
+ * Day a mission's payloads are charged to under payload_policy.daily_limits (day boundaries every 24h from t=0)
+ * @param {Object} consumption - results.payload_consumption
+ * @param {string} ptype - Payload type
+ * @param {number} time - Mission start time in hours
+ * @returns {Object|null} { entry, day }: the type's consumption entry and day index, or null for an unlimited type
+ */
+function payloadBudgetDay(consumption, ptype, time) {
+  const entry = consumption?.[ptype];
+  if (!entry) return null;
+  return { entry, day: Math.min(entry.by_day.length - 1, Math.floor(time / 24)) };
+}
+
+/**
+ * True when a mission's payloads would exceed the day's issue limit for any limited type
+ * @param {Object} consumption - results.payload_consumption (undefined without daily limits)
+ * @param {Array<string>} payloadTypes - Payload types the mission mounts
+ * @param {number} time - Mission start time in hours
+ * @returns {boolean}
+ */
+function payloadBudgetExhausted(consumption, payloadTypes, time) {
+  const needed = {};
+  for (const ptype of payloadTypes) needed[ptype] = (needed[ptype] || 0) + 1;
+  return Object.entries(needed).some(([ptype, count]) => {
+    const budget = payloadBudgetDay(consumption, ptype, time);
+    return budget !== null && budget.entry.by_day[budget.day] + count > budget.entry.daily_limit;
+  });
+}
+
+/**
+ * Record a rejected mission demand in the global, per-type, per-unit and timeline results
+ * A demand with attempts left under its retry policy is instead counted as a retry and given
+ * next_attempt_time (the caller resubmits it); only the final rejection reaches the counters
+ * 
+ * @param {Object} results - Results being accumulated
+ * @param {Object} ev - Mission demand event
+ * @param {string} unit - Unit the demand was assigned to
+ * @param {string} missionType - Mission type name
+ * @param {string} reason - Rejection reason (aircraft, control_station, launch_recovery, concurrency_cap, pilot, so, intel,
+ *                          payload, payload_budget, window, or an additional crew role)
+ * @param {Function} keepRejectionEvent - Optional sampler; when it returns false the timeline entry is skipped
+ *                                        (counters are always updated)
+ */
+function recordRejection(results, ev, unit, missionType, reason, keepRejectionEvent = null) {
+  if (ev.retry && ev.attempt < ev.retry.max_attempts) {
+    const nextAttemptTime = ev.time + Math.max(0, sampleDist(ev.retry.delay));
+    if (withinHorizon(nextAttemptTime, results.horizon_hours)) {
+      logWithLocation(`  ↻ Retry ${ev.attempt + 1}/${ev.retry.max_attempts} at t=${nextAttemptTime.toFixed(1)}h`);
+      results.retries++;
+      ev.next_attempt_time = nextAttemptTime;
+      return;
+    }
+  }
+  recordRejected(results, missionType, ev.demand_source, unit, reason);
+  addRejection(results.rejection_density, results.horizon_hours, reason, ev.time);
+  if (results.hourly_profile) countInHour(results.hourly_profile.rejections, ev.time);
+  if (!results.timeline.discarding && (!keepRejectionEvent || keepRejectionEvent())) {
+    results.timeline.push({ type: 'rejection', mission_id: ev.mission_id, time: ev.time, unit, mission_type: missionType, demand_source: ev.demand_source, reason });
+  }
+  recordMissionDetail(results, ev, { unit, mission_type: missionType, outcome: 'rejected', reason });
+}
+
+/**
+ * Append a row to results.missions_detail when mission detail is enabled
+ * 
+ * @param {Object} results - Results being accumulated
+ * @param {Object} ev - Mission demand event
+ * @param {Object} fields - Row fields overriding the defaults
+ */
+function recordMissionDetail(results, ev, fields) {
+  if (!results.missions_detail) return;
+  results.missions_detail.push({
+    mission_id: ev.mission_id,
+    mission_type: ev.mission_type,
+    demand_source: ev.demand_source,
+    unit: ev.assignedUnit || null,
+    demand_time: ev.time,
+    outcome: 'started',
+    reason: null,
+    crew_composition: null,
+    start_time: null,
+    finish_time: null,
+    completed: false,
+    durations: null,
+    resources: null,
+    ...(ev.retry ? { attempts: ev.attempt } : {}),
+    ...(ev.deferred_from !== undefined ? { demand_time: ev.deferred_from, launch_deferral_hours: ev.launch_deferral_hours } : {}),
+    ...fields
+  });
+}
+
 /**
  * Process a single mission demand event
  * 
@@ -116,41 +297,115 @@ function processMissionDemand(params) {
     results,
     missionIndex,
     acceptedMissions,
-    getUpcomingDutyRequirements
+    getUpcomingDutyRequirements,
+    resourceHoldModel = 'legacy',
+    keepRejectionEvent = null,
+    concurrencyPools = null,
+    trace = null,
+    infeasibleTypes = null
   } = params;
 
-  results.missions.requested++;
+  // A resubmitted or deferred demand was already counted on its first attempt
+  if (!(ev.attempt > 1) && ev.deferred_from === undefined) {
+    recordMission(results, 'requested', ev.mission_type, ev.demand_source);
+    if (results.hourly_profile) countInHour(results.hourly_profile.arrivals, ev.time);
+  }
   const mt = missionTypes.get(ev.mission_type);
-  if (!mt) return { success: false };
+  if (!mt) {
+    if (trace) trace.outcome = 'unknown_type';
+    recordMissionDetail(results, ev, { outcome: 'unknown_type' });
+    return { success: false };
+  }
+
+  // No unit could ever fly this type (helpers/feasibility.js): not a rejection, nothing to retry or defer
+  if (infeasibleTypes?.has(mt.name)) {
+    if (trace) trace.outcome = 'infeasible';
+    recordInfeasible(results, mt.name, ev.demand_source);
+    recordMissionDetail(results, ev, { outcome: 'infeasible' });
+    return { success: false };
+  }
 
   const unit = ev.assignedUnit;
-  if (!unit) return { success: false };
+  if (!unit) {
+    if (trace) trace.outcome = 'unassigned';
+    recordMissionDetail(results, ev, { outcome: 'unassigned' });
+    return { success: false };
+  }
 
   const pool = pools[unit];
+  // Standby missions (unit_policy.reservations) may also use the unit's reserved aircraft and crew
+  const useReserve = Boolean(pool.reservation?.forMissionTypes.has(mt.name));
+
+  // Launch windows (mission_types[].launch_windows): outside every window the demand waits for the next opening
+  // (deferred_until; the caller re-queues it) or, under launch_window_policy 'reject', is rejected outright
+  if (!inLaunchWindow(mt, ev.time)) {
+    traceCheck(trace, 'window', 1, 0);
+    const opening = nextLaunchWindowOpening(mt, ev.time);
+    if (mt.launch_window_policy !== 'reject' && withinHorizon(opening, results.horizon_hours)) {
+      logWithLocation(`  ⏸ ${mt.name} outside its launch windows, deferred to t=${opening.toFixed(1)}h`);
+      const deferral = results.launch_deferrals;
+      deferral.count++;
+      deferral.total_hours += opening - ev.time;
+      deferral.max_hours = Math.max(deferral.max_hours, opening - ev.time);
+      results.timeline.push({ type: 'deferral', mission_id: ev.mission_id, time: ev.time, until: opening, unit, mission_type: mt.name, demand_source: ev.demand_source });
+      ev.deferred_until = opening;
+      return { success: false };
+    }
+    logWithLocation(`  ✗ REJECTED - window: ${mt.name} outside its launch windows`);
+    recordRejection(results, ev, unit, mt.name, 'window', keepRejectionEvent);
+    return { success: false };
+  }
 
   let mountTime = 0;
   for (const ptype of mt.required_payload_types || []) {
     const spec = scenario.process_times?.mount_times?.[ptype];
     mountTime += sampleDist(spec);
   }
-  const pre = sampleDist(preSpec);
-  const flight = sampleDist(mt.flight_time);
+  // Less experienced crews take longer on the ground (unit_policy.crew_experience)
+  const pre = sampleDist(preSpec) * pool.crewExperience;
+  const flight = applyFlightTimeModifiers(mt, sampleDist(mt.flight_time));
   const transitIn = mt.flight_time.transit_in_hours || 0;
   const transitOut = mt.flight_time.transit_out_hours || 0;
-  const post = sampleDist(postSpec);
+  const post = sampleDist(postSpec) * pool.crewExperience;
   const turnaround = sampleDist(turnSpec);
-  const duration = pre + mountTime + transitIn + flight + transitOut + post + turnaround;
+  // Payloads come off after postflight, before turnaround unless process_times.dismount_after_turnaround
+  const hasDismount = Boolean(scenario.process_times?.dismount_times);
+  const dismountAfterTurnaround = scenario.process_times?.dismount_after_turnaround === true;
+  let dismountTime = 0;
+  for (const ptype of mt.required_payload_types || []) {
+    dismountTime += sampleDist(scenario.process_times?.dismount_times?.[ptype]);
+  }
+  const duration = pre + mountTime + transitIn + flight + transitOut + post + dismountTime + turnaround;
+  if (trace) {
+    trace.durations = {
+      preflight: pre, mount: mountTime, transit_in: transitIn, flight, transit_out: transitOut, postflight: post,
+      ...(hasDismount ? { dismount: dismountTime } : {}),
+      turnaround
+    };
+  }
 
   // Determine crew hold duration based on hold_crew_during_process_times flag
+  // per_resource: crews walk away after postflight, so dismount and turnaround only hold the aircraft
+  const perResource = resourceHoldModel === 'per_resource';
   const holdCrewDuringProcessTimes = scenario.process_times?.hold_crew_during_process_times !== false;
   const crewHoldDuration = holdCrewDuringProcessTimes
-    ? duration
+    ? (perResource ? duration - turnaround - dismountTime : duration)
     : (transitIn + flight + transitOut);
 
   const crewStartTime = holdCrewDuringProcessTimes ? ev.time : (ev.time + pre + mountTime);
 
-  const needPilot = mt.required_aircrew?.pilot || 0;
-  const needSO = mt.required_aircrew?.so || 0;
+  // Payload hold: whole mission (legacy) or mount through transit out, optionally through postflight (per_resource);
+  // a payload that is dismounted stays held until it is off the aircraft
+  const holdPayloadThroughPostflight = scenario.process_times?.hold_payload_through_postflight === true || dismountTime > 0;
+  const payloadStartTime = perResource ? ev.time + pre : ev.time;
+  const payloadHoldDuration = perResource
+    ? mountTime + transitIn + flight + transitOut + (holdPayloadThroughPostflight ? post : 0) +
+      (dismountTime > 0 ? dismountTime + (dismountAfterTurnaround ? turnaround : 0) : 0)
+    : duration;
+
+  // Long sorties need a fresh pilot/SO crew every required_aircrew.per_flight_hours of flight
+  const crewShifts = crewShiftCount(mt, flight);
+  const compositions = crewCompositions(mt).map(c => ({ pilot: c.pilot * crewShifts, so: c.so * crewShifts }));
   const needIntel = mt.required_aircrew?.intel || 0;
   const payloadTypes = mt.required_payload_types || [];
 
@@ -166,6 +421,7 @@ function processMissionDemand(params) {
   let payloadOk = true;
   for (const ptype of payloadTypes) {
     const p = pool.payloads[ptype] || new EquipmentPool(`payload:${unit}:${ptype}`, 0);
+    traceCheck(trace, `payload:${ptype}`, 1, p.availableAt(ev.time));
     if (p.availableAt(ev.time) < 1) {
       payloadOk = false;
       break;
@@ -173,21 +429,57 @@ function processMissionDemand(params) {
   }
 
   if (!payloadOk) {
-    results.missions.rejected++;
-    results.rejections.payload++;
-    results.timeline.push({ type: 'rejection', time: ev.time, unit, mission_type: mt.name, reason: 'payload' });
+    recordRejection(results, ev, unit, mt.name, 'payload', keepRejectionEvent);
+    return { success: false };
+  }
+
+  // Payloads on hand but the day's force-wide issue is used up (payload_policy.daily_limits)
+  const budgetExhausted = payloadBudgetExhausted(results.payload_consumption, payloadTypes, ev.time);
+  if (results.payload_consumption) traceCheck(trace, 'payload_budget', 1, budgetExhausted ? 0 : 1);
+  if (budgetExhausted) {
+    logWithLocation(`  ✗ REJECTED - payload_budget: daily payload limit reached`);
+    recordRejection(results, ev, unit, mt.name, 'payload_budget', keepRejectionEvent);
     return { success: false };
   }
 
   // Check aircraft availability
-  if (pool.aircraft.availableAt(ev.time) < 1) {
-    results.missions.rejected++;
-    results.rejections.aircraft++;
-    const btR = results.by_type[mt.name] || { requested: 0, started: 0, completed: 0, rejected: 0 };
-    btR.requested = (btR.requested || 0) + 1;
-    btR.rejected = (btR.rejected || 0) + 1;
-    results.by_type[mt.name] = btR;
-    results.timeline.push({ type: 'rejection', time: ev.time, unit, mission_type: mt.name, reason: 'aircraft' });
+  traceCheck(trace, 'aircraft', 1, pool.aircraft.availableAt(ev.time, useReserve));
+  if (pool.aircraft.availableAt(ev.time, useReserve) < 1) {
+    recordRejection(results, ev, unit, mt.name, 'aircraft', keepRejectionEvent);
+    return { success: false };
+  }
+
+  // Check concurrent-mission cap (unit_policy.max_concurrent_missions)
+  if (pool.controlStations) traceCheck(trace, 'control_station', 1, pool.controlStations.availableAt(ev.time));
+  if (pool.controlStations && pool.controlStations.availableAt(ev.time) < 1) {
+    recordRejection(results, ev, unit, mt.name, 'control_station', keepRejectionEvent);
+    return { success: false };
+  }
+
+  // Cross-unit cap on this type's airborne instances (mission_types[].max_concurrent), over transit in → transit out
+  const airborneStart = ev.time + pre + mountTime;
+  const airborneEnd = airborneStart + transitIn + flight + transitOut;
+
+  // Launch/recovery equipment (mission_types[].requires_launch_recovery): one set through preflight and mount, and
+  // one again through postflight; the recovery window is already known, so both must be free now or the demand is rejected
+  const launchRecovery = mt.requires_launch_recovery ? pool.launchRecovery : null;
+  const launchRecoveryWindows = [
+    { start: ev.time, end: airborneStart },
+    { start: airborneEnd, end: airborneEnd + post }
+  ];
+  if (launchRecovery) {
+    traceCheck(trace, 'launch_recovery', 1, Math.min(...launchRecoveryWindows.map(w => launchRecovery.availableDuring(w.start, w.end))));
+  }
+  if (launchRecovery && !launchRecovery.fitsAll(launchRecoveryWindows)) {
+    logWithLocation(`  ✗ REJECTED - launch_recovery: no set free for launch and recovery`);
+    recordRejection(results, ev, unit, mt.name, 'launch_recovery', keepRejectionEvent);
+    return { success: false };
+  }
+  const concurrency = concurrencyPools?.get(mt.name);
+  if (concurrency) traceCheck(trace, 'concurrency_cap', 1, concurrency.limit - concurrency.peakDuring(airborneStart, airborneEnd));
+  if (concurrency && !concurrency.fits(airborneStart, airborneEnd)) {
+    logWithLocation(`  ✗ REJECTED - concurrency_cap: ${concurrency.limit} ${mt.name} already airborne`);
+    recordRejection(results, ev, unit, mt.name, 'concurrency_cap', keepRejectionEvent);
     return { success: false };
   }
 
@@ -196,45 +488,133 @@ function processMissionDemand(params) {
   logWithLocation(`  Crew Distribution: ${mt.crew_distribution || 'concentrate'}`);
 
   // Check crew availability for all MOS types
-  const crewChecks = [
-    {
-      name: 'pilot',
-      needed: needPilot,
-      pool: pool.pilot,
-      label: 'Pilots',
-      totalAvailable: pool.pilot.availableAt(ev.time),
-      reserved: upcomingDuties.pilotsNeeded
-    },
-    {
-      name: 'so',
-      needed: needSO,
-      pool: pool.so,
-      label: 'SOs',
-      totalAvailable: pool.so.availableAt(ev.time),
-      reserved: upcomingDuties.sosNeeded
-    },
-    {
+  // Pilot/SO compositions are tried in order; the first one the pools can fill is flown
+  const pilotAvailable = pool.pilot.availableAt(ev.time, useReserve);
+  const soAvailable = pool.so.availableAt(ev.time, useReserve);
+  let composition = null;
+  let firstShortfall = null;
+
+  for (const candidate of compositions) {
+    if (compositions.length > 1) {
+      logWithLocation(`  Crew composition: ${candidate.pilot} pilot(s), ${candidate.so} SO(s)`);
+    }
+    const seatChecks = [
+      {
+        name: 'pilot',
+        needed: candidate.pilot,
+        pool: pool.pilot,
+        label: 'Pilots',
+        totalAvailable: pilotAvailable,
+        reserved: upcomingDuties.pilotsNeeded
+      },
+      {
+        name: 'so',
+        needed: candidate.so,
+        pool: pool.so,
+        label: 'SOs',
+        totalAvailable: soAvailable,
+        reserved: upcomingDuties.sosNeeded
+      }
+    ];
+
+    const shortfall = seatChecks.find(check => check.needed > 0 && !checkCrewAvailability(check).sufficient);
+    if (trace) {
+      for (const check of seatChecks.filter(c => c.needed > 0)) {
+        traceCheck(trace, check.name, check.needed, check.totalAvailable - check.reserved, {
+          reserved_for_duty: check.reserved,
+          ...(compositions.length > 1 ? { composition: candidate } : {})
+        });
+      }
+    }
+    if (!shortfall) {
+      composition = candidate;
+      break;
+    }
+    logWithLocation(`  ✗ ${shortfall.label}: insufficient crew`);
+    firstShortfall = firstShortfall || shortfall;
+  }
+
+  if (!composition) {
+    // Attribute the rejection to the preferred composition's shortfall
+    logWithLocation(`  ✗ REJECTED - ${firstShortfall.label}: insufficient crew`);
+    recordRejection(results, ev, unit, mt.name, firstShortfall.name, keepRejectionEvent);
+    return { success: false };
+  }
+
+  if (needIntel > 0) {
+    const intelCheck = {
       name: 'intel',
       needed: needIntel,
       pool: pool.intel,
       label: 'Intel',
-      totalAvailable: pool.intel.availableAt(ev.time),
+      totalAvailable: pool.intel.availableAt(ev.time, useReserve),
       reserved: upcomingDuties.intelNeeded
+    };
+    traceCheck(trace, 'intel', needIntel, intelCheck.totalAvailable - intelCheck.reserved, { reserved_for_duty: intelCheck.reserved });
+    if (!checkCrewAvailability(intelCheck).sufficient) {
+      logWithLocation(`  ✗ REJECTED - ${intelCheck.label}: insufficient crew`);
+      recordRejection(results, ev, unit, mt.name, intelCheck.name, keepRejectionEvent);
+      return { success: false };
     }
-  ];
+  }
+
+  const needPilot = composition.pilot;
+  const needSO = composition.so;
+
+  // Additional crew roles (mission-wide and per process step)
+  const t1Start = ev.time + pre;
+  const postflightEnd = t1Start + mountTime + transitIn + flight + transitOut + post;
+  const dismountStart = dismountAfterTurnaround ? postflightEnd + turnaround : postflightEnd;
+  const turnaroundStart = dismountAfterTurnaround ? postflightEnd : postflightEnd + dismountTime;
+  const roleNeeds = roleRequirements(mt, scenario, { start: crewStartTime, duration: crewHoldDuration }, {
+    preflight: { start: ev.time, duration: pre },
+    mount: { start: t1Start, duration: mountTime },
+    postflight: { start: t1Start + mountTime + transitIn + flight + transitOut, duration: post },
+    dismount: { start: dismountStart, duration: dismountTime },
+    turnaround: { start: turnaroundStart, duration: turnaround }
+  });
+  const missionWideNeed = role => (role === 'pilot' ? needPilot : role === 'so' ? needSO : role === 'intel' ? needIntel
+    : roleNeeds.filter(r => r.role === role && r.step === null).reduce((sum, r) => sum + r.needed, 0));
+  for (const req of roleNeeds) {
+    const rolePool = crewPoolFor(pool, req.role);
+    // A step requirement needs its own crew on top of the mission-wide crew of the same role
+    const needed = req.step === null ? req.needed : req.needed + missionWideNeed(req.role);
+    const available = rolePool ? rolePool.availableAt(req.start) : 0;
+    logWithLocation(`  ${req.role}${req.step ? ` (${req.step})` : ''}: Need ${needed}, Available ${available}/${rolePool ? rolePool.total : 0}`);
+    traceCheck(trace, req.role, needed, available, req.step ? { step: req.step } : {});
+    if (available < needed) {
+      logWithLocation(`  ✗ REJECTED - ${req.role}: insufficient crew`);
+      recordRejection(results, ev, unit, mt.name, req.role, keepRejectionEvent);
+      return { success: false };
+    }
+  }
 
-  for (const check of crewChecks) {
-    if (check.needed > 0) {
-      const availability = checkCrewAvailability(check);
-      if (!availability.sufficient) {
-        logWithLocation(`  ✗ REJECTED - ${check.label}: insufficient crew`);
-        results.missions.rejected++;
-        results.rejections[check.name]++;
-        const btR = results.by_type[mt.name] || { requested: 0, started: 0, completed: 0, rejected: 0 };
-        btR.requested = (btR.requested || 0) + 1;
-        btR.rejected = (btR.rejected || 0) + 1;
-        results.by_type[mt.name] = btR;
-        results.timeline.push({ type: 'rejection', time: ev.time, unit, mission_type: mt.name, reason: check.name });
+  const crewDistribution = mt.crew_distribution || 'concentrate';
+
+  // per_resource: swapped crews hold a seat only for their own stretch of the flight;
+  // legacy holds every crew for the whole crew hold
+  const handoverChains = { pilot: null, so: null };
+  if (perResource && crewShifts > 1) {
+    const perFlightHours = mt.required_aircrew.per_flight_hours;
+    const flightStart = ev.time + pre + mountTime + transitIn;
+    const crewEnd = crewStartTime + crewHoldDuration;
+    const handoverShifts = [flightStart + perFlightHours - crewStartTime];
+    for (let c = 1; c < crewShifts - 1; c++) handoverShifts.push(perFlightHours);
+    handoverShifts.push(crewEnd - (flightStart + (crewShifts - 1) * perFlightHours));
+
+    // The chains are taken before anything else is held, so a seat that cannot be filled
+    // rejects the mission with every crew it took released
+    const seats = [
+      { name: 'pilot', label: 'Pilots', pool: pool.pilot, needed: needPilot },
+      { name: 'so', label: 'SOs', pool: pool.so, needed: needSO }
+    ];
+    const checkpoints = seats.map(seat => seat.pool.checkpoint());
+    for (const seat of seats) {
+      handoverChains[seat.name] = acquireHandoverChains(seat.pool, seat.needed, handoverShifts, crewStartTime, crewDistribution, useReserve);
+      if (!handoverChains[seat.name]) {
+        seats.forEach((other, k) => other.pool.rollback(checkpoints[k]));
+        logWithLocation(`  ✗ REJECTED - ${seat.label}: handover chain cannot be filled`);
+        recordRejection(results, ev, unit, mt.name, seat.name, keepRejectionEvent);
         return { success: false };
       }
     }
@@ -242,12 +622,18 @@ function processMissionDemand(params) {
 
   // Acquire resources
   for (const ptype of payloadTypes) {
-    pool.payloads[ptype].tryAcquire(ev.time, duration, 1);
+    pool.payloads[ptype].tryAcquire(payloadStartTime, payloadHoldDuration, 1);
+    const budget = payloadBudgetDay(results.payload_consumption, ptype, ev.time);
+    if (budget) budget.entry.by_day[budget.day]++;
+  }
+  pool.aircraft.tryAcquire(ev.time, duration, 1, useReserve);
+  if (pool.controlStations) {
+    pool.controlStations.tryAcquire(ev.time, duration, 1);
   }
-  pool.aircraft.tryAcquire(ev.time, duration, 1);
+  if (launchRecovery) launchRecovery.acquire(ev.time, launchRecoveryWindows);
+  if (concurrency) concurrency.acquire(ev.time, airborneStart, airborneEnd);
   // Allocate crew
   const crewRotation = mt.crew_rotation;
-  const crewDistribution = mt.crew_distribution || 'concentrate';
   const hasRotation = crewRotation && crewRotation.enabled;
   const sequential = hasRotation ? (crewRotation.sequential !== false) : false;
 
@@ -290,22 +676,57 @@ function processMissionDemand(params) {
       hasRotation,
       sequential,
       crewDistribution,
+      ev,
+      useReserve,
+      handoverAssignments: crew.name === 'intel' ? null : handoverChains[crew.name]
+    });
+  }
+
+  // Mission-wide additional roles first, so step crews are drawn from whoever is left
+  const roleAssignments = {};
+  for (const req of roleNeeds) {
+    const assignments = allocateCrewForMOS({
+      pool: crewPoolFor(pool, req.role),
+      label: req.step ? `${req.role} (${req.step})` : req.role,
+      needed: req.needed,
+      rotationShifts: null,
+      crewStartTime: req.start,
+      crewHoldDuration: req.duration,
+      hasRotation: false,
+      sequential: false,
+      crewDistribution,
       ev
     });
+    roleAssignments[req.role] = (roleAssignments[req.role] || []).concat(assignments);
   }
 
   const pilotAssignments = crewConfigs[0].assignments;
   const soAssignments = crewConfigs[1].assignments;
   const intelAssignments = crewConfigs[2].assignments;
 
+  if (trace) {
+    trace.finish_time = ev.time + duration;
+    trace.crew = {
+      pilots: pilotAssignments.map(a => a.id),
+      sos: soAssignments.map(a => a.id),
+      intel: intelAssignments.map(a => a.id),
+      ...(roleNeeds.length > 0
+        ? { roles: Object.fromEntries(Object.entries(roleAssignments).map(([role, list]) => [role, list.map(a => a.id)])) }
+        : {})
+    };
+  }
+
+  pool.missionStarts.push(ev.time);
+  if (results.hourly_profile) countInHour(results.hourly_profile.starts, ev.time);
   pool.missionFinishes.push(ev.time + duration);
+  // Partial-success accounting (scenario.quality_penalties, see helpers/quality.js)
+  const quality = scenario.quality_penalties
+    ? missionQuality(scenario.quality_penalties, { crew_flex: compositions.indexOf(composition) > 0 })
+    : null;
+  if (quality !== null) pool.missionQualities.push({ mission_type: mt.name, finish_time: ev.time + duration, quality });
 
-  results.missions.started++;
+  recordMission(results, 'started', mt.name, ev.demand_source);
   const missionNumber = results.missions.started;
-  const bt = results.by_type[mt.name] || { requested: 0, started: 0, completed: 0, rejected: 0 };
-  bt.requested = (bt.requested || 0) + 1;
-  bt.started = (bt.started || 0) + 1;
-  results.by_type[mt.name] = bt;
 
   const t0 = ev.time;
   const t1 = t0 + pre;
@@ -314,7 +735,8 @@ function processMissionDemand(params) {
   const t4 = t3 + flight;
   const t5 = t4 + transitOut;
   const t6 = t5 + post;
-  const t7 = t6 + turnaround;
+  const t7 = t6 + dismountTime + turnaround;
+  pool.flightHours += t5 - t2;
 
   // Track accepted mission for ODO alignment
   acceptedMissions.push({
@@ -324,28 +746,72 @@ function processMissionDemand(params) {
     postflightEnd: t6
   });
 
-  results.timeline.push({
-    type: 'mission',
+  if (results.timeline.discarding) {
+    // settings.collectTimeline false: skip building the event, Stage 6 only needs the completion
+    results.timeline.countMission(mt.name, ev.demand_source, t7);
+  } else {
+    results.timeline.push({
+      type: 'mission',
+      mission_id: ev.mission_id,
+      unit,
+      mission_type: mt.name,
+      demand_source: ev.demand_source,
+      mission_number: missionNumber,
+      demand_time: t0,
+      ...(ev.deferred_from !== undefined ? { deferred_from: ev.deferred_from } : {}),
+      finish_time: t7,
+      crew_hold_start: crewStartTime,
+      crew_hold_end: crewStartTime + crewHoldDuration,
+      payload_hold_start: payloadStartTime,
+      payload_hold_end: payloadStartTime + payloadHoldDuration,
+      segments: [
+        { name: 'preflight', start: t0, end: t1 },
+        { name: 'mount', start: t1, end: t2 },
+        { name: 'transit_in', start: t2, end: t3 },
+        { name: 'flight', start: t3, end: t4 },
+        { name: 'transit_out', start: t4, end: t5 },
+        { name: 'postflight', start: t5, end: t6 },
+        ...(hasDismount ? [{ name: 'dismount', start: dismountStart, end: dismountStart + dismountTime }] : []),
+        { name: 'turnaround', start: turnaroundStart, end: turnaroundStart + turnaround },
+      ],
+      crew: {
+        pilots: pilotAssignments,
+        sos: soAssignments,
+        intel: intelAssignments,
+        ...(roleNeeds.length > 0 ? { roles: roleAssignments } : {})
+      }
+    });
+  }
+
+  recordMissionDetail(results, ev, {
     unit,
     mission_type: mt.name,
-    mission_number: missionNumber,
-    demand_time: t0,
+    start_time: t0,
     finish_time: t7,
-    crew_hold_start: crewStartTime,
-    crew_hold_end: crewStartTime + crewHoldDuration,
-    segments: [
-      { name: 'preflight', start: t0, end: t1 },
-      { name: 'mount', start: t1, end: t2 },
-      { name: 'transit_in', start: t2, end: t3 },
-      { name: 'flight', start: t3, end: t4 },
-      { name: 'transit_out', start: t4, end: t5 },
-      { name: 'postflight', start: t5, end: t6 },
-      { name: 'turnaround', start: t6, end: t7 },
-    ],
-    crew: {
-      pilots: pilotAssignments,
-      sos: soAssignments,
-      intel: intelAssignments
+    crew_composition: { pilot: needPilot, so: needSO },
+    ...(crewShifts > 1 ? { crew_shifts: crewShifts } : {}),
+    ...(quality !== null ? { quality } : {}),
+    durations: {
+      preflight: pre,
+      mount: mountTime,
+      transit_in: transitIn,
+      flight,
+      transit_out: transitOut,
+      postflight: post,
+      ...(hasDismount ? { dismount: dismountTime } : {}),
+      turnaround
+    },
+    resources: {
+      aircraft: 1,
+      control_station: pool.controlStations ? 1 : 0,
+      ...(launchRecovery ? { launch_recovery: 1 } : {}),
+      payloads: payloadTypes.slice(),
+      pilots: pilotAssignments.map(a => a.id),
+      sos: soAssignments.map(a => a.id),
+      intel: intelAssignments.map(a => a.id),
+      ...(roleNeeds.length > 0
+        ? { roles: Object.fromEntries(Object.entries(roleAssignments).map(([role, list]) => [role, list.map(a => a.id)])) }
+        : {})
     }
   });
 
@@ -3,6 +3,8 @@
 
 const { logWithLocation } = require('../../../utils');
 const { sampleDist } = require('../helpers/distributions');
+const { applyFlightTimeModifiers } = require('../helpers/demand');
+const { missionSplit } = require('../helpers/assignment');
 
 /**
  * Process a single duty demand event
@@ -38,11 +40,8 @@ function processDutyDemand(params) {
   const shiftsPerDay = dutyConfig.shifts_per_day || 1;
 
   // Filter units based on mission split - skip units with 0% allocation
-  const split = scenario.unit_policy?.mission_split || {};
-  const activeUnits = unitList.filter(unit => {
-    if (Object.keys(split).length === 0) return true;
-    return (split[unit] || 0) > 0;
-  });
+  const split = missionSplit(scenario);
+  const activeUnits = unitList.filter(unit => !split || (split[unit] || 0) > 0);
 
   for (const unit of activeUnits) {
     const pool = pools[unit];
@@ -116,13 +115,17 @@ function processDutyDemand(params) {
             const spec = scenario.process_times?.mount_times?.[ptype];
             mountTime += sampleDist(spec);
           }
-          const pre = sampleDist(preSpec);
-          const flight = sampleDist(mt.flight_time);
+          const pre = sampleDist(preSpec) * pool.crewExperience;
+          const flight = applyFlightTimeModifiers(mt, sampleDist(mt.flight_time));
           const transitIn = mt.flight_time.transit_in_hours || 0;
           const transitOut = mt.flight_time.transit_out_hours || 0;
-          const post = sampleDist(postSpec);
+          const post = sampleDist(postSpec) * pool.crewExperience;
           const turnaround = sampleDist(turnSpec);
-          estimatedMissionDuration = pre + mountTime + transitIn + flight + transitOut + post + turnaround;
+          let dismountTime = 0;
+          for (const ptype of mt.required_payload_types || []) {
+            dismountTime += sampleDist(scenario.process_times?.dismount_times?.[ptype]);
+          }
+          estimatedMissionDuration = pre + mountTime + transitIn + flight + transitOut + post + dismountTime + turnaround;
         }
         const estimatedMissionEnd = futureEvent.time + estimatedMissionDuration;
 
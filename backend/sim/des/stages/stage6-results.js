@@ -3,6 +3,13 @@
 
 const { logWithLocation } = require('../../../utils');
 const { generateAvailabilityTimeline } = require('../helpers/availability');
+const { withinHorizon } = require('../helpers/clock');
+const { peakWindow, DEFAULT_PEAK_WINDOW_HOURS } = require('../helpers/throughput');
+const { recordMission } = require('../helpers/ledger');
+const { crewAvailabilityResults } = require('../helpers/absence');
+const { weightedCompletionScore } = require('../helpers/priority');
+const { completionQuality } = require('../helpers/quality');
+const { unitNames } = require('../helpers/state');
 
 /**
  * Generate final results with completion counts and metrics
@@ -12,22 +19,53 @@ const { generateAvailabilityTimeline } = require('../helpers/availability');
  * @returns {Object} Complete results with utilization and availability timeline
  */
 function generateResults(results, context) {
-  const { pools, horizon, initial, availability, scenario } = context;
-  const unitList = Object.keys(pools);
+  const { pools, horizon, initial, availability, scenario, peakWindowHours = DEFAULT_PEAK_WINDOW_HOURS, priorityWeights = null } = context;
+  const unitList = unitNames(pools);
 
-  // Count completed missions
-  for (const unit of unitList) {
-    const pool = pools[unit];
-    const completed = pool.missionFinishes.filter(t => t <= horizon).length;
-    results.missions.completed += completed;
+  // Count completed missions (finished within the horizon) in the totals and breakdowns
+  if (results.timeline.streaming || results.timeline.discarding) {
+    // Streamed (or never kept) timeline: events are gone, so use the sink's tallies and emit the last chunk
+    results.timeline.flush();
+    for (const [missionType, bySource] of Object.entries(results.timeline.completed)) {
+      for (const [source, completed] of Object.entries(bySource)) {
+        recordMission(results, 'completed', missionType, source, completed);
+      }
+    }
+    if (results.timeline.streaming) results.timeline_events = results.timeline.count;
+    delete results.timeline;
+  } else {
+    for (const item of results.timeline) {
+      if (item.type !== 'mission') continue;
+      if (withinHorizon(item.finish_time, horizon)) {
+        recordMission(results, 'completed', item.mission_type, item.demand_source);
+      } else {
+        // Started but cut off by the horizon
+        item.truncated = true;
+      }
+    }
+  }
+
+  // Started missions still flying (or in postflight/turnaround) at the horizon, so started = completed + in progress
+  results.missions.in_progress_at_horizon = results.missions.started - results.missions.completed;
+  for (const counts of [...Object.values(results.by_type), ...Object.values(results.by_demand_source)]) {
+    counts.in_progress_at_horizon = counts.started - counts.completed;
   }
+  results.in_progress_by_unit = Object.fromEntries(unitList.map(unit => [
+    unit, pools[unit].missionFinishes.filter(t => !withinHorizon(t, horizon)).length
+  ]));
+
+  // Completions weighted by mission type priority, over weighted requests (see helpers/priority.js)
+  results.weighted_completion_score = weightedCompletionScore(results.by_type, scenario.mission_types, priorityWeights);
 
-  // Track per-mission-type completion counts
-  for (const item of results.timeline) {
-    if (item.type === 'mission' && item.finish_time <= horizon) {
-      const btC = results.by_type[item.mission_type] || { requested: 0, started: 0, completed: 0, rejected: 0 };
-      btC.completed = (btC.completed || 0) + 1;
-      results.by_type[item.mission_type] = btC;
+  // Mean quality of completed missions and degraded completions (scenario.quality_penalties, see helpers/quality.js)
+  if (scenario.quality_penalties) {
+    results.completion_quality = completionQuality(unitList.flatMap(unit => pools[unit].missionQualities), t => withinHorizon(t, horizon));
+  }
+
+  // Mark completed rows in the mission detail table
+  if (results.missions_detail) {
+    for (const row of results.missions_detail) {
+      row.completed = row.outcome === 'started' && withinHorizon(row.finish_time, horizon);
     }
   }
 
@@ -52,15 +90,19 @@ function generateResults(results, context) {
     results.utilization[unit] = {
       aircraft: Number(pool.aircraft.utilization().toFixed(3)),
       aircraft_efficiency: Number(pool.aircraft.efficiency(horizon).toFixed(3)),
+      aircraft_efficiency_of_available: Number(pool.aircraft.efficiencyOfAvailable(horizon).toFixed(3)),
       aircraft_stats: aircraftStats,
       pilot: Number(pool.pilot.utilization().toFixed(3)),
       pilot_efficiency: Number(pool.pilot.efficiency(horizon).toFixed(3)),
+      pilot_efficiency_of_available: Number(pool.pilot.efficiencyOfAvailable(horizon).toFixed(3)),
       pilot_stats: pilotStats,
       so: Number(pool.so.utilization().toFixed(3)),
       so_efficiency: Number(pool.so.efficiency(horizon).toFixed(3)),
+      so_efficiency_of_available: Number(pool.so.efficiencyOfAvailable(horizon).toFixed(3)),
       so_stats: soStats,
       intel: Number(pool.intel.utilization().toFixed(3)),
       intel_efficiency: Number(pool.intel.efficiency(horizon).toFixed(3)),
+      intel_efficiency_of_available: Number(pool.intel.efficiencyOfAvailable(horizon).toFixed(3)),
       intel_stats: intelStats,
       availability_factors: pool.availability_factors,
       initial_crew: pool.initial_crew,
@@ -70,8 +112,80 @@ function generateResults(results, context) {
         intel: pool.intel.total
       }
     };
+
+    // Standby reservations (unit_policy.reservations): reserved versus used
+    if (pool.reservation) {
+      results.reservations = results.reservations || {};
+      results.reservations[unit] = { for_mission_types: Array.from(pool.reservation.forMissionTypes) };
+      for (const resource of ['aircraft', 'pilot', 'so', 'intel']) {
+        if (pool[resource].reserved > 0) {
+          results.reservations[unit][resource] = pool[resource].reservationStats(horizon);
+        }
+      }
+    }
+
+    // Additional crew roles (scenario.mos_roles), flattened like pilot/so/intel
+    for (const [role, rolePool] of Object.entries(pool.roles || {})) {
+      Object.assign(results.utilization[unit], {
+        [role]: Number(rolePool.utilization().toFixed(3)),
+        [`${role}_efficiency`]: Number(rolePool.efficiency(horizon).toFixed(3)),
+        [`${role}_efficiency_of_available`]: Number(rolePool.efficiencyOfAvailable(horizon).toFixed(3)),
+        [`${role}_stats`]: rolePool.getStats(horizon, rolePool.total, pool.initial_crew?.[role] || rolePool.total)
+      });
+      results.utilization[unit].effective_crew[role] = rolePool.total;
+    }
+
+    // Crew turn (crew_policy.min_crew_turn_hours): *_efficiency is time on missions, *_turn_fraction time in turn
+    if (scenario.crew_policy?.min_crew_turn_hours > 0) {
+      const crewPools = { pilot: pool.pilot, so: pool.so, intel: pool.intel, ...pool.roles };
+      for (const [role, crewPool] of Object.entries(crewPools)) {
+        results.utilization[unit][`${role}_turn_fraction`] = Number(crewPool.turnFraction(horizon).toFixed(3));
+      }
+    }
+
+    // Concurrent-mission slots, only for units with unit_policy.max_concurrent_missions
+    if (pool.controlStations) {
+      Object.assign(results.utilization[unit], {
+        control_station: Number(pool.controlStations.utilization().toFixed(3)),
+        control_station_efficiency: Number(pool.controlStations.efficiency(horizon).toFixed(3)),
+        control_station_stats: pool.controlStations.getStats(horizon)
+      });
+    }
+
+    // Launch/recovery sets, only for units that have them (v_launch_recovery or overrides launch_recovery)
+    if (pool.launchRecovery) {
+      Object.assign(results.utilization[unit], {
+        launch_recovery: Number(pool.launchRecovery.utilization().toFixed(3)),
+        launch_recovery_efficiency: Number(pool.launchRecovery.efficiency(horizon).toFixed(3)),
+        launch_recovery_stats: pool.launchRecovery.getStats(horizon)
+      });
+    }
+  }
+
+  // Payload busy-time efficiency and mount counts per unit and payload type
+  // Each started mission holds one of each required payload type, so allocations = missions mounted
+  results.payload_utilization = {};
+  results.payload_missions = {};
+  for (const unit of unitList) {
+    results.payload_utilization[unit] = {};
+    results.payload_missions[unit] = {};
+    for (const [ptype, payloadPool] of Object.entries(pools[unit].payloads)) {
+      results.payload_utilization[unit][ptype] = Number(payloadPool.efficiency(horizon).toFixed(3));
+      results.payload_missions[unit][ptype] = payloadPool.allocations;
+    }
   }
 
+  // Most sorties started in any sliding window, overall and per unit
+  results.peak_window = {
+    window_hours: peakWindowHours,
+    ...peakWindow(unitList.flatMap(unit => pools[unit].missionStarts), peakWindowHours),
+    by_unit: Object.fromEntries(unitList.map(unit => [unit, peakWindow(pools[unit].missionStarts, peakWindowHours)]))
+  };
+
+  // Crew on hand against headcount, only with crew_policy.availability_rate
+  const crewAvailability = crewAvailabilityResults(pools, scenario, horizon);
+  if (crewAvailability) results.crew_availability = crewAvailability;
+
   // Generate availability timeline
   const personnelAvailability = availability.personnelAvailability;
   if (personnelAvailability['7318'] || personnelAvailability['7314'] || personnelAvailability['0231']) {
@@ -128,4 +242,19 @@ function generateResults(results, context) {
   return results;
 }
 
-module.exports = { generateResults };
+/**
+ * Copy of a results value with every object's keys in sorted order (arrays keep their order)
+ * Map-like fields (utilization, by_type, rejections_by_type, ...) are keyed in the order entries were first
+ * touched during the run; sorting makes the serialized results identical byte for byte
+ * @param {*} value - Results (or any JSON value)
+ * @returns {*} Sorted copy
+ */
+function sortResultKeys(value) {
+  if (Array.isArray(value)) return value.map(sortResultKeys);
+  if (!value || typeof value !== 'object') return value;
+  const sorted = {};
+  for (const key of Object.keys(value).sort()) sorted[key] = sortResultKeys(value[key]);
+  return sorted;
+}
+
+module.exports = { generateResults, sortResultKeys };
@@ -0,0 +1,124 @@
+/**
+ * MONTE CARLO INPUT/OUTPUT ANALYSIS
+ *
+ * Answers "which uncertain input drives mission completion?" without exporting iterations:
+ * for each requested (input, output) pair, correlates the per-iteration input draws with
+ * the per-iteration output values.
+ *
+ * A pair is { input, output }: input is an inputUncertainty path, output is a metric
+ * expression as in thresholds, e.g.
+ *   { input: 'overrides.units.VMU-1.aircraft', output: 'missions.completed / missions.requested' }
+ */
+
+const { compileMetric, evaluate } = require('./thresholds');
+const { pathSegments } = require('./uncertainty');
+
+const round = v => (v === null ? null : Number(v.toFixed(4)));
+
+/**
+ * Validate and compile analysis pairs. Throws on the first invalid one so the caller
+ * can fail before any iteration runs.
+ *
+ * @param {Array<Object>} pairs - Pairs ({ input, output })
+ * @param {Array<Object>|null} inputUncertainty - Compiled input uncertainty entries
+ * @returns {Array<Object>} - Compiled pairs ({ key, input, output, ast })
+ */
+function compileInputOutputAnalysis(pairs, inputUncertainty) {
+  if (!Array.isArray(pairs)) {
+    throw new Error('inputOutputAnalysis must be an array of { input, output }');
+  }
+  const inputs = new Set((inputUncertainty || []).map(c => c.key));
+  return pairs.map((pair, i) => {
+    const where = `inputOutputAnalysis[${i}]`;
+    if (!pair || typeof pair !== 'object') throw new Error(`${where} must be an object`);
+    const input = pathSegments(pair.input).join('.');
+    if (!inputs.has(input)) {
+      throw new Error(`${where}: input '${input}' is not an inputUncertainty path`);
+    }
+    const ast = compileMetric(pair.output, where);
+    const output = pair.output.trim();
+    return { key: `${input} ~ ${output}`, input, output, ast };
+  });
+}
+
+/**
+ * Ranks (1-based), with ties sharing the average of the ranks they span.
+ *
+ * @param {Array<number>} values - Values
+ * @returns {Array<number>} - Rank of each value, in input order
+ */
+function ranks(values) {
+  const order = values.map((v, i) => i).sort((a, b) => values[a] - values[b]);
+  const out = new Array(values.length);
+  for (let start = 0; start < order.length;) {
+    let end = start;
+    while (end + 1 < order.length && values[order[end + 1]] === values[order[start]]) end++;
+    const rank = (start + end) / 2 + 1;
+    for (let k = start; k <= end; k++) out[order[k]] = rank;
+    start = end + 1;
+  }
+  return out;
+}
+
+/**
+ * Pearson correlation and least-squares fit of y on x.
+ *
+ * @param {Array<number>} x - Inputs
+ * @param {Array<number>} y - Outputs
+ * @returns {Object} - { pearson, slope, intercept }; null where a variance is zero
+ */
+function linearFit(x, y) {
+  const n = x.length;
+  const mx = x.reduce((a, b) => a + b, 0) / n;
+  const my = y.reduce((a, b) => a + b, 0) / n;
+  let sxy = 0;
+  let sxx = 0;
+  let syy = 0;
+  for (let i = 0; i < n; i++) {
+    sxy += (x[i] - mx) * (y[i] - my);
+    sxx += (x[i] - mx) ** 2;
+    syy += (y[i] - my) ** 2;
+  }
+  const slope = sxx > 0 ? sxy / sxx : null;
+  return {
+    pearson: sxx > 0 && syy > 0 ? sxy / Math.sqrt(sxx * syy) : null,
+    slope,
+    intercept: slope === null ? null : my - slope * mx
+  };
+}
+
+/**
+ * Correlate each pair's input draws with its output over the iterations.
+ * Iterations where the output is not a finite number (e.g. 0 / 0) are left out.
+ *
+ * @param {Array<Object>} compiled - Output of compileInputOutputAnalysis
+ * @param {Array<Object>} iterations - DES result objects carrying sampled_inputs
+ * @returns {Object} - pair key → { input, output, count, pearson, spearman, slope, intercept }
+ */
+function inputOutputAnalysis(compiled, iterations) {
+  const out = {};
+  for (const c of compiled) {
+    const x = [];
+    const y = [];
+    for (const iter of iterations) {
+      const value = evaluate(c.ast, iter);
+      if (!Number.isFinite(value)) continue;
+      x.push(iter.sampled_inputs[c.input]);
+      y.push(value);
+    }
+    const fit = x.length > 1 ? linearFit(x, y) : { pearson: null, slope: null, intercept: null };
+    const spearman = x.length > 1 ? linearFit(ranks(x), ranks(y)).pearson : null;
+    out[c.key] = {
+      input: c.input,
+      output: c.output,
+      count: x.length,
+      pearson: round(fit.pearson),
+      spearman: round(spearman),
+      slope: round(fit.slope),
+      intercept: round(fit.intercept)
+    };
+  }
+  return out;
+}
+
+module.exports = { compileInputOutputAnalysis, inputOutputAnalysis, ranks };
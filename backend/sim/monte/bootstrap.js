@@ -0,0 +1,84 @@
+/**
+ * MONTE CARLO DEMAND BOOTSTRAP
+ *
+ * Instead of drawing demand from a fitted rate, each iteration can replay one realization of
+ * demand picked (with replacement) from a library, e.g. one historical day of actual demand per
+ * entry. The picked realization replaces scenario.demand for that iteration; it is usually a
+ * list of scheduled entries ({ type: 'scheduled', mission_type, at_hours }) but any demand
+ * entries are accepted.
+ *
+ * With seedLinked (the default) the pick is drawn from a sub-stream of the iteration seed, so two
+ * runs with the same seed (a what-if compare, a sensitivity sweep) replay the same realization in
+ * every iteration. With seedLinked: false a seeded run picks from a stream of the Monte Carlo seed
+ * instead, apart from the iteration seeds, so it still reproduces (and survives a retry or a
+ * checkpoint resume). Unseeded runs pick with Math.random.
+ */
+
+const { makeRng, deriveSeed, deriveStreamSeed } = require('../des/helpers/random');
+const { validateScenario } = require('../des/helpers/validation');
+
+// deriveSeed stream index for the bootstrap pick; with seedLinked: false, the deriveStreamSeed
+// stream of the Monte Carlo seed indexed by iteration instead
+const BOOTSTRAP_STREAM = 0x4253;
+
+/**
+ * Check options.demandBootstrap against the scenario it will be substituted into.
+ *
+ * @param {Object} bootstrap - { library: Array<Array<Object>>, seedLinked }
+ * @param {Object} scenario - Scenario (after unit normalization)
+ * @returns {Object} - { library, seedLinked }
+ */
+function validateDemandBootstrap(bootstrap, scenario) {
+  if (!bootstrap || typeof bootstrap !== 'object' || !Array.isArray(bootstrap.library) || bootstrap.library.length === 0) {
+    throw new Error('demandBootstrap must be { library, seedLinked } with a non-empty library of demand lists');
+  }
+  const seedLinked = bootstrap.seedLinked ?? true;
+  if (typeof seedLinked !== 'boolean') {
+    throw new Error(`demandBootstrap.seedLinked must be a boolean, got ${seedLinked}`);
+  }
+  bootstrap.library.forEach((demand, k) => {
+    if (!Array.isArray(demand)) {
+      throw new Error(`demandBootstrap.library[${k}] must be an array of demand entries`);
+    }
+    try {
+      validateScenario({ ...scenario, demand });
+    } catch (error) {
+      throw new Error(`demandBootstrap.library[${k}]: ${error.message}`);
+    }
+  });
+  return { library: bootstrap.library, seedLinked };
+}
+
+/**
+ * Library index one iteration replays.
+ *
+ * @param {Object} bootstrap - Output of validateDemandBootstrap
+ * @param {number|null} baseSeed - The Monte Carlo seed, or null for an unseeded run
+ * @param {number} iterationIndex - Iteration index
+ * @returns {number} - Index into bootstrap.library
+ */
+function pickDemandRealization(bootstrap, baseSeed, iterationIndex) {
+  let rng = Math.random;
+  if (baseSeed !== null) {
+    const seed = bootstrap.seedLinked
+      ? deriveSeed(deriveSeed(baseSeed, iterationIndex), BOOTSTRAP_STREAM)
+      : deriveSeed(deriveStreamSeed(baseSeed, BOOTSTRAP_STREAM), iterationIndex);
+    rng = makeRng(seed);
+  }
+  return Math.min(bootstrap.library.length - 1, Math.floor(rng() * bootstrap.library.length));
+}
+
+/**
+ * How often each library entry was replayed over the successful iterations.
+ *
+ * @param {Object} bootstrap - Output of validateDemandBootstrap
+ * @param {Array<Object>} iterations - DES results carrying bootstrap_index
+ * @returns {Object} - { library_size, seed_linked, counts } with counts[k] iterations replaying entry k
+ */
+function summarizeDemandBootstrap(bootstrap, iterations) {
+  const counts = new Array(bootstrap.library.length).fill(0);
+  for (const iter of iterations) counts[iter.bootstrap_index]++;
+  return { library_size: bootstrap.library.length, seed_linked: bootstrap.seedLinked, counts };
+}
+
+module.exports = { validateDemandBootstrap, pickDemandRealization, summarizeDemandBootstrap };
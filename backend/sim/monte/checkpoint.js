@@ -0,0 +1,108 @@
+/**
+ * MONTE CARLO CHECKPOINTS
+ *
+ * A long run can write its finished iterations to disk as it goes and a later run can pick
+ * up where it stopped. Iterations are seeded by index, so a resumed seeded run returns the
+ * same result as one that was never interrupted.
+ *
+ * A checkpoint is a JSON file:
+ *   { version, fingerprint, iterations, seed, next_index, results, failures, timings }
+ * next_index is the first iteration not yet run; results, failures and timings cover
+ * iterations [0, next_index) exactly as the engine collected them.
+ */
+
+const crypto = require('crypto');
+const fs = require('fs');
+
+const CHECKPOINT_VERSION = 1;
+
+// Options that decide what each iteration computes; anything else may change on resume
+const RUN_OPTIONS = [
+  'state', 'overrides', 'iterations', 'seed', 'algorithm', 'simulateSettings', 'inputUncertainty', 'inputSampling',
+  'limits', 'iterationTimeoutMs', 'resourceHoldModel', 'timelineRejectionSampling', 'peakWindowHours',
+  'rejectionDensityBucketHours', 'demandBootstrap', 'hourlyProfile', 'collectTimeline',
+  'priorityWeights'
+];
+
+// Error fields kept for a failed iteration (see iterationError in engine.js)
+const ERROR_FIELDS = ['iteration', 'timed_out', 'code', 'limit', 'max', 'counts'];
+
+/**
+ * Check options.checkpoint.
+ *
+ * @param {Object} checkpoint - { path, everyIterations }
+ * @returns {Object} - { path, everyIterations }
+ */
+function validateCheckpoint(checkpoint) {
+  if (!checkpoint || typeof checkpoint !== 'object' || typeof checkpoint.path !== 'string' || checkpoint.path === '') {
+    throw new Error('checkpoint must be { path, everyIterations } with a file path');
+  }
+  const every = checkpoint.everyIterations;
+  if (!Number.isInteger(every) || every <= 0) {
+    throw new Error(`checkpoint.everyIterations must be a positive integer, got ${every}`);
+  }
+  return { path: checkpoint.path, everyIterations: every };
+}
+
+/**
+ * Hash of everything that decides the iterations, so a checkpoint is only resumed by the same run.
+ *
+ * @param {Object} scenario - Scenario (after unit normalization)
+ * @param {Object} options - runMonteCarlo options
+ * @returns {string} - Hex digest
+ */
+function runFingerprint(scenario, options) {
+  const inputs = { scenario, ...Object.fromEntries(RUN_OPTIONS.map(key => [key, options[key] ?? null])) };
+  return crypto.createHash('sha256').update(JSON.stringify(inputs)).digest('hex');
+}
+
+/**
+ * Write a checkpoint atomically (temporary file, then rename), so a crash mid-write keeps the previous one.
+ *
+ * @param {string} filePath - Checkpoint path
+ * @param {Object} state - { fingerprint, iterations, seed, next_index, results, failures, timings }
+ * @returns {Promise<void>}
+ */
+async function writeCheckpoint(filePath, state) {
+  const body = {
+    version: CHECKPOINT_VERSION,
+    ...state,
+    failures: state.failures.map(({ iteration, error }) => ({
+      iteration,
+      error: { message: error.message, ...Object.fromEntries(ERROR_FIELDS.map(key => [key, error[key]])) }
+    }))
+  };
+  const tmp = `${filePath}.tmp`;
+  await fs.promises.writeFile(tmp, JSON.stringify(body));
+  await fs.promises.rename(tmp, filePath);
+}
+
+/**
+ * Load a checkpoint written by the same run.
+ *
+ * @param {string} filePath - Checkpoint path
+ * @param {string} fingerprint - runFingerprint of the resuming run
+ * @returns {Promise<Object>} - { next_index, results, failures (with Error objects), timings }
+ */
+async function readCheckpoint(filePath, fingerprint) {
+  let body;
+  try {
+    body = JSON.parse(await fs.promises.readFile(filePath, 'utf8'));
+  } catch (error) {
+    throw new Error(`resumeFrom: cannot read checkpoint '${filePath}': ${error.message}`);
+  }
+  if (body.version !== CHECKPOINT_VERSION) {
+    throw new Error(`resumeFrom: checkpoint version ${body.version} is not supported (expected ${CHECKPOINT_VERSION})`);
+  }
+  if (body.fingerprint !== fingerprint) {
+    throw new Error('resumeFrom: checkpoint was written by a run with a different scenario, state or iteration options');
+  }
+  return {
+    next_index: body.next_index,
+    results: body.results,
+    failures: body.failures.map(({ iteration, error }) => ({ iteration, error: Object.assign(new Error(error.message), error) })),
+    timings: body.timings
+  };
+}
+
+module.exports = { validateCheckpoint, runFingerprint, writeCheckpoint, readCheckpoint };
@@ -0,0 +1,250 @@
+/**
+ * MONTE CARLO RESULT COMPARISON
+ *
+ * Compares two aggregated Monte Carlo results (e.g. before/after a what-if change)
+ * and summarizes which metrics moved, by how much, and whether the move is likely real.
+ *
+ * Every aggregated statistics object ({ count, mean, p10, ..., stddev }) is a metric,
+ * named by its dot path (e.g. 'by_type.ISR.completed'). Threshold probabilities are
+ * compared as plain values.
+ *
+ * diffFields is the exact counterpart for results that should be identical (a replayed
+ * DES run, a pinned golden output): every field is compared, numbers within a tolerance.
+ */
+
+// Sections of a Monte Carlo result that are not metrics
+const SKIPPED_SECTIONS = new Set([
+  'iterations', 'percentile_timelines', 'initial_resources', 'unitSplit',
+  'personnel_availability', 'iteration_errors', 'input_uncertainty', 'meta'
+]);
+
+// 95% two-sided normal quantile for confidence intervals on the mean
+const Z_95 = 1.96;
+
+const DEFAULT_OPTIONS = {
+  percentiles: ['p10', 'p50', 'p90'],
+  relativeThreshold: 0.05,
+  absoluteThreshold: 0,
+  top: 20
+};
+
+// Percentile keys of an aggregated statistics object (p10, p50, ...)
+const PERCENTILE_KEY = /^p\d+$/;
+
+/**
+ * Check compareResults options and fill in the defaults.
+ *
+ * @param {Object} options - compareResults options
+ * @returns {Object} - Resolved options
+ */
+function validateCompareOptions(options = {}) {
+  if (options === null || typeof options !== 'object' || Array.isArray(options)) {
+    throw new Error('compareResults options must be an object');
+  }
+  const resolved = {
+    percentiles: options.percentiles ?? DEFAULT_OPTIONS.percentiles,
+    relativeThreshold: options.relativeThreshold ?? DEFAULT_OPTIONS.relativeThreshold,
+    absoluteThreshold: options.absoluteThreshold ?? DEFAULT_OPTIONS.absoluteThreshold,
+    top: options.top ?? DEFAULT_OPTIONS.top
+  };
+  if (!Array.isArray(resolved.percentiles) || !resolved.percentiles.every(p => typeof p === 'string' && PERCENTILE_KEY.test(p))) {
+    throw new Error(`compareResults percentiles must be an array of percentile keys like 'p50', got ${JSON.stringify(resolved.percentiles)}`);
+  }
+  for (const key of ['relativeThreshold', 'absoluteThreshold']) {
+    if (typeof resolved[key] !== 'number' || !Number.isFinite(resolved[key]) || resolved[key] < 0) {
+      throw new Error(`compareResults ${key} must be a non-negative number, got ${JSON.stringify(resolved[key])}`);
+    }
+  }
+  if (!Number.isInteger(resolved.top) || resolved.top < 1) {
+    throw new Error(`compareResults top must be a positive integer, got ${JSON.stringify(resolved.top)}`);
+  }
+  return resolved;
+}
+
+/**
+ * True for an aggregated statistics object produced by aggregateStatistics.
+ *
+ * @param {*} value - Candidate value
+ * @returns {boolean}
+ */
+function isStatistics(value) {
+  return value !== null && typeof value === 'object' && typeof value.mean === 'number' && typeof value.p50 === 'number';
+}
+
+/**
+ * Collect every metric in a Monte Carlo result, keyed by dot path.
+ *
+ * @param {Object} results - Aggregated Monte Carlo result
+ * @returns {Map<string, Object>} - path → statistics ({ mean, ... })
+ */
+function collectMetrics(results) {
+  const metrics = new Map();
+
+  function walk(node, path) {
+    if (isStatistics(node)) {
+      metrics.set(path.join('.'), node);
+      return;
+    }
+    if (node === null || typeof node !== 'object' || Array.isArray(node)) return;
+    for (const [key, value] of Object.entries(node)) {
+      if (path.length === 0 && SKIPPED_SECTIONS.has(key)) continue;
+      walk(value, [...path, key]);
+    }
+  }
+
+  walk(results || {}, []);
+
+  for (const [key, value] of Object.entries(results?.threshold_probabilities || {})) {
+    if (typeof value === 'number') metrics.set(`threshold_probabilities.${key}`, { mean: value });
+  }
+
+  return metrics;
+}
+
+/**
+ * 95% confidence interval for the mean, or null without a stddev and count.
+ *
+ * @param {Object} stats - Aggregated statistics
+ * @returns {Array<number>|null} - [low, high]
+ */
+function meanInterval(stats) {
+  if (typeof stats.stddev !== 'number' || !(stats.count > 0)) return null;
+  const half = Z_95 * stats.stddev / Math.sqrt(stats.count);
+  return [stats.mean - half, stats.mean + half];
+}
+
+/**
+ * Compare one metric between two runs.
+ *
+ * @param {string} metric - Metric path
+ * @param {Object} a - Statistics from the first (baseline) run
+ * @param {Object} b - Statistics from the second run
+ * @param {Object} options - Resolved comparison options
+ * @returns {Object} - Per-metric change record
+ */
+function compareMetric(metric, a, b, options) {
+  const delta = { mean: b.mean - a.mean };
+  const relative = { mean: a.mean !== 0 ? delta.mean / Math.abs(a.mean) : (delta.mean === 0 ? 0 : null) };
+  for (const p of options.percentiles) {
+    if (typeof a[p] === 'number' && typeof b[p] === 'number') {
+      delta[p] = b[p] - a[p];
+      relative[p] = a[p] !== 0 ? delta[p] / Math.abs(a[p]) : (delta[p] === 0 ? 0 : null);
+    }
+  }
+
+  const ciA = meanInterval(a);
+  const ciB = meanInterval(b);
+  const ciOverlap = ciA && ciB ? ciA[0] <= ciB[1] && ciB[0] <= ciA[1] : null;
+
+  // Relative change from zero is undefined; treat any move off zero as exceeding the threshold
+  const exceedsRelative = relative.mean === null || Math.abs(relative.mean) > options.relativeThreshold;
+  const exceedsAbsolute = Math.abs(delta.mean) > options.absoluteThreshold;
+
+  return {
+    metric,
+    a: a.mean,
+    b: b.mean,
+    delta,
+    relative,
+    ci_overlap: ciOverlap,
+    significant: delta.mean !== 0 && ((exceedsRelative && exceedsAbsolute) || ciOverlap === false)
+  };
+}
+
+/**
+ * Compare two aggregated Monte Carlo results.
+ *
+ * @param {Object} a - Baseline result (runMonteCarlo output)
+ * @param {Object} b - Result to compare against the baseline
+ * @param {Object} options - Optional settings:
+ *   - percentiles: Percentile keys to diff besides the mean (default: ['p10', 'p50', 'p90'])
+ *   - relativeThreshold: Flag |relative mean change| above this (default: 0.05)
+ *   - absoluteThreshold: ...and |absolute mean change| above this (default: 0)
+ *   - top: Number of biggest movers to return (default: 20)
+ *   Invalid options (see validateCompareOptions) throw
+ * @returns {Object} - { metrics_compared, significant_count, biggest_movers, significant, added, removed }
+ */
+function compareResults(a, b, options = {}) {
+  if (!a || typeof a !== 'object' || !b || typeof b !== 'object') {
+    throw new Error('compareResults requires two Monte Carlo result objects');
+  }
+  const resolved = validateCompareOptions(options);
+
+  const metricsA = collectMetrics(a);
+  const metricsB = collectMetrics(b);
+
+  const changes = [];
+  for (const [metric, statsA] of metricsA) {
+    const statsB = metricsB.get(metric);
+    if (statsB) changes.push(compareMetric(metric, statsA, statsB, resolved));
+  }
+
+  // Biggest movers first: by relative mean change (moves off zero first), then absolute
+  const magnitude = c => (c.relative.mean === null ? Infinity : Math.abs(c.relative.mean));
+  changes.sort((x, y) => magnitude(y) - magnitude(x) || Math.abs(y.delta.mean) - Math.abs(x.delta.mean) || x.metric.localeCompare(y.metric));
+
+  const significant = changes.filter(c => c.significant);
+
+  return {
+    metrics_compared: changes.length,
+    significant_count: significant.length,
+    biggest_movers: changes.filter(c => c.delta.mean !== 0).slice(0, resolved.top),
+    significant,
+    // Metrics present in only one run (e.g. a unit or mission type added or removed)
+    added: [...metricsB.keys()].filter(k => !metricsA.has(k)).sort(),
+    removed: [...metricsA.keys()].filter(k => !metricsB.has(k)).sort()
+  };
+}
+
+/**
+ * Field-by-field differences between two JSON values.
+ * Numbers match within a relative tolerance (absolute below 1); everything else must be equal.
+ *
+ * @param {*} expected - Reference value (e.g. a stored result)
+ * @param {*} actual - Value to check against it
+ * @param {Object} options - Optional settings:
+ *   - tolerance: Relative tolerance for numbers (default: 1e-9)
+ *   - maxDifferences: Stop after this many differences (default: 20)
+ * @returns {Array<Object>} - [{ path, kind, expected, actual }]; kind is 'changed', 'missing' (only in expected),
+ *                            'added' (only in actual) or 'length' (arrays of different lengths, then compared up
+ *                            to the shorter)
+ */
+function diffFields(expected, actual, options = {}) {
+  const tolerance = options.tolerance ?? 1e-9;
+  const maxDifferences = options.maxDifferences ?? 20;
+  const diffs = [];
+
+  function walk(e, a, path) {
+    if (diffs.length >= maxDifferences) return;
+    if (typeof e === 'number' && typeof a === 'number') {
+      const scale = Math.max(1, Math.abs(e), Math.abs(a));
+      if (!(Math.abs(e - a) <= tolerance * scale)) diffs.push({ path, kind: 'changed', expected: e, actual: a });
+      return;
+    }
+    if (Array.isArray(e) || Array.isArray(a)) {
+      if (!Array.isArray(e) || !Array.isArray(a)) {
+        diffs.push({ path, kind: 'changed', expected: e, actual: a });
+        return;
+      }
+      if (e.length !== a.length) diffs.push({ path, kind: 'length', expected: e.length, actual: a.length });
+      const n = Math.min(e.length, a.length);
+      for (let i = 0; i < n; i++) walk(e[i], a[i], `${path}[${i}]`);
+      return;
+    }
+    if (e && a && typeof e === 'object' && typeof a === 'object') {
+      for (const key of new Set([...Object.keys(e), ...Object.keys(a)])) {
+        if (diffs.length >= maxDifferences) return;
+        if (!(key in a)) diffs.push({ path: `${path}.${key}`, kind: 'missing', expected: e[key], actual: undefined });
+        else if (!(key in e)) diffs.push({ path: `${path}.${key}`, kind: 'added', expected: undefined, actual: a[key] });
+        else walk(e[key], a[key], `${path}.${key}`);
+      }
+      return;
+    }
+    if (e !== a) diffs.push({ path, kind: 'changed', expected: e, actual: a });
+  }
+
+  walk(expected, actual, '$');
+  return diffs;
+}
+
+module.exports = { compareResults, validateCompareOptions, diffFields };
@@ -4,8 +4,8 @@
  * This engine wraps the DES engine to run multiple stochastic iterations
  * and aggregate results with percentiles and statistics.
  * 
- * The DES engine is already stochastic (uses Math.random() for durations and demand),
- * so we simply run it multiple times and aggregate the results.
+ * The DES engine is already stochastic (random durations and demand), so we simply run
+ * it multiple times and aggregate the results. Pass options.seed for reproducible runs.
  * 
  * PERFORMANCE: Uses worker threads to run simulations in parallel for maximum speed.
  */
@@ -13,11 +13,34 @@
 const { Worker } = require('worker_threads');
 const path = require('path');
 const os = require('os');
+const { compileThresholds, thresholdProbabilities } = require('./thresholds');
+const { compileInputUncertainty, sampleInputs, applyInputs, latinHypercube, validateInputSampling } = require('./uncertainty');
+const { compileInputOutputAnalysis, inputOutputAnalysis } = require('./analysis');
+const { compileExtractors, extractValues, compileExtremeMetrics, findExtremes } = require('./extractors');
+const { validateDemandBootstrap, pickDemandRealization, summarizeDemandBootstrap } = require('./bootstrap');
+const { compileStratifyBy, stratifyIterations, DEFAULT_STRATIFY_BUCKETS } = require('./strata');
+const { makeRng, deriveSeed, deriveStreamSeed, isValidSeed } = require('../des/helpers/random');
+const { validateLimits, isLimitError } = require('../des/helpers/limits');
+const { normalizeScenarioUnits } = require('../des/helpers/units');
+const { validateScenario } = require('../des/helpers/validation');
+const { resolveMaxOutputBytes, limitOutputSize } = require('../des/helpers/outputsize');
+const { resolvePriorityWeights } = require('../des/helpers/priority');
+const { acquireRunSlot, validateOnBusy } = require('./slots');
+const { validateCheckpoint, runFingerprint, writeCheckpoint, readCheckpoint } = require('./checkpoint');
 
 // Number of CPU cores available (use all but 1 to keep system responsive)
 const CPU_COUNT = os.cpus().length;
 const WORKER_POOL_SIZE = Math.max(1, CPU_COUNT - 1);
 
+// Hard worker timeout when no iterationTimeoutMs is given
+const WORKER_TIMEOUT_MS = 60000;
+// Extra time past iterationTimeoutMs before a worker that missed the cooperative check is terminated
+const WORKER_TIMEOUT_GRACE_MS = 1000;
+// An iteration slower than this multiple of the median wall time is reported as a straggler
+const STRAGGLER_FACTOR = 10;
+// Stream of the Monte Carlo seed (deriveStreamSeed) used to pick the sampled iterations (options.keepSampleIterations)
+const SAMPLE_ITERATIONS_STREAM = 0x5349;
+
 /**
  * Calculate percentiles from an array of values.
  * 
@@ -45,7 +68,9 @@ function calculatePercentiles(values, percentiles = [10, 25, 50, 75, 90, 95, 99]
 /**
  * Aggregate statistics from multiple DES runs.
  * 
- * Calculates mean, percentiles, min, max, and standard deviation.
+ * Calculates mean, percentiles, min, max, and standard deviation, plus the number of
+ * values they were computed from (fewer than the iteration count for keys that only
+ * appear in some iterations).
  * 
  * @param {Array<number>} values - Array of numeric values from multiple iterations
  * @returns {Object|null} - Aggregated statistics or null if empty
@@ -63,6 +88,7 @@ function aggregateStatistics(values) {
   const percentiles = calculatePercentiles(sorted);
   
   return {
+    count: values.length,
     mean: Number(mean.toFixed(2)),
     ...percentiles,
     min: sorted[0],
@@ -126,6 +152,192 @@ function aggregateObject(iterations, path) {
   return result;
 }
 
+/**
+ * Aggregate a unit → payload type → number map (payload_utilization or payload_missions).
+ * 
+ * Payload pools are fixed by the state snapshot and overrides, so every iteration
+ * normally reports the same unit/type pairs; missing pairs are skipped.
+ * 
+ * @param {Array<Object>} iterations - Array of DES result objects
+ * @param {string} field - Top-level field name
+ * @returns {Object} - result[unit][payloadType] = aggregated statistics
+ */
+function aggregateUnitPayloadMap(iterations, field) {
+  const pairs = new Map();
+  for (const iter of iterations) {
+    for (const [unit, byType] of Object.entries(iter[field] || {})) {
+      if (!pairs.has(unit)) pairs.set(unit, new Set());
+      Object.keys(byType).forEach(t => pairs.get(unit).add(t));
+    }
+  }
+  
+  const result = {};
+  for (const [unit, types] of pairs) {
+    result[unit] = {};
+    for (const ptype of types) {
+      const values = iterations
+        .map(iter => iter[field]?.[unit]?.[ptype])
+        .filter(v => typeof v === 'number');
+      if (values.length > 0) {
+        result[unit][ptype] = aggregateStatistics(values);
+      }
+    }
+  }
+  
+  return result;
+}
+
+/**
+ * Aggregate standby reservation statistics (present when unit_policy.reservations is set).
+ * 
+ * @param {Array<Object>} iterations - Array of DES result objects
+ * @returns {Object} - result[unit][resource][allocations | busy_hours | efficiency | peak_in_use] = aggregated statistics
+ */
+function aggregateReservations(iterations) {
+  const result = {};
+  const fields = ['allocations', 'busy_hours', 'efficiency', 'peak_in_use'];
+  for (const iter of iterations) {
+    for (const [unit, byResource] of Object.entries(iter.reservations || {})) {
+      result[unit] = result[unit] || { for_mission_types: byResource.for_mission_types };
+      for (const resource of Object.keys(byResource)) {
+        if (resource !== 'for_mission_types') result[unit][resource] = result[unit][resource] || {};
+      }
+    }
+  }
+  
+  for (const [unit, byResource] of Object.entries(result)) {
+    for (const resource of Object.keys(byResource)) {
+      if (resource === 'for_mission_types') continue;
+      byResource[resource].reserved = iterations.find(iter => iter.reservations?.[unit]?.[resource])?.reservations[unit][resource].reserved;
+      for (const field of fields) {
+        const values = iterations
+          .map(iter => iter.reservations?.[unit]?.[resource]?.[field])
+          .filter(v => typeof v === 'number');
+        if (values.length > 0) byResource[resource][field] = aggregateStatistics(values);
+      }
+    }
+  }
+  
+  return result;
+}
+
+/**
+ * Aggregate keyed mission counters (by_type or by_demand_source).
+ * 
+ * Structure: result[key][stat] = aggregated statistics, for the requested, started,
+ * completed and rejected counters. By default only iterations that have an entry for a
+ * key contribute; with missingAsZero an iteration without the key contributes zeros.
+ * 
+ * @param {Array<Object>} iterations - Array of DES result objects
+ * @param {string} field - Top-level field name
+ * @param {boolean} missingAsZero - Count a missing key as zero instead of skipping the iteration
+ * @param {Array<string>} declaredKeys - Keys reported even if no iteration has them (e.g. scenario mission types)
+ * @returns {Object} - Aggregated statistics per key
+ */
+function aggregateMissionStats(iterations, field, missingAsZero = false, declaredKeys = []) {
+  const keys = new Set(declaredKeys);
+  for (const iter of iterations) {
+    if (iter[field]) Object.keys(iter[field]).forEach(k => keys.add(k));
+  }
+  
+  const result = {};
+  const stats = ['requested', 'started', 'completed', 'rejected', 'infeasible', 'in_progress_at_horizon'];
+  for (const key of keys) {
+    result[key] = {};
+    for (const stat of stats) {
+      // infeasible is only set where a type could not be flown; once any iteration has it, the others count zero
+      if (stat === 'infeasible') {
+        if (!iterations.some(iter => iter[field]?.[key]?.infeasible !== undefined)) continue;
+        result[key].infeasible = aggregateStatistics(iterations
+          .filter(iter => iter[field]?.[key] || missingAsZero)
+          .map(iter => iter[field]?.[key]?.infeasible || 0));
+        continue;
+      }
+      const values = iterations
+        .map(iter => iter[field]?.[key]?.[stat] ?? (missingAsZero && !iter[field]?.[key] ? 0 : undefined))
+        .filter(v => typeof v === 'number');
+      
+      if (values.length > 0) {
+        result[key][stat] = aggregateStatistics(values);
+      }
+    }
+  }
+  
+  return result;
+}
+
+/**
+ * Mean of each iteration's rejection_density, bucket by bucket.
+ * Iterations with shorter horizons contribute zero to the buckets they do not cover.
+ *
+ * @param {Array<Object>} iterations - DES result objects with rejection_density
+ * @returns {Object} - { bucket_hours, buckets: reason → Array<number> (mean count per bucket) }
+ */
+function aggregateRejectionDensity(iterations) {
+  const reasons = new Set(iterations.flatMap(iter => Object.keys(iter.rejection_density.buckets)));
+  const length = Math.max(0, ...iterations.flatMap(iter => Object.values(iter.rejection_density.buckets).map(b => b.length)));
+  const buckets = {};
+  for (const reason of reasons) {
+    const sums = new Array(length).fill(0);
+    for (const iter of iterations) {
+      (iter.rejection_density.buckets[reason] || []).forEach((count, i) => { sums[i] += count; });
+    }
+    buckets[reason] = sums.map(sum => Number((sum / iterations.length).toFixed(4)));
+  }
+  return { bucket_hours: iterations[0].rejection_density.bucket_hours, buckets };
+}
+
+/**
+ * Mean of each iteration's hourly_profile, hour by hour.
+ * Iterations with shorter horizons contribute zero to the hours they do not cover.
+ *
+ * @param {Array<Object>} iterations - DES result objects with hourly_profile
+ * @returns {Object} - { arrivals_mean, starts_mean, rejections_mean } (Array<number>, one entry per hour)
+ */
+function aggregateHourlyProfile(iterations) {
+  const length = Math.max(0, ...iterations.map(iter => iter.hourly_profile.arrivals.length));
+  const mean = key => {
+    const sums = new Array(length).fill(0);
+    for (const iter of iterations) iter.hourly_profile[key].forEach((count, i) => { sums[i] += count; });
+    return sums.map(sum => Number((sum / iterations.length).toFixed(4)));
+  };
+  return { arrivals_mean: mean('arrivals'), starts_mean: mean('starts'), rejections_mean: mean('rejections') };
+}
+
+/**
+ * Aggregate a keyed rejection breakdown (rejections_by_type or rejections_by_unit).
+ * 
+ * DES only creates an entry once a key sees its first rejection, so an iteration
+ * with no entry for a key contributes zeros rather than being skipped.
+ * 
+ * @param {Array<Object>} iterations - Array of DES result objects
+ * @param {string} field - Top-level breakdown field name
+ * @returns {Object} - breakdown[key][reason] = aggregated statistics
+ */
+function aggregateRejectionBreakdown(iterations, field) {
+  const keys = new Set();
+  for (const iter of iterations) {
+    if (iter[field]) Object.keys(iter[field]).forEach(k => keys.add(k));
+  }
+  
+  // Fixed reasons plus any additional crew roles (scenario.mos_roles) seen in the iterations
+  const reasons = new Set(['aircraft', 'control_station', 'pilot', 'so', 'intel', 'payload']);
+  for (const iter of iterations) {
+    Object.values(iter[field] || {}).forEach(counts => Object.keys(counts).forEach(r => reasons.add(r)));
+  }
+
+  const result = {};
+  for (const key of keys) {
+    result[key] = {};
+    for (const reason of reasons) {
+      const values = iterations.map(iter => iter[field]?.[key]?.[reason] || 0);
+      result[key][reason] = aggregateStatistics(values);
+    }
+  }
+  
+  return result;
+}
+
 /**
  * Step algorithm: Calculate round-robin value for a simulated setting.
  * 
@@ -163,12 +375,13 @@ function calculateStepValue(iterationIndex, defaultValue, min, max, step) {
  * 
  * @param {number} alpha - Shape parameter α > 0
  * @param {number} beta - Shape parameter β > 0
+ * @param {Function} rng - Uniform [0, 1) generator (default: Math.random)
  * @returns {number} - Random sample from Beta(α, β) distribution [0, 1]
  */
-function sampleBeta(alpha, beta) {
+function sampleBeta(alpha, beta, rng = Math.random) {
   // Generate two independent Gamma samples
-  const x = sampleGamma(alpha, 1);
-  const y = sampleGamma(beta, 1);
+  const x = sampleGamma(alpha, 1, rng);
+  const y = sampleGamma(beta, 1, rng);
   
   // Beta = X / (X + Y)
   const betaSample = x / (x + y);
@@ -176,7 +389,7 @@ function sampleBeta(alpha, beta) {
   // Handle edge cases (shouldn't happen with proper parameters, but safety check)
   if (!isFinite(betaSample) || betaSample < 0 || betaSample > 1) {
     // Fallback: return uniform random if something goes wrong
-    return Math.random();
+    return rng();
   }
   
   return betaSample;
@@ -190,9 +403,10 @@ function sampleBeta(alpha, beta) {
  * 
  * @param {number} shape - Shape parameter α > 0
  * @param {number} scale - Scale parameter β > 0
+ * @param {Function} rng - Uniform [0, 1) generator (default: Math.random)
  * @returns {number} - Random sample from Gamma(α, β) distribution
  */
-function sampleGamma(shape, scale) {
+function sampleGamma(shape, scale, rng = Math.random) {
   if (shape <= 0 || scale <= 0) {
     throw new Error(`Gamma parameters must be positive: shape=${shape}, scale=${scale}`);
   }
@@ -200,8 +414,8 @@ function sampleGamma(shape, scale) {
   // Handle α < 1 case
   if (shape < 1) {
     // Gamma(α, β) = Gamma(α + 1, β) * U^(1/α)
-    const u = Math.random();
-    return sampleGamma(shape + 1, scale) * Math.pow(u, 1 / shape);
+    const u = rng();
+    return sampleGamma(shape + 1, scale, rng) * Math.pow(u, 1 / shape);
   }
   
   // Marsaglia and Tsang's method for α >= 1
@@ -213,12 +427,12 @@ function sampleGamma(shape, scale) {
     
     // Generate normal random variable
     do {
-      x = sampleNormal();
+      x = sampleNormal(rng);
       v = 1 + c * x;
     } while (v <= 0);
     
     v = v * v * v;
-    const u = Math.random();
+    const u = rng();
     
     // Acceptance condition
     if (u < 1 - 0.0331 * (x * x) * (x * x)) {
@@ -234,12 +448,13 @@ function sampleGamma(shape, scale) {
 /**
  * Sample from a standard normal distribution using Box-Muller transform.
  * 
+ * @param {Function} rng - Uniform [0, 1) generator (default: Math.random)
  * @returns {number} - Random sample from N(0, 1)
  */
-function sampleNormal() {
+function sampleNormal(rng = Math.random) {
   // Box-Muller transform
-  const u1 = Math.random();
-  const u2 = Math.random();
+  const u1 = rng();
+  const u2 = rng();
   const z = Math.sqrt(-2 * Math.log(u1)) * Math.cos(2 * Math.PI * u2);
   return z;
 }
@@ -264,9 +479,10 @@ function sampleNormal() {
  * @param {number} min - Minimum value (optimistic, a)
  * @param {number} max - Maximum value (pessimistic, b)
  * @param {number} step - Step size (for discretization)
+ * @param {Function} rng - Uniform [0, 1) generator (default: Math.random)
  * @returns {number} - The value for this iteration
  */
-function calculatePERTValue(iterationIndex, defaultValue, min, max, step) {
+function calculatePERTValue(iterationIndex, defaultValue, min, max, step, rng = Math.random) {
   // Validate inputs
   if (min >= max) {
     console.warn(`PERT: min (${min}) >= max (${max}), returning default value`);
@@ -291,9 +507,8 @@ function calculatePERTValue(iterationIndex, defaultValue, min, max, step) {
   const beta = 1 + lambda * (max - mean) / range;
   
   // Sample from Beta distribution
-  // Note: We use iterationIndex to seed randomness, but since each iteration
-  // runs in parallel, we rely on Math.random() which should be sufficiently random
-  const u = sampleBeta(alpha, beta);
+  // Seeded runs pass a per-iteration generator so the value depends only on the seed and iterationIndex
+  const u = sampleBeta(alpha, beta, rng);
   
   // Transform to variable domain: X = a + U(b - a)
   let value = min + u * range;
@@ -479,9 +694,10 @@ function mapPathToScenario(frontendPath) {
  * @param {number} min - Minimum value
  * @param {number} max - Maximum value
  * @param {number} step - Step size
+ * @param {Function} rng - Uniform [0, 1) generator for PERT (default: Math.random)
  * @returns {number} - The value for this iteration
  */
-function calculateSimulatedValue(algorithm, iterationIndex, defaultValue, min, max, step) {
+function calculateSimulatedValue(algorithm, iterationIndex, defaultValue, min, max, step, rng = Math.random) {
   // Normalize algorithm name (case-insensitive)
   const algo = (algorithm || 'PERT').toLowerCase();
   
@@ -489,7 +705,7 @@ function calculateSimulatedValue(algorithm, iterationIndex, defaultValue, min, m
     case 'step':
       return calculateStepValue(iterationIndex, defaultValue, min, max, step);
     case 'pert':
-      return calculatePERTValue(iterationIndex, defaultValue, min, max, step);
+      return calculatePERTValue(iterationIndex, defaultValue, min, max, step, rng);
     default:
       console.warn(`Unknown algorithm "${algorithm}", defaulting to Step`);
       return calculateStepValue(iterationIndex, defaultValue, min, max, step);
@@ -504,9 +720,10 @@ function calculateSimulatedValue(algorithm, iterationIndex, defaultValue, min, m
  * @param {Array} simulateSettings - Array of { path, defaultValue, min, max, step }
  * @param {number} iterationIndex - Zero-based iteration index
  * @param {string} algorithm - Algorithm to use for value calculation ('Step' or 'PERT')
+ * @param {Function} rng - Uniform [0, 1) generator for PERT (default: Math.random)
  * @returns {Object} - Object with { scenario, overrides } containing modified scenario and overrides
  */
-function applySimulateSettings(scenario, baseOverrides, simulateSettings, iterationIndex, algorithm = 'PERT') {
+function applySimulateSettings(scenario, baseOverrides, simulateSettings, iterationIndex, algorithm = 'PERT', rng = Math.random) {
   if (!simulateSettings || simulateSettings.length === 0) {
     return { scenario, overrides: baseOverrides };
   }
@@ -537,7 +754,7 @@ function applySimulateSettings(scenario, baseOverrides, simulateSettings, iterat
     const { path, defaultValue, min, max, step } = setting;
     
     // Calculate the value for this iteration using the selected algorithm
-    const value = calculateSimulatedValue(algorithm, iterationIndex, defaultValue, min, max, step);
+    const value = calculateSimulatedValue(algorithm, iterationIndex, defaultValue, min, max, step, rng);
     
     // Check if this is an override path
     const overrideMapping = mapPathToOverrides(path);
@@ -623,9 +840,11 @@ function applySimulateSettings(scenario, baseOverrides, simulateSettings, iterat
  * @param {Object} scenario - Scenario configuration
  * @param {Object} settings - Simulation settings
  * @param {number} maxRetries - Maximum number of retries (default: 2)
- * @returns {Promise<Object>} Simulation result
+ * @param {number|null} iterationTimeoutMs - Optional per-iteration timeout; the worker is terminated shortly after it
+ * @returns {Promise<Object>} { result, wallMs }: simulation result and the DES run's wall time in ms
  */
-async function runSingleWithRetry(workerPath, scenario, settings, maxRetries = 2) {
+async function runSingleWithRetry(workerPath, scenario, settings, maxRetries = 2, iterationTimeoutMs = null) {
+  const hardTimeoutMs = iterationTimeoutMs ? iterationTimeoutMs + WORKER_TIMEOUT_GRACE_MS : WORKER_TIMEOUT_MS;
   let lastError;
   
   for (let attempt = 0; attempt <= maxRetries; attempt++) {
@@ -637,15 +856,26 @@ async function runSingleWithRetry(workerPath, scenario, settings, maxRetries = 2
         
         const timeout = setTimeout(() => {
           worker.terminate();
-          reject(new Error('Worker timeout after 60 seconds'));
-        }, 60000); // 60 second timeout per simulation
+          const timeoutError = new Error(`Worker timeout after ${hardTimeoutMs / 1000} seconds`);
+          timeoutError.timedOut = iterationTimeoutMs !== null;
+          reject(timeoutError);
+        }, hardTimeoutMs);
         
         worker.on('message', (message) => {
           clearTimeout(timeout);
           if (message.success) {
-            resolve(message.result);
+            resolve({ result: message.result, wallMs: message.wallMs });
           } else {
-            reject(new Error(message.error.message || 'Worker error'));
+            const workerError = new Error(message.error.message || 'Worker error');
+            if (isLimitError(message.error)) {
+              Object.assign(workerError, {
+                code: message.error.code,
+                limit: message.error.limit,
+                max: message.error.max,
+                counts: message.error.counts
+              });
+            }
+            reject(workerError);
           }
           worker.terminate();
         });
@@ -665,6 +895,8 @@ async function runSingleWithRetry(workerPath, scenario, settings, maxRetries = 2
       });
     } catch (error) {
       lastError = error;
+      // A tripped limit is a property of the inputs, so retrying would trip it again; so would a timeout
+      if (isLimitError(error) || error.timedOut) break;
       if (attempt < maxRetries) {
         // Wait a bit before retrying (exponential backoff)
         await new Promise(resolve => setTimeout(resolve, 100 * Math.pow(2, attempt)));
@@ -676,6 +908,92 @@ async function runSingleWithRetry(workerPath, scenario, settings, maxRetries = 2
   throw lastError;
 }
 
+/**
+ * Wrap an iteration's failure so callers can tell which iteration failed and why.
+ * 
+ * @param {Error} error - Error from the worker
+ * @param {number} iterationIndex - Zero-based iteration index
+ * @param {number|null} iterationTimeoutMs - Optional per-iteration timeout, to recognise timed-out iterations
+ * @returns {Error} - Error with iteration, timed_out, plus any structured limit fields (code, limit, max, counts)
+ */
+function iterationError(error, iterationIndex, iterationTimeoutMs = null) {
+  // Iteration timeouts share one message (without the varying counters) so they group in iteration_errors
+  const timedOut = iterationTimeoutMs !== null &&
+    (error.timedOut === true || (isLimitError(error) && error.limit === 'maxWallMs' && error.max === iterationTimeoutMs));
+  const message = timedOut ? `iteration timed out after ${iterationTimeoutMs}ms` : error.message;
+  const wrapped = new Error(`Iteration ${iterationIndex} failed: ${message}`);
+  Object.assign(wrapped, {
+    iteration: iterationIndex,
+    timed_out: timedOut,
+    code: error.code,
+    limit: error.limit,
+    max: error.max,
+    counts: error.counts
+  });
+  return wrapped;
+}
+
+/**
+ * Build the scenario and worker settings for one iteration.
+ * 
+ * With a base seed, the DES run and the PERT draws each get their own sub-seed derived
+ * from the iteration index, so an iteration's inputs never depend on scheduling.
+ * 
+ * @param {Object} scenario - Scenario configuration
+ * @param {Object} settings - Simulation settings (state, overrides)
+ * @param {number} iterationIndex - Zero-based iteration index
+ * @param {Array} simulateSettings - Optional array of settings to vary across iterations
+ * @param {string} algorithm - Algorithm to use for value calculation ('Step' or 'PERT')
+ * @param {number|null} baseSeed - Optional Monte Carlo seed
+ * @param {Array<Object>|null} inputUncertainty - Optional compiled input uncertainty entries
+ * @param {Object|null} inputDesign - Optional Latin hypercube design for the input draws (see latinHypercube)
+ * @param {Object|null} demandBootstrap - Optional validated demand bootstrap (see bootstrap.js)
+ * @returns {Object} - { scenario, settings, inputs, bootstrapIndex } for this iteration (inputs: sampled values,
+ *                     or null; bootstrapIndex: the replayed library entry, or null)
+ */
+function prepareIteration(scenario, settings, iterationIndex, simulateSettings, algorithm, baseSeed, inputUncertainty = null, inputDesign = null, demandBootstrap = null) {
+  const iterationSeed = baseSeed === null ? null : deriveSeed(baseSeed, iterationIndex);
+  let iterationScenario = scenario;
+  let iterationSettings = iterationSeed === null ? settings : { ...settings, seed: iterationSeed };
+  
+  // Replay one demand realization from the library in place of the scenario's demand
+  let bootstrapIndex = null;
+  if (demandBootstrap) {
+    bootstrapIndex = pickDemandRealization(demandBootstrap, baseSeed, iterationIndex);
+    iterationScenario = { ...iterationScenario, demand: demandBootstrap.library[bootstrapIndex] };
+  }
+  
+  // Apply simulate settings to create modified scenario and overrides for this iteration
+  if (simulateSettings) {
+    const rng = iterationSeed === null ? Math.random : makeRng(deriveSeed(iterationSeed, 1));
+    const { scenario: modScenario, overrides: modOverrides } = applySimulateSettings(
+      iterationScenario, 
+      settings.overrides || null, 
+      simulateSettings, 
+      iterationIndex, 
+      algorithm,
+      rng
+    );
+    iterationScenario = modScenario;
+    iterationSettings = {
+      ...iterationSettings,
+      overrides: modOverrides
+    };
+  }
+  
+  // Iteration-level input draws, written over any simulate settings for the same field
+  let inputs = null;
+  if (inputUncertainty) {
+    inputs = sampleInputs(inputUncertainty, iterationSeed, inputDesign, iterationIndex);
+    iterationSettings = {
+      ...iterationSettings,
+      overrides: applyInputs(inputUncertainty, iterationSettings.overrides || null, inputs)
+    };
+  }
+  
+  return { scenario: iterationScenario, settings: iterationSettings, inputs, bootstrapIndex };
+}
+
 /**
  * Run a batch of simulations using worker threads.
  * 
@@ -685,40 +1003,38 @@ async function runSingleWithRetry(workerPath, scenario, settings, maxRetries = 2
  * @param {number} batchStartIndex - Zero-based index of the first iteration in this batch
  * @param {Array} simulateSettings - Optional array of settings to vary across iterations
  * @param {string} algorithm - Algorithm to use for value calculation ('Step' or 'PERT')
- * @returns {Promise<Array>} Array of simulation results (guaranteed to have batchSize results)
+ * @param {number|null} baseSeed - Optional Monte Carlo seed (see prepareIteration)
+ * @param {Array<Object>|null} inputUncertainty - Optional compiled input uncertainty entries
+ * @param {number|null} iterationTimeoutMs - Optional per-iteration timeout (see runMonteCarlo)
+ * @param {Object|null} inputDesign - Optional Latin hypercube design for the input draws
+ * @param {Object|null} demandBootstrap - Optional validated demand bootstrap (see prepareIteration)
+ * @returns {Promise<Object>} - { results, failures, timings }: results in iteration order (failed iterations omitted,
+ *                              each carrying sampled_inputs with input uncertainty and bootstrap_index with a
+ *                              demand bootstrap), failures as
+ *                              { iteration, error } and timings as { iteration, wall_ms } for the successes
  */
-async function runBatch(scenario, settings, batchSize, batchStartIndex = 0, simulateSettings = null, algorithm = 'PERT') {
+async function runBatch(scenario, settings, batchSize, batchStartIndex = 0, simulateSettings = null, algorithm = 'PERT', baseSeed = null, inputUncertainty = null, iterationTimeoutMs = null, inputDesign = null, demandBootstrap = null) {
   const workerPath = path.join(__dirname, 'worker.js');
-  const results = [];
+  // Results are stored by position in the batch, not completion order
+  const results = new Array(batchSize);
+  const failures = new Array(batchSize);
+  const timings = new Array(batchSize);
   const retries = [];
   
   // Create a worker for each simulation in the batch
   const promises = [];
+  // Sampled inputs travel with the result so outputs can be regressed against them, as does the replayed demand
+  const withInputs = (result, iteration) => ({
+    ...result,
+    ...(iteration.inputs ? { sampled_inputs: iteration.inputs } : {}),
+    ...(iteration.bootstrapIndex !== null ? { bootstrap_index: iteration.bootstrapIndex } : {})
+  });
   for (let i = 0; i < batchSize; i++) {
-    const iterationIndex = batchStartIndex + i;
-    
-    // Apply simulate settings to create modified scenario and overrides for this iteration
-    let modifiedScenario = scenario;
-    let modifiedSettings = settings;
-    
-    if (simulateSettings) {
-      const { scenario: modScenario, overrides: modOverrides } = applySimulateSettings(
-        scenario, 
-        settings.overrides || null, 
-        simulateSettings, 
-        iterationIndex, 
-        algorithm
-      );
-      modifiedScenario = modScenario;
-      modifiedSettings = {
-        ...settings,
-        overrides: modOverrides
-      };
-    }
+    const iteration = prepareIteration(scenario, settings, batchStartIndex + i, simulateSettings, algorithm, baseSeed, inputUncertainty, inputDesign, demandBootstrap);
     
     promises.push(
-      runSingleWithRetry(workerPath, modifiedScenario, modifiedSettings)
-        .then(result => ({ success: true, result, index: i }))
+      runSingleWithRetry(workerPath, iteration.scenario, iteration.settings, 2, iterationTimeoutMs)
+        .then(({ result, wallMs }) => ({ success: true, result: withInputs(result, iteration), wallMs, index: i }))
         .catch(error => ({ success: false, error, index: i }))
     );
   }
@@ -729,51 +1045,113 @@ async function runBatch(scenario, settings, batchSize, batchStartIndex = 0, simu
   // Separate successes and failures
   for (const outcome of outcomes) {
     if (outcome.success) {
-      results.push(outcome.result);
+      results[outcome.index] = outcome.result;
+      timings[outcome.index] = outcome.wallMs;
+    } else if (isLimitError(outcome.error) || outcome.error.timedOut) {
+      // Runaway iteration (settings.limits or iterationTimeoutMs): retrying would trip the same limit
+      failures[outcome.index] = iterationError(outcome.error, batchStartIndex + outcome.index, iterationTimeoutMs);
     } else {
       retries.push(outcome);
     }
   }
   
-  // Retry failed simulations until we have enough results
-  while (results.length < batchSize && retries.length > 0) {
-    const retry = retries.shift();
-    const retryIterationIndex = batchStartIndex + retry.index;
-    
-    // Apply simulate settings for retry as well
-    let retryScenario = scenario;
-    let retrySettings = settings;
-    
-    if (simulateSettings) {
-      const { scenario: modScenario, overrides: modOverrides } = applySimulateSettings(
-        scenario, 
-        settings.overrides || null, 
-        simulateSettings, 
-        retryIterationIndex, 
-        algorithm
-      );
-      retryScenario = modScenario;
-      retrySettings = {
-        ...settings,
-        overrides: modOverrides
-      };
-    }
+  // Retry failed simulations with the same inputs (and seed) as the first attempt
+  for (const retry of retries) {
+    const iteration = prepareIteration(scenario, settings, batchStartIndex + retry.index, simulateSettings, algorithm, baseSeed, inputUncertainty, inputDesign, demandBootstrap);
     
     try {
-      const result = await runSingleWithRetry(workerPath, retryScenario, retrySettings, 3); // Extra retries for failed ones
-      results.push(result);
+      const { result, wallMs } = await runSingleWithRetry(workerPath, iteration.scenario, iteration.settings, 3, iterationTimeoutMs); // Extra retries for failed ones
+      results[retry.index] = withInputs(result, iteration);
+      timings[retry.index] = wallMs;
     } catch (error) {
       console.error(`Failed to retry simulation after multiple attempts: ${error.message}`);
-      // If retry still fails, we'll have fewer results - log warning
-      console.warn(`Warning: Will have ${results.length} results instead of ${batchSize} for this batch`);
+      failures[retry.index] = iterationError(error, batchStartIndex + retry.index, iterationTimeoutMs);
     }
   }
   
-  if (results.length < batchSize) {
-    console.warn(`Warning: Only got ${results.length} results out of ${batchSize} requested for this batch`);
+  const completed = results.filter(r => r !== undefined);
+  if (completed.length < batchSize) {
+    console.warn(`Warning: Only got ${completed.length} results out of ${batchSize} requested for this batch`);
   }
   
-  return results;
+  return {
+    results: completed,
+    failures: failures.filter(f => f !== undefined).map(error => ({ iteration: error.iteration, error })),
+    timings: timings
+      .map((wallMs, i) => (wallMs === undefined ? undefined : { iteration: batchStartIndex + i, wall_ms: wallMs }))
+      .filter(t => t !== undefined)
+  };
+}
+
+/**
+ * Collapse iteration failures into one entry per distinct message.
+ * 
+ * @param {Array<Object>} failures - { iteration, error } in iteration order
+ * @returns {Array<Object>} - { message, count, first_iteration }, most frequent first
+ */
+function summarizeIterationErrors(failures) {
+  const byMessage = new Map();
+  for (const { iteration, error } of failures) {
+    // Strip the "Iteration <i> failed: " prefix so identical causes group together
+    const message = error.message.replace(/^Iteration \d+ failed: /, '');
+    const entry = byMessage.get(message);
+    if (entry) {
+      entry.count++;
+    } else {
+      byMessage.set(message, { message, count: 1, first_iteration: iteration });
+    }
+  }
+  return [...byMessage.values()].sort((a, b) => b.count - a.count || a.first_iteration - b.first_iteration);
+}
+
+/**
+ * Pick full iteration results to keep, uniformly at random over the successful iterations.
+ * 
+ * Reservoir sampling in iteration order, so the pick depends only on the seed, not on worker timing.
+ * 
+ * @param {Array<Object>} results - Successful DES results in iteration order
+ * @param {Array<Object>} timings - { iteration, wall_ms } aligned with results (gives each result's index)
+ * @param {number} count - Iterations to keep
+ * @param {number|null} seed - Monte Carlo seed, or null to pick with Math.random
+ * @returns {Array<Object>} - { iteration, seed, results } in iteration order; seed is the iteration's sub-seed (or null)
+ */
+function sampleIterations(results, timings, count, seed) {
+  const rng = seed === null ? Math.random : makeRng(deriveStreamSeed(seed, SAMPLE_ITERATIONS_STREAM));
+  const reservoir = [];
+  results.forEach((_, k) => {
+    if (reservoir.length < count) {
+      reservoir.push(k);
+    } else {
+      const j = Math.floor(rng() * (k + 1));
+      if (j < count) reservoir[j] = k;
+    }
+  });
+  return reservoir.sort((a, b) => a - b).map(k => ({
+    iteration: timings[k].iteration,
+    seed: seed === null ? null : deriveSeed(seed, timings[k].iteration),
+    results: results[k]
+  }));
+}
+
+/**
+ * Summarize per-iteration DES wall times and flag stragglers.
+ * 
+ * @param {Array<Object>} timings - { iteration, wall_ms } for successful iterations
+ * @param {Array<Object>} failures - { iteration, error } for failed iterations
+ * @returns {Object} - { iteration_wall_ms, stragglers, timed_out }: stragglers took more than
+ *                     STRAGGLER_FACTOR × the median, slowest first
+ */
+function summarizeTimings(timings, failures) {
+  const rounded = timings.map(t => ({ iteration: t.iteration, wall_ms: Number(t.wall_ms.toFixed(2)) }));
+  const wallMs = rounded.map(t => t.wall_ms);
+  const median = calculatePercentiles([...wallMs].sort((a, b) => a - b), [50]).p50;
+  return {
+    iteration_wall_ms: aggregateStatistics(wallMs),
+    stragglers: rounded
+      .filter(t => median > 0 && t.wall_ms > STRAGGLER_FACTOR * median)
+      .sort((a, b) => b.wall_ms - a.wall_ms),
+    timed_out: failures.filter(f => f.error.timed_out).length
+  };
 }
 
 /**
@@ -790,21 +1168,130 @@ async function runBatch(scenario, settings, batchSize, batchStartIndex = 0, simu
  *   - iterations: Number of Monte Carlo iterations (default: 1000)
  *   - algorithm: Algorithm to use for value calculation ('Step' or 'PERT', default: 'PERT')
  *   - keepIterations: Whether to store individual iteration results (default: false)
+ *   - extremeMetrics: Optional array of threshold-style metric expressions (default missions.completed and
+ *             missions.rejected); extremes reports each one's min and max with the iteration and sub-seed behind it
+ *   - keepSampleIterations: Optional number of iterations kept whole (timeline included) in sample_iterations,
+ *             picked uniformly at random (from the seed when there is one) regardless of keepIterations
  *   - state: State snapshot (required, same as DES)
  *   - overrides: Resource overrides (optional, same as DES)
  *   - maxConcurrent: Maximum concurrent workers (default: CPU_COUNT - 1)
  *   - logLevel: DES log level for workers ('silent', 'error', 'warn', 'info', 'verbose', 'debug')
  *                Defaults to 'silent' to prevent log clutter during Monte Carlo runs
  *   - simulateSettings: Optional array of settings to vary across iterations
+ *   - seed: Optional integer seed; iteration i runs with a sub-seed derived from (seed, i), so
+ *           results are identical for the same seed regardless of maxConcurrent
+ *   - thresholds: Optional array of { metric, op, value } conditions; each iteration is checked
+ *                 and threshold_probabilities reports the fraction that satisfied each one
+ *   - inputUncertainty: Optional array of { path, dist } (path: 'overrides.units.<unit>.<field>'); each
+ *                 iteration draws every dist once (with its own seed) and runs with the drawn override values
+ *   - inputSampling: 'random' (default) or 'lhs'; with 'lhs' the inputUncertainty draws form a Latin hypercube
+ *                 (each input's range cut into one stratum per iteration, every stratum used exactly once)
+ *   - inputOutputAnalysis: Optional array of { input, output } (input: an inputUncertainty path, output: a
+ *                 threshold-style metric); input_output_analysis reports Pearson and Spearman correlation and
+ *                 the least-squares slope of output on input over the successful iterations
+ *   - timelineRejectionSampling: Optional fraction of rejection timeline entries each iteration keeps
+ *   - collectTimeline: When false, iterations build no timeline at all (faster on mission-heavy scenarios);
+ *             percentile_timelines is then left out and sample_iterations carry no timeline (default: true)
+ *   - resourceHoldModel: Optional DES resource hold model for every iteration ('legacy' or 'per_resource')
+ *   - peakWindowHours: Optional sliding window for each iteration's peak_window (default: 24)
+ *   - rejectionDensityBucketHours: Optional bucket length for each iteration's rejection_density (default: 1);
+ *             rejection_density reports the mean rejections per bucket and reason
+ *   - hourlyProfile: When true, every iteration counts mission arrivals, starts and final rejections per hour
+ *             and hourly_profile reports their means hour by hour (demand versus capacity over the run)
+ *   - limits: Optional DES watchdog limits { maxEvents, maxTimelineEvents, maxWallMs } applied to every
+ *             iteration; an iteration that trips one fails like any other iteration error
+ *   - continueOnIterationError: When true, failed iterations are recorded in iteration_errors and statistics
+ *             are computed over the successes (default: false, the first failure fails the run naming its iteration)
+ *   - maxIterationErrorFraction: With continueOnIterationError, the run still fails once more than this
+ *             fraction of iterations has failed (default: 0.1)
+ *   - iterationTimeoutMs: Optional per-iteration wall-time budget; a slower iteration is aborted (cooperatively
+ *             through the DES maxWallMs limit, with a hard worker stop shortly after) and recorded in
+ *             iteration_errors without failing the run, though it still counts toward maxIterationErrorFraction
+ *   - checkpoint: Optional { path, everyIterations }; finished iterations are written to path (JSON) once at least
+ *             everyIterations more have finished since the last write, and when the run completes
+ *   - resumeFrom: Optional checkpoint path; its iterations are reused and only the rest are run. The checkpoint
+ *             must come from a run with the same scenario, state and iteration options (seed, iterations, ...)
+ *   - onBusy: 'queue' (default) or 'reject'; what to do when every run slot is taken (see slots.js): wait for one,
+ *             or fail at once with an error whose code is 'ENGINE_BUSY'
+ *   - onProgress: Optional callback receiving { type: 'queued', position } while the run waits for a slot and
+ *             { type: 'iterations', finished, failed, total } after each batch
+ *   - extractors: Optional { name: metric } where metric is a function(result) returning a number or a
+ *             threshold-style metric expression; custom[name] summarizes its finite values over the iterations
+ *   - byTypeMissingAsZero: When true (default), every mission type declared in the scenario is reported and an
+ *             iteration with no by_type entry for a type counts as zero for it; false skips such iterations
+ *   - demandBootstrap: Optional { library, seedLinked } (see bootstrap.js); each iteration replaces scenario.demand
+ *             with one library entry picked with replacement (from the iteration seed, or from a stream of the
+ *             Monte Carlo seed when seedLinked is false) and records it as bootstrap_index; demand_bootstrap counts how often each entry was replayed
+ *   - maxOutputBytes: Cap on the aggregated result's estimated JSON size (default 256 MiB, null for none); over
+ *             it, iterations, sample_iterations and percentile_timelines are dropped in that order, truncated
+ *             lists them and meta.output_size has the estimated and actual sizes
+ *   - echoConfig: When true (default), resolved_config reports the resolved run options, the normalized scenario
+ *             and the settings each iteration ran with; iterations themselves never carry one
+ *   - priorityWeights: Optional { priority: weight } for each iteration's weighted_completion_score (default
+ *             1 / priority); weighted_completion_score summarizes the score over the iterations
+ *   - With scenario.quality_penalties, completion_quality summarizes each iteration's mean completion quality and
+ *             degraded completions, overall and per mission type
+ *   - stratifyBy: Optional iteration attribute (bootstrap_index, an inputUncertainty path or a threshold-style
+ *             metric); strata reports missions, rejections and by_type statistics per value of it, or per
+ *             quantile bucket when it takes more than stratifyBuckets values (default 4)
  * @returns {Object} - Aggregated Monte Carlo results with percentiles
  */
 async function runMonteCarlo(scenario, options = {}) {
+  // Resolve unit-tagged values once so simulate settings and aggregation see hours
+  scenario = normalizeScenarioUnits(scenario);
+  validateScenario(scenario);
   const iterations = options.iterations || 1000;
   const algorithm = options.algorithm || 'PERT';
   const keepIterations = options.keepIterations || false;
+  const keepSampleIterations = options.keepSampleIterations ?? 0;
+  if (!Number.isInteger(keepSampleIterations) || keepSampleIterations < 0) {
+    throw new Error(`keepSampleIterations must be a non-negative integer, got ${keepSampleIterations}`);
+  }
   const maxConcurrent = options.maxConcurrent || WORKER_POOL_SIZE;
   const simulateSettings = options.simulateSettings || null;
+  if (options.seed !== undefined && options.seed !== null && !isValidSeed(options.seed)) {
+    throw new Error(`seed must be an integer, got ${options.seed}`);
+  }
+  const seed = isValidSeed(options.seed) ? options.seed : null;
+  validateLimits(options.limits);
+  const continueOnIterationError = options.continueOnIterationError === true;
+  const maxIterationErrorFraction = options.maxIterationErrorFraction ?? 0.1;
+  if (typeof maxIterationErrorFraction !== 'number' || maxIterationErrorFraction < 0 || maxIterationErrorFraction > 1) {
+    throw new Error(`maxIterationErrorFraction must be a number between 0 and 1, got ${maxIterationErrorFraction}`);
+  }
+  const maxFailures = Math.floor(maxIterationErrorFraction * iterations);
+  const iterationTimeoutMs = options.iterationTimeoutMs ?? null;
+  if (iterationTimeoutMs !== null && !(typeof iterationTimeoutMs === 'number' && iterationTimeoutMs > 0)) {
+    throw new Error(`iterationTimeoutMs must be a positive number, got ${iterationTimeoutMs}`);
+  }
+  const collectTimeline = options.collectTimeline ?? true;
+  if (typeof collectTimeline !== 'boolean') {
+    throw new Error(`collectTimeline must be a boolean, got ${collectTimeline}`);
+  }
+  const maxOutputBytes = resolveMaxOutputBytes(options.maxOutputBytes, 'maxOutputBytes');
+  const priorityWeights = resolvePriorityWeights(options.priorityWeights, 'priorityWeights');
+  const echoConfig = options.echoConfig ?? true;
+  if (typeof echoConfig !== 'boolean') {
+    throw new Error(`echoConfig must be a boolean, got ${echoConfig}`);
+  }
+  const byTypeMissingAsZero = options.byTypeMissingAsZero !== false;
+  const onBusy = validateOnBusy(options.onBusy);
+  const onProgress = typeof options.onProgress === 'function' ? options.onProgress : null;
+  // Validate thresholds up front so a bad metric path fails before any iteration runs
+  const thresholds = options.thresholds ? compileThresholds(options.thresholds) : null;
+  const inputUncertainty = options.inputUncertainty ? compileInputUncertainty(options.inputUncertainty) : null;
+  const inputSampling = validateInputSampling(options.inputSampling);
+  const inputDesign = inputUncertainty && inputSampling === 'lhs' ? latinHypercube(inputUncertainty, iterations, seed) : null;
+  const ioAnalysis = options.inputOutputAnalysis ? compileInputOutputAnalysis(options.inputOutputAnalysis, inputUncertainty) : null;
+  const extractors = options.extractors ? compileExtractors(options.extractors) : null;
+  const extremeMetrics = compileExtremeMetrics(options.extremeMetrics ?? undefined);
+  const demandBootstrap = options.demandBootstrap ? validateDemandBootstrap(options.demandBootstrap, scenario) : null;
+  const strata = options.stratifyBy !== undefined && options.stratifyBy !== null
+    ? compileStratifyBy(options.stratifyBy, options.stratifyBuckets ?? DEFAULT_STRATIFY_BUCKETS, inputUncertainty, demandBootstrap)
+    : null;
   const individualResults = [];
+  const failures = [];
+  const timings = [];
   
   // Prepare settings object for workers
   // Default to 'silent' log level to prevent thousands of DES logs from cluttering console
@@ -812,7 +1299,21 @@ async function runMonteCarlo(scenario, options = {}) {
   const settings = {
     state: options.state,
     overrides: options.overrides,
-    logLevel: options.logLevel || 'silent'
+    logLevel: options.logLevel || 'silent',
+    limits: iterationTimeoutMs !== null
+      ? { ...options.limits, maxWallMs: Math.min(options.limits?.maxWallMs ?? Infinity, iterationTimeoutMs) }
+      : options.limits || undefined,
+    resourceHoldModel: options.resourceHoldModel || undefined,
+    timelineRejectionSampling: options.timelineRejectionSampling ?? undefined,
+    collectTimeline,
+    peakWindowHours: options.peakWindowHours ?? undefined,
+    rejectionDensityBucketHours: options.rejectionDensityBucketHours ?? undefined,
+    hourlyProfile: options.hourlyProfile === true,
+    priorityWeights: priorityWeights ?? undefined,
+    // The run echoes its configuration once (below), not per iteration
+    echoConfig: false,
+    // Iterations come back by structured clone, not as JSON; only the aggregate is capped
+    maxOutputBytes: null
   };
   
   // Log simulate settings if present
@@ -826,38 +1327,81 @@ async function runMonteCarlo(scenario, options = {}) {
   // Run simulations in batches to control concurrency
   // Each batch runs maxConcurrent simulations in parallel
   const batchSize = Math.min(maxConcurrent, iterations);
-  const numBatches = Math.ceil(iterations / batchSize);
   
+  // Iterations a checkpoint already holds are not run again
+  const checkpoint = options.checkpoint ? validateCheckpoint(options.checkpoint) : null;
+  const fingerprint = checkpoint || options.resumeFrom ? runFingerprint(scenario, { ...options, iterations, seed }) : null;
+  let resumeIndex = 0;
+  if (options.resumeFrom) {
+    const saved = await readCheckpoint(options.resumeFrom, fingerprint);
+    individualResults.push(...saved.results);
+    failures.push(...saved.failures);
+    timings.push(...saved.timings);
+    resumeIndex = saved.next_index;
+    console.log(`Resuming from ${options.resumeFrom}: ${resumeIndex}/${iterations} iterations already done`);
+  }
+  let checkpointedAt = resumeIndex;
+  const numBatches = Math.ceil((iterations - resumeIndex) / batchSize);
+  
+  // Hold a run slot while iterations execute, so concurrent runs queue (or are rejected) instead of
+  // oversubscribing the CPUs
+  const releaseSlot = await acquireRunSlot(onBusy, onProgress ? position => onProgress({ type: 'queued', position }) : null);
   const startTime = Date.now();
   console.log(`Running ${iterations} Monte Carlo iterations using ${maxConcurrent} parallel workers (${numBatches} batches)...`);
   
-  for (let batchIndex = 0; batchIndex < numBatches; batchIndex++) {
-    const remainingIterations = iterations - individualResults.length;
-    const currentBatchSize = Math.min(batchSize, remainingIterations);
-    const batchStartIndex = individualResults.length;
-    
-    if (currentBatchSize <= 0) break;
-    
-    try {
-      // Run batch of simulations in parallel
-      const batchResults = await runBatch(scenario, settings, currentBatchSize, batchStartIndex, simulateSettings, algorithm);
-      individualResults.push(...batchResults);
+  try {
+    for (let batchIndex = 0; batchIndex < numBatches; batchIndex++) {
+      // Iteration indices are fixed by batch position so a failed iteration never shifts the seeds of later ones
+      const batchStartIndex = resumeIndex + batchIndex * batchSize;
+      const currentBatchSize = Math.min(batchSize, iterations - batchStartIndex);
       
-      // Progress logging with time estimates
-      const progress = ((individualResults.length / iterations) * 100).toFixed(1);
-      const elapsed = (Date.now() - startTime) / 1000;
-      const rate = individualResults.length / elapsed;
-      const remaining = (iterations - individualResults.length) / rate;
-      console.log(
-        `Progress: ${individualResults.length}/${iterations} iterations (${progress}%) | ` +
-        `Elapsed: ${elapsed.toFixed(1)}s | ` +
-        `Rate: ${rate.toFixed(1)} iter/s | ` +
-        `ETA: ${remaining.toFixed(1)}s`
-      );
-    } catch (error) {
-      console.error(`Error in batch ${batchIndex + 1}:`, error.message);
-      throw error;
+      if (currentBatchSize <= 0) break;
+      
+      try {
+        // Run batch of simulations in parallel
+        const batch = await runBatch(scenario, settings, currentBatchSize, batchStartIndex, simulateSettings, algorithm, seed, inputUncertainty, iterationTimeoutMs, inputDesign, demandBootstrap);
+        individualResults.push(...batch.results);
+        failures.push(...batch.failures);
+        timings.push(...batch.timings);
+        
+        // Timed-out stragglers are recorded, not fatal
+        const fatal = failures.find(f => !f.error.timed_out);
+        if (fatal && !continueOnIterationError) {
+          throw fatal.error;
+        }
+        if (failures.length > maxFailures) {
+          const error = new Error(
+            `${failures.length} of ${iterations} iterations failed, more than maxIterationErrorFraction ${maxIterationErrorFraction} allows ` +
+            `(first: ${failures[0].error.message})`
+          );
+          error.iteration_errors = summarizeIterationErrors(failures);
+          throw error;
+        }
+        
+        // Progress logging with time estimates
+        const finished = individualResults.length + failures.length;
+        const progress = ((finished / iterations) * 100).toFixed(1);
+        const elapsed = (Date.now() - startTime) / 1000;
+        const rate = (finished - resumeIndex) / elapsed;
+        const remaining = (iterations - finished) / rate;
+        console.log(
+          `Progress: ${finished}/${iterations} iterations (${progress}%)${failures.length ? ` [${failures.length} failed]` : ''} | ` +
+          `Elapsed: ${elapsed.toFixed(1)}s | ` +
+          `Rate: ${rate.toFixed(1)} iter/s | ` +
+          `ETA: ${remaining.toFixed(1)}s`
+        );
+        if (checkpoint && (finished - checkpointedAt >= checkpoint.everyIterations || finished === iterations)) {
+          await writeCheckpoint(checkpoint.path, { fingerprint, iterations, seed, next_index: finished, results: individualResults, failures, timings });
+          checkpointedAt = finished;
+        }
+        if (onProgress) onProgress({ type: 'iterations', finished, failed: failures.length, total: iterations });
+      } catch (error) {
+        console.error(`Error in batch ${batchIndex + 1}:`, error.message);
+        throw error;
+      }
     }
+  } finally {
+    releaseSlot();
   }
   
   const totalTime = (Date.now() - startTime) / 1000;
@@ -871,6 +1415,15 @@ async function runMonteCarlo(scenario, options = {}) {
   const aggregated = {
     iterations,
     horizon_hours: scenario.horizon_hours || 24,
+    seed,
+    
+    // Statistics below cover successful iterations only
+    iterations_succeeded: individualResults.length,
+    iterations_failed: failures.length,
+    iteration_errors: summarizeIterationErrors(failures),
+    
+    // Per-iteration DES wall time (not reproducible across runs, even with a seed)
+    meta: summarizeTimings(timings, failures),
     
     // Aggregate mission statistics
     missions: aggregateObject(individualResults, 'missions'),
@@ -881,10 +1434,196 @@ async function runMonteCarlo(scenario, options = {}) {
     // Aggregate utilization per unit (handled separately due to nested structure)
     utilization: {},
     
-    // Aggregate by mission type (handled separately due to nested structure)
-    by_type: {}
+    // Aggregate by mission type and by demand entry (handled separately due to nested structure)
+    by_type: byTypeMissingAsZero
+      ? aggregateMissionStats(individualResults, 'by_type', true, (scenario.mission_types || []).map(mt => mt.name))
+      : aggregateMissionStats(individualResults, 'by_type'),
+    by_demand_source: aggregateMissionStats(individualResults, 'by_demand_source'),
+    
+    // Aggregate rejection reasons per mission type and per unit
+    rejections_by_type: aggregateRejectionBreakdown(individualResults, 'rejections_by_type'),
+    rejections_by_unit: aggregateRejectionBreakdown(individualResults, 'rejections_by_unit'),
+    
+    // Missions cut off by the horizon per unit
+    in_progress_by_unit: aggregateObject(individualResults, 'in_progress_by_unit'),
+    
+    // Priority-weighted completion (iterations with no weighted requests are left out)
+    weighted_completion_score: aggregateStatistics(individualResults
+      .map(iter => iter.weighted_completion_score)
+      .filter(score => typeof score === 'number')),
+    
+    // Aggregate payload efficiency and mount counts per unit and payload type
+    payload_utilization: aggregateUnitPayloadMap(individualResults, 'payload_utilization'),
+    payload_missions: aggregateUnitPayloadMap(individualResults, 'payload_missions')
   };
   
+  // Demands of mission types no unit could fly; an iteration where a type was feasible counts as zero for it
+  const infeasibleRuns = individualResults.filter(iter => iter.infeasible_by_type);
+  if (infeasibleRuns.length > 0) {
+    aggregated.missions.infeasible = aggregateStatistics(individualResults.map(iter => iter.missions.infeasible || 0));
+    const types = new Set(infeasibleRuns.flatMap(iter => Object.keys(iter.infeasible_by_type)));
+    aggregated.infeasible_by_type = Object.fromEntries([...types].map(type => [type, {
+      iterations: infeasibleRuns.filter(iter => iter.infeasible_by_type[type]).length,
+      missions: aggregateStatistics(individualResults.map(iter => iter.infeasible_by_type?.[type]?.missions || 0))
+    }]));
+  }
+  
+  // Completion quality (scenario.quality_penalties): mean quality over iterations that completed something, and
+  // degraded completions; a type an iteration never completed counts zero degraded there
+  const rated = individualResults.filter(iter => iter.completion_quality);
+  if (rated.length > 0) {
+    const types = new Set(rated.flatMap(iter => Object.keys(iter.completion_quality.by_type)));
+    const meanStats = values => aggregateStatistics(values.filter(mean => typeof mean === 'number'));
+    aggregated.completion_quality = {
+      mean: meanStats(rated.map(iter => iter.completion_quality.mean)),
+      degraded: aggregateStatistics(rated.map(iter => iter.completion_quality.degraded)),
+      by_type: Object.fromEntries([...types].map(type => [type, {
+        mean: meanStats(rated.map(iter => iter.completion_quality.by_type[type]?.mean)),
+        degraded: aggregateStatistics(rated.map(iter => iter.completion_quality.by_type[type]?.degraded || 0))
+      }]))
+    };
+  }
+  
+  // Best sliding-window sortie count, overall and per unit
+  const windowed = individualResults.filter(iter => iter.peak_window);
+  if (windowed.length > 0) {
+    const units = new Set(windowed.flatMap(iter => Object.keys(iter.peak_window.by_unit || {})));
+    aggregated.peak_window = {
+      window_hours: windowed[0].peak_window.window_hours,
+      max_starts: aggregateStatistics(windowed.map(iter => iter.peak_window.max_starts)),
+      by_unit: Object.fromEntries([...units].map(unit => [
+        unit,
+        { max_starts: aggregateStatistics(windowed.map(iter => iter.peak_window.by_unit?.[unit]?.max_starts || 0)) }
+      ]))
+    };
+  }
+  
+  // Mean rejections per time bucket and reason (a reason an iteration never hit counts as zero there)
+  const dense = individualResults.filter(iter => iter.rejection_density);
+  if (dense.length > 0) {
+    aggregated.rejection_density = aggregateRejectionDensity(dense);
+  }
+
+  // Mean arrivals, starts and rejections per hour (options.hourlyProfile)
+  const profiled = individualResults.filter(iter => iter.hourly_profile);
+  if (profiled.length > 0) {
+    aggregated.hourly_profile = aggregateHourlyProfile(profiled);
+  }
+  
+  // Distribution of the sampled inputs, one entry per inputUncertainty path
+  if (inputUncertainty) {
+    aggregated.input_sampling = inputSampling;
+    aggregated.input_uncertainty = Object.fromEntries(inputUncertainty.map(c => [
+      c.key, aggregateStatistics(individualResults.map(iter => iter.sampled_inputs[c.key]))
+    ]));
+  }
+  
+  // How each requested output moves with an uncertain input
+  if (ioAnalysis) {
+    aggregated.input_output_analysis = inputOutputAnalysis(ioAnalysis, individualResults);
+  }
+  
+  // Caller-defined metrics (options.extractors), summarized like the built-in ones
+  if (extractors) {
+    aggregated.custom = Object.fromEntries(Object.entries(extractValues(extractors, individualResults))
+      .map(([name, values]) => [name, aggregateStatistics(values)]));
+  }
+  
+  // Which iterations gave the lowest and highest value of each extreme metric, with the sub-seed to re-run them
+  aggregated.extremes = findExtremes(extremeMetrics, individualResults, timings.map(t => ({
+    iteration: t.iteration,
+    seed: seed === null ? null : deriveSeed(seed, t.iteration)
+  })));
+  
+  // Standby reservations, only when the scenario defines them
+  if (individualResults.some(iter => iter.reservations)) {
+    aggregated.reservations = aggregateReservations(individualResults);
+  }
+  
+  // Scheduled aircraft transfers, only when the scenario defines them
+  const reallocated = individualResults.filter(iter => iter.reallocations);
+  if (reallocated.length > 0) {
+    aggregated.reallocations = Object.fromEntries(['count', 'aircraft_requested', 'aircraft_moved']
+      .map(field => [field, aggregateStatistics(reallocated.map(iter => iter.reallocations[field]))]));
+  }
+  
+  // Sampled Poisson rates per demand entry, only when some entry has rate_uncertainty
+  const realizedRates = individualResults.map(iter => iter.demand_stats?.realized_rates).filter(Boolean);
+  if (realizedRates.length > 0) {
+    const sources = [...new Set(realizedRates.flatMap(rates => Object.keys(rates)))];
+    aggregated.demand_rates = Object.fromEntries(sources.map(source =>
+      [source, aggregateStatistics(realizedRates.map(rates => rates[source]).filter(rate => rate !== undefined))]));
+  }
+
+  // Daily payload issue per limited type and day, only when the scenario sets payload_policy.daily_limits
+  const consumed = individualResults.filter(iter => iter.payload_consumption);
+  if (consumed.length > 0) {
+    aggregated.payload_consumption = Object.fromEntries(Object.entries(consumed[0].payload_consumption).map(([ptype, entry]) => [ptype, {
+      daily_limit: entry.daily_limit,
+      by_day: entry.by_day.map((_, day) => aggregateStatistics(consumed.map(iter => iter.payload_consumption[ptype].by_day[day]))),
+      total: aggregateStatistics(consumed.map(iter => iter.payload_consumption[ptype].by_day.reduce((a, b) => a + b, 0)))
+    }]));
+  }
+
+  // Which demand realization the iterations replayed, only with a demand bootstrap
+  if (demandBootstrap) {
+    aggregated.demand_bootstrap = summarizeDemandBootstrap(demandBootstrap, individualResults);
+  }
+
+  // Headline statistics per stratum of the stratifyBy attribute, each over its own iterations
+  if (strata) {
+    aggregated.stratified_by = strata.by;
+    aggregated.strata = Object.fromEntries(stratifyIterations(strata, individualResults).map(({ key, members, ...label }) => [key, {
+      ...label,
+      iterations: members.length,
+      missions: aggregateObject(members, 'missions'),
+      rejections: aggregateObject(members, 'rejections'),
+      by_type: byTypeMissingAsZero
+        ? aggregateMissionStats(members, 'by_type', true, (scenario.mission_types || []).map(mt => mt.name))
+        : aggregateMissionStats(members, 'by_type')
+    }]));
+  }
+
+  // Resubmitted rejected demand, only when some demand entry has a retry policy
+  const retried = individualResults.filter(iter => iter.retries !== undefined);
+  if (retried.length > 0) {
+    aggregated.retries = aggregateStatistics(retried.map(iter => iter.retries));
+  }
+
+  // Cross-unit airborne caps, only when some mission type has max_concurrent
+  const capped = individualResults.filter(iter => iter.concurrency_caps);
+  if (capped.length > 0) {
+    aggregated.concurrency_caps = Object.fromEntries(Object.entries(capped[0].concurrency_caps).map(([type, cap]) => [type, {
+      max_concurrent: cap.max_concurrent,
+      ...Object.fromEntries(['peak_concurrent', 'allocations', 'times_bound']
+        .map(key => [key, aggregateStatistics(capped.map(iter => iter.concurrency_caps[type][key]))]))
+    }]));
+  }
+
+  // Crew on hand, only with crew_policy.availability_rate (stochastic absences differ between iterations)
+  const absences = individualResults.filter(iter => iter.crew_availability);
+  if (absences.length > 0) {
+    const { mode, units } = absences[0].crew_availability;
+    aggregated.crew_availability = {
+      mode,
+      units: Object.fromEntries(Object.entries(units).map(([unit, roles]) => [unit, Object.fromEntries(
+        Object.entries(roles).map(([role, first]) => [role, {
+          rate: first.rate,
+          ...Object.fromEntries(['headcount', 'mean_available', 'realized_rate'].map(key => [key, aggregateStatistics(
+            absences.map(iter => iter.crew_availability.units[unit]?.[role]?.[key]).filter(v => typeof v === 'number')
+          )]))
+        }])
+      )]))
+    };
+  }
+
+  // Launch-window deferrals, only when some mission type has launch_windows
+  const deferring = individualResults.filter(iter => iter.launch_deferrals);
+  if (deferring.length > 0) {
+    aggregated.launch_deferrals = Object.fromEntries(['count', 'total_hours', 'max_hours']
+      .map(key => [key, aggregateStatistics(deferring.map(iter => iter.launch_deferrals[key]))]));
+  }
+  
   // Aggregate utilization per unit
   // Structure: utilization[unit][resourceType] = value
   const units = new Set();
@@ -896,7 +1635,25 @@ async function runMonteCarlo(scenario, options = {}) {
   
   for (const unit of units) {
     aggregated.utilization[unit] = {};
-    const resourceTypes = ['aircraft', 'pilot', 'so'];
+    const resourceTypes = [
+      'aircraft', 'pilot', 'so',
+      'aircraft_efficiency', 'pilot_efficiency', 'so_efficiency', 'intel_efficiency',
+      'aircraft_efficiency_of_available', 'pilot_efficiency_of_available',
+      'so_efficiency_of_available', 'intel_efficiency_of_available',
+      'pilot_turn_fraction', 'so_turn_fraction', 'intel_turn_fraction',
+      'control_station', 'control_station_efficiency',
+      'launch_recovery', 'launch_recovery_efficiency'
+    ];
+    // Additional crew roles report under their own names, listed in effective_crew
+    const extraRoles = new Set();
+    for (const iter of individualResults) {
+      Object.keys(iter.utilization?.[unit]?.effective_crew || {})
+        .filter(role => !['pilot', 'so', 'intel'].includes(role))
+        .forEach(role => extraRoles.add(role));
+    }
+    for (const role of extraRoles) {
+      resourceTypes.push(role, `${role}_efficiency`, `${role}_efficiency_of_available`, `${role}_turn_fraction`);
+    }
     
     for (const resourceType of resourceTypes) {
       const values = individualResults
@@ -909,34 +1666,15 @@ async function runMonteCarlo(scenario, options = {}) {
     }
   }
   
-  // Aggregate by mission type
-  // Structure: by_type[missionType][stat] = value
-  const missionTypes = new Set();
-  for (const iter of individualResults) {
-    if (iter.by_type) {
-      Object.keys(iter.by_type).forEach(mt => missionTypes.add(mt));
-    }
-  }
-  
-  for (const mt of missionTypes) {
-    aggregated.by_type[mt] = {};
-    const stats = ['requested', 'started', 'completed', 'rejected'];
-    
-    for (const stat of stats) {
-      const values = individualResults
-        .map(iter => iter.by_type?.[mt]?.[stat])
-        .filter(v => typeof v === 'number');
-      
-      if (values.length > 0) {
-        aggregated.by_type[mt][stat] = aggregateStatistics(values);
-      }
-    }
+  // Probability of meeting each threshold condition
+  if (thresholds) {
+    aggregated.threshold_probabilities = thresholdProbabilities(thresholds, individualResults);
   }
   
   // Aggregate percentile timelines
   // For each percentile, select the iteration whose missions.completed matches that percentile
-  // This gives us representative timelines for each percentile
-  if (individualResults.length > 0) {
+  // This gives us representative timelines for each percentile (skipped when iterations keep no timeline)
+  if (individualResults.length > 0 && collectTimeline) {
     const completedValues = individualResults.map(iter => iter.missions?.completed || 0);
     const sortedIndices = completedValues
       .map((val, idx) => ({ val, idx }))
@@ -1031,10 +1769,18 @@ async function runMonteCarlo(scenario, options = {}) {
   // Only include minimal data (missions.completed) to avoid JSON stringify errors
   if (keepIterations) {
     aggregated.iterations = individualResults.map(iter => ({
-      missions: iter.missions ? { completed: iter.missions.completed } : undefined
+      missions: iter.missions ? { completed: iter.missions.completed } : undefined,
+      ...(iter.sampled_inputs ? { inputs: iter.sampled_inputs } : {}),
+      ...(iter.demand_stats?.realized_rates ? { demand_rates: iter.demand_stats.realized_rates } : {}),
+      ...(iter.bootstrap_index !== undefined ? { bootstrap_index: iter.bootstrap_index } : {})
     }));
   }
   
+  // A few whole iterations for inspection (e.g. the worst day among them)
+  if (keepSampleIterations > 0) {
+    aggregated.sample_iterations = sampleIterations(individualResults, timings, keepSampleIterations, seed);
+  }
+  
   // Include initial resources from first iteration (same across all iterations)
   if (individualResults.length > 0 && individualResults[0].initial_resources) {
     aggregated.initial_resources = individualResults[0].initial_resources;
@@ -1058,8 +1804,133 @@ async function runMonteCarlo(scenario, options = {}) {
   if (scenario.personnel_availability) {
     aggregated.personnel_availability = scenario.personnel_availability;
   }
+
+  // The options the run resolved to, and the settings every iteration ran with (state left out for size)
+  if (echoConfig) {
+    const { state, logLevel, ...iterationSettings } = settings;
+    aggregated.resolved_config = {
+      scenario: { ...scenario, horizon_hours: scenario.horizon_hours || 24 },
+      options: {
+        iterations,
+        seed,
+        algorithm,
+        percentile_method: 'nearest_rank',
+        simulate_settings: simulateSettings ? simulateSettings.map(s => s.pathString) : null,
+        input_sampling: inputUncertainty ? inputSampling : null,
+        demand_bootstrap: demandBootstrap !== null,
+        stratify_by: strata ? strata.by : null,
+        by_type_missing_as_zero: byTypeMissingAsZero,
+        continue_on_iteration_error: continueOnIterationError,
+        max_iteration_error_fraction: maxIterationErrorFraction,
+        iteration_timeout_ms: iterationTimeoutMs
+      },
+      iteration_settings: JSON.parse(JSON.stringify(iterationSettings))
+    };
+  }
+
+  // Over the size cap, drop per-iteration data rather than fail later in JSON.stringify
+  const limited = limitOutputSize(aggregated, maxOutputBytes, ['iterations', 'sample_iterations', 'percentile_timelines']);
+  if (limited) {
+    console.warn(`Monte Carlo result over ${maxOutputBytes} bytes; removed ${limited.truncated.join(', ')}`);
+    aggregated.truncated = limited.truncated;
+    aggregated.meta.output_size = limited.sizes;
+  }
   
   return aggregated;
 }
 
-module.exports = { runMonteCarlo };
+/**
+ * Run Monte Carlo with caller-defined per-iteration metrics (options.extractors)
+ *
+ * @param {Object} scenario - Scenario configuration
+ * @param {Object} options - runMonteCarlo options
+ * @param {Object} extractors - name → function(result) returning a number, or metric expression
+ * @returns {Object} - Aggregated results with custom[name] statistics
+ */
+async function runMonteCarloWithExtractors(scenario, options, extractors) {
+  return runMonteCarlo(scenario, { ...options, extractors });
+}
+
+/**
+ * Run Monte Carlo over a whole day-by-day sequence (see runSimulationSequence in the DES engine): every iteration runs
+ * all the segments back to back with carried-over holds, so a bad day's backlog weighs on the days after it
+ *
+ * @param {Array<Object>} scenarios - One scenario per segment, in order
+ * @param {Object} options - Options including:
+ *   - iterations: Number of iterations (default: 1000)
+ *   - seed: Optional integer seed; iteration i runs its sequence with a sub-seed derived from (seed, i)
+ *   - state: State snapshot (required, same as DES)
+ *   - overrides: Resource overrides (optional, same as DES)
+ *   - maxConcurrent: Maximum concurrent workers (default: CPU_COUNT - 1)
+ *   - logLevel: DES log level for workers (default: 'silent')
+ *   - keepIterations: Whether to store each iteration's summary (default: false)
+ *   - onBusy: 'queue' (default) or 'reject', as runMonteCarlo
+ * Iterations build no timeline; the first failed iteration fails the run
+ * @returns {Object} - { iterations, seed, summary: { missions, rejections, by_type, flight_hours },
+ *                     days: [{ missions, rejections }], meta }, each value aggregated over the iterations
+ */
+async function runMonteCarloSequence(scenarios, options = {}) {
+  if (!Array.isArray(scenarios) || scenarios.length === 0) {
+    throw new Error('runMonteCarloSequence requires a non-empty array of scenarios');
+  }
+  scenarios = scenarios.map(scenario => normalizeScenarioUnits(scenario));
+  scenarios.forEach(scenario => validateScenario(scenario));
+  const iterations = options.iterations || 1000;
+  const maxConcurrent = options.maxConcurrent || WORKER_POOL_SIZE;
+  if (options.seed !== undefined && options.seed !== null && !isValidSeed(options.seed)) {
+    throw new Error(`seed must be an integer, got ${options.seed}`);
+  }
+  const seed = isValidSeed(options.seed) ? options.seed : null;
+  const onBusy = validateOnBusy(options.onBusy);
+  const settings = {
+    state: options.state,
+    overrides: options.overrides,
+    logLevel: options.logLevel || 'silent',
+    collectTimeline: false,
+    echoConfig: false,
+    maxOutputBytes: null
+  };
+
+  const workerPath = path.join(__dirname, 'worker.js');
+  const results = [];
+  const timings = [];
+  const releaseSlot = await acquireRunSlot(onBusy, null);
+  try {
+    for (let start = 0; start < iterations; start += maxConcurrent) {
+      const indices = Array.from({ length: Math.min(maxConcurrent, iterations - start) }, (_, k) => start + k);
+      const batch = await Promise.all(indices.map(i => {
+        const iterationSettings = seed === null ? settings : { ...settings, seed: deriveSeed(seed, i) };
+        return runSingleWithRetry(workerPath, scenarios, iterationSettings).catch(error => {
+          throw iterationError(error, i);
+        });
+      }));
+      batch.forEach(({ result, wallMs }, k) => {
+        results.push(result);
+        timings.push({ iteration: indices[k], wall_ms: wallMs });
+      });
+    }
+  } finally {
+    releaseSlot();
+  }
+
+  const summaries = results.map(r => r.summary);
+  const aggregated = {
+    iterations,
+    seed,
+    summary: {
+      missions: aggregateObject(summaries, 'missions'),
+      rejections: aggregateObject(summaries, 'rejections'),
+      by_type: aggregateMissionStats(summaries, 'by_type', true, [...new Set(scenarios.flatMap(s => (s.mission_types || []).map(mt => mt.name)))]),
+      flight_hours: aggregateObject(summaries, 'flight_hours')
+    },
+    days: scenarios.map((_, d) => ({
+      missions: aggregateObject(results.map(r => r.days[d]), 'missions'),
+      rejections: aggregateObject(results.map(r => r.days[d]), 'rejections')
+    })),
+    meta: summarizeTimings(timings, [])
+  };
+  if (options.keepIterations) aggregated.iteration_summaries = summaries;
+  return aggregated;
+}
+
+module.exports = { runMonteCarlo, runMonteCarloWithExtractors, runMonteCarloSequence };
@@ -0,0 +1,108 @@
+/**
+ * MONTE CARLO CUSTOM METRICS
+ *
+ * Lets callers summarize metrics the results do not carry without the engine growing a field
+ * for each one. An extractor maps one iteration's DES result to a number; its values over the
+ * successful iterations are summarized like any other statistic under results.custom[name].
+ *
+ * An extractor is either a function (JavaScript API) or a metric expression as in thresholds,
+ * e.g. 'missions.completed / missions.requested' or "utilization['VMU-1'].aircraft" (routes accept
+ * expressions only).
+ *
+ * Extremes use the same expressions: for each metric, the lowest and highest value any
+ * iteration produced and which iteration produced it, so that iteration can be re-run alone.
+ */
+
+const { compileMetric, evaluate } = require('./thresholds');
+
+// Metrics whose extremes are reported when options.extremeMetrics is not given
+const DEFAULT_EXTREME_METRICS = ['missions.completed', 'missions.rejected'];
+
+/**
+ * Validate and compile extractors. Throws on the first invalid one so the caller can fail
+ * before any iteration runs.
+ *
+ * @param {Object} extractors - name → function(result) returning a number, or metric expression
+ * @returns {Array<Object>} - Compiled extractors ({ name, extract })
+ */
+function compileExtractors(extractors) {
+  if (!extractors || typeof extractors !== 'object' || Array.isArray(extractors)) {
+    throw new Error('extractors must be an object mapping names to functions or metric expressions');
+  }
+  return Object.entries(extractors).map(([name, extractor]) => {
+    const where = `extractors['${name}']`;
+    if (typeof extractor === 'function') {
+      return {
+        name,
+        extract: result => {
+          try {
+            return extractor(result);
+          } catch (error) {
+            throw new Error(`${where} failed: ${error.message}`);
+          }
+        }
+      };
+    }
+    if (typeof extractor === 'string') {
+      const ast = compileMetric(extractor, where);
+      return { name, extract: result => evaluate(ast, result) };
+    }
+    throw new Error(`${where} must be a function or a metric expression string`);
+  });
+}
+
+/**
+ * Each extractor's finite values over the iterations; non-numbers and non-finite values
+ * (e.g. 0 / 0) are left out.
+ *
+ * @param {Array<Object>} compiled - Output of compileExtractors
+ * @param {Array<Object>} iterations - DES result objects
+ * @returns {Object} - name → values
+ */
+function extractValues(compiled, iterations) {
+  return Object.fromEntries(compiled.map(c => [
+    c.name,
+    iterations.map(iter => c.extract(iter)).filter(v => typeof v === 'number' && Number.isFinite(v))
+  ]));
+}
+
+/**
+ * Validate and compile the metrics whose extremes are reported.
+ *
+ * @param {Array<string>} metrics - Metric expressions (default DEFAULT_EXTREME_METRICS)
+ * @returns {Array<Object>} - Compiled metrics ({ name, extract })
+ */
+function compileExtremeMetrics(metrics = DEFAULT_EXTREME_METRICS) {
+  if (!Array.isArray(metrics) || metrics.some(m => typeof m !== 'string')) {
+    throw new Error('extremeMetrics must be an array of metric expressions');
+  }
+  return metrics.map((metric, i) => {
+    const ast = compileMetric(metric, `extremeMetrics[${i}]`);
+    return { name: metric, extract: result => evaluate(ast, result) };
+  });
+}
+
+/**
+ * Lowest and highest value of each metric and the iteration that produced it (the earliest on ties);
+ * non-finite values are left out, and a metric with none has min and max null.
+ *
+ * @param {Array<Object>} compiled - Output of compileExtremeMetrics
+ * @param {Array<Object>} iterations - DES result objects
+ * @param {Array<Object>} tags - { iteration, seed } aligned with iterations
+ * @returns {Object} - metric → { min: { value, iteration, seed }, max: { value, iteration, seed } }
+ */
+function findExtremes(compiled, iterations, tags) {
+  return Object.fromEntries(compiled.map(c => {
+    let min = null;
+    let max = null;
+    iterations.forEach((iter, k) => {
+      const value = c.extract(iter);
+      if (typeof value !== 'number' || !Number.isFinite(value)) return;
+      if (min === null || value < min.value) min = { value, ...tags[k] };
+      if (max === null || value > max.value) max = { value, ...tags[k] };
+    });
+    return [c.name, { min, max }];
+  }));
+}
+
+module.exports = { compileExtractors, extractValues, compileExtremeMetrics, findExtremes, DEFAULT_EXTREME_METRICS };
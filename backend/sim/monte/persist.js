@@ -0,0 +1,276 @@
+/**
+ * MONTE CARLO RESULT PERSISTENCE
+ *
+ * Aggregated results are mostly the same few keys (mean, p50, stddev, ...) repeated under every
+ * unit and mission type, so stored JSON is large and slow to parse back for later comparisons.
+ * serializeResults writes a compact self-describing binary form instead:
+ *
+ *   'MCRB' | version (1 byte) | key count, keys (each once, UTF-8) | root value
+ *
+ * Every value starts with a tag; objects refer to their keys by index into the key table and
+ * integers are zigzag varints. Decoding gives exactly what JSON.parse(JSON.stringify(results))
+ * would (undefined fields dropped, non-finite numbers as null, -0 as 0), so stored results
+ * compare the same whichever way they were kept.
+ *
+ * Forward compatibility: tags from EXTENSION_TAG up carry their byte length, so a reader skips
+ * value kinds added after it was written (the field is dropped, an array slot reads null).
+ * FORMAT_VERSION only changes if the layout itself does.
+ */
+
+const MAGIC = Buffer.from('MCRB', 'ascii');
+const FORMAT_VERSION = 1;
+
+// Value tags
+const TAG_NULL = 0;
+const TAG_FALSE = 1;
+const TAG_TRUE = 2;
+const TAG_INT = 3;
+const TAG_FLOAT = 4;
+const TAG_STRING = 5;
+const TAG_ARRAY = 6;
+const TAG_OBJECT = 7;
+// First tag of the length-prefixed range readers skip when they do not know it
+const EXTENSION_TAG = 0x40;
+
+/**
+ * Growable byte buffer.
+ */
+class Writer {
+  constructor() {
+    this.buf = Buffer.alloc(1024);
+    this.length = 0;
+  }
+
+  reserve(n) {
+    if (this.length + n <= this.buf.length) return;
+    const next = Buffer.alloc(Math.max(this.buf.length * 2, this.length + n));
+    this.buf.copy(next, 0, 0, this.length);
+    this.buf = next;
+  }
+
+  byte(b) {
+    this.reserve(1);
+    this.buf[this.length++] = b;
+  }
+
+  // Unsigned varint (7 bits per byte); safe for integers up to 2^53
+  varint(n) {
+    while (n >= 0x80) {
+      this.byte((n % 0x80) | 0x80);
+      n = Math.floor(n / 0x80);
+    }
+    this.byte(n);
+  }
+
+  float(x) {
+    this.reserve(8);
+    this.buf.writeDoubleLE(x, this.length);
+    this.length += 8;
+  }
+
+  bytes(b) {
+    this.reserve(b.length);
+    b.copy(this.buf, this.length);
+    this.length += b.length;
+  }
+
+  string(s) {
+    const b = Buffer.from(s, 'utf8');
+    this.varint(b.length);
+    this.bytes(b);
+  }
+
+  result() {
+    return this.buf.subarray(0, this.length);
+  }
+}
+
+/**
+ * Value JSON.stringify would write for v (toJSON applied), or undefined when it would drop it.
+ *
+ * @param {*} v - Value
+ * @returns {*}
+ */
+function jsonValue(v) {
+  if (v !== null && typeof v === 'object' && typeof v.toJSON === 'function') v = v.toJSON();
+  if (v === undefined || typeof v === 'function' || typeof v === 'symbol') return undefined;
+  if (typeof v === 'number' && !Number.isFinite(v)) return null;
+  if (Object.is(v, -0)) return 0;
+  if (typeof v === 'bigint') throw new Error('serializeResults: BigInt values are not supported');
+  return v;
+}
+
+/**
+ * Collect every object key into the key table (first-seen order).
+ *
+ * @param {*} v - Value
+ * @param {Map<string, number>} keys - key → index
+ */
+function collectKeys(v, keys) {
+  v = jsonValue(v);
+  if (v === null || typeof v !== 'object') return;
+  if (Array.isArray(v)) {
+    for (const item of v) collectKeys(item, keys);
+    return;
+  }
+  for (const [key, item] of Object.entries(v)) {
+    if (jsonValue(item) === undefined) continue;
+    if (!keys.has(key)) keys.set(key, keys.size);
+    collectKeys(item, keys);
+  }
+}
+
+function writeValue(w, v, keys) {
+  v = jsonValue(v);
+  if (v === null || v === undefined) {
+    w.byte(TAG_NULL);
+  } else if (v === false) {
+    w.byte(TAG_FALSE);
+  } else if (v === true) {
+    w.byte(TAG_TRUE);
+  } else if (typeof v === 'number') {
+    if (Number.isSafeInteger(v)) {
+      w.byte(TAG_INT);
+      w.varint(v >= 0 ? v * 2 : -v * 2 - 1);
+    } else {
+      w.byte(TAG_FLOAT);
+      w.float(v);
+    }
+  } else if (typeof v === 'string') {
+    w.byte(TAG_STRING);
+    w.string(v);
+  } else if (Array.isArray(v)) {
+    w.byte(TAG_ARRAY);
+    w.varint(v.length);
+    for (const item of v) writeValue(w, item, keys);
+  } else {
+    const entries = Object.entries(v).filter(([, item]) => jsonValue(item) !== undefined);
+    w.byte(TAG_OBJECT);
+    w.varint(entries.length);
+    for (const [key, item] of entries) {
+      w.varint(keys.get(key));
+      writeValue(w, item, keys);
+    }
+  }
+}
+
+/**
+ * Serialize Monte Carlo (or any JSON-compatible) results to the compact binary form.
+ *
+ * @param {Object} results - Aggregated results
+ * @returns {Buffer} - Serialized results
+ */
+function serializeResults(results) {
+  const keys = new Map();
+  collectKeys(results, keys);
+  const w = new Writer();
+  w.bytes(MAGIC);
+  w.byte(FORMAT_VERSION);
+  w.varint(keys.size);
+  for (const key of keys.keys()) w.string(key);
+  writeValue(w, results, keys);
+  return w.result();
+}
+
+/**
+ * Read a buffer written by serializeResults.
+ *
+ * @param {Buffer|Uint8Array} buffer - Serialized results
+ * @returns {*} - The results as JSON.parse(JSON.stringify(results)) would give them
+ */
+function deserializeResults(buffer) {
+  if (!(buffer instanceof Uint8Array)) {
+    throw new Error('deserializeResults requires a Buffer');
+  }
+  const buf = Buffer.from(buffer.buffer, buffer.byteOffset, buffer.byteLength);
+  if (buf.length < MAGIC.length + 1 || !buf.subarray(0, MAGIC.length).equals(MAGIC)) {
+    throw new Error('deserializeResults: not a serialized Monte Carlo result (bad header)');
+  }
+  const version = buf[MAGIC.length];
+  if (version !== FORMAT_VERSION) {
+    throw new Error(`deserializeResults: format version ${version} is not supported (expected ${FORMAT_VERSION})`);
+  }
+  let pos = MAGIC.length + 1;
+
+  const need = n => {
+    if (pos + n > buf.length) throw new Error('deserializeResults: truncated buffer');
+  };
+  const varint = () => {
+    let n = 0;
+    let scale = 1;
+    for (;;) {
+      need(1);
+      const b = buf[pos++];
+      n += (b & 0x7f) * scale;
+      if (b < 0x80) return n;
+      scale *= 0x80;
+    }
+  };
+  const string = () => {
+    const len = varint();
+    need(len);
+    const s = buf.toString('utf8', pos, pos + len);
+    pos += len;
+    return s;
+  };
+
+  const keyTable = new Array(varint());
+  for (let i = 0; i < keyTable.length; i++) keyTable[i] = string();
+
+  // Returns undefined for a skipped extension value
+  const value = () => {
+    need(1);
+    const tag = buf[pos++];
+    switch (tag) {
+      case TAG_NULL:
+        return null;
+      case TAG_FALSE:
+        return false;
+      case TAG_TRUE:
+        return true;
+      case TAG_INT: {
+        const z = varint();
+        return z % 2 === 0 ? z / 2 : -(z + 1) / 2;
+      }
+      case TAG_FLOAT: {
+        need(8);
+        const x = buf.readDoubleLE(pos);
+        pos += 8;
+        return x;
+      }
+      case TAG_STRING:
+        return string();
+      case TAG_ARRAY: {
+        const out = new Array(varint());
+        for (let i = 0; i < out.length; i++) out[i] = value() ?? null;
+        return out;
+      }
+      case TAG_OBJECT: {
+        const out = {};
+        const n = varint();
+        for (let i = 0; i < n; i++) {
+          const index = varint();
+          if (index >= keyTable.length) throw new Error(`deserializeResults: key index ${index} out of range`);
+          const item = value();
+          if (item !== undefined) out[keyTable[index]] = item;
+        }
+        return out;
+      }
+      default: {
+        if (tag < EXTENSION_TAG) throw new Error(`deserializeResults: unknown value tag ${tag}`);
+        const len = varint();
+        need(len);
+        pos += len;
+        return undefined;
+      }
+    }
+  };
+
+  const root = value();
+  if (pos !== buf.length) {
+    throw new Error(`deserializeResults: ${buf.length - pos} trailing bytes`);
+  }
+  return root ?? null;
+}
+
+module.exports = { serializeResults, deserializeResults, FORMAT_VERSION, EXTENSION_TAG };
@@ -0,0 +1,161 @@
+/**
+ * MONTE CARLO SENSITIVITY (TORNADO)
+ *
+ * One-factor-at-a-time sweeps: a baseline run with every factor at its base value,
+ * then one run per factor at its low and at its high value with the others held at base.
+ * Every run uses the same seed, so the swings reflect the factor rather than sampling noise.
+ *
+ * A factor is { name?, path, low, high, base? }. Paths are either override fields
+ * (as in input uncertainty) or numeric scenario fields:
+ *   'overrides.units.VMU-1.aircraft'
+ *   'scenario.horizon_hours'
+ *   'scenario.mission_types.ISR.flight_time.value_hours'
+ * Top-level mission_types and demand entries are addressed by name / mission_type,
+ * and the change is applied as a scenario patch.
+ */
+
+const { runMonteCarlo } = require('./engine');
+const { parseOverridePath, overridePathError, setOverride, getOverride, pathSegments } = require('./uncertainty');
+const { applyScenarioPatch, KEYED_ARRAYS } = require('../des/helpers/patch');
+const { isValidSeed } = require('../des/helpers/random');
+
+const STATISTICS = new Set(['mean', 'p10', 'p25', 'p50', 'p75', 'p90', 'p95', 'p99', 'min', 'max']);
+
+/**
+ * Resolve a 'scenario.' path to its current value and a patch builder.
+ *
+ * @param {Object} scenario - Base scenario
+ * @param {Array<string>} segments - Path segments without the leading 'scenario'
+ * @returns {Object|null} - { current, patch(value) } or null if the path does not name a field
+ */
+function resolveScenarioPath(scenario, segments) {
+  const nest = (path, value) => path.reduceRight((acc, seg) => ({ [seg]: acc }), value);
+  let node = scenario;
+  let path = segments;
+  let patch = value => nest(segments, value);
+
+  const [head, key, ...inner] = segments;
+  if (head in KEYED_ARRAYS && Array.isArray(scenario?.[head])) {
+    const keyField = KEYED_ARRAYS[head];
+    node = scenario[head].find(el => el && el[keyField] === key);
+    if (!node) return null;
+    path = inner;
+    patch = value => ({ [head]: [{ [keyField]: key, ...nest(inner, value) }] });
+  }
+
+  for (const seg of path) {
+    if (node === null || typeof node !== 'object' || Array.isArray(node)) return null;
+    node = node[seg];
+  }
+  return path.length > 0 ? { current: node, patch } : null;
+}
+
+/**
+ * Validate factors and resolve how each one is applied. Throws on the first invalid one so
+ * the caller can fail before any run starts.
+ *
+ * @param {Array<Object>} factors - Factors ({ name?, path, low, high, base? })
+ * @param {Object} scenario - Base scenario
+ * @param {Object|null} overrides - Base overrides
+ * @returns {Array<Object>} - Compiled factors ({ name, key, low, base, high, apply })
+ */
+function compileFactors(factors, scenario, overrides) {
+  if (!Array.isArray(factors) || factors.length === 0) {
+    throw new Error('factors must be a non-empty array of { path, low, high }');
+  }
+  const seen = new Set();
+  return factors.map((f, i) => {
+    const where = `factors[${i}]`;
+    if (!f || typeof f !== 'object') throw new Error(`${where} must be an object`);
+    const segments = pathSegments(f.path);
+    const key = segments.join('.');
+    if (seen.has(key)) throw new Error(`${where}: path '${key}' appears more than once`);
+    seen.add(key);
+
+    let current;
+    let apply;
+    if (segments[0] === 'overrides') {
+      const target = parseOverridePath(segments);
+      if (!target) throw new Error(overridePathError(where, segments));
+      current = getOverride(overrides, target);
+      apply = (inputs, value) => ({ ...inputs, overrides: setOverride(inputs.overrides, target, value) });
+    } else if (segments[0] === 'scenario') {
+      const resolved = resolveScenarioPath(scenario, segments.slice(1));
+      if (!resolved) throw new Error(`${where}: unknown scenario path '${key}'`);
+      current = resolved.current;
+      apply = (inputs, value) => ({ ...inputs, scenario: applyScenarioPatch(inputs.scenario, resolved.patch(value)) });
+    } else {
+      throw new Error(`${where}: path '${key}' must start with 'overrides.' or 'scenario.'`);
+    }
+
+    const base = f.base ?? current;
+    for (const [label, value] of [['low', f.low], ['high', f.high], ['base', base]]) {
+      if (typeof value !== 'number' || !Number.isFinite(value)) {
+        const hint = label === 'base' && f.base === undefined ? ` (path '${key}' has no numeric value; give base)` : '';
+        throw new Error(`${where}: ${label} must be a finite number${hint}`);
+      }
+    }
+    return { name: f.name || key, key, low: f.low, base, high: f.high, apply };
+  });
+}
+
+/**
+ * Run a one-factor-at-a-time sensitivity sweep for a tornado chart.
+ *
+ * @param {Object} scenario - Base scenario
+ * @param {Object} options - runMonteCarlo options, plus:
+ *   - metric: aggregated statistics path to report (default 'missions.completed'; array for names with dots)
+ *   - statistic: which statistic of the metric to compare (default 'mean')
+ * @param {Array<Object>} factors - Factors ({ name?, path, low, high, base? })
+ * @returns {Promise<Object>} - { metric, statistic, seed, baseline, factors } with factors sorted by swing, largest first
+ */
+async function runSensitivity(scenario, options = {}, factors) {
+  const metric = pathSegments(options.metric ?? 'missions.completed');
+  const statistic = options.statistic ?? 'mean';
+  if (!STATISTICS.has(statistic)) {
+    throw new Error(`statistic must be one of ${[...STATISTICS].join(', ')}, got ${statistic}`);
+  }
+  if (options.seed !== undefined && options.seed !== null && !isValidSeed(options.seed)) {
+    throw new Error(`seed must be an integer, got ${options.seed}`);
+  }
+  const compiled = compileFactors(factors, scenario, options.overrides ?? null);
+  // Common random numbers: every run shares one seed
+  const seed = isValidSeed(options.seed) ? options.seed : Math.floor(Math.random() * 2 ** 31);
+  const mcOptions = { ...options };
+  delete mcOptions.metric;
+  delete mcOptions.statistic;
+
+  const baseInputs = compiled.reduce((inputs, c) => c.apply(inputs, c.base), { scenario, overrides: options.overrides ?? null });
+  const run = async inputs => {
+    const mc = await runMonteCarlo(inputs.scenario, { ...mcOptions, overrides: inputs.overrides ?? undefined, seed, keepIterations: false });
+    const stats = metric.reduce((o, p) => (o == null ? undefined : o[p]), mc);
+    if (!stats || typeof stats[statistic] !== 'number') {
+      throw new Error(`metric '${metric.join('.')}' is not an aggregated statistic with '${statistic}'`);
+    }
+    return stats[statistic];
+  };
+
+  const baseline = await run(baseInputs);
+  const rows = [];
+  for (const c of compiled) {
+    const lowValue = await run(c.apply(baseInputs, c.low));
+    const highValue = await run(c.apply(baseInputs, c.high));
+    rows.push({
+      name: c.name,
+      path: c.key,
+      low: c.low,
+      base: c.base,
+      high: c.high,
+      low_value: lowValue,
+      high_value: highValue,
+      low_delta: Number((lowValue - baseline).toFixed(4)),
+      high_delta: Number((highValue - baseline).toFixed(4)),
+      swing: Number(Math.abs(highValue - lowValue).toFixed(4))
+    });
+  }
+  rows.sort((a, b) => b.swing - a.swing);
+
+  return { metric: metric.join('.'), statistic, seed, baseline, factors: rows };
+}
+
+module.exports = { runSensitivity, compileFactors };
@@ -0,0 +1,128 @@
+/**
+ * MONTE CARLO RUN SLOTS
+ *
+ * Each Monte Carlo run fans out one worker per CPU, so two runs at once fight over the
+ * same cores. A run holds a slot while its iterations execute; at most maxConcurrentRuns
+ * (default 1, MONTE_MAX_CONCURRENT_RUNS) hold one at a time. A run that finds every slot
+ * taken either waits its turn ('queue') or fails at once with code 'ENGINE_BUSY' ('reject').
+ */
+
+const BUSY_ERROR_CODE = 'ENGINE_BUSY';
+const ON_BUSY = ['queue', 'reject'];
+
+let maxConcurrentRuns = Number.parseInt(process.env.MONTE_MAX_CONCURRENT_RUNS, 10) > 0
+  ? Number.parseInt(process.env.MONTE_MAX_CONCURRENT_RUNS, 10)
+  : 1;
+let running = 0;
+// Waiting runs, oldest first: { grant, onQueued }
+const queue = [];
+
+/**
+ * Check options.onBusy.
+ *
+ * @param {*} onBusy - Candidate value (undefined means 'queue')
+ * @returns {string} - 'queue' or 'reject'
+ */
+function validateOnBusy(onBusy) {
+  const value = onBusy ?? 'queue';
+  if (!ON_BUSY.includes(value)) {
+    throw new Error(`onBusy must be one of ${ON_BUSY.join(', ')}, got ${value}`);
+  }
+  return value;
+}
+
+/**
+ * Tell every waiting run its (1-based) place in the queue.
+ */
+function notifyQueued() {
+  queue.forEach((entry, i) => {
+    if (entry.onQueued) entry.onQueued(i + 1);
+  });
+}
+
+/**
+ * Hand free slots to the oldest waiting runs.
+ */
+function dispatch() {
+  let granted = false;
+  while (running < maxConcurrentRuns && queue.length > 0) {
+    running++;
+    queue.shift().grant();
+    granted = true;
+  }
+  if (granted) notifyQueued();
+}
+
+/**
+ * Take a run slot, waiting for one or failing fast when all are taken.
+ *
+ * @param {string} onBusy - 'queue' or 'reject'
+ * @param {Function} onQueued - Optional callback receiving the run's queue position each time it changes
+ * @returns {Promise<Function>} - Release function; call it exactly once when the run's iterations are done
+ */
+async function acquireRunSlot(onBusy = 'queue', onQueued = null) {
+  let released = false;
+  const release = () => {
+    if (released) return;
+    released = true;
+    running--;
+    dispatch();
+  };
+  if (running < maxConcurrentRuns && queue.length === 0) {
+    running++;
+    return release;
+  }
+  if (onBusy === 'reject') {
+    const error = new Error(`Monte Carlo engine is busy (${running} running, ${queue.length} queued, max ${maxConcurrentRuns} concurrent)`);
+    error.code = BUSY_ERROR_CODE;
+    error.running = running;
+    error.queued = queue.length;
+    throw error;
+  }
+  await new Promise(grant => {
+    queue.push({ grant, onQueued });
+    if (onQueued) onQueued(queue.length);
+  });
+  return release;
+}
+
+/**
+ * Current slot usage, for dashboards.
+ *
+ * @returns {Object} - { running, queued, max_concurrent_runs }
+ */
+function getEngineStatus() {
+  return { running, queued: queue.length, max_concurrent_runs: maxConcurrentRuns };
+}
+
+/**
+ * Change how many runs may hold a slot at once. Raising it starts waiting runs immediately.
+ *
+ * @param {number} n - Positive integer
+ */
+function setMaxConcurrentRuns(n) {
+  if (!Number.isInteger(n) || n < 1) {
+    throw new Error(`maxConcurrentRuns must be a positive integer, got ${n}`);
+  }
+  maxConcurrentRuns = n;
+  dispatch();
+}
+
+/**
+ * True for the error a rejected busy run throws.
+ *
+ * @param {Error} error - Error to check
+ * @returns {boolean}
+ */
+function isBusyError(error) {
+  return Boolean(error) && error.code === BUSY_ERROR_CODE;
+}
+
+module.exports = {
+  acquireRunSlot,
+  validateOnBusy,
+  getEngineStatus,
+  setMaxConcurrentRuns,
+  isBusyError,
+  BUSY_ERROR_CODE
+};
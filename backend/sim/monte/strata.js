@@ -0,0 +1,89 @@
+/**
+ * MONTE CARLO STRATIFIED REPORTING
+ *
+ * When iterations fall into different regimes (a demand bootstrap replaying a quiet and a busy
+ * day, an uncertain input drawn low or high) the overall statistics mix them. stratifyBy names
+ * an iteration-level attribute and strata reports the headline statistics separately per value:
+ *
+ *   'bootstrap_index'                  - the demand library entry replayed (demandBootstrap)
+ *   'overrides.units.VMU-1.aircraft'   - an inputUncertainty path (the iteration's draw)
+ *   any threshold-style metric         - e.g. 'missions.requested'
+ *
+ * An attribute taking at most `buckets` distinct values gets one stratum per value; otherwise
+ * the iterations are sorted by it and cut into `buckets` quantile buckets of near-equal size
+ * (q1 holds the lowest values).
+ */
+
+const { compileMetric, evaluate } = require('./thresholds');
+const { pathSegments } = require('./uncertainty');
+
+const DEFAULT_STRATIFY_BUCKETS = 4;
+
+/**
+ * Validate options.stratifyBy and options.stratifyBuckets. Throws so the caller can fail
+ * before any iteration runs.
+ *
+ * @param {string} stratifyBy - Attribute to stratify by
+ * @param {number} buckets - Quantile buckets for many-valued attributes (default 4)
+ * @param {Array<Object>|null} inputUncertainty - Compiled input uncertainty entries
+ * @param {Object|null} demandBootstrap - Validated demandBootstrap option
+ * @returns {Object} - { by, buckets, value: function(iteration) → number }
+ */
+function compileStratifyBy(stratifyBy, buckets = DEFAULT_STRATIFY_BUCKETS, inputUncertainty = null, demandBootstrap = null) {
+  if (typeof stratifyBy !== 'string' || stratifyBy.trim() === '') {
+    throw new Error('stratifyBy must be bootstrap_index, an inputUncertainty path or a metric expression');
+  }
+  if (!Number.isInteger(buckets) || buckets < 2) {
+    throw new Error(`stratifyBuckets must be an integer of at least 2, got ${buckets}`);
+  }
+  const by = stratifyBy.trim();
+  if (by === 'bootstrap_index') {
+    if (!demandBootstrap) throw new Error('stratifyBy bootstrap_index requires demandBootstrap');
+    return { by, buckets, value: iter => iter.bootstrap_index };
+  }
+  const inputs = new Set((inputUncertainty || []).map(c => c.key));
+  if (by.startsWith('overrides.')) {
+    const input = pathSegments(by).join('.');
+    if (!inputs.has(input)) throw new Error(`stratifyBy: '${input}' is not an inputUncertainty path`);
+    return { by: input, buckets, value: iter => iter.sampled_inputs[input] };
+  }
+  const ast = compileMetric(by, 'stratifyBy');
+  return { by, buckets, value: iter => evaluate(ast, iter) };
+}
+
+/**
+ * Group iterations into strata. Iterations whose attribute is not a finite number are left out.
+ *
+ * @param {Object} compiled - Output of compileStratifyBy
+ * @param {Array<Object>} iterations - DES result objects (with sampled_inputs / bootstrap_index)
+ * @returns {Array<Object>} - [{ key, value } or { key, range: [min, max] }, with members], in ascending order
+ */
+function stratifyIterations(compiled, iterations) {
+  const tagged = iterations
+    .map(iter => ({ iter, value: compiled.value(iter) }))
+    .filter(t => Number.isFinite(t.value))
+    .sort((a, b) => a.value - b.value);
+  const distinct = [...new Set(tagged.map(t => t.value))];
+
+  if (distinct.length <= compiled.buckets) {
+    return distinct.map(value => ({
+      key: String(value),
+      value,
+      members: tagged.filter(t => t.value === value).map(t => t.iter)
+    }));
+  }
+
+  const strata = [];
+  for (let k = 0; k < compiled.buckets; k++) {
+    const slice = tagged.slice(Math.floor(k * tagged.length / compiled.buckets), Math.floor((k + 1) * tagged.length / compiled.buckets));
+    if (slice.length === 0) continue;
+    strata.push({
+      key: `q${k + 1}`,
+      range: [slice[0].value, slice[slice.length - 1].value],
+      members: slice.map(t => t.iter)
+    });
+  }
+  return strata;
+}
+
+module.exports = { compileStratifyBy, stratifyIterations, DEFAULT_STRATIFY_BUCKETS };
@@ -0,0 +1,290 @@
+/**
+ * MONTE CARLO THRESHOLD PROBABILITIES
+ *
+ * Evaluates threshold conditions against each iteration's DES results and reports
+ * the fraction of iterations that satisfied each one.
+ *
+ * A condition is { metric, op, value } where metric is a small arithmetic expression
+ * over result paths, e.g. 'missions.completed / missions.requested'.
+ *
+ * Paths are dot-separated; names containing spaces, dots or '-' use brackets:
+ *   by_type["Long ISR"].completed, utilization['HMLA-267'].aircraft
+ * so an unspaced '-' after a path always subtracts: missions.requested-missions.rejected
+ */
+
+const OPERATORS = {
+  '>=': (a, b) => a >= b,
+  '>': (a, b) => a > b,
+  '<=': (a, b) => a <= b,
+  '<': (a, b) => a < b,
+  '==': (a, b) => a === b
+};
+
+const REASONS = ['aircraft', 'control_station', 'launch_recovery', 'concurrency_cap', 'pilot', 'so', 'intel', 'payload', 'payload_budget', 'window'];
+const COUNTS = ['requested', 'started', 'completed', 'rejected', 'infeasible', 'in_progress_at_horizon'];
+
+// Known result paths. '*' matches any mission type or unit name.
+const METRIC_SCHEMA = {
+  horizon_hours: true,
+  weighted_completion_score: true,
+  completion_quality: { mean: true, degraded: true },
+  missions: Object.fromEntries(COUNTS.map(c => [c, true])),
+  in_progress_by_unit: { '*': true },
+  rejections: Object.fromEntries(REASONS.map(r => [r, true])),
+  duties: { requested: true, filled: true, unfilled: true },
+  by_type: { '*': Object.fromEntries(COUNTS.map(c => [c, true])) },
+  by_demand_source: { '*': Object.fromEntries(COUNTS.map(c => [c, true])) },
+  rejections_by_type: { '*': Object.fromEntries(REASONS.map(r => [r, true])) },
+  rejections_by_unit: { '*': Object.fromEntries(REASONS.map(r => [r, true])) },
+  payload_utilization: { '*': { '*': true } },
+  payload_missions: { '*': { '*': true } },
+  reallocations: { count: true, aircraft_requested: true, aircraft_moved: true },
+  peak_window: { max_starts: true, by_unit: { '*': { max_starts: true } } },
+  utilization: {
+    '*': {
+      ...Object.fromEntries(
+        ['aircraft', 'pilot', 'so', 'intel'].flatMap(k => [[k, true], [`${k}_efficiency`, true], [`${k}_efficiency_of_available`, true]])
+      ),
+      control_station: true,
+      control_station_efficiency: true,
+      launch_recovery: true,
+      launch_recovery_efficiency: true
+    }
+  }
+};
+
+/**
+ * Split a metric expression into tokens.
+ *
+ * @param {string} expr - Metric expression
+ * @returns {Array<Object>} - Tokens ({ kind: 'num' | 'path' | 'op', value })
+ */
+function tokenize(expr) {
+  const tokens = [];
+  let i = 0;
+  while (i < expr.length) {
+    const ch = expr[i];
+    if (/\s/.test(ch)) { i++; continue; }
+
+    if ('+-*/()'.includes(ch)) {
+      tokens.push({ kind: 'op', value: ch });
+      i++;
+      continue;
+    }
+
+    const num = /^\d+(\.\d+)?/.exec(expr.slice(i));
+    if (num) {
+      tokens.push({ kind: 'num', value: Number(num[0]) });
+      i += num[0].length;
+      continue;
+    }
+
+    const head = /^[A-Za-z_]\w*/.exec(expr.slice(i));
+    if (!head) throw new Error(`Unexpected character '${ch}' in metric '${expr}'`);
+    const segments = [head[0]];
+    i += head[0].length;
+    while (i < expr.length) {
+      if (expr[i] === '.') {
+        const seg = /^\w+/.exec(expr.slice(i + 1));
+        if (!seg) throw new Error(`Expected a name after '.' in metric '${expr}'`);
+        segments.push(seg[0]);
+        i += 1 + seg[0].length;
+      } else if (expr[i] === '[') {
+        const seg = /^\[\s*(?:"([^"]*)"|'([^']*)')\s*\]/.exec(expr.slice(i));
+        if (!seg) throw new Error(`Expected ["name"] or ['name'] in metric '${expr}'`);
+        segments.push(seg[1] ?? seg[2]);
+        i += seg[0].length;
+      } else {
+        break;
+      }
+    }
+    tokens.push({ kind: 'path', value: segments });
+  }
+  return tokens;
+}
+
+/**
+ * Parse a metric expression into an AST (standard precedence, left associative).
+ *
+ * @param {string} expr - Metric expression
+ * @returns {Object} - AST node
+ */
+function parseMetric(expr) {
+  if (typeof expr !== 'string' || expr.trim() === '') {
+    throw new Error('Threshold metric must be a non-empty string');
+  }
+  const tokens = tokenize(expr);
+  let pos = 0;
+
+  const peek = () => tokens[pos];
+  const isOp = (...ops) => peek() && peek().kind === 'op' && ops.includes(peek().value);
+
+  function primary() {
+    const tok = tokens[pos++];
+    if (!tok) throw new Error(`Unexpected end of metric '${expr}'`);
+    if (tok.kind === 'num') return { kind: 'num', value: tok.value };
+    if (tok.kind === 'path') return { kind: 'path', value: tok.value };
+    if (tok.value === '(') {
+      const inner = additive();
+      if (!isOp(')')) throw new Error(`Missing ')' in metric '${expr}'`);
+      pos++;
+      return inner;
+    }
+    if (tok.value === '-') return { kind: 'neg', arg: primary() };
+    throw new Error(`Unexpected '${tok.value}' in metric '${expr}'`);
+  }
+
+  function multiplicative() {
+    let node = primary();
+    while (isOp('*', '/')) {
+      const op = tokens[pos++].value;
+      node = { kind: 'bin', op, left: node, right: primary() };
+    }
+    return node;
+  }
+
+  function additive() {
+    let node = multiplicative();
+    while (isOp('+', '-')) {
+      const op = tokens[pos++].value;
+      node = { kind: 'bin', op, left: node, right: multiplicative() };
+    }
+    return node;
+  }
+
+  const ast = additive();
+  if (pos < tokens.length) throw new Error(`Unexpected trailing input in metric '${expr}'`);
+  return ast;
+}
+
+/**
+ * Collect every path referenced by an AST.
+ *
+ * @param {Object} node - AST node
+ * @param {Array<Array<string>>} out - Accumulator
+ * @returns {Array<Array<string>>} - Referenced paths
+ */
+function collectPaths(node, out = []) {
+  if (node.kind === 'path') out.push(node.value);
+  if (node.kind === 'neg') collectPaths(node.arg, out);
+  if (node.kind === 'bin') {
+    collectPaths(node.left, out);
+    collectPaths(node.right, out);
+  }
+  return out;
+}
+
+/**
+ * Check a path against the known result schema.
+ *
+ * @param {Array<string>} segments - Path segments
+ * @returns {boolean} - True if the path names a numeric result field
+ */
+function isKnownPath(segments) {
+  let node = METRIC_SCHEMA;
+  for (const seg of segments) {
+    if (node === true || !node) return false;
+    node = Object.prototype.hasOwnProperty.call(node, seg) ? node[seg] : node['*'];
+  }
+  return node === true;
+}
+
+/**
+ * Evaluate an AST against one iteration's results.
+ * Missing keyed entries (e.g. a mission type with no demand) evaluate to 0.
+ *
+ * @param {Object} node - AST node
+ * @param {Object} result - DES result object
+ * @returns {number} - Value (may be NaN or Infinity after division by zero)
+ */
+function evaluate(node, result) {
+  switch (node.kind) {
+    case 'num':
+      return node.value;
+    case 'path': {
+      const value = node.value.reduce((o, p) => (o == null ? undefined : o[p]), result);
+      return typeof value === 'number' ? value : 0;
+    }
+    case 'neg':
+      return -evaluate(node.arg, result);
+    default: {
+      const a = evaluate(node.left, result);
+      const b = evaluate(node.right, result);
+      if (node.op === '+') return a + b;
+      if (node.op === '-') return a - b;
+      if (node.op === '*') return a * b;
+      return a / b;
+    }
+  }
+}
+
+/**
+ * Parse a metric expression and check every path it references against the result schema.
+ *
+ * @param {string} expr - Metric expression
+ * @param {string} where - Location, for error messages
+ * @returns {Object} - AST node, for evaluate
+ */
+function compileMetric(expr, where) {
+  let ast;
+  try {
+    ast = parseMetric(expr);
+  } catch (error) {
+    throw new Error(`${where}: ${error.message}`);
+  }
+  for (const segments of collectPaths(ast)) {
+    if (!isKnownPath(segments)) {
+      throw new Error(`${where}: unknown metric path '${segments.join('.')}'`);
+    }
+  }
+  return ast;
+}
+
+/**
+ * Validate and compile threshold conditions. Throws on the first invalid one so the
+ * caller can fail before any iteration runs.
+ *
+ * @param {Array<Object>} thresholds - Conditions ({ metric, op, value })
+ * @returns {Array<Object>} - Compiled conditions ({ key, ast, test, value })
+ */
+function compileThresholds(thresholds) {
+  if (!Array.isArray(thresholds)) {
+    throw new Error('thresholds must be an array of { metric, op, value }');
+  }
+  return thresholds.map((t, i) => {
+    const where = `thresholds[${i}]`;
+    if (!t || typeof t !== 'object') throw new Error(`${where} must be an object`);
+    const op = t.op || '>=';
+    if (!OPERATORS[op]) {
+      throw new Error(`${where}: unsupported op '${op}' (expected one of ${Object.keys(OPERATORS).join(', ')})`);
+    }
+    if (typeof t.value !== 'number' || !Number.isFinite(t.value)) {
+      throw new Error(`${where}: value must be a finite number`);
+    }
+    const ast = compileMetric(t.metric, where);
+    return { key: `${t.metric.trim()} ${op} ${t.value}`, ast, test: OPERATORS[op], value: t.value };
+  });
+}
+
+/**
+ * Fraction of iterations satisfying each compiled condition.
+ * Iterations where the metric is not a finite number (e.g. 0 / 0) count as not satisfied.
+ *
+ * @param {Array<Object>} compiled - Output of compileThresholds
+ * @param {Array<Object>} iterations - DES result objects
+ * @returns {Object} - threshold key → probability in [0, 1]
+ */
+function thresholdProbabilities(compiled, iterations) {
+  const out = {};
+  for (const c of compiled) {
+    let hits = 0;
+    for (const iter of iterations) {
+      const v = evaluate(c.ast, iter);
+      if (Number.isFinite(v) && c.test(v, c.value)) hits++;
+    }
+    out[c.key] = iterations.length > 0 ? Number((hits / iterations.length).toFixed(4)) : 0;
+  }
+  return out;
+}
+
+module.exports = { compileThresholds, thresholdProbabilities, compileMetric, evaluate };
@@ -0,0 +1,212 @@
+/**
+ * MONTE CARLO INPUT UNCERTAINTY
+ *
+ * Iteration-level uncertainty in the inputs themselves ("8 ± 2 aircraft available"),
+ * on top of the per-event randomness inside each DES run.
+ *
+ * An entry is { path, dist }: path names a numeric override field, e.g.
+ *   'overrides.units.VMU-1.aircraft'
+ *   'overrides.units.VMU-1.payload_by_type.SkyTower II'
+ * and dist is a DES distribution spec. Each iteration draws every entry once and runs
+ * the DES with the drawn values written into its overrides.
+ */
+
+const { sampleDist, distQuantile, DISTRIBUTION_TYPES } = require('../des/helpers/distributions');
+const { setSeed, restoreRng, deriveSeed, deriveStreamSeed, makeRng, random } = require('../des/helpers/random');
+
+// Per-unit override counts (overrides.units.<unit>.<field>)
+const COUNT_FIELDS = new Set(['aircraft', 'pilot', 'so', 'intel', 'payload_per_type']);
+// Per-unit override maps (overrides.units.<unit>.<map>.<key>)
+const COUNT_MAPS = new Set(['payload_by_type', 'crew_by_role']);
+
+// Sub-stream of the iteration seed used for input draws (stream 1 is the PERT simulate settings)
+const INPUT_UNCERTAINTY_STREAM = 2;
+
+// Stream of the Monte Carlo seed (deriveStreamSeed) used to shuffle Latin hypercube strata
+const LHS_STREAM = 0x4C48;
+
+const INPUT_SAMPLING = ['random', 'lhs'];
+
+/**
+ * Split a path into segments. Dots separate segments; pass an array for names containing dots.
+ *
+ * @param {string|Array<string>} path - Override path
+ * @returns {Array<string>} - Segments
+ */
+function pathSegments(path) {
+  return Array.isArray(path) ? path.map(String) : String(path).split('.');
+}
+
+/**
+ * Resolve a path to a per-unit numeric override field.
+ *
+ * @param {string|Array<string>} path - e.g. 'overrides.units.VMU-1.aircraft'
+ * @returns {Object|null} - { key, unit, field, mapKey } or null if the path is not a numeric override field
+ */
+function parseOverridePath(path) {
+  const segments = pathSegments(path);
+  const [root, units, unit, field, mapKey] = segments;
+  const isCount = segments.length === 4 && COUNT_FIELDS.has(field);
+  const isMapEntry = segments.length === 5 && COUNT_MAPS.has(field) && mapKey !== '';
+  if (root !== 'overrides' || units !== 'units' || !unit || !(isCount || isMapEntry)) return null;
+  return { key: segments.join('.'), unit, field, mapKey: isMapEntry ? mapKey : null };
+}
+
+/**
+ * Error message for a path that is not a numeric override field.
+ *
+ * @param {string} where - Entry location, for the message
+ * @param {string|Array<string>} path - Offending path
+ * @returns {string}
+ */
+function overridePathError(where, path) {
+  return `${where}: path '${pathSegments(path).join('.')}' is not a numeric override field ` +
+    `(expected overrides.units.<unit>.<${[...COUNT_FIELDS].join('|')}> or overrides.units.<unit>.<${[...COUNT_MAPS].join('|')}>.<name>)`;
+}
+
+/**
+ * Set a parsed override field to a value in a copy of the overrides.
+ *
+ * @param {Object|null} overrides - Overrides (not modified)
+ * @param {Object} target - Output of parseOverridePath
+ * @param {number} value - Value to write
+ * @returns {Object} - Updated overrides
+ */
+function setOverride(overrides, target, value) {
+  const out = overrides ? JSON.parse(JSON.stringify(overrides)) : {};
+  out.units = out.units || {};
+  const unit = out.units[target.unit] = out.units[target.unit] || {};
+  if (target.mapKey === null) {
+    unit[target.field] = value;
+  } else {
+    unit[target.field] = unit[target.field] || {};
+    unit[target.field][target.mapKey] = value;
+  }
+  return out;
+}
+
+/**
+ * Current value of a parsed override field, if set.
+ *
+ * @param {Object|null} overrides - Overrides
+ * @param {Object} target - Output of parseOverridePath
+ * @returns {*} - Value, or undefined
+ */
+function getOverride(overrides, target) {
+  const unit = overrides?.units?.[target.unit];
+  return target.mapKey === null ? unit?.[target.field] : unit?.[target.field]?.[target.mapKey];
+}
+
+/**
+ * Validate input uncertainty entries. Throws on the first invalid one so the caller
+ * can fail before any iteration runs.
+ *
+ * @param {Array<Object>} entries - Entries ({ path, dist })
+ * @returns {Array<Object>} - Compiled entries ({ key, unit, field, mapKey, dist })
+ */
+function compileInputUncertainty(entries) {
+  if (!Array.isArray(entries)) {
+    throw new Error('inputUncertainty must be an array of { path, dist }');
+  }
+  const seen = new Set();
+  return entries.map((entry, i) => {
+    const where = `inputUncertainty[${i}]`;
+    if (!entry || typeof entry !== 'object') throw new Error(`${where} must be an object`);
+    const target = parseOverridePath(entry.path);
+    if (!target) throw new Error(overridePathError(where, entry.path));
+    if (seen.has(target.key)) throw new Error(`${where}: path '${target.key}' appears more than once`);
+    seen.add(target.key);
+
+    const dist = entry.dist;
+    if (!dist || typeof dist !== 'object' || !DISTRIBUTION_TYPES.includes(dist.type || 'deterministic')) {
+      throw new Error(`${where}: dist must be a distribution spec (${DISTRIBUTION_TYPES.join(', ')})`);
+    }
+    return { ...target, dist };
+  });
+}
+
+/**
+ * Latin hypercube design: each entry's [0, 1) range is cut into one stratum per iteration and the
+ * strata are shuffled independently per entry, so every stratum is used by exactly one iteration.
+ *
+ * @param {Array<Object>} compiled - Output of compileInputUncertainty
+ * @param {number} iterations - Number of iterations (strata per entry)
+ * @param {number|null} seed - Monte Carlo seed (null shuffles with Math.random)
+ * @returns {Object} - { iterations, strata: path → Array<number> (iteration index → stratum) }
+ */
+function latinHypercube(compiled, iterations, seed) {
+  const rng = seed === null ? Math.random : makeRng(deriveStreamSeed(seed, LHS_STREAM));
+  const strata = {};
+  for (const c of compiled) {
+    const perm = Array.from({ length: iterations }, (_, i) => i);
+    for (let i = perm.length - 1; i > 0; i--) {
+      const j = Math.floor(rng() * (i + 1));
+      [perm[i], perm[j]] = [perm[j], perm[i]];
+    }
+    strata[c.key] = perm;
+  }
+  return { iterations, strata };
+}
+
+/**
+ * Draw every entry once for an iteration. Values are whole, non-negative counts, as the DES applies them.
+ * With a Latin hypercube design the draw is taken uniformly inside the iteration's stratum.
+ *
+ * @param {Array<Object>} compiled - Output of compileInputUncertainty
+ * @param {number|null} iterationSeed - The iteration's seed (null for unseeded runs)
+ * @param {Object|null} design - Optional output of latinHypercube
+ * @param {number} iterationIndex - Zero-based iteration index (with a design)
+ * @returns {Object} - path → sampled value
+ */
+function sampleInputs(compiled, iterationSeed, design = null, iterationIndex = 0) {
+  const previous = setSeed(iterationSeed === null ? null : deriveSeed(iterationSeed, INPUT_UNCERTAINTY_STREAM));
+  try {
+    return Object.fromEntries(compiled.map(c => {
+      const value = design
+        ? distQuantile(c.dist, (design.strata[c.key][iterationIndex] + random()) / design.iterations)
+        : sampleDist(c.dist);
+      return [c.key, Math.max(0, Math.floor(value))];
+    }));
+  } finally {
+    restoreRng(previous);
+  }
+}
+
+/**
+ * Check options.inputSampling.
+ *
+ * @param {*} inputSampling - Candidate value (undefined means 'random')
+ * @returns {string} - 'random' or 'lhs'
+ */
+function validateInputSampling(inputSampling) {
+  const value = inputSampling ?? 'random';
+  if (!INPUT_SAMPLING.includes(value)) {
+    throw new Error(`inputSampling must be one of ${INPUT_SAMPLING.join(', ')}, got ${value}`);
+  }
+  return value;
+}
+
+/**
+ * Write sampled values into a copy of the overrides.
+ *
+ * @param {Array<Object>} compiled - Output of compileInputUncertainty
+ * @param {Object|null} overrides - Iteration overrides (not modified)
+ * @param {Object} inputs - Output of sampleInputs
+ * @returns {Object} - Overrides with the sampled values applied
+ */
+function applyInputs(compiled, overrides, inputs) {
+  return compiled.reduce((out, c) => setOverride(out, c, inputs[c.key]), overrides ? JSON.parse(JSON.stringify(overrides)) : { units: {} });
+}
+
+module.exports = {
+  compileInputUncertainty,
+  sampleInputs,
+  applyInputs,
+  latinHypercube,
+  validateInputSampling,
+  parseOverridePath,
+  overridePathError,
+  setOverride,
+  getOverride,
+  pathSegments
+};
@@ -30,7 +30,7 @@ if (getLogLevel() !== logLevel) {
 }
 
 // Now require DES engine (it will use the log level we just set)
-const { runSimulation } = require('../des/engine');
+const { runSimulation, runSimulationSequence } = require('../des/engine');
 
 /**
  * Run a single DES simulation
@@ -44,10 +44,15 @@ async function runSingleSimulation() {
     }
     
     // Run simulation (log level already set above to 'silent' by default)
-    const result = await runSimulation(scenario, settings);
+    const startedAt = performance.now();
+    // An array of scenarios is one runSimulationSequence iteration (runMonteCarloSequence)
+    const result = Array.isArray(scenario)
+      ? await runSimulationSequence(scenario, settings)
+      : await runSimulation(scenario, settings);
+    const wallMs = performance.now() - startedAt;
     
-    // Send result back to main thread
-    parentPort.postMessage({ success: true, result });
+    // Send result back to main thread, with the DES run's own wall time (excludes worker start-up)
+    parentPort.postMessage({ success: true, result, wallMs });
   } catch (error) {
     // Send error back to main thread
     parentPort.postMessage({ 
@@ -55,7 +60,12 @@ async function runSingleSimulation() {
       error: {
         message: error.message,
         stack: error.stack,
-        name: error.name
+        name: error.name,
+        // Structured fields from a tripped DES watchdog (settings.limits)
+        code: error.code,
+        limit: error.limit,
+        max: error.max,
+        counts: error.counts
       }
     });
   }
@@ -2,7 +2,7 @@
 // Tests only the public API contract and observable behavior
 // No knowledge of internal implementation details or data structures
 
-const { runSimulation, loadState } = require('../sim/des/engine');
+const { runSimulation, runSimulationSequence, replayAndVerify, previewDemand, sampleDistribution, summarizeScenario, summarizeResults, loadState } = require('../sim/des/engine');
 const { mockState, basicScenario } = require('./fixtures');
 
 describe('DES Engine - Blackbox Tests', () => {
@@ -367,6 +367,58 @@ describe('DES Engine - Blackbox Tests', () => {
       expect(result1.missions.requested).toBe(result2.missions.requested);
     });
 
+    test('same seed reproduces a stochastic run exactly', async () => {
+      const stochastic = {
+        ...basicScenario,
+        mission_types: [{ ...basicScenario.mission_types[0], flight_time: { type: 'triangular', a: 1, m: 2, b: 4 }, crew_distribution: 'random' }],
+        demand: [{ type: 'poisson', mission_type: 'ISR', rate_per_hour: 0.5 }]
+      };
+
+      const a = await runSimulation(stochastic, { state: mockState, seed: 42 });
+      const b = await runSimulation(stochastic, { state: mockState, seed: 42 });
+      const c = await runSimulation(stochastic, { state: mockState, seed: 43 });
+
+      expect(JSON.stringify(b)).toBe(JSON.stringify(a));
+      expect(JSON.stringify(c.timeline)).not.toBe(JSON.stringify(a.timeline));
+      await expect(runSimulation(stochastic, { state: mockState, seed: 1.5 })).rejects.toThrow('seed');
+    });
+
+    test('limits abort runaway runs with a structured error', async () => {
+      const runaway = {
+        ...basicScenario,
+        horizon_hours: 10000,
+        demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 0.01, start_at_hours: 0 }]
+      };
+
+      let error;
+      try {
+        await runSimulation(runaway, { state: mockState, limits: { maxEvents: 5000 } });
+      } catch (e) {
+        error = e;
+      }
+      expect(error.code).toBe('SIMULATION_LIMIT');
+      expect(error.limit).toBe('maxEvents');
+      expect(error.counts.events).toBe(5001);
+      expect(error.counts.processedEvents).toBe(0);
+
+      let timelineError;
+      try {
+        await runSimulation(basicScenario, { state: mockState, limits: { maxTimelineEvents: 2 } });
+      } catch (e) {
+        timelineError = e;
+      }
+      expect(timelineError.limit).toBe('maxTimelineEvents');
+      expect(timelineError.counts.timelineEvents).toBe(3);
+
+      // Generous limits leave the run untouched
+      const unlimited = await runSimulation(basicScenario, { state: mockState });
+      const limited = await runSimulation(basicScenario, { state: mockState, limits: { maxEvents: 1e6, maxTimelineEvents: 1e6, maxWallMs: 60000 } });
+      expect(limited.missions).toEqual(unlimited.missions);
+
+      await expect(runSimulation(basicScenario, { state: mockState, limits: { maxEvents: -1 } })).rejects.toThrow('positive number');
+      await expect(runSimulation(basicScenario, { state: mockState, limits: { maxEvent: 10 } })).rejects.toThrow('not a known limit');
+    });
+
     test('validates scenario configuration before running', async () => {
       const invalidScenario = {
         // Missing required fields
@@ -377,6 +429,334 @@ describe('DES Engine - Blackbox Tests', () => {
       await expect(runSimulation(invalidScenario, { state: mockState })).rejects.toThrow();
     });
 
+    test('rejects scenarios that cannot mean anything with INVALID_SCENARIO and the offending path', async () => {
+      const invalid = async scenario => runSimulation(scenario, { state: mockState }).catch(e => e);
+
+      for (const horizon_hours of [0, -24, Infinity, NaN]) {
+        const error = await invalid({ ...basicScenario, horizon_hours });
+        expect(error.code).toBe('INVALID_SCENARIO');
+        expect(error.path).toBe('horizon_hours');
+        expect(error.message).toContain('greater than 0');
+      }
+
+      const noTypes = await invalid({ ...basicScenario, mission_types: [] });
+      expect(noTypes.code).toBe('INVALID_SCENARIO');
+      expect(noTypes.path).toBe('mission_types');
+
+      const typo = await invalid({
+        ...basicScenario,
+        demand: [...basicScenario.demand, { type: 'deterministic', mission_type: 'ISRR', every_hours: 8 }]
+      });
+      expect(typo.path).toBe(`demand[${basicScenario.demand.length}].mission_type`);
+      expect(typo.message).toContain("'ISRR'");
+
+      // Checks made while pools and demand are built report the same way
+      const demand = { ...basicScenario.demand[0] };
+      const later = [
+        [{ unit_policy: { reservations: { 'HMLA-167': { aircraft: 1, for_mission_types: [] } } } }, "unit_policy.reservations['HMLA-167'].for_mission_types"],
+        [{ unit_policy: { crew_experience: { 'HMLA-167': 0 } } }, "unit_policy.crew_experience['HMLA-167']"],
+        [{ unit_policy: { crew_experience: { 'VMU-9': 1 } } }, "unit_policy.crew_experience['VMU-9']"],
+        [{ demand: [{ ...demand, unit: 'VMU-9' }] }, 'demand[0].unit'],
+        [{ demand: [{ ...demand, retry: { max_attempts: 0 } }] }, 'demand[0].retry.max_attempts'],
+        [{ demand: [{ ...demand, type: 'deterministic', rate_uncertainty: { type: 'triangular', a: 0.5, m: 1, b: 1.5 } }] }, 'demand[0].rate_uncertainty']
+      ];
+      for (const [patch, path] of later) {
+        const error = await invalid({ ...basicScenario, ...patch });
+        expect({ path, code: error.code, got: error.path }).toEqual({ path, code: 'INVALID_SCENARIO', got: path });
+      }
+
+      // Same checks before a preview
+      expect(() => previewDemand({ ...basicScenario, horizon_hours: 0 })).toThrow('horizon_hours');
+
+      // No demand needs no mission types
+      const idle = await runSimulation({ ...basicScenario, mission_types: [], demand: [] }, { state: mockState });
+      expect(idle.missions.requested).toBe(0);
+    });
+
+    test('sampleDistribution previews a spec and fails exactly where a run would', async () => {
+      const spec = { type: 'triangular', a: 60, m: 120, b: 240, unit: 'minutes' };
+      const preview = sampleDistribution(spec, 2000, { seed: 4, bins: 10 });
+
+      expect(preview.dist).toEqual({ type: 'triangular', a: 1, m: 2, b: 4 });
+      expect(preview.samples).toHaveLength(2000);
+      expect(preview.mean).toBeCloseTo(7 / 3, 1);
+      expect(preview.min).toBeGreaterThanOrEqual(1);
+      expect(preview.max).toBeLessThanOrEqual(4);
+      expect(preview.histogram.counts).toHaveLength(10);
+      expect(preview.histogram.edges).toHaveLength(11);
+      expect(preview.histogram.counts.reduce((a, b) => a + b, 0)).toBe(2000);
+      expect(sampleDistribution(spec, 2000, { seed: 4, bins: 10 })).toEqual(preview);
+
+      // A constant fills one bin
+      expect(sampleDistribution({ value: 3 }, 5).histogram).toEqual({ bin_width: 0, edges: [3, 3], counts: [5] });
+
+      // The same spec inside a scenario fails validation with the same message
+      const bad = { type: 'triangular', a: 3, m: 2, b: 4 };
+      const previewError = (() => { try { sampleDistribution(bad, 10); } catch (e) { return e; } })();
+      const runError = await runSimulation({
+        ...basicScenario,
+        mission_types: [{ ...basicScenario.mission_types[0], flight_time: bad }]
+      }, { state: mockState }).catch(e => e);
+      expect(previewError.code).toBe('INVALID_SCENARIO');
+      expect(runError.code).toBe('INVALID_SCENARIO');
+      expect(runError.path).toBe('mission_types[0].flight_time');
+      expect(runError.message.replace('mission_types[0].flight_time', 'dist')).toBe(previewError.message);
+
+      expect(() => sampleDistribution({ type: 'gamma' }, 10)).toThrow("dist.type must be one of");
+      expect(() => sampleDistribution({ value: 1, value_hours: 1 }, 10)).toThrow('sets both value and value_hours');
+      expect(() => sampleDistribution({ value: 1 }, 0)).toThrow('n must be an integer from 1 to 100000');
+    });
+
+    test('warns instead of silently rejecting everything when no unit has aircraft', async () => {
+      const grounded = { units: { 'HMLA-167': { aircraft: 0 }, 'HMLA-267': { aircraft: 0 } } };
+      const results = await runSimulation(basicScenario, { state: mockState, overrides: grounded });
+
+      expect(results.missions.completed).toBe(0);
+      expect(results.warnings).toEqual([
+        expect.objectContaining({ code: 'NO_AIRCRAFT' }),
+        expect.objectContaining({ code: 'INFEASIBLE_MISSION_TYPE', mission_type: 'ISR' })
+      ]);
+
+      const normal = await runSimulation(basicScenario, { state: mockState });
+      expect(normal.warnings).toBeUndefined();
+    });
+
+    test('a mission_split with zero total falls back to round robin with a warning', async () => {
+      const scenario = { ...basicScenario, unit_policy: { mission_split: { 'HMLA-167': 0, 'HMLA-267': 0 } } };
+      const results = await runSimulation(scenario, { state: mockState, seed: 1 });
+
+      // Every demand is still assigned, alternating in state order
+      expect(results.missions).toMatchObject({ requested: 3, started: 3 });
+      expect(results.timeline.filter(e => e.type === 'mission').map(e => e.unit)).toEqual(['HMLA-167', 'HMLA-267', 'HMLA-167']);
+      expect(results.warnings).toEqual([expect.objectContaining({ code: 'MISSION_SPLIT_ZERO_TOTAL' })]);
+
+      const unknown = await runSimulation({ ...basicScenario, unit_policy: { mission_split: { 'HMLA-167': 1, 'VMU-9': 1 } } }, { state: mockState }).catch(e => e);
+      expect(unknown.message).toContain("names unknown unit 'VMU-9'");
+      expect(unknown.code).toBe('INVALID_SCENARIO');
+      expect(unknown.path).toBe('unit_policy.mission_split.VMU-9');
+      const invalid = await runSimulation({ ...basicScenario, unit_policy: { mission_split: { 'HMLA-167': -1 } } }, { state: mockState }).catch(e => e);
+      expect(invalid.code).toBe('INVALID_SCENARIO');
+      expect(invalid.path).toBe('unit_policy.mission_split.HMLA-167');
+    });
+
+    test('round robin follows state order for integer-like unit names, run after run', async () => {
+      // Object.keys would list '267' before '1670'; the state lists units sorted
+      const state = JSON.parse(JSON.stringify(mockState).replace(/HMLA-167/g, '1670').replace(/HMLA-267/g, '267'));
+      const { unit_policy: _split, ...scenario } = basicScenario;
+      const runs = [];
+      for (let i = 0; i < 3; i++) runs.push(await runSimulation(scenario, { state, seed: 11, emitResourceLevels: true }));
+
+      expect(runs[0].initial_resources.units).toEqual(['1670', '267']);
+      expect(runs[0].timeline.filter(e => e.type === 'mission').map(e => e.unit)).toEqual(['1670', '267', '1670']);
+      // Results list units in the same order: resource levels at t=0 come unit by unit
+      const levelUnits = runs[0].timeline.filter(e => e.type === 'resource_level' && e.time === 0).map(e => e.unit);
+      expect([...new Set(levelUnits)]).toEqual(['1670', '267']);
+      for (const run of runs.slice(1)) {
+        expect(run.utilization).toEqual(runs[0].utilization);
+        expect(run.in_progress_by_unit).toEqual(runs[0].in_progress_by_unit);
+      }
+    });
+
+    test('scores completions flown with a fallback crew composition as degraded', async () => {
+      // HMLA-167 has one SO, so it falls back to two pilots; HMLA-267 flies the preferred composition
+      const mission = { ...basicScenario.mission_types[0], crew_flex: { allowed_compositions: [{ pilot: 1, so: 2 }, { pilot: 2, so: 0 }] } };
+      const scenario = { ...basicScenario, mission_types: [mission], quality_penalties: { crew_flex: 0.25 } };
+      const results = await runSimulation(scenario, { state: mockState, seed: 1, missionDetail: true });
+
+      expect(results.missions.completed).toBe(3);
+      expect(results.completion_quality).toEqual({
+        mean: 0.833,
+        degraded: 2,
+        by_type: { ISR: { completed: 3, mean: 0.833, degraded: 2 } }
+      });
+      expect(results.missions_detail.map(row => [row.unit, row.quality])).toEqual([
+        ['HMLA-167', 0.75], ['HMLA-167', 0.75], ['HMLA-267', 1]
+      ]);
+
+      // Off unless the scenario sets penalties
+      const plain = await runSimulation({ ...scenario, quality_penalties: undefined }, { state: mockState, seed: 1, missionDetail: true });
+      expect(plain.completion_quality).toBeUndefined();
+      expect(plain.missions_detail[0].quality).toBeUndefined();
+
+      const unknown = await runSimulation({ ...scenario, quality_penalties: { payload_substitution: 0.5 } }, { state: mockState }).catch(e => e);
+      expect(unknown.code).toBe('INVALID_SCENARIO');
+      expect(unknown.path).toBe('quality_penalties.payload_substitution');
+      await expect(runSimulation({ ...scenario, quality_penalties: { crew_flex: 2 } }, { state: mockState })).rejects.toThrow('between 0 and 1');
+    });
+
+    test('counts demands no unit could ever fly as infeasible, not rejected', async () => {
+      const scenario = {
+        ...basicScenario,
+        mission_types: [
+          ...basicScenario.mission_types,
+          // More pilots than any unit has
+          { name: 'Heavy', flight_time: { type: 'deterministic', value_hours: 1 }, required_aircrew: { pilot: 40, so: 1 }, required_payload_types: [] }
+        ],
+        demand: [...basicScenario.demand, { type: 'deterministic', mission_type: 'Heavy', every_hours: 6, start_at_hours: 1 }]
+      };
+      const results = await runSimulation(scenario, { state: mockState, missionDetail: true });
+
+      expect(results.by_type.Heavy).toMatchObject({ requested: 4, started: 0, rejected: 0, infeasible: 4 });
+      expect(results.by_type.ISR.infeasible).toBeUndefined();
+      expect(results.missions.infeasible).toBe(4);
+      expect(results.missions.requested).toBe(results.missions.started + results.missions.rejected + results.missions.infeasible);
+      expect(results.rejections.pilot).toBe(0);
+      expect(results.infeasible_by_type).toEqual({
+        Heavy: { missions: 4, reasons_by_unit: { 'HMLA-167': ['pilot'], 'HMLA-267': ['pilot'] } }
+      });
+      expect(results.warnings).toEqual([expect.objectContaining({ code: 'INFEASIBLE_MISSION_TYPE', mission_type: 'Heavy' })]);
+      expect(results.missions_detail.filter(row => row.outcome === 'infeasible')).toHaveLength(4);
+
+      // Feasible on one unit is feasible: demands sent to the unit that cannot fly it are ordinary rejections
+      const oneUnit = await runSimulation(scenario, {
+        state: mockState, overrides: { units: { 'HMLA-267': { pilot: 40 } } }
+      });
+      expect(oneUnit.infeasible_by_type).toBeUndefined();
+      expect(oneUnit.missions.infeasible).toBeUndefined();
+      expect(oneUnit.rejections_by_unit['HMLA-167'].pilot).toBeGreaterThan(0);
+    });
+
+    test('reads old field names as their canonical fields and warns about fields the type ignores', async () => {
+      const legacy = {
+        ...basicScenario,
+        mission_types: [{ ...basicScenario.mission_types[0], flight_time: { type: 'deterministic', value: 2, transit_in_hours: 0.5, transit_out_hours: 0.5 } }],
+        demand: [{ type: 'deterministic', mission_type: 'ISR', interval_hours: 8, start_at_hours: 0, rate: 3 }]
+      };
+      const results = await runSimulation(legacy, { state: mockState });
+      const canonical = await runSimulation(basicScenario, { state: mockState });
+
+      expect(results.missions).toEqual(canonical.missions);
+      expect(results.timeline).toEqual(canonical.timeline);
+      expect(results.warnings).toEqual([{
+        code: 'IRRELEVANT_FIELD',
+        path: 'demand[0].rate_per_hour',
+        message: "demand[0].rate_per_hour is only used by poisson entries and is ignored for type 'deterministic'"
+      }]);
+
+      const both = { ...basicScenario, demand: [{ ...basicScenario.demand[0], interval_hours: 4 }] };
+      await expect(runSimulation(both, { state: mockState })).rejects.toThrow('demand[0] sets both interval_hours and every_hours');
+    });
+
+    test('summarizes a scenario with its policies and warnings without running it', () => {
+      const scenario = { ...basicScenario, payload_policy: { daily_limits: { 'SkyTower II': 2 } } };
+      const grounded = { units: { 'HMLA-167': { aircraft: 0 }, 'HMLA-267': { aircraft: 0 } } };
+
+      const summary = summarizeScenario(scenario, { state: mockState, overrides: grounded });
+      expect(summary).toContain('Scenario: 24h horizon');
+      expect(summary).toContain('  HMLA-167: aircraft 0;');
+      expect(summary).toContain('payload_policy.daily_limits (SkyTower II 2)');
+      expect(summary).toContain('Warnings: NO_AIRCRAFT: ');
+
+      expect(summarizeScenario(scenario)).toContain('Units: no state snapshot');
+      expect(() => summarizeScenario({ ...scenario, horizon_hours: -1 })).toThrow('horizon_hours');
+    });
+
+    test('summarizes stored results into a capped timeline that keeps every rejection', async () => {
+      const scenario = {
+        ...basicScenario,
+        horizon_hours: 240,
+        demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 1, start_at_hours: 0 }]
+      };
+      const stored = JSON.parse(JSON.stringify(await runSimulation(scenario, { state: mockState, seed: 3, missionDetail: true })));
+      const countByType = timeline => timeline.reduce((acc, e) => ({ ...acc, [e.type]: (acc[e.type] || 0) + 1 }), {});
+      const rejections = stored.timeline.filter(e => e.type === 'rejection').length;
+
+      const summary = summarizeResults(stored, { maxTimelineEvents: rejections + 40, bucketHours: 24 });
+
+      expect(summary.missions).toEqual(stored.missions);
+      expect(summary.utilization).toEqual(stored.utilization);
+      expect(summary.missions_detail).toBeUndefined();
+      expect(summary.timeline.length).toBe(rejections + 40);
+      expect(summary.timeline.filter(e => e.type === 'rejection')).toEqual(stored.timeline.filter(e => e.type === 'rejection'));
+      // Bucket counts add back up to the original timeline, kept counts to the sampled one
+      const meta = summary.timeline_summary;
+      const sums = table => Object.fromEntries(Object.entries(table).map(([type, byBucket]) => [type, byBucket.reduce((a, b) => a + b, 0)]));
+      expect(meta.original_events).toBe(stored.timeline.length);
+      expect(meta.counts.mission.length).toBe(10);
+      expect(sums(meta.counts)).toEqual(countByType(stored.timeline));
+      expect(sums(meta.kept)).toEqual(countByType(summary.timeline));
+      // Missions are sampled across every bucket, not just the first ones
+      expect(meta.kept.mission.every(n => n > 0)).toBe(true);
+
+      expect(summarizeResults(stored).timeline.length).toBe(stored.timeline.length);
+      expect(() => summarizeResults(stored, { bucketHours: 0 })).toThrow('bucketHours');
+    });
+
+    test('replayAndVerify reproduces a stored seeded result and reports tampered fields', async () => {
+      const scenario = { ...basicScenario, demand: [{ type: 'poisson', mission_type: 'ISR', rate_per_hour: 0.5 }] };
+      const settings = { state: mockState, seed: 77, logLevel: 'silent' };
+      const stored = JSON.parse(JSON.stringify(await runSimulation(scenario, settings)));
+
+      // The seed comes from resolved_config when the caller does not pass one
+      const verified = await replayAndVerify(scenario, { state: mockState }, stored);
+      expect(verified).toEqual({ match: true, seed: 77, differences: [] });
+
+      const tampered = JSON.parse(JSON.stringify(stored));
+      tampered.missions.completed += 1;
+      tampered.utilization['HMLA-167'].aircraft += 1e-6;
+      delete tampered.peak_window;
+      const checked = await replayAndVerify(scenario, { state: mockState }, tampered);
+      expect(checked.match).toBe(false);
+      expect(checked.differences).toEqual(expect.arrayContaining([
+        { path: '$.missions.completed', kind: 'changed', expected: stored.missions.completed + 1, actual: stored.missions.completed },
+        expect.objectContaining({ path: '$.utilization.HMLA-167.aircraft', kind: 'changed' }),
+        expect.objectContaining({ path: '$.peak_window', kind: 'added' })
+      ]));
+      // Float noise within the tolerance is not a difference
+      const noisy = JSON.parse(JSON.stringify(stored));
+      noisy.utilization['HMLA-167'].aircraft *= 1 + 1e-12;
+      expect((await replayAndVerify(scenario, { state: mockState }, noisy)).match).toBe(true);
+
+      // Other inputs than the stored run's do not reproduce it
+      const otherInputs = await replayAndVerify(scenario, { state: mockState, seed: 78 }, stored);
+      expect(otherInputs.match).toBe(false);
+
+      const { resolved_config: _config, ...unseeded } = stored;
+      await expect(replayAndVerify(scenario, { state: mockState }, unseeded)).rejects.toThrow('needs the seed');
+    });
+
+    test('runSimulationSequence carries holds and flight hours across day boundaries', async () => {
+      // Demand at 6, 14 and 22: the last sortie of each day (4h, 2.5h past midnight) runs into the next
+      const day = { ...basicScenario, demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 8, start_at_hours: 6 }] };
+      const { days, summary } = await runSimulationSequence([day, day, day], { state: mockState, seed: 5, logLevel: 'silent' });
+
+      expect(days).toHaveLength(3);
+      expect(days[0].initial_resources.carried_holds).toBeUndefined();
+      expect(days[0].missions.in_progress_at_horizon).toBe(1);
+      const carried = days[0].end_state.units['HMLA-267'].holds;
+      expect(carried.aircraft).toEqual([2.5]);
+      expect(carried.pilot).toEqual([2.5]);
+      expect(carried.payloads['SkyTower II']).toEqual([2.5]);
+      expect(days[1].initial_resources.carried_holds).toBe(4);
+
+      // Each ISR sortie is airborne 3h (transit in, on station, transit out)
+      expect(days[2].end_state.units['HMLA-167'].flight_hours + days[2].end_state.units['HMLA-267'].flight_hours).toBe(27);
+      expect(summary.days).toBe(3);
+      expect(summary.horizon_hours).toBe(72);
+      expect(summary.flight_hours).toEqual({ 'HMLA-167': 18, 'HMLA-267': 9 });
+      // Carried missions finish the next day, so only the last day's stays in progress
+      expect(summary.missions).toMatchObject({ requested: 9, completed: 8, in_progress_at_horizon: 1 });
+      expect(summary.by_type.ISR).toMatchObject({ requested: 9, completed: 8, in_progress_at_horizon: 1 });
+
+      // Carried holds block their resources on the next day: the 1h demand finds the single payload still held
+      const single = {
+        ...day,
+        demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 24, start_at_hours: 1 }],
+        unit_policy: { mission_split: { 'HMLA-267': 1 } }
+      };
+      const fresh = await runSimulation(single, { state: mockState, seed: 5, logLevel: 'silent' });
+      const seeded = await runSimulation(single, { state: mockState, seed: 5, logLevel: 'silent', initialState: days[0].end_state });
+      expect(fresh.missions.completed).toBe(1);
+      expect(seeded.missions.rejected).toBe(1);
+      expect(seeded.rejections.payload).toBe(1);
+
+      await expect(runSimulationSequence([], { state: mockState })).rejects.toThrow('non-empty array');
+      await expect(runSimulation(day, { state: mockState, logLevel: 'silent', initialState: { units: { 'HMLA-999': { holds: {} } } } }))
+        .rejects.toThrow("unknown unit 'HMLA-999'");
+      await expect(runSimulation(day, { state: mockState, logLevel: 'silent', initialState: { units: { 'HMLA-267': { holds: { aircraft: [-1] } } } } }))
+        .rejects.toThrow('positive hours');
+    });
+
     test('resource constraints affect mission success rate', async () => {
       const constrainedScenario = {
         ...basicScenario,
@@ -395,5 +775,59 @@ describe('DES Engine - Blackbox Tests', () => {
       // With limited resources and high demand, some missions should be rejected
       expect(result.missions.rejected).toBeGreaterThan(0);
     });
+
+    test('streaming the timeline in chunks yields the same events and summary', async () => {
+      const busyScenario = {
+        ...basicScenario,
+        demand: [
+          { type: 'deterministic', mission_type: 'ISR', every_hours: 1, start_at_hours: 0 }
+        ]
+      };
+
+      const full = await runSimulation(busyScenario, { state: mockState });
+      const chunks = [];
+      const streamed = await runSimulation(busyScenario, {
+        state: mockState,
+        timelineChunkSize: 5,
+        onTimelineChunk: events => chunks.push(events)
+      });
+
+      expect(streamed.timeline).toBeUndefined();
+      expect(chunks.slice(0, -1).every(c => c.length === 5)).toBe(true);
+      expect(chunks.flat()).toEqual(full.timeline);
+      expect(streamed.timeline_events).toBe(full.timeline.length);
+      expect(streamed.missions).toEqual(full.missions);
+      expect(streamed.by_type).toEqual(full.by_type);
+    });
+
+    test('mission detail rows carry stable IDs that join to the timeline', async () => {
+      const constrainedScenario = {
+        ...basicScenario,
+        demand: [
+          { type: 'deterministic', mission_type: 'ISR', every_hours: 1, start_at_hours: 0 }
+        ]
+      };
+
+      const plain = await runSimulation(constrainedScenario, { state: mockState });
+      expect(plain.missions_detail).toBeUndefined();
+
+      const result = await runSimulation(constrainedScenario, { state: mockState, missionDetail: true });
+      const rows = result.missions_detail;
+      expect(rows.length).toBe(result.missions.requested);
+      expect(rows.map(r => r.mission_id)).toEqual(rows.map((_, i) => i + 1));
+
+      const byId = new Map(rows.map(r => [r.mission_id, r]));
+      for (const item of result.timeline.filter(e => e.type === 'mission' || e.type === 'rejection')) {
+        const row = byId.get(item.mission_id);
+        expect(row.unit).toBe(item.unit);
+        expect(row.outcome).toBe(item.type === 'mission' ? 'started' : 'rejected');
+        if (item.type === 'rejection') expect(row.reason).toBe(item.reason);
+        if (item.type === 'mission') {
+          expect(row.finish_time).toBe(item.finish_time);
+          expect(row.resources.pilots).toEqual(item.crew.pilots.map(a => a.id));
+        }
+      }
+      expect(rows.filter(r => r.completed).length).toBe(result.missions.completed);
+    });
   });
 });
@@ -0,0 +1,291 @@
+// Property Tests for DES Engine
+// Generates small random scenarios and checks conservation invariants that must always hold
+// Uses a seeded generator so any failing case can be reproduced from its case number
+
+const { runSimulation } = require('../sim/des/engine');
+const { runMonteCarlo } = require('../sim/monte/engine');
+const { mockState } = require('./fixtures');
+
+const CASES = 40;
+const EPS = 1e-9;
+
+/**
+ * Small seeded PRNG (mulberry32) so generated cases are reproducible
+ * @param {number} seed - Integer seed
+ * @returns {Function} Generator returning floats in [0, 1)
+ */
+function makeRng(seed) {
+  let a = seed >>> 0;
+  return function () {
+    a = (a + 0x6D2B79F5) >>> 0;
+    let t = a;
+    t = Math.imul(t ^ (t >>> 15), t | 1);
+    t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+    return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+  };
+}
+
+const PAYLOAD_TYPES = ['SkyTower II', 'Hellfire', 'EW Pod'];
+const UNITS = ['HMLA-167', 'HMLA-267', 'HMLA-367'];
+
+/**
+ * Generate a random scenario and matching overrides
+ * @param {number} caseNumber - Case number used as the generator seed
+ * @returns {Object} { scenario, settings }
+ */
+function generateCase(caseNumber) {
+  const rng = makeRng(caseNumber + 1);
+  const int = (lo, hi) => lo + Math.floor(rng() * (hi - lo + 1));
+  const pick = arr => arr[int(0, arr.length - 1)];
+
+  const unitCount = int(1, UNITS.length);
+  const units = UNITS.slice(0, unitCount);
+  const overrides = { units: {} };
+  for (const unit of units) {
+    const payloadByType = {};
+    for (const ptype of PAYLOAD_TYPES) payloadByType[ptype] = int(0, 3);
+    overrides.units[unit] = { aircraft: int(0, 4), pilot: int(0, 5), so: int(0, 5), intel: int(0, 2), payload_by_type: payloadByType };
+  }
+
+  const missionTypes = [];
+  const typeCount = int(1, 3);
+  for (let i = 0; i < typeCount; i++) {
+    const payloads = PAYLOAD_TYPES.filter(() => rng() < 0.4);
+    missionTypes.push({
+      name: `MT${i}`,
+      flight_time: rng() < 0.5
+        ? { type: 'deterministic', value_hours: int(1, 6), transit_in_hours: int(0, 1) * 0.5, transit_out_hours: int(0, 1) * 0.5 }
+        : { type: 'triangular', a: 1, m: 2 + rng() * 2, b: 6 },
+      required_aircrew: { pilot: int(0, 2), so: int(0, 2), intel: int(0, 1) },
+      required_payload_types: payloads,
+      crew_distribution: pick(['concentrate', 'rotate', 'random'])
+    });
+  }
+
+  const demand = missionTypes.map(mt => rng() < 0.5
+    ? { type: 'deterministic', mission_type: mt.name, every_hours: int(1, 6), start_at_hours: int(0, 3) }
+    : { type: 'poisson', mission_type: mt.name, rate_per_hour: 0.2 + rng() });
+
+  const split = {};
+  if (rng() < 0.5) {
+    for (const unit of units) split[unit] = int(1, 4);
+  }
+
+  const mountTimes = {};
+  for (const ptype of PAYLOAD_TYPES) mountTimes[ptype] = { type: 'deterministic', value_hours: int(0, 2) * 0.25 };
+
+  const scenario = {
+    horizon_hours: int(12, 72),
+    mission_types: missionTypes,
+    demand,
+    process_times: {
+      preflight: { type: 'deterministic', value_hours: 0.5 },
+      postflight: { type: 'deterministic', value_hours: 0.25 },
+      turnaround: { type: 'deterministic', value_hours: int(0, 2) * 0.5 },
+      hold_crew_during_process_times: rng() < 0.5,
+      mount_times: mountTimes
+    },
+    unit_policy: { mission_split: split }
+  };
+
+  // Some cases add a mapped crew role (scenario.mos_roles), held for the mission crew window or for one process step
+  if (rng() < 0.5) {
+    scenario.mos_roles = { '6046': 'maintainer' };
+    for (const unit of units) overrides.units[unit].crew_by_role = { maintainer: int(0, 3) };
+    for (const mt of missionTypes) mt.required_aircrew.maintainer = int(0, 1);
+    if (rng() < 0.5) scenario.process_times.role_requirements = { turnaround: { maintainer: 1 } };
+  }
+
+  // Restrict the snapshot to the generated units so every pool comes from overrides
+  const state = {
+    tables: {
+      ...mockState.tables,
+      v_unit: { rows: units.map(u => ({ Unit: u })) },
+      v_aircraft: { rows: [] },
+      v_payload: { rows: [] },
+      v_staffing: { rows: [] }
+    }
+  };
+
+  return { scenario, settings: { state, overrides, logLevel: 'silent' } };
+}
+
+/**
+ * Check that concurrent holds never exceed a pool total by sweeping interval endpoints
+ * @param {Array<{start: number, end: number}>} intervals - Hold intervals
+ * @param {number} total - Pool capacity
+ * @returns {number} Peak concurrent holds
+ */
+function peakConcurrency(intervals, total) {
+  const points = [];
+  for (const iv of intervals) {
+    points.push([iv.start, 1]);
+    points.push([iv.end, -1]);
+  }
+  // Releases sort before acquisitions at the same instant (release at t frees the slot for t)
+  points.sort((a, b) => (a[0] - b[0]) || (a[1] - b[1]));
+  let level = 0;
+  let peak = 0;
+  for (const [, delta] of points) {
+    level += delta;
+    peak = Math.max(peak, level);
+  }
+  return peak;
+}
+
+describe('DES Engine - Property Tests', () => {
+  const generated = [];
+
+  beforeAll(async () => {
+    for (let i = 0; i < CASES; i++) {
+      const { scenario, settings } = generateCase(i);
+      const result = await runSimulation(scenario, settings);
+      generated.push({ caseNumber: i, scenario, settings, result });
+    }
+  });
+
+  test('requested equals started plus rejected plus infeasible', () => {
+    for (const { caseNumber, result } of generated) {
+      const m = result.missions;
+      expect({ caseNumber, sum: m.started + m.rejected + (m.infeasible || 0) }).toEqual({ caseNumber, sum: m.requested });
+      expect(m.completed).toBeLessThanOrEqual(m.started);
+    }
+  });
+
+  test('per-type and per-source counts sum to the global totals', () => {
+    for (const { caseNumber, result } of generated) {
+      for (const key of ['requested', 'started', 'completed', 'rejected']) {
+        const sum = Object.values(result.by_type).reduce((acc, bt) => acc + (bt[key] || 0), 0);
+        expect({ caseNumber, key, sum }).toEqual({ caseNumber, key, sum: result.missions[key] });
+        const sourceSum = Object.values(result.by_demand_source).reduce((acc, bs) => acc + bs[key], 0);
+        expect({ caseNumber, key, sourceSum }).toEqual({ caseNumber, key, sourceSum: result.missions[key] });
+      }
+    }
+  });
+
+  test('rejection reasons sum to rejected missions and match timeline rejections', () => {
+    for (const { caseNumber, result } of generated) {
+      const reasonSum = Object.values(result.rejections).reduce((a, b) => a + b, 0);
+      expect({ caseNumber, reasonSum }).toEqual({ caseNumber, reasonSum: result.missions.rejected });
+
+      const timelineByReason = {};
+      for (const item of result.timeline) {
+        if (item.type === 'rejection') timelineByReason[item.reason] = (timelineByReason[item.reason] || 0) + 1;
+      }
+      for (const [reason, count] of Object.entries(result.rejections)) {
+        expect({ caseNumber, reason, count: timelineByReason[reason] || 0 }).toEqual({ caseNumber, reason, count });
+      }
+    }
+  });
+
+  test('rejection reasons by type and by unit sum to the global reason counts', () => {
+    for (const { caseNumber, result } of generated) {
+      for (const breakdown of ['rejections_by_type', 'rejections_by_unit']) {
+        for (const [reason, count] of Object.entries(result.rejections)) {
+          const sum = Object.values(result[breakdown]).reduce((acc, counts) => acc + (counts[reason] || 0), 0);
+          expect({ caseNumber, breakdown, reason, sum }).toEqual({ caseNumber, breakdown, reason, sum: count });
+        }
+      }
+      const inProgress = Object.values(result.in_progress_by_unit).reduce((a, b) => a + b, 0);
+      expect({ caseNumber, inProgress }).toEqual({ caseNumber, inProgress: result.missions.in_progress_at_horizon });
+    }
+  });
+
+  test('breakdowns stay additive with retried and pinned demand', async () => {
+    for (let caseNumber = 0; caseNumber < 10; caseNumber++) {
+      const { scenario, settings } = generateCase(caseNumber);
+      const demand = scenario.demand.map((d, i) => (i === 0
+        ? { ...d, retry: { max_attempts: 3, delay: { type: 'exponential', rate_per_hour: 1 } } }
+        : { ...d, unit: Object.keys(settings.overrides.units)[0] }));
+      const result = await runSimulation({ ...scenario, demand }, settings);
+      const m = result.missions;
+      expect({ caseNumber, sum: m.started + m.rejected + (m.infeasible || 0) }).toEqual({ caseNumber, sum: m.requested });
+      for (const key of ['requested', 'started', 'completed', 'rejected']) {
+        for (const breakdown of ['by_type', 'by_demand_source']) {
+          const sum = Object.values(result[breakdown]).reduce((acc, counts) => acc + counts[key], 0);
+          expect({ caseNumber, breakdown, key, sum }).toEqual({ caseNumber, breakdown, key, sum: m[key] });
+        }
+      }
+      const reasonSum = Object.values(result.rejections).reduce((a, b) => a + b, 0);
+      expect({ caseNumber, reasonSum }).toEqual({ caseNumber, reasonSum: m.rejected });
+    }
+  });
+
+  test('utilization and efficiency stay within [0, 1]', () => {
+    for (const { result } of generated) {
+      for (const util of Object.values(result.utilization)) {
+        for (const key of ['aircraft', 'pilot', 'so', 'intel', 'aircraft_efficiency', 'pilot_efficiency', 'so_efficiency', 'intel_efficiency']) {
+          expect(util[key]).toBeGreaterThanOrEqual(0);
+          expect(util[key]).toBeLessThanOrEqual(1);
+        }
+        for (const res of ['aircraft', 'pilot', 'so', 'intel']) {
+          // Available hours never exceed total * horizon, so this is never the smaller figure
+          expect(util[`${res}_efficiency_of_available`]).toBeLessThanOrEqual(1);
+          expect(util[`${res}_efficiency_of_available`]).toBeGreaterThanOrEqual(util[`${res}_efficiency`]);
+        }
+      }
+    }
+  });
+
+  test('mission segments are contiguous and non-negative', () => {
+    for (const { result } of generated) {
+      for (const mission of result.timeline.filter(e => e.type === 'mission')) {
+        expect(mission.segments[0].start).toBeCloseTo(mission.demand_time, 9);
+        for (let i = 0; i < mission.segments.length; i++) {
+          const seg = mission.segments[i];
+          expect(seg.end - seg.start).toBeGreaterThanOrEqual(-EPS);
+          if (i > 0) expect(Math.abs(seg.start - mission.segments[i - 1].end)).toBeLessThan(EPS);
+        }
+        expect(mission.segments[mission.segments.length - 1].end).toBeCloseTo(mission.finish_time, 9);
+      }
+    }
+  });
+
+  test('concurrent aircraft, payload and crew holds never exceed pool totals', () => {
+    for (const { caseNumber, scenario, result } of generated) {
+      const missionTypes = new Map(scenario.mission_types.map(mt => [mt.name, mt]));
+      const initial = result.initial_resources;
+      for (const unit of initial.units) {
+        const missions = result.timeline.filter(e => e.type === 'mission' && e.unit === unit);
+        const intervals = missions.map(m => ({ start: m.demand_time, end: m.finish_time }));
+        const aircraftTotal = initial.aircraftByUnit[unit] || 0;
+        expect({ caseNumber, unit, ok: peakConcurrency(intervals, aircraftTotal) <= aircraftTotal }).toEqual({ caseNumber, unit, ok: true });
+
+        for (const ptype of PAYLOAD_TYPES) {
+          const payloadIntervals = missions
+            .filter(m => (missionTypes.get(m.mission_type).required_payload_types || []).includes(ptype))
+            .map(m => ({ start: m.demand_time, end: m.finish_time }));
+          const payloadTotal = (initial.payloadByUnit[unit] || {})[ptype] || 0;
+          expect(peakConcurrency(payloadIntervals, payloadTotal)).toBeLessThanOrEqual(payloadTotal);
+        }
+
+        // Crew holds as assigned (pilot, SO, intel and any mos_roles role), against the unit's headcount
+        const crewHolds = { pilot: m => m.crew.pilots, so: m => m.crew.sos, intel: m => m.crew.intel };
+        for (const [role, crewTotal] of Object.entries(initial.staffingByUnit[unit] || {})) {
+          const holds = missions.flatMap(m => (crewHolds[role] ? crewHolds[role](m) : m.crew.roles?.[role]) || []);
+          const peak = peakConcurrency(holds, crewTotal);
+          expect({ caseNumber, unit, role, ok: peak <= crewTotal }).toEqual({ caseNumber, unit, role, ok: true });
+        }
+      }
+    }
+  });
+
+  test('aggregated Monte Carlo statistics respect the same invariants', async () => {
+    const { scenario, settings } = generateCase(3);
+    const mc = await runMonteCarlo(scenario, {
+      state: settings.state,
+      overrides: settings.overrides,
+      iterations: 10,
+      maxConcurrent: 2
+    });
+
+    for (const stats of Object.values(mc.missions)) {
+      expect(stats.min).toBeLessThanOrEqual(stats.mean);
+      expect(stats.mean).toBeLessThanOrEqual(stats.max);
+    }
+    // Means are rounded to 2 decimals, so allow for rounding drift
+    const m = mc.missions;
+    expect(Math.abs(m.started.mean + m.rejected.mean - m.requested.mean)).toBeLessThanOrEqual(0.02);
+    expect(m.completed.max).toBeLessThanOrEqual(m.started.max);
+  }, 60000);
+});
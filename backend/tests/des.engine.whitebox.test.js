@@ -2,7 +2,8 @@
 // Tests internal implementation details, data structures, and algorithms
 // Requires knowledge of how the engine works internally
 
-const { runSimulation, loadState } = require('../sim/des/engine');
+const { runSimulation, runSimulationWithPolicy, previewDemand, loadState, applyScenarioPatch } = require('../sim/des/engine');
+const { EquipmentPool } = require('../sim/des/helpers/resources');
 const { mockState, basicScenario } = require('./fixtures');
 
 describe('DES Engine - Whitebox Tests', () => {
@@ -42,6 +43,33 @@ describe('DES Engine - Whitebox Tests', () => {
       expect(result.staffingByUnit['HMLA-267'].so).toBe(2);
     });
 
+    test('maps additional MOS codes to named crew roles with mos_roles', () => {
+      const stateWithMaintainers = {
+        tables: {
+          ...mockState.tables,
+          v_staffing: {
+            rows: [
+              ...mockState.tables.v_staffing.rows,
+              { 'Unit Name': 'HMLA-167', 'MOS Number': '6276' },
+              { 'Unit Name': 'HMLA-167', 'MOS Number': '6316' },
+              { 'Unit Name': 'HMLA-267', 'MOS Number': '6276' }
+            ]
+          }
+        }
+      };
+
+      const defaults = loadState(stateWithMaintainers);
+      expect(defaults.staffingByUnit['HMLA-167']).toEqual({ pilot: 2, so: 1, intel: 0 });
+      expect(defaults.crewRoles).toEqual([]);
+
+      const result = loadState(stateWithMaintainers, { '6276': 'maintainer', '6316': 'maintainer' });
+      expect(result.crewRoles).toEqual(['maintainer']);
+      expect(result.staffingByUnit['HMLA-167']).toEqual({ pilot: 2, so: 1, intel: 0, maintainer: 2 });
+      expect(result.staffingByUnit['HMLA-267']).toEqual({ pilot: 1, so: 2, intel: 0, maintainer: 1 });
+
+      expect(() => loadState(stateWithMaintainers, { '6276': 'aircraft' })).toThrow('reserved');
+    });
+
     test('includes all units from any resource table', () => {
       const stateWithMismatchedUnits = {
         tables: {
@@ -60,6 +88,23 @@ describe('DES Engine - Whitebox Tests', () => {
       expect(result.units).toContain('HMLA-367');
     });
 
+    test('unit order is sorted whatever the row order and override keys', async () => {
+      const reversed = {
+        tables: Object.fromEntries(Object.entries(mockState.tables).map(([key, t]) => [key, { rows: t.rows.slice().reverse() }]))
+      };
+      expect(loadState(reversed).units).toEqual(['HMLA-167', 'HMLA-267']);
+
+      const settings = { logLevel: 'silent', seed: 7, sortedOutput: true, echoConfig: false, overrides: { units: { 'HMLA-367': { aircraft: 1, pilot: 1 } } } };
+      const a = await runSimulation(basicScenario, { ...settings, state: mockState });
+      const b = await runSimulation(basicScenario, { ...settings, state: reversed });
+      expect(Object.keys(a.utilization)).toEqual(['HMLA-167', 'HMLA-267', 'HMLA-367']);
+      expect(JSON.stringify(b)).toBe(JSON.stringify(a));
+
+      // Without sortedOutput the values are the same; only key order may differ
+      const unsorted = await runSimulation(basicScenario, { ...settings, state: reversed, sortedOutput: false });
+      expect(unsorted).toEqual(a);
+    });
+
     test('initializes empty structures for units without resources', () => {
       const result = loadState(mockState);
 
@@ -71,6 +116,24 @@ describe('DES Engine - Whitebox Tests', () => {
         }
       }
     });
+
+    test('column-wise tables derive the same state as row tables', () => {
+      const toColumns = rows => {
+        const names = [...new Set(rows.flatMap(r => Object.keys(r)))];
+        return { columns: Object.fromEntries(names.map(n => [n, rows.map(r => r[n])])) };
+      };
+      const columnar = {
+        tables: Object.fromEntries(Object.entries(mockState.tables).map(([key, t]) => [key, toColumns(t.rows)]))
+      };
+      expect(loadState(columnar)).toEqual(loadState(mockState));
+
+      // Formats can be mixed across tables, not within one
+      const mixed = { tables: { ...mockState.tables, v_unit: toColumns(mockState.tables.v_unit.rows) } };
+      expect(loadState(mixed)).toEqual(loadState(mockState));
+      expect(() => loadState({ tables: { v_unit: { rows: [], columns: {} } } })).toThrow('give either rows or columns');
+      expect(() => loadState({ tables: { v_unit: { columns: { Unit: ['A', 'B'], Base: ['X'] } } } }))
+        .toThrow('columns must be arrays of the same length');
+    });
   });
 
   describe('runSimulation - Internal Result Structure', () => {
@@ -190,6 +253,29 @@ describe('DES Engine - Whitebox Tests', () => {
       }
     });
 
+    test('attributes missions to the demand entry that generated them', async () => {
+      const twoStreams = {
+        ...basicScenario,
+        demand: [
+          { name: 'baseline', type: 'deterministic', mission_type: 'ISR', every_hours: 8, start_at_hours: 0 },
+          { type: 'deterministic', mission_type: 'ISR', every_hours: 6, start_at_hours: 1 }
+        ]
+      };
+
+      const result = await runSimulation(twoStreams, { state: mockState });
+      const sources = result.by_demand_source;
+
+      expect(Object.keys(sources).sort()).toEqual(['baseline', 'demand[1]']);
+      expect(sources.baseline.requested).toBe(3);
+      expect(sources['demand[1]'].requested).toBe(4);
+      for (const key of ['requested', 'started', 'completed', 'rejected']) {
+        expect(sources.baseline[key] + sources['demand[1]'][key]).toBe(result.by_type.ISR[key]);
+      }
+      for (const item of result.timeline.filter(e => e.type === 'mission')) {
+        expect(item.demand_source).toBe(item.demand_time % 8 === 0 ? 'baseline' : 'demand[1]');
+      }
+    });
+
     test('tracks statistics by mission type', async () => {
       const result = await runSimulation(basicScenario, { state: mockState });
 
@@ -232,6 +318,65 @@ describe('DES Engine - Whitebox Tests', () => {
       }
     });
 
+    test('efficiency of available hours uses the crew schedule as its denominator', async () => {
+      const { CrewQueue, EquipmentPool } = require('../sim/des/helpers/resources');
+
+      // 5 on / 2 off, 16 working hours a day: 80 available hours per member per week
+      const crew = new CrewQueue('pilot', 3, 0, { days_on: 5, days_off: 2, daily_start_hour: 6, daily_crew_rest_hours: 8 });
+      expect(crew.availableHours(168)).toBeCloseTo(3 * 80, 9);
+      crew.busyTime = 120;
+      expect(crew.efficiency(168)).toBeCloseTo(120 / (3 * 168), 9);
+      expect(crew.efficiencyOfAvailable(168)).toBeCloseTo(120 / 240, 9);
+      expect(crew.getStats(168, 3, 3).efficiencyOfAvailable).toBeCloseTo(0.5, 9);
+
+      // Shift 2 works the other half of the day, so a split crew covers the same hours
+      const split = new CrewQueue('so', 2, 0, { days_on: 7, days_off: 0, daily_crew_rest_hours: 12, shift_split_enabled: true });
+      expect(split.availableHours(48)).toBeCloseTo(2 * 24, 9);
+
+      // No calendar: both figures agree
+      const aircraft = new EquipmentPool('aircraft', 2);
+      aircraft.tryAcquire(0, 6);
+      expect(aircraft.efficiencyOfAvailable(24)).toBe(aircraft.efficiency(24));
+
+      const result = await runSimulation(basicScenario, { state: mockState });
+      for (const util of Object.values(result.utilization)) {
+        expect(util.aircraft_efficiency_of_available).toBe(util.aircraft_efficiency);
+        expect(util.pilot_efficiency_of_available).toBe(util.pilot_efficiency);
+      }
+    });
+
+    test('busy hours count only the part of each acquisition inside the horizon', () => {
+      const { CrewQueue, EquipmentPool } = require('../sim/des/helpers/resources');
+
+      // 2 units held [20, 30) against a 24h horizon: 2 × 4h inside, not 2 × 10h
+      const aircraft = new EquipmentPool('aircraft', 4);
+      aircraft.tryAcquire(20, 10, 2);
+      expect(aircraft.busyTime).toBe(20);
+      expect(aircraft.busyHoursWithin(24)).toBe(8);
+      expect(aircraft.efficiency(24)).toBeCloseTo(8 / (4 * 24), 9);
+      expect(aircraft.getStats(24).busyHours).toBe(8);
+
+      // Seeded from t=0 and running past the end: both ends clipped, scaled by count
+      aircraft.seedHold(30, 2);
+      expect(aircraft.busyHoursWithin(24)).toBe(8 + 2 * 24);
+      // A horizon covering everything gives back the booked hours
+      expect(aircraft.busyHoursWithin(40)).toBe(aircraft.busyTime);
+
+      // Crew: three members on the same shift straddling the horizon, one sequential pair past it
+      const crew = new CrewQueue('pilot', 5);
+      crew.tryAcquireShifts(22, [4, 4, 4]);
+      crew.tryAcquireShifts(21, [2, 2], false, false, true);
+      expect(crew.busyTime).toBe(3 * 4 + 2 * 2);
+      expect(crew.busyHoursWithin(24)).toBe(3 * 2 + 2 + 1);
+      expect(crew.efficiencyOfAvailable(24)).toBeCloseTo(9 / (5 * 24), 9);
+
+      // Standby reservations are clipped the same way
+      const reserved = new EquipmentPool('aircraft', 2);
+      reserved.setReserved(2);
+      reserved.tryAcquire(20, 10, 2, true);
+      expect(reserved.reservationStats(24).busy_hours).toBe(8);
+    });
+
     test('utilization includes initial and effective crew counts', async () => {
       const result = await runSimulation(basicScenario, { state: mockState });
 
@@ -450,6 +595,1354 @@ describe('DES Engine - Whitebox Tests', () => {
       }
     });
 
+    test('rejection breakdown attributes each reason to its mission type and unit', async () => {
+      const hourly = name => ({ type: 'deterministic', mission_type: name, every_hours: 1, start_at_hours: 0 });
+      const starvedScenario = {
+        ...basicScenario,
+        horizon_hours: 12,
+        mission_types: [
+          // No Hellfire anywhere: always payload-starved
+          { name: 'Strike', flight_time: { type: 'deterministic', value_hours: 1 }, required_aircrew: { pilot: 0, so: 0 }, required_payload_types: ['Hellfire'] },
+          // One pilot, hourly 3h+ missions: pilot-starved after the first
+          { name: 'ISR', flight_time: { type: 'deterministic', value_hours: 3 }, required_aircrew: { pilot: 1, so: 0 }, required_payload_types: [] },
+          // No intel analysts: always intel-starved
+          { name: 'SIGINT', flight_time: { type: 'deterministic', value_hours: 1 }, required_aircrew: { pilot: 0, so: 0, intel: 1 }, required_payload_types: [] }
+        ],
+        demand: [hourly('Strike'), hourly('ISR'), hourly('SIGINT')],
+        unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } }
+      };
+      const overrides = {
+        units: {
+          'HMLA-167': { aircraft: 50, pilot: 1, so: 50, intel: 0, payload_by_type: { 'SkyTower II': 50, 'Hellfire': 0 } },
+          // HMLA-267 could fly Strike and SIGINT, so they are feasible and HMLA-167's failures stay rejections
+          'HMLA-267': { intel: 1, payload_by_type: { 'Hellfire': 1 } }
+        }
+      };
+
+      const result = await runSimulation(starvedScenario, { state: mockState, overrides });
+      expect(result.infeasible_by_type).toBeUndefined();
+      const byType = result.rejections_by_type;
+
+      expect(byType.Strike).toEqual({ aircraft: 0, control_station: 0, pilot: 0, so: 0, intel: 0, payload: result.by_type.Strike.rejected });
+      expect(byType.ISR.pilot).toBe(result.by_type.ISR.rejected);
+      expect(byType.ISR.pilot).toBeGreaterThan(0);
+      expect(byType.SIGINT).toEqual({ aircraft: 0, control_station: 0, pilot: 0, so: 0, intel: result.by_type.SIGINT.rejected, payload: 0 });
+
+      expect(Object.keys(result.rejections_by_unit)).toEqual(['HMLA-167']);
+      expect(result.rejections_by_unit['HMLA-167']).toEqual(result.rejections);
+    });
+
+    test('payload usage is reported per unit and payload type', async () => {
+      const payloadScenario = {
+        ...basicScenario,
+        horizon_hours: 10,
+        mission_types: [
+          { name: 'ISR', flight_time: { type: 'deterministic', value_hours: 1 }, required_aircrew: { pilot: 0, so: 0 }, required_payload_types: ['SkyTower II'] }
+        ],
+        demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 2, start_at_hours: 0 }],
+        unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } }
+      };
+      const overrides = {
+        units: {
+          'HMLA-167': { aircraft: 50, pilot: 50, so: 50, intel: 50, payload_by_type: { 'SkyTower II': 2, 'Hellfire': 3 } }
+        }
+      };
+
+      const result = await runSimulation(payloadScenario, { state: mockState, overrides });
+      const used = result.payload_missions['HMLA-167'];
+
+      expect(used['SkyTower II']).toBe(result.by_type.ISR.started);
+      expect(used['Hellfire']).toBe(0);
+      expect(result.payload_utilization['HMLA-167']['Hellfire']).toBe(0);
+      expect(result.payload_utilization['HMLA-167']['SkyTower II']).toBeGreaterThan(0);
+      expect(result.payload_utilization['HMLA-167']['SkyTower II']).toBeLessThanOrEqual(1);
+    });
+
+    test('crew_flex flies the alternate composition when SOs run out', async () => {
+      const isr = {
+        name: 'ISR',
+        flight_time: { type: 'deterministic', value_hours: 3 },
+        required_aircrew: { pilot: 1, so: 1 },
+        required_payload_types: []
+      };
+      const flexScenario = flex => ({
+        ...basicScenario,
+        horizon_hours: 12,
+        mission_types: [flex ? { ...isr, crew_flex: { total_seats: 2, allowed_compositions: [{ pilot: 1, so: 1 }, { pilot: 2 }] } } : isr],
+        demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 1, start_at_hours: 0 }],
+        unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } }
+      });
+      const overrides = {
+        units: {
+          'HMLA-167': { aircraft: 50, pilot: 20, so: 1, intel: 50, payload_by_type: {} }
+        }
+      };
+
+      const fixed = await runSimulation(flexScenario(false), { state: mockState, overrides, missionDetail: true });
+      const flexible = await runSimulation(flexScenario(true), { state: mockState, overrides, missionDetail: true });
+
+      expect(flexible.missions.started).toBeGreaterThan(fixed.missions.started);
+      expect(flexible.rejections.so).toBeLessThan(fixed.rejections.so);
+
+      const flown = flexible.missions_detail.filter(r => r.outcome === 'started');
+      const twoPilot = flown.filter(r => r.crew_composition.pilot === 2 && r.crew_composition.so === 0);
+      expect(twoPilot.length).toBeGreaterThan(0);
+      for (const row of twoPilot) {
+        expect(row.resources.pilots.length).toBe(2);
+        expect(row.resources.sos.length).toBe(0);
+      }
+      expect(flexible.utilization['HMLA-167'].pilot_efficiency).toBeGreaterThan(fixed.utilization['HMLA-167'].pilot_efficiency);
+    });
+
+    test('crew_flex compositions must fill total_seats', async () => {
+      const badScenario = {
+        ...basicScenario,
+        mission_types: [
+          { ...basicScenario.mission_types[0], crew_flex: { total_seats: 2, allowed_compositions: [{ pilot: 1, so: 1 }, { pilot: 3 }] } }
+        ]
+      };
+
+      await expect(runSimulation(badScenario, { state: mockState })).rejects.toThrow(/expected total_seats 2/);
+    });
+
+    test('renewal demand with a deterministic inter-arrival reproduces deterministic demand', async () => {
+      const withDemand = demand => ({ ...basicScenario, horizon_hours: 48, demand: [demand] });
+      const deterministic = await runSimulation(
+        withDemand({ type: 'deterministic', mission_type: 'ISR', every_hours: 3, start_at_hours: 1 }),
+        { state: mockState, seed: 3 }
+      );
+      const renewal = await runSimulation(
+        withDemand({ type: 'renewal', mission_type: 'ISR', interarrival: { type: 'deterministic', value_hours: 3 }, start_at_hours: 1 }),
+        { state: mockState, seed: 3 }
+      );
+
+      expect(renewal.timeline).toEqual(deterministic.timeline);
+      expect(renewal.missions).toEqual(deterministic.missions);
+      expect(renewal.demand_stats.renewal_gap_clamps).toBe(0);
+    });
+
+    test('renewal demand clamps non-positive gaps and counts them', async () => {
+      const scenario = {
+        ...basicScenario,
+        horizon_hours: 0.01,
+        demand: [{ type: 'renewal', mission_type: 'ISR', interarrival: { type: 'deterministic', value_hours: 0 } }]
+      };
+
+      const result = await runSimulation(scenario, { state: mockState });
+
+      // Gaps of 0 become 0.001h: demands at t = 0, 0.001, ..., 0.009
+      expect(result.missions.requested).toBe(10);
+      expect(result.demand_stats.renewal_gap_clamps).toBe(10);
+    });
+
+    test('max_concurrent_missions caps simultaneous missions per unit', async () => {
+      const cappedScenario = {
+        ...basicScenario,
+        horizon_hours: 24,
+        mission_types: [
+          { name: 'ISR', flight_time: { type: 'deterministic', value_hours: 3 }, required_aircrew: { pilot: 0, so: 0 }, required_payload_types: [] }
+        ],
+        demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 0.5, start_at_hours: 0 }],
+        unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 }, max_concurrent_missions: { 'HMLA-167': 2 } }
+      };
+      const overrides = {
+        units: {
+          'HMLA-167': { aircraft: 10, pilot: 50, so: 50, intel: 50, payload_by_type: {} }
+        }
+      };
+
+      const result = await runSimulation(cappedScenario, { state: mockState, overrides });
+
+      // Peak concurrency never exceeds the two slots even though 10 aircraft are available
+      const missions = result.timeline.filter(e => e.type === 'mission');
+      for (const m of missions) {
+        const overlapping = missions.filter(o => o.demand_time <= m.demand_time && o.finish_time > m.demand_time);
+        expect(overlapping.length).toBeLessThanOrEqual(2);
+      }
+      expect(result.utilization['HMLA-167'].control_station_stats.total).toBe(2);
+      expect(result.utilization['HMLA-167'].control_station).toBe(1);
+      expect(result.utilization['HMLA-167'].aircraft).toBeLessThanOrEqual(0.2);
+      expect(result.rejections.control_station).toBe(result.missions.rejected);
+      expect(result.rejections.control_station).toBeGreaterThan(0);
+      expect(result.utilization['HMLA-267'].control_station).toBeUndefined();
+    });
+
+    test('per_resource hold model releases crew and payloads before turnaround', async () => {
+      const scenario = {
+        ...basicScenario,
+        process_times: { ...basicScenario.process_times, turnaround: { type: 'deterministic', value_hours: 3 } }
+      };
+
+      const legacy = await runSimulation(scenario, { state: mockState });
+      const perResource = await runSimulation(scenario, { state: mockState, resourceHoldModel: 'per_resource' });
+      const [legacyMission] = legacy.timeline.filter(e => e.type === 'mission');
+      const [mission] = perResource.timeline.filter(e => e.type === 'mission');
+      const segment = name => mission.segments.find(s => s.name === name);
+
+      // Aircraft timing is unchanged; crew leaves after postflight, payload after transit out
+      expect(mission.finish_time).toBe(legacyMission.finish_time);
+      expect(legacyMission.crew_hold_end).toBe(legacyMission.finish_time);
+      expect(mission.crew_hold_end).toBe(segment('postflight').end);
+      expect(mission.payload_hold_start).toBe(segment('mount').start);
+      expect(mission.payload_hold_end).toBe(segment('transit_out').end);
+
+      const unit = mission.unit;
+      expect(perResource.utilization[unit].aircraft_efficiency).toBe(legacy.utilization[unit].aircraft_efficiency);
+      expect(perResource.utilization[unit].pilot_efficiency).toBeLessThan(legacy.utilization[unit].pilot_efficiency);
+      expect(perResource.payload_utilization[unit]['SkyTower II']).toBeLessThan(legacy.payload_utilization[unit]['SkyTower II']);
+
+      await expect(runSimulation(scenario, { state: mockState, resourceHoldModel: 'split' })).rejects.toThrow('resourceHoldModel');
+    });
+
+    test('dismount_times keep the aircraft and payload after postflight, not the crew', async () => {
+      const hours = value_hours => ({ type: 'deterministic', value_hours });
+      const scenario = {
+        ...basicScenario,
+        mission_types: [
+          { name: 'ISR', flight_time: hours(2), required_aircrew: { pilot: 1, so: 1 }, required_payload_types: ['SkyTower II'] }
+        ],
+        process_times: {
+          preflight: hours(0.5), postflight: hours(0.25), turnaround: hours(0.25),
+          mount_times: { 'SkyTower II': hours(0.5) },
+          dismount_times: { 'SkyTower II': hours(0.75) }
+        },
+        unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } }
+      };
+      const noPayload = {
+        ...scenario,
+        mission_types: [{ ...scenario.mission_types[0], required_payload_types: [] }]
+      };
+      const options = { state: mockState, resourceHoldModel: 'per_resource', missionDetail: true };
+
+      const withDismount = await runSimulation(scenario, options);
+      const plain = await runSimulation(noPayload, options);
+      const [mission] = withDismount.timeline.filter(e => e.type === 'mission');
+      const [plainMission] = plain.timeline.filter(e => e.type === 'mission');
+      const segment = name => mission.segments.find(s => s.name === name);
+
+      // postflight → dismount → turnaround; the aircraft is held for mount + dismount longer
+      expect(segment('dismount')).toEqual({ name: 'dismount', start: 3.25, end: 4 });
+      expect(segment('turnaround')).toEqual({ name: 'turnaround', start: 4, end: 4.25 });
+      expect(mission.finish_time - plainMission.finish_time).toBeCloseTo(1.25, 9);
+      expect(withDismount.utilization['HMLA-167'].aircraft_efficiency)
+        .toBeGreaterThan(plain.utilization['HMLA-167'].aircraft_efficiency);
+      expect(plainMission.segments.find(s => s.name === 'dismount')).toEqual({ name: 'dismount', start: 2.75, end: 2.75 });
+
+      // Crews leave after postflight; the payload stays until it is off the aircraft
+      expect(mission.crew_hold_end).toBe(3.25);
+      expect(mission.payload_hold_start).toBe(0.5);
+      expect(mission.payload_hold_end).toBe(4);
+      expect(withDismount.missions_detail[0].durations.dismount).toBe(0.75);
+
+      // Dismount after turnaround keeps the payload through turnaround too
+      const late = { ...scenario, process_times: { ...scenario.process_times, dismount_after_turnaround: true } };
+      const [lateMission] = (await runSimulation(late, options)).timeline.filter(e => e.type === 'mission');
+      expect(lateMission.segments.find(s => s.name === 'turnaround')).toEqual({ name: 'turnaround', start: 3.25, end: 3.5 });
+      expect(lateMission.segments.find(s => s.name === 'dismount')).toEqual({ name: 'dismount', start: 3.5, end: 4.25 });
+      expect(lateMission.payload_hold_end).toBe(4.25);
+      expect(lateMission.finish_time).toBe(mission.finish_time);
+
+      await expect(runSimulation({ ...scenario, process_times: { ...scenario.process_times, dismount_after_turnaround: 'yes' } }, options))
+        .rejects.toThrow('dismount_after_turnaround must be a boolean');
+    });
+
+    test('payload_policy.daily_limits caps force-wide payload issue per day apart from inventory', async () => {
+      const hours = value_hours => ({ type: 'deterministic', value_hours });
+      const scenario = {
+        ...basicScenario,
+        horizon_hours: 48,
+        mission_types: [
+          { name: 'ISR', flight_time: hours(1), required_aircrew: { pilot: 1, so: 1 }, required_payload_types: ['SkyTower II'] }
+        ],
+        demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 6, start_at_hours: 0 }],
+        payload_policy: { daily_limits: { 'SkyTower II': 2 } }
+      };
+      const plenty = { aircraft: 10, pilot: 10, so: 10, payload_by_type: { 'SkyTower II': 10 } };
+      const overrides = { units: { 'HMLA-167': plenty, 'HMLA-267': plenty } };
+
+      const results = await runSimulation(scenario, { state: mockState, overrides });
+
+      expect(results.payload_consumption).toEqual({ 'SkyTower II': { daily_limit: 2, by_day: [2, 2] } });
+      expect(results.missions.requested).toBe(8);
+      expect(results.missions.started).toBe(4);
+      expect(results.rejections.payload_budget).toBe(4);
+      expect(results.rejections.payload).toBe(0);
+      // Each day's first two demands fly, the budget resets at the day boundary
+      const rejectedAt = results.timeline.filter(e => e.type === 'rejection').map(e => e.time);
+      expect(rejectedAt).toEqual([12, 18, 36, 42]);
+
+      const unlimited = await runSimulation({ ...scenario, payload_policy: undefined }, { state: mockState, overrides });
+      expect(unlimited.missions.started).toBe(8);
+      expect(unlimited.payload_consumption).toBeUndefined();
+      expect(unlimited.rejections.payload_budget).toBeUndefined();
+
+      const fractional = await runSimulation({ ...scenario, payload_policy: { daily_limits: { 'SkyTower II': 1.5 } } }, { state: mockState }).catch(e => e);
+      expect(fractional.message).toContain('non-negative integer');
+      expect(fractional.code).toBe('INVALID_SCENARIO');
+      expect(fractional.path).toBe("payload_policy.daily_limits['SkyTower II']");
+    });
+
+    test('crew_policy.min_crew_turn_hours keeps crew out after each sortie while aircraft turn on their own', async () => {
+      const hours = value_hours => ({ type: 'deterministic', value_hours });
+      const scenario = {
+        ...basicScenario,
+        mission_types: [{ name: 'ISR', flight_time: hours(3), required_aircrew: { pilot: 1, so: 0 }, required_payload_types: [] }],
+        process_times: { preflight: hours(0), postflight: hours(0), turnaround: hours(0) },
+        demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 4, start_at_hours: 0, unit: 'HMLA-167' }],
+        crew_policy: { min_crew_turn_hours: 2 }
+      };
+      // Two aircraft, one pilot: the aircraft could fly every demand, the pilot's 3h sortie + 2h turn cannot
+      const overrides = { units: { 'HMLA-167': { aircraft: 2, pilot: 1, so: 0 } } };
+
+      const results = await runSimulation(scenario, { state: mockState, overrides });
+
+      expect(results.missions.requested).toBe(6);
+      expect(results.missions.started).toBe(3);
+      expect(results.rejections.pilot).toBe(3);
+      expect(results.rejections.aircraft).toBe(0);
+      const rejectedAt = results.timeline.filter(e => e.type === 'rejection').map(e => e.time);
+      expect(rejectedAt).toEqual([4, 12, 20]);
+      // Busy (on missions) and unavailable (in turn) are reported apart
+      expect(results.utilization['HMLA-167'].pilot_efficiency).toBe(0.375);
+      expect(results.utilization['HMLA-167'].pilot_turn_fraction).toBe(0.25);
+
+      const noTurn = await runSimulation({ ...scenario, crew_policy: undefined }, { state: mockState, overrides });
+      expect(noTurn.missions.started).toBe(6);
+      expect(noTurn.utilization['HMLA-167'].pilot_turn_fraction).toBeUndefined();
+
+      const negative = await runSimulation({ ...scenario, crew_policy: { min_crew_turn_hours: -1 } }, { state: mockState }).catch(e => e);
+      expect(negative.message).toContain('non-negative number');
+      expect(negative.code).toBe('INVALID_SCENARIO');
+      expect(negative.path).toBe('crew_policy.min_crew_turn_hours');
+    });
+
+    test('launch_windows defer night demand to the next opening and reject it past the horizon', async () => {
+      const scenario = {
+        ...basicScenario,
+        mission_types: [{ ...basicScenario.mission_types[0], launch_windows: [{ start_hour_of_day: 6, end_hour_of_day: 22 }] }],
+        demand: [{ type: 'scheduled', mission_type: 'ISR', at_hours: [2, 14, 23] }]
+      };
+
+      const results = await runSimulation(scenario, { state: mockState, missionDetail: true });
+
+      expect(results.missions.requested).toBe(3);
+      expect(results.missions.started).toBe(2);
+      expect(results.rejections.window).toBe(1);
+      expect(results.launch_deferrals).toEqual({ count: 1, total_hours: 4, max_hours: 4 });
+      const deferral = results.timeline.find(e => e.type === 'deferral');
+      expect(deferral).toEqual(expect.objectContaining({ time: 2, until: 6, mission_type: 'ISR' }));
+      const launches = results.timeline.filter(e => e.type === 'mission');
+      expect(launches.map(e => e.demand_time)).toEqual([6, 14]);
+      expect(launches[0].deferred_from).toBe(2);
+      expect(launches[1].deferred_from).toBeUndefined();
+      // The detail row keeps the arrival time, so start_time - demand_time is the launch latency
+      const row = results.missions_detail.find(r => r.mission_id === deferral.mission_id);
+      expect(row).toEqual(expect.objectContaining({ demand_time: 2, start_time: 6, launch_deferral_hours: 4 }));
+      // 23h waits for 0600 the next day, past the 24h horizon
+      expect(results.timeline.find(e => e.type === 'rejection')).toEqual(expect.objectContaining({ time: 23, reason: 'window' }));
+
+      const rejecting = {
+        ...scenario,
+        mission_types: [{ ...scenario.mission_types[0], launch_window_policy: 'reject' }]
+      };
+      const rejected = await runSimulation(rejecting, { state: mockState });
+      expect(rejected.missions.started).toBe(1);
+      expect(rejected.rejections.window).toBe(2);
+      expect(rejected.launch_deferrals.count).toBe(0);
+
+      const unwindowed = await runSimulation({ ...scenario, mission_types: basicScenario.mission_types }, { state: mockState });
+      expect(unwindowed.rejections.window).toBeUndefined();
+      expect(unwindowed.launch_deferrals).toBeUndefined();
+
+      const withWindows = launch_windows => ({ ...scenario, mission_types: [{ ...basicScenario.mission_types[0], launch_windows }] });
+      const late = await runSimulation(withWindows([{ start_hour_of_day: 6, end_hour_of_day: 25 }]), { state: mockState }).catch(e => e);
+      expect(late.message).toContain('launch_windows[0] hours must be numbers in [0, 24]');
+      expect(late.code).toBe('INVALID_SCENARIO');
+      expect(late.path).toBe('mission_types[0].launch_windows[0]');
+      await expect(runSimulation(withWindows([{ start_hour_of_day: 6, end_hour_of_day: 6 }]), { state: mockState }))
+        .rejects.toThrow('must not start and end at the same hour');
+      await expect(runSimulation(withWindows([]), { state: mockState })).rejects.toThrow('non-empty array');
+    });
+
+    test('max_concurrent serializes a mission type across units despite abundant resources', async () => {
+      const scenario = {
+        ...basicScenario,
+        mission_types: [{ ...basicScenario.mission_types[0], max_concurrent: 1 }],
+        demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 1, start_at_hours: 0 }]
+      };
+      const plenty = { aircraft: 10, pilot: 10, so: 10, payload_by_type: { 'SkyTower II': 10 } };
+      const overrides = { units: { 'HMLA-167': plenty, 'HMLA-267': plenty } };
+
+      const results = await runSimulation(scenario, { state: mockState, overrides });
+
+      // Airborne stretch is 3h (transit in, flight, transit out) starting 1h after demand
+      const airborne = results.timeline.filter(e => e.type === 'mission')
+        .map(e => [e.segments.find(s => s.name === 'transit_in').start, e.segments.find(s => s.name === 'transit_out').end]);
+      expect(airborne).toEqual([[1, 4], [4, 7], [7, 10], [10, 13], [13, 16], [16, 19], [19, 22], [22, 25]]);
+      expect(results.missions.started).toBe(8);
+      expect(results.rejections.concurrency_cap).toBe(16);
+      expect(results.rejections_by_type.ISR.concurrency_cap).toBe(16);
+      expect(results.rejections.aircraft + results.rejections.pilot + results.rejections.so + results.rejections.payload).toBe(0);
+      expect(results.concurrency_caps).toEqual({ ISR: { max_concurrent: 1, peak_concurrent: 1, allocations: 8, times_bound: 16 } });
+
+      const uncapped = await runSimulation(basicScenario, { state: mockState, overrides });
+      expect(uncapped.rejections.concurrency_cap).toBeUndefined();
+      expect(uncapped.concurrency_caps).toBeUndefined();
+
+      const invalid = { ...scenario, mission_types: [{ ...basicScenario.mission_types[0], max_concurrent: 1.5 }] };
+      const error = await runSimulation(invalid, { state: mockState }).catch(e => e);
+      expect(error.message).toContain('max_concurrent must be a non-negative integer');
+      expect(error.code).toBe('INVALID_SCENARIO');
+      expect(error.path).toBe('mission_types[0].max_concurrent');
+    });
+
+    test('launch_recovery sets are booked for launch and recovery windows only', async () => {
+      const { WindowPool } = require('../sim/des/helpers/resources');
+      const scenario = {
+        ...basicScenario,
+        mission_types: [{ ...basicScenario.mission_types[0], requires_launch_recovery: true }],
+        demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 1, start_at_hours: 0 }],
+        unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } }
+      };
+      const plenty = { aircraft: 10, pilot: 10, so: 10, payload_by_type: { 'SkyTower II': 10 }, launch_recovery: 1 };
+      const results = await runSimulation(scenario, { state: mockState, overrides: { units: { 'HMLA-167': plenty } } });
+
+      // Launch is [t, t+1), recovery [t+4, t+4.25): one set serves four consecutive launches, then the
+      // next four demands would launch into an earlier mission's recovery and are rejected up front
+      const starts = results.timeline.filter(e => e.type === 'mission').map(e => e.demand_time);
+      expect(starts).toEqual([0, 1, 2, 3, 8, 9, 10, 11, 16, 17, 18, 19]);
+      expect(results.rejections.launch_recovery).toBe(12);
+      expect(results.rejections_by_unit['HMLA-167'].launch_recovery).toBe(12);
+      expect(results.utilization['HMLA-167'].launch_recovery).toBe(1);
+      expect(results.utilization['HMLA-167'].launch_recovery_efficiency).toBe(0.625);
+      expect(results.utilization['HMLA-167'].launch_recovery_stats).toMatchObject({ total: 1, allocations: 12, denials: 12, busyHours: 15 });
+      expect(results.utilization['HMLA-267'].launch_recovery).toBeUndefined();
+
+      // Types that do not ask for the equipment ignore it
+      const unneeded = await runSimulation({ ...scenario, mission_types: basicScenario.mission_types }, { state: mockState, overrides: { units: { 'HMLA-167': plenty } } });
+      expect(unneeded.missions.started).toBe(24);
+      expect(unneeded.rejections.launch_recovery).toBe(0);
+
+      // A free launch window is not enough when the recovery window is taken; nothing is booked on a miss
+      const sets = new WindowPool('launch_recovery:test', 1);
+      sets.acquire(0, [{ start: 0, end: 1 }, { start: 4, end: 5 }]);
+      expect(sets.availableAt(2)).toBe(1);
+      expect(sets.fitsAll([{ start: 2, end: 3 }, { start: 4.5, end: 5.5 }])).toBe(false);
+      expect(sets.fitsAll([{ start: 2, end: 3 }, { start: 5, end: 6 }])).toBe(true);
+      expect(sets.busyIntervals.length).toBe(2);
+
+      // Counted from v_launch_recovery rows, serviceable ones only
+      const withTable = { tables: { ...mockState.tables, v_launch_recovery: { rows: [{ Unit: 'HMLA-267', Status: 'FMC' }, { Unit: 'HMLA-267', Status: 'NMC' }, { Unit: 'HMLA-267' }] } } };
+      expect(loadState(withTable).launchRecoveryByUnit).toEqual({ 'HMLA-267': 2 });
+
+      const invalid = { ...scenario, mission_types: [{ ...scenario.mission_types[0], requires_launch_recovery: 'yes' }] };
+      const error = await runSimulation(invalid, { state: mockState }).catch(e => e);
+      expect(error.message).toContain('requires_launch_recovery must be a boolean');
+      expect(error.code).toBe('INVALID_SCENARIO');
+      expect(error.path).toBe('mission_types[0].requires_launch_recovery');
+    });
+
+    test('trace logs each check for the selected demands without changing the run', async () => {
+      const scenario = {
+        ...basicScenario,
+        horizon_hours: 6,
+        demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 1, start_at_hours: 0 }]
+      };
+      const results = await runSimulation(scenario, { state: mockState, trace: { missionTypes: ['ISR'], timeRange: [1, 3] } });
+
+      expect(results.trace.map(t => [t.mission_id, t.time, t.unit, t.outcome, t.reason])).toEqual([
+        [2, 1, 'HMLA-167', 'rejected', 'so'],
+        [3, 2, 'HMLA-167', 'rejected', 'so'],
+        [4, 3, 'HMLA-267', 'started', null]
+      ]);
+      // HMLA-167 flew its only SO at t=0; everything it holds comes back at 4.5
+      const [soShort, , started] = results.trace;
+      expect(soShort.assignment).toEqual({ by: 'mission_split', split: { 'HMLA-167': 0.6, 'HMLA-267': 0.4 } });
+      expect(soShort.availability.so).toEqual({ total: 1, available: 0, next_release: 4.5 });
+      expect(soShort.availability.aircraft).toEqual({ total: 2, available: 1, next_release: 4.5 });
+      expect(soShort.checks.map(c => [c.check, c.needed, c.available, c.ok])).toEqual([
+        ['payload:SkyTower II', 1, 1, true],
+        ['aircraft', 1, 1, true],
+        ['pilot', 1, 1, true],
+        ['so', 1, 0, false]
+      ]);
+      expect(started.durations).toEqual({ preflight: 0.5, mount: 0.5, transit_in: 0.5, flight: 2, transit_out: 0.5, postflight: 0.25, turnaround: 0.25 });
+      expect(started.finish_time).toBe(7.5);
+      expect(started.crew).toEqual({ pilots: [0], sos: [0], intel: [] });
+
+      // Seeded stochastic runs trace the same and fly the same as without a trace
+      const poisson = { ...basicScenario, demand: [{ type: 'poisson', mission_type: 'ISR', rate_per_hour: 0.5 }] };
+      const traced = await runSimulation(poisson, { state: mockState, seed: 11, trace: {} });
+      const again = await runSimulation(poisson, { state: mockState, seed: 11, trace: {} });
+      const plain = await runSimulation(poisson, { state: mockState, seed: 11 });
+      expect(traced.trace).toEqual(again.trace);
+      expect(traced.trace).toHaveLength(traced.missions.requested);
+      expect(traced.timeline).toEqual(plain.timeline);
+      expect(plain.trace).toBeUndefined();
+
+      await expect(runSimulation(scenario, { state: mockState, trace: { timeRange: [3, 1] } }))
+        .rejects.toThrow('settings.trace.timeRange must be [start, end]');
+    });
+
+    test('collectTimeline false builds no timeline and leaves every counter unchanged', async () => {
+      // Missions finishing after t=20 are started but not completed
+      const scenario = { ...basicScenario, horizon_hours: 20, demand: [{ type: 'poisson', mission_type: 'ISR', rate_per_hour: 0.5 }] };
+      const full = await runSimulation(scenario, { state: mockState, seed: 5, emitResourceLevels: true, echoConfig: false });
+      const lean = await runSimulation(scenario, { state: mockState, seed: 5, emitResourceLevels: true, echoConfig: false, collectTimeline: false });
+
+      expect(lean.timeline).toBeUndefined();
+      expect(lean.timeline_events).toBeUndefined();
+      expect(full.missions.in_progress_at_horizon).toBeGreaterThan(0);
+      const { timeline, ...rest } = full;
+      expect(lean).toEqual(rest);
+
+      await expect(runSimulation(scenario, { state: mockState, collectTimeline: 'no' }))
+        .rejects.toThrow('settings.collectTimeline must be a boolean');
+    });
+
+    test('crew_policy.availability_rate halves two-seat throughput under saturation', async () => {
+      // Ten demands an hour, each holding a pilot and an SO for 4.5h: both units are crew-bound
+      const unit = { aircraft: 20, pilot: 10, so: 10, payload_by_type: { 'SkyTower II': 20 } };
+      const settings = { state: mockState, seed: 3, overrides: { units: { 'HMLA-167': unit, 'HMLA-267': unit } } };
+      const saturated = { ...basicScenario, horizon_hours: 96, demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 0.1 }] };
+      const withRate = mode => ({ ...saturated, crew_policy: { availability_rate: { pilot: 0.5, so: 0.5 }, availability_mode: mode } });
+
+      const full = await runSimulation(saturated, settings);
+      const scaled = await runSimulation(withRate('deterministic'), settings);
+      const drawn = await runSimulation(withRate('stochastic'), settings);
+
+      expect(full.crew_availability).toBeUndefined();
+      expect(scaled.crew_availability.units['HMLA-167'].pilot).toEqual({ rate: 0.5, headcount: 10, mean_available: 5, realized_rate: 0.5 });
+      expect(scaled.missions.started / full.missions.started).toBeGreaterThan(0.4);
+      expect(scaled.missions.started / full.missions.started).toBeLessThan(0.6);
+      // Daily binomial draws: half the crew on an average day, fewer complete pilot/SO pairs
+      const so = drawn.crew_availability.units['HMLA-267'].so;
+      expect(drawn.crew_availability.mode).toBe('stochastic');
+      expect(so.realized_rate).toBeGreaterThan(0.2);
+      expect(so.realized_rate).toBeLessThan(0.8);
+      expect(drawn.missions.started / full.missions.started).toBeGreaterThan(0.3);
+      expect(drawn.missions.started / full.missions.started).toBeLessThan(0.6);
+      expect((await runSimulation(withRate('stochastic'), settings)).missions).toEqual(drawn.missions);
+
+      await expect(runSimulation({ ...saturated, crew_policy: { availability_rate: { pilto: 0.5 } } }, settings))
+        .rejects.toThrow("crew_policy.availability_rate names unknown crew role 'pilto'");
+      const over = await runSimulation({ ...saturated, crew_policy: { availability_rate: { pilot: 1.5 } } }, settings).catch(e => e);
+      expect(over.message).toContain('crew_policy.availability_rate.pilot must be a number between 0 and 1');
+      expect(over.code).toBe('INVALID_SCENARIO');
+      const mode = await runSimulation({ ...saturated, crew_policy: { availability_mode: 'sometimes' } }, settings).catch(e => e);
+      expect(mode.code).toBe('INVALID_SCENARIO');
+      expect(mode.path).toBe('crew_policy.availability_mode');
+    });
+
+    test('resolved_config echoes the resolved scenario and settings the run used', async () => {
+      const scenario = { ...basicScenario, horizon_hours: undefined };
+      const results = await runSimulation(scenario, { state: mockState, seed: 11, trace: { missionTypes: ['ISR'] } });
+      const { settings, scenario: echoed } = results.resolved_config;
+
+      expect(echoed.horizon_hours).toBe(24);
+      expect(echoed.mission_types.map(m => m.name)).toEqual(['ISR']);
+      expect(settings).toMatchObject({
+        seed: 11,
+        mode: 'stochastic',
+        resourceHoldModel: 'legacy',
+        timelineRejectionSampling: null,
+        collectTimeline: true,
+        timelineStreaming: false,
+        trace: { missionTypes: ['ISR'], timeRange: null }
+      });
+      expect(typeof settings.peakWindowHours).toBe('number');
+      expect(JSON.parse(JSON.stringify(results.resolved_config))).toEqual(results.resolved_config);
+
+      // Expected-value runs resolve an unset seed to 0
+      const ev = await runSimulation(basicScenario, { state: mockState, mode: 'expected_value' });
+      expect(ev.resolved_config.settings.seed).toBe(0);
+
+      const lean = await runSimulation(basicScenario, { state: mockState, seed: 11, echoConfig: false });
+      expect(lean.resolved_config).toBeUndefined();
+      await expect(runSimulation(basicScenario, { state: mockState, echoConfig: 'no' }))
+        .rejects.toThrow('settings.echoConfig must be a boolean');
+    });
+
+    test('maxOutputBytes drops the timeline of an oversized result instead of failing', async () => {
+      const { limitOutputSize, estimateJsonBytes } = require('../sim/des/helpers/outputsize');
+
+      // Synthetic result: small counters and a 20,000-entry timeline
+      const entry = i => ({ type: 'mission', mission_id: i, unit: 'HMLA-167', demand_time: i / 100, finish: i / 100 + 4.5 });
+      const synthetic = { missions: { requested: 20000 }, timeline: Array.from({ length: 20000 }, (_, i) => entry(i)), missions_detail: [] };
+      const exact = JSON.stringify(synthetic).length;
+      expect(Math.abs(estimateJsonBytes(synthetic) - exact) / exact).toBeLessThan(0.05);
+
+      const limited = limitOutputSize(synthetic, 10000, ['timeline', 'missions_detail']);
+      expect(limited.truncated).toEqual(['timeline']);
+      expect(synthetic.timeline).toBeUndefined();
+      expect(synthetic.missions_detail).toEqual([]);
+      expect(limited.sizes.estimated_bytes).toBeGreaterThan(10000);
+      expect(limited.sizes.actual_bytes).toBe(JSON.stringify(synthetic).length);
+      expect(limitOutputSize({ missions: {} }, 10000, ['timeline'])).toBeNull();
+      let error = null;
+      try {
+        limitOutputSize({ missions: { note: 'x'.repeat(200) } }, 100, ['timeline']);
+      } catch (e) {
+        error = e;
+      }
+      expect(error.code).toBe('OUTPUT_TOO_LARGE');
+
+      const full = await runSimulation(basicScenario, { state: mockState, seed: 3, missionDetail: true });
+      const capped = await runSimulation(basicScenario, { state: mockState, seed: 3, missionDetail: true, maxOutputBytes: 7000 });
+      expect(full.truncated).toBeUndefined();
+      expect(capped.truncated).toEqual(['timeline', 'missions_detail']);
+      expect(capped.timeline).toBeUndefined();
+      expect(capped.missions).toEqual(full.missions);
+      expect(capped.output_size.actual_bytes).toBeLessThanOrEqual(7000);
+
+      await expect(runSimulation(basicScenario, { state: mockState, maxOutputBytes: 0 }))
+        .rejects.toThrow('settings.maxOutputBytes must be a positive number or null');
+    });
+
+    test('weighted_completion_score counts high-priority failures for more', async () => {
+      const flown = { flight_time: { type: 'deterministic', value_hours: 1 }, required_aircrew: { pilot: 1, so: 1 }, required_payload_types: [] };
+      // Needing more pilots than any unit has makes every demand of that type infeasible
+      const unflyable = { ...flown, required_aircrew: { pilot: 99, so: 1 } };
+      const scenarioFailing = failing => ({
+        ...basicScenario,
+        mission_types: [
+          { name: 'High', priority: 1, ...(failing === 'High' ? unflyable : flown) },
+          { name: 'Low', priority: 5, ...(failing === 'Low' ? unflyable : flown) }
+        ],
+        demand: [
+          { type: 'deterministic', mission_type: 'High', every_hours: 8, start_at_hours: 0 },
+          { type: 'deterministic', mission_type: 'Low', every_hours: 8, start_at_hours: 1 }
+        ]
+      });
+
+      const lowFails = await runSimulation(scenarioFailing('Low'), { state: mockState });
+      const highFails = await runSimulation(scenarioFailing('High'), { state: mockState });
+      expect(lowFails.missions.infeasible).toBe(3);
+      expect(highFails.missions.infeasible).toBe(3);
+      expect(lowFails.missions.completed).toBe(highFails.missions.completed);
+
+      // Default weights 1 / priority: 3 of (3 × 1 + 3 × 0.2) versus 0.6 of 3.6
+      expect(lowFails.weighted_completion_score).toBe(0.833);
+      expect(highFails.weighted_completion_score).toBe(0.167);
+
+      // Explicit weights replace 1 / priority for the priorities they name
+      const flat = await runSimulation(scenarioFailing('High'), { state: mockState, priorityWeights: { 1: 1, 5: 1 } });
+      expect(flat.weighted_completion_score).toBe(0.5);
+
+      await expect(runSimulation(scenarioFailing('High'), { state: mockState, priorityWeights: { 0: 1 } }))
+        .rejects.toThrow("settings.priorityWeights: priority '0' must be a positive integer");
+      const fractional = await runSimulation({ ...scenarioFailing('High'), mission_types: [{ name: 'High', priority: 0.5, ...flown }, { name: 'Low', ...flown }] }, { state: mockState }).catch(e => e);
+      expect(fractional.message).toContain('priority must be a positive integer');
+      expect(fractional.code).toBe('INVALID_SCENARIO');
+      expect(fractional.path).toBe('mission_types[0].priority');
+    });
+
+    test('flight_time_modifiers scale flight time for mounted payloads', async () => {
+      const zero = { type: 'deterministic', value_hours: 0 };
+      const scenario = {
+        ...basicScenario,
+        mission_types: [
+          { name: 'ISR', flight_time: { type: 'deterministic', value_hours: 2 }, required_aircrew: { pilot: 1, so: 1 }, required_payload_types: ['SkyTower II'] }
+        ],
+        process_times: { preflight: zero, postflight: zero, turnaround: zero, mount_times: { 'SkyTower II': zero } },
+        unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } }
+      };
+      const heavy = {
+        ...scenario,
+        mission_types: [{ ...scenario.mission_types[0], flight_time_modifiers: { 'SkyTower II': 2, 'Hellfire': 5 } }]
+      };
+
+      const base = await runSimulation(scenario, { state: mockState });
+      const doubled = await runSimulation(heavy, { state: mockState });
+      const flightHours = r => r.timeline.filter(e => e.type === 'mission')
+        .map(m => m.segments.find(s => s.name === 'flight'))
+        .map(s => s.end - s.start);
+
+      // Only the mounted payload's modifier applies (Hellfire is not carried)
+      expect(flightHours(base)).toEqual([2, 2, 2]);
+      expect(flightHours(doubled)).toEqual([4, 4, 4]);
+      expect(doubled.missions.completed).toBe(base.missions.completed);
+      expect(doubled.utilization['HMLA-167'].aircraft_efficiency)
+        .toBeCloseTo(2 * base.utilization['HMLA-167'].aircraft_efficiency, 2);
+
+      // Additive hours on top of the multiplier
+      const longer = {
+        ...scenario,
+        mission_types: [{ ...scenario.mission_types[0], flight_time_modifiers: { 'SkyTower II': { multiplier: 1.5, add_hours: 0.5 } } }]
+      };
+      expect(flightHours(await runSimulation(longer, { state: mockState }))).toEqual([3.5, 3.5, 3.5]);
+
+      // Negative multipliers are rejected up front, negative resulting durations when sampled
+      const negativeMultiplier = { ...scenario, mission_types: [{ ...scenario.mission_types[0], flight_time_modifiers: { 'SkyTower II': -1 } }] };
+      await expect(runSimulation(negativeMultiplier, { state: mockState })).rejects.toThrow('multiplier must be a non-negative number');
+      const negativeFlight = { ...scenario, mission_types: [{ ...scenario.mission_types[0], flight_time_modifiers: { 'SkyTower II': { add_hours: -3 } } }] };
+      await expect(runSimulation(negativeFlight, { state: mockState })).rejects.toThrow('negative flight time');
+    });
+
+    test('additional crew roles gate missions and process steps', async () => {
+      const scenario = {
+        ...basicScenario,
+        mos_roles: { '6276': 'maintainer' },
+        mission_types: [{ ...basicScenario.mission_types[0], required_aircrew: { pilot: 1, so: 1, maintainer: 1 } }],
+        process_times: { ...basicScenario.process_times, role_requirements: { turnaround: { maintainer: 2 } } },
+        unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } }
+      };
+      const withMaintainers = count => ({
+        units: { 'HMLA-167': { aircraft: 2, pilot: 4, so: 4, crew_by_role: { maintainer: count } } }
+      });
+
+      // One mission-wide maintainer plus two for turnaround: two maintainers cannot cover it
+      const short = await runSimulation(scenario, { state: mockState, overrides: withMaintainers(2) });
+      expect(short.missions.started).toBe(0);
+      expect(short.rejections.maintainer).toBe(short.missions.requested);
+      expect(short.rejections_by_unit['HMLA-167'].maintainer).toBe(short.missions.requested);
+
+      const staffed = await runSimulation(scenario, { state: mockState, overrides: withMaintainers(3) });
+      expect(staffed.missions.started).toBe(staffed.missions.requested);
+      expect(staffed.rejections.maintainer).toBe(0);
+      const [mission] = staffed.timeline.filter(e => e.type === 'mission');
+      const turnaround = mission.segments.find(seg => seg.name === 'turnaround');
+      expect(mission.crew.roles.maintainer).toHaveLength(3);
+      expect(mission.crew.roles.maintainer.filter(a => a.start === turnaround.start && a.end === turnaround.end)).toHaveLength(2);
+      expect(staffed.utilization['HMLA-167'].maintainer_efficiency).toBeGreaterThan(0);
+      expect(staffed.utilization['HMLA-167'].effective_crew.maintainer).toBe(3);
+
+      // Scenarios without mos_roles report no extra roles
+      const plain = await runSimulation(basicScenario, { state: mockState });
+      expect(plain.timeline.find(e => e.type === 'mission').crew.roles).toBeUndefined();
+      expect(Object.keys(plain.rejections)).toEqual(['aircraft', 'control_station', 'pilot', 'so', 'intel', 'payload']);
+
+      await expect(runSimulation(scenario, { state: mockState, overrides: { units: { 'HMLA-167': { crew_by_role: { welder: 1 } } } } }))
+        .rejects.toThrow("unknown role 'welder'");
+    });
+
+    test('deterministic demand stays on its grid and excludes the horizon boundary', async () => {
+      const zero = { type: 'deterministic', value_hours: 0 };
+      const gridScenario = (horizon, every) => ({
+        ...basicScenario,
+        horizon_hours: horizon,
+        mission_types: [{ name: 'ISR', flight_time: { type: 'deterministic', value_hours: every }, required_aircrew: { pilot: 0, so: 0 }, required_payload_types: [] }],
+        demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: every, start_at_hours: 0 }],
+        process_times: { preflight: zero, postflight: zero, turnaround: zero },
+        unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } }
+      });
+      const overrides = { units: { 'HMLA-167': { aircraft: 5 } } };
+
+      // Summing 0.1 ten times gives 0.9999999999999999 < 1, which used to add an 11th demand at the horizon
+      const tenth = await runSimulation(gridScenario(1, 0.1), { state: mockState, overrides });
+      const times = tenth.timeline.filter(e => e.type === 'mission').map(m => m.demand_time);
+      expect(tenth.missions.requested).toBe(10);
+      expect(times[9]).toBe(0.9);
+      // The last mission finishes exactly at the horizon and counts as completed
+      expect(tenth.missions.completed).toBe(10);
+
+      const twentieth = await runSimulation(gridScenario(3, 0.05), { state: mockState, overrides });
+      expect(twentieth.missions.requested).toBe(60);
+    });
+
+    test('unit-tagged horizon and distributions match the hour-denominated scenario', async () => {
+      const inHours = {
+        ...basicScenario,
+        horizon_hours: 72,
+        mission_types: [{
+          ...basicScenario.mission_types[0],
+          flight_time: { type: 'triangular', a: 1.5, m: 2, b: 3, transit_in_hours: 0.5, transit_out_hours: 0.5 }
+        }],
+        demand: [{ type: 'renewal', mission_type: 'ISR', interarrival: { type: 'exponential', rate_per_hour: 0.25 } }],
+        process_times: {
+          ...basicScenario.process_times,
+          preflight: { type: 'deterministic', value_hours: 0.5 },
+          turnaround: { type: 'lognormal', mu: -1, sigma: 0.3 }
+        }
+      };
+      const mixed = {
+        ...inHours,
+        horizon_hours: undefined,
+        horizon: { value: 3, unit: 'days' },
+        mission_types: [{
+          ...inHours.mission_types[0],
+          flight_time: { type: 'triangular', a: 90, m: 120, b: 180, transit_in_hours: 30, transit_out_hours: 30, unit: 'minutes' }
+        }],
+        demand: [{ type: 'renewal', mission_type: 'ISR', interarrival: { type: 'exponential', rate: 6, unit: 'days' } }],
+        process_times: {
+          ...inHours.process_times,
+          preflight: { type: 'deterministic', value_hours: 30, unit: 'minutes' },
+          turnaround: { type: 'lognormal', mu: -1 + Math.log(60), sigma: 0.3, unit: 'minutes' }
+        }
+      };
+
+      const a = await runSimulation(inHours, { state: mockState, seed: 11 });
+      const b = await runSimulation(mixed, { state: mockState, seed: 11 });
+      expect(b.horizon_hours).toBe(72);
+      expect(b.missions).toEqual(a.missions);
+      expect(b.utilization).toEqual(a.utilization);
+      const finishes = r => r.timeline.filter(e => e.type === 'mission').map(m => m.finish_time);
+      finishes(b).forEach((t, i) => expect(t).toBeCloseTo(finishes(a)[i], 9));
+      // The caller's scenario is left as written
+      expect(mixed.horizon).toEqual({ value: 3, unit: 'days' });
+
+      const negative = { ...inHours, process_times: { ...inHours.process_times, preflight: { type: 'deterministic', value_hours: -15, unit: 'minutes' } } };
+      await expect(runSimulation(negative, { state: mockState })).rejects.toThrow('-15 minutes resolves to -0.25 hours');
+      await expect(runSimulation({ ...inHours, horizon: { value: 2, unit: 'days' } }, { state: mockState }))
+        .rejects.toThrow('48 hours) conflicts with horizon_hours 72');
+      await expect(runSimulation({ ...inHours, horizon: { value: 2, unit: 'weeks' } }, { state: mockState }))
+        .rejects.toThrow('scenario.horizon.unit must be one of minutes, hours, days');
+    });
+
+    test('reserved aircraft and crew are never used by non-alert missions under saturation', async () => {
+      const zero = { type: 'deterministic', value_hours: 0 };
+      const scenario = {
+        ...basicScenario,
+        horizon_hours: 48,
+        mission_types: [
+          { name: 'ISR', flight_time: { type: 'deterministic', value_hours: 4 }, required_aircrew: { pilot: 1, so: 0 }, required_payload_types: [] },
+          { name: 'Alert', flight_time: { type: 'deterministic', value_hours: 2 }, required_aircrew: { pilot: 1, so: 0 }, required_payload_types: [] }
+        ],
+        demand: [
+          { type: 'deterministic', mission_type: 'ISR', every_hours: 0.5, start_at_hours: 0 },
+          { type: 'deterministic', mission_type: 'Alert', every_hours: 12, start_at_hours: 1 }
+        ],
+        process_times: { preflight: zero, postflight: zero, turnaround: zero },
+        unit_policy: {
+          mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 },
+          reservations: { 'HMLA-167': { aircraft: 1, pilot: 1, for_mission_types: ['Alert'] } }
+        }
+      };
+      const overrides = { units: { 'HMLA-167': { aircraft: 3, pilot: 4, so: 0 } } };
+
+      const result = await runSimulation(scenario, { state: mockState, overrides });
+      const missions = result.timeline.filter(e => e.type === 'mission');
+      const routine = missions.filter(m => m.mission_type === 'ISR');
+      const alerts = missions.filter(m => m.mission_type === 'Alert');
+
+      // Routine missions saturate the two unreserved aircraft but never reach the third or pilot 3
+      expect(result.rejections.aircraft).toBeGreaterThan(0);
+      for (const m of routine) {
+        const concurrent = routine.filter(o => o.demand_time <= m.demand_time && o.finish_time > m.demand_time);
+        expect(concurrent.length).toBeLessThanOrEqual(2);
+        expect(m.crew.pilots.map(p => p.id)).not.toContain(3);
+      }
+
+      // Every alert launches on the reserve
+      expect(alerts.length).toBe(4);
+      expect(result.by_type.Alert.rejected).toBe(0);
+      expect(alerts.every(m => m.crew.pilots[0].id === 3)).toBe(true);
+      expect(result.reservations['HMLA-167'].for_mission_types).toEqual(['Alert']);
+      expect(result.reservations['HMLA-167'].aircraft).toMatchObject({ reserved: 1, allocations: 4, busy_hours: 8, peak_in_use: 1 });
+      expect(result.reservations['HMLA-167'].aircraft.efficiency).toBeCloseTo(8 / 48, 3);
+      expect(result.reservations['HMLA-167'].pilot).toMatchObject({ reserved: 1, allocations: 4 });
+      expect(result.reservations['HMLA-167'].so).toBeUndefined();
+
+      // Without reservations the field is absent
+      expect((await runSimulation(basicScenario, { state: mockState })).reservations).toBeUndefined();
+    });
+
+    test('peak_window finds the sliding window with the most starts', async () => {
+      const { peakWindow } = require('../sim/des/helpers/throughput');
+
+      // The cluster at 30-54h (5 starts) beats the 4 starts of 0-20h; the window is half-open,
+      // so the start at 54h (exactly 24h after 30h) is not in it
+      const starts = [0, 5, 10, 20, 30, 35, 40, 45, 53.999, 54, 80];
+      expect(peakWindow(starts, 24)).toEqual({ max_starts: 5, window_start: 30 });
+      expect(peakWindow([0, 24, 48], 24)).toEqual({ max_starts: 1, window_start: 0 });
+      expect(peakWindow([12, 1, 2, 3], 2)).toEqual({ max_starts: 2, window_start: 1 });
+      expect(peakWindow([], 24)).toEqual({ max_starts: 0, window_start: null });
+
+      // Deterministic demand every 8h on one unit: three starts in any 24h window, four in 25h
+      const scenario = { ...basicScenario, horizon_hours: 72, unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } } };
+      const result = await runSimulation(scenario, { state: mockState });
+      expect(result.peak_window).toEqual({
+        window_hours: 24,
+        max_starts: 3,
+        window_start: 0,
+        by_unit: { 'HMLA-167': { max_starts: 3, window_start: 0 }, 'HMLA-267': { max_starts: 0, window_start: null } }
+      });
+      const wider = await runSimulation(scenario, { state: mockState, peakWindowHours: 25 });
+      expect(wider.peak_window.max_starts).toBe(4);
+
+      await expect(runSimulation(scenario, { state: mockState, peakWindowHours: 0 })).rejects.toThrow('peakWindowHours');
+    });
+
+    test('demand pinned to a unit bypasses mission_split and charges that unit', async () => {
+      // The split sends everything to HMLA-167, but the tasking stream belongs to HMLA-267
+      const scenario = {
+        ...basicScenario,
+        horizon_hours: 48,
+        demand: [
+          { type: 'deterministic', name: 'routine', mission_type: 'ISR', every_hours: 8, start_at_hours: 0 },
+          { type: 'deterministic', name: 'tasking', mission_type: 'ISR', every_hours: 1, start_at_hours: 0.5, unit: 'HMLA-267' }
+        ],
+        unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } }
+      };
+      const result = await runSimulation(scenario, { state: mockState });
+
+      const missions = result.timeline.filter(e => e.type === 'mission');
+      const rejections = result.timeline.filter(e => e.type === 'rejection');
+      expect(missions.filter(e => e.demand_source === 'tasking').every(e => e.unit === 'HMLA-267')).toBe(true);
+      expect(missions.filter(e => e.demand_source === 'routine').every(e => e.unit === 'HMLA-167')).toBe(true);
+      expect(rejections.filter(e => e.demand_source === 'tasking').every(e => e.unit === 'HMLA-267')).toBe(true);
+
+      // Hourly tasking saturates HMLA-267; HMLA-167 carries only the routine stream
+      const charged = Object.values(result.rejections_by_unit['HMLA-267']).reduce((a, b) => a + b, 0);
+      expect(result.by_demand_source.tasking.rejected).toBeGreaterThan(0);
+      expect(charged).toBe(result.by_demand_source.tasking.rejected);
+      expect(result.by_demand_source.routine.rejected).toBe(0);
+      expect(missions.filter(e => e.unit === 'HMLA-167')).toHaveLength(result.by_demand_source.routine.started);
+
+      const unknown = { ...scenario, demand: [{ ...scenario.demand[1], unit: 'VMU-3' }] };
+      await expect(runSimulation(unknown, { state: mockState })).rejects.toThrow("pinned to unknown unit 'VMU-3'");
+      const both = { ...scenario, demand: [{ ...scenario.demand[1], units: ['HMLA-167'] }] };
+      await expect(runSimulation(both, { state: mockState })).rejects.toThrow('either unit or units');
+    });
+
+    test('crew_experience scales preflight and postflight for that unit only', async () => {
+      // Two identical units, six missions each, all finished before the horizon; HMLA-267's crews take 1.5x on the ground
+      const unit = { aircraft: 2, pilot: 4, so: 4, payload_by_type: { 'SkyTower II': 4 } };
+      const scenario = {
+        ...basicScenario,
+        horizon_hours: 96,
+        demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 8, start_at_hours: 0 }],
+        unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 1 }, crew_experience: { 'HMLA-167': 1.0, 'HMLA-267': 1.5 } }
+      };
+      const overrides = { units: { 'HMLA-167': unit, 'HMLA-267': unit } };
+      const result = await runSimulation(scenario, { state: mockState, overrides });
+
+      // Preflight 0.5h + postflight 0.25h grows by 0.375h per mission
+      const busy = u => result.utilization[u].pilot_stats.busyHours;
+      expect(busy('HMLA-167')).toBeCloseTo(6 * 4.5, 6);
+      expect(busy('HMLA-267') - busy('HMLA-167')).toBeCloseTo(6 * 0.375, 6);
+      expect(result.utilization['HMLA-267'].pilot_efficiency).toBeGreaterThan(result.utilization['HMLA-167'].pilot_efficiency);
+      expect(result.initial_resources.crew_experience).toEqual({ 'HMLA-167': 1, 'HMLA-267': 1.5 });
+
+      const plain = await runSimulation({ ...scenario, unit_policy: { mission_split: scenario.unit_policy.mission_split } }, { state: mockState, overrides });
+      expect(plain.initial_resources.crew_experience).toBeUndefined();
+
+      const bad = { ...scenario, unit_policy: { ...scenario.unit_policy, crew_experience: { 'HMLA-267': 0 } } };
+      await expect(runSimulation(bad, { state: mockState, overrides })).rejects.toThrow('must be a positive number');
+      const unknown = { ...scenario, unit_policy: { ...scenario.unit_policy, crew_experience: { 'VMU-3': 1.2 } } };
+      await expect(runSimulation(unknown, { state: mockState, overrides })).rejects.toThrow("unknown unit 'VMU-3'");
+    });
+
+    test('scheduled reallocation moves free aircraft between bases after transit', async () => {
+      // Hourly tasking on HMLA-267 (one aircraft); HMLA-167 sends two of its three idle aircraft at 10h
+      const scenario = {
+        ...basicScenario,
+        horizon_hours: 48,
+        demand: [{ type: 'deterministic', name: 'tasking', mission_type: 'ISR', every_hours: 1, start_at_hours: 0, unit: 'HMLA-267' }],
+        bases: { North: ['HMLA-167'], South: ['HMLA-267'] },
+        reallocation: { transit_hours: 4, transfers: [{ at_hours: 10, from: 'HMLA-167', to: 'HMLA-267', aircraft: 2 }] }
+      };
+      const crew = { pilot: 10, so: 10, payload_by_type: { 'SkyTower II': 10 } };
+      const overrides = { units: { 'HMLA-167': { aircraft: 3, ...crew }, 'HMLA-267': { aircraft: 1, ...crew } } };
+      const result = await runSimulation(scenario, { state: mockState, overrides });
+      const stay = await runSimulation({ ...scenario, reallocation: undefined }, { state: mockState, overrides });
+
+      const moves = result.timeline.filter(e => e.type === 'reallocation');
+      expect(moves).toEqual([{
+        type: 'reallocation', time: 10, arrival_time: 14, from_unit: 'HMLA-167', to_unit: 'HMLA-267',
+        from_base: 'North', to_base: 'South', requested: 2, aircraft: 2
+      }]);
+      expect(result.reallocations).toEqual({ count: 1, aircraft_requested: 2, aircraft_moved: 2 });
+      expect(stay.reallocations).toBeUndefined();
+
+      // Tails in transit count for neither unit; the extra aircraft only fly after arrival
+      expect(result.utilization['HMLA-167'].aircraft_stats.availableHours).toBeCloseTo(3 * 48 - 2 * 38, 6);
+      expect(result.utilization['HMLA-267'].aircraft_stats.availableHours).toBeCloseTo(3 * 48 - 2 * 14, 6);
+      const starts = r => r.timeline.filter(e => e.type === 'mission').map(e => e.demand_time);
+      expect(starts(result).filter(t => t < 14)).toEqual(starts(stay).filter(t => t < 14));
+      expect(result.missions.started).toBeGreaterThan(stay.missions.started);
+
+      // Only free aircraft leave; within one base the transfer is immediate
+      const sameBase = {
+        ...scenario,
+        bases: { North: ['HMLA-167', 'HMLA-267'] },
+        reallocation: { transit_hours: 4, transfers: [{ at_hours: 0.5, from: 'HMLA-267', to: 'HMLA-167', aircraft: 1 }] }
+      };
+      const busy = await runSimulation(sameBase, { state: mockState, overrides });
+      expect(busy.reallocations).toEqual({ count: 0, aircraft_requested: 1, aircraft_moved: 0 });
+      sameBase.reallocation.transfers[0] = { at_hours: 5, from: 'HMLA-167', to: 'HMLA-267', aircraft: 1 };
+      const local = await runSimulation(sameBase, { state: mockState, overrides });
+      expect(local.timeline.find(e => e.type === 'reallocation').arrival_time).toBe(5);
+
+      const bad = { ...scenario, reallocation: { transfers: [{ at_hours: 1, from: 'HMLA-167', to: 'VMU-3', aircraft: 1 }] } };
+      await expect(runSimulation(bad, { state: mockState, overrides })).rejects.toThrow("unknown unit 'VMU-3'");
+    });
+
+    test('missions cut off by the horizon are counted and flagged truncated', async () => {
+      // Demands at 0, 8 and 16h take 4.5h each; the last is still out at the 18h horizon
+      const scenario = { ...basicScenario, horizon_hours: 18, unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } } };
+      const result = await runSimulation(scenario, { state: mockState });
+
+      expect(result.missions).toMatchObject({ started: 3, completed: 2, in_progress_at_horizon: 1 });
+      expect(result.by_type.ISR.in_progress_at_horizon).toBe(1);
+      expect(result.by_demand_source['demand[0]'].in_progress_at_horizon).toBe(1);
+      expect(result.in_progress_by_unit).toEqual({ 'HMLA-167': 1, 'HMLA-267': 0 });
+      const missions = result.timeline.filter(e => e.type === 'mission');
+      expect(missions.filter(e => e.truncated).map(e => e.demand_time)).toEqual([16]);
+
+      // Streamed timelines carry the same flag
+      const chunks = [];
+      await runSimulation(scenario, { state: mockState, onTimelineChunk: c => chunks.push(...c) });
+      expect(chunks.filter(e => e.type === 'mission' && e.truncated)).toHaveLength(1);
+    });
+
+    test('active missions in the snapshot hold resources from t=0', async () => {
+      // Both of HMLA-167's FMC aircraft are airborne until t=5, so the demand at t=0 is rejected
+      const state = {
+        tables: {
+          ...mockState.tables,
+          v_active_missions: { rows: [{ Unit: 'HMLA-167', 'Mission Type': 'ISR', 'Remaining Hours': 5, 'Elapsed Hours': 1.5, Aircraft: 2 }] }
+        }
+      };
+      const scenario = { ...basicScenario, unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } } };
+      const result = await runSimulation(scenario, { state });
+      const cold = await runSimulation(scenario, { state: mockState });
+
+      const rejections = result.timeline.filter(e => e.type === 'rejection');
+      expect(rejections.map(e => [e.time, e.reason])).toEqual([[0, 'aircraft']]);
+      expect(result.missions).toMatchObject({ requested: 3, started: 2 });
+      expect(cold.missions.started).toBe(3);
+
+      // Crew and payload not in the row come from the ISR requirements; busy time counts from t=0
+      const seeded = result.timeline.find(e => e.type === 'active_mission');
+      expect(seeded).toMatchObject({ unit: 'HMLA-167', demand_time: -1.5, finish_time: 5, payload_types: ['SkyTower II'] });
+      expect(seeded.crew.pilots).toHaveLength(1);
+      expect(seeded.crew.sos).toHaveLength(1);
+      expect(result.initial_resources.active_missions).toBe(1);
+      const aircraftBusy = r => r.utilization['HMLA-167'].aircraft_stats.busyHours;
+      expect(aircraftBusy(result)).toBeCloseTo(aircraftBusy(cold) / 3 * 2 + 2 * 5, 6);
+
+      const tooMany = { tables: { ...state.tables, v_active_missions: { rows: [{ Unit: 'HMLA-167', 'Remaining Hours': 5, Aircraft: 3 }] } } };
+      const tooManyError = await runSimulation(scenario, { state: tooMany }).catch(e => e);
+      expect(tooManyError.message).toContain('too few free aircraft');
+      expect(tooManyError).toMatchObject({ code: 'INVALID_SCENARIO', path: 'v_active_missions[0]' });
+    });
+
+    test('expected_value mode replaces every distribution with its mean', async () => {
+      const { distMean, sampleDist, setExpectedValueMode } = require('../sim/des/helpers/distributions');
+      expect(distMean({ type: 'deterministic', value_hours: 2 })).toBe(2);
+      expect(distMean({ type: 'exponential', rate_per_hour: 0.25 })).toBe(4);
+      expect(distMean({ type: 'triangular', a: 1, m: 2, b: 6 })).toBe(3);
+      expect(distMean({ type: 'lognormal', mu: 0, sigma: 1 })).toBeCloseTo(Math.exp(0.5), 12);
+      const previous = setExpectedValueMode(true);
+      expect(sampleDist({ type: 'triangular', a: 1, m: 2, b: 6 })).toBe(3);
+      setExpectedValueMode(previous);
+
+      const scenario = {
+        ...basicScenario,
+        horizon_hours: 24,
+        mission_types: [{ ...basicScenario.mission_types[0], flight_time: { type: 'triangular', a: 1, m: 2, b: 6, transit_in_hours: 0.5, transit_out_hours: 0.5 } }],
+        demand: [{ type: 'poisson', mission_type: 'ISR', rate_per_hour: 0.2 }]
+      };
+      const result = await runSimulation(scenario, { state: mockState, mode: 'expected_value' });
+      expect(result.mode).toBe('expected_value');
+
+      // Poisson demand every 1 / rate hours; flight takes the triangular mean
+      const missions = result.timeline.filter(e => e.type === 'mission');
+      expect(missions.map(m => m.demand_time)).toEqual([5, 10, 15, 20]);
+      const flight = missions[0].segments.find(seg => seg.name === 'flight');
+      expect(flight.end - flight.start).toBeCloseTo(3, 9);
+
+      // Reproducible without a seed; stochastic runs are unchanged and carry no mode
+      const again = await runSimulation(scenario, { state: mockState, mode: 'expected_value' });
+      expect(again.missions).toEqual(result.missions);
+      const stochastic = await runSimulation(scenario, { state: mockState, seed: 3 });
+      expect(stochastic.mode).toBeUndefined();
+      expect(stochastic).toEqual(await runSimulation(scenario, { state: mockState, seed: 3, mode: 'stochastic' }));
+      await expect(runSimulation(scenario, { state: mockState, mode: 'mean' })).rejects.toThrow('settings.mode must be one of');
+    });
+
+    test('demand preview matches the demand a seeded run sees and checks each entry against its expected count', async () => {
+      const scenario = {
+        ...basicScenario,
+        horizon_hours: 100,
+        demand: [
+          { name: 'fixed', mission_type: 'ISR', type: 'deterministic', every_hours: 8, start_at_hours: 2 },
+          { name: 'random', mission_type: 'ISR', type: 'poisson', rate_per_hour: 0.5 },
+          { name: 'gaps', mission_type: 'ISR', type: 'renewal', interarrival: { type: 'triangular', a: 2, m: 4, b: 6 } }
+        ]
+      };
+      const preview = previewDemand(scenario, { seed: 21, maxEvents: 5 });
+      expect(preview.events).toHaveLength(5);
+      expect(preview.events_truncated).toBe(true);
+      expect(preview.events[0]).toEqual({ time: 0, mission_type: 'ISR', demand_source: 'gaps' });
+
+      const check = Object.fromEntries(preview.expected_rate_check.map(row => [row.demand_source, row]));
+      expect(check.fixed).toMatchObject({ type: 'deterministic', expected: 13, generated: 13 });
+      expect(check.random).toMatchObject({ type: 'poisson', expected: 50 });
+      expect(check.gaps).toMatchObject({ type: 'renewal', expected: 25 });
+      expect(Math.abs(check.random.generated - 50)).toBeLessThan(25);
+      expect(Math.abs(check.gaps.generated - 25)).toBeLessThanOrEqual(2);
+
+      const generated = preview.expected_rate_check.reduce((n, row) => n + row.generated, 0);
+      expect(preview.counts_by_type).toEqual({ ISR: generated });
+      const results = await runSimulation(scenario, { state: mockState, seed: 21 });
+      expect(results.missions.requested).toBe(generated);
+      expect(previewDemand(scenario, { seed: 21, maxEvents: 5 })).toEqual(preview);
+    });
+
+    test('scheduled demand replays the given times in order and drops those past the horizon', () => {
+      const scenario = {
+        ...basicScenario,
+        horizon_hours: 24,
+        demand: [{ name: 'day', mission_type: 'ISR', type: 'scheduled', at_hours: [13.5, 2, 7.25, 24, 30] }]
+      };
+      const preview = previewDemand(scenario);
+      expect(preview.events.map(e => e.time)).toEqual([2, 7.25, 13.5]);
+      expect(preview.expected_rate_check[0]).toMatchObject({ type: 'scheduled', expected: 3, generated: 3 });
+
+      expect(() => previewDemand({ ...scenario, demand: [{ mission_type: 'ISR', type: 'scheduled', at_hours: [1, -2] }] }))
+        .toThrow('demand[0].at_hours must be an array of non-negative demand times');
+    });
+
+    test('rejection density against zero aircraft is flat at the demand rate in every bucket', async () => {
+      const scenario = {
+        ...basicScenario,
+        horizon_hours: 24,
+        demand: [{ mission_type: 'ISR', type: 'deterministic', every_hours: 0.5 }],
+        unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } }
+      };
+      // HMLA-267's aircraft keeps ISR feasible, so HMLA-167's demands are rejected rather than infeasible
+      const overrides = { units: { 'HMLA-167': { aircraft: 0 }, 'HMLA-267': { aircraft: 1 } } };
+      const results = await runSimulation(scenario, { state: mockState, overrides, seed: 3 });
+      expect(results.rejection_density).toEqual({ bucket_hours: 1, buckets: { aircraft: new Array(24).fill(2) } });
+
+      // Independent of the timeline: a streamed run reports the same density
+      const streamed = await runSimulation(scenario, {
+        state: mockState, overrides, seed: 3, rejectionDensityBucketHours: 5, onTimelineChunk: () => {}
+      });
+      expect(streamed.timeline).toBeUndefined();
+      expect(streamed.rejection_density).toEqual({ bucket_hours: 5, buckets: { aircraft: [10, 10, 10, 10, 8] } });
+
+      await expect(runSimulation(scenario, { state: mockState, rejectionDensityBucketHours: 0 }))
+        .rejects.toThrow('settings.rejectionDensityBucketHours must be a positive number');
+    });
+
+    test('assignment policies: built-ins reproduce the default path and custom policies see event-time availability', async () => {
+      const { roundRobinPolicy, weightedSplitPolicy, leastUtilizedPolicy } = require('../sim/des/helpers/assignment');
+      const settings = { state: mockState, seed: 8, missionDetail: true };
+
+      // mission_split through the policy API gives the same run as the pre-assigned default
+      const count = previewDemand(basicScenario, { seed: 8 }).events.length;
+      const split = await runSimulationWithPolicy(basicScenario, settings, weightedSplitPolicy(basicScenario.unit_policy.mission_split, count));
+      expect(split).toEqual(await runSimulation(basicScenario, settings));
+
+      const noSplit = { ...basicScenario, unit_policy: {} };
+      const roundRobin = await runSimulationWithPolicy(noSplit, settings, roundRobinPolicy(['HMLA-167', 'HMLA-267']));
+      expect(roundRobin).toEqual(await runSimulation(noSplit, settings));
+
+      // A custom policy sees the demand and a read-only availability snapshot at demand time
+      const busy = { ...basicScenario, demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 2 }] };
+      const seen = [];
+      const custom = {
+        selectUnit(ctx) {
+          const snapshot = ctx.availability('HMLA-167');
+          seen.push({ time: ctx.time, mission_type: ctx.mission_type, free: snapshot.aircraft.available });
+          expect(Object.isFrozen(snapshot)).toBe(true);
+          expect(snapshot.aircraft.total).toBe(2);
+          return 'HMLA-167';
+        }
+      };
+      const pinned = await runSimulationWithPolicy(busy, settings, custom);
+      expect(seen).toHaveLength(pinned.missions.requested);
+      expect(seen.every(s => s.mission_type === 'ISR')).toBe(true);
+      expect(seen.some(s => s.free < 2)).toBe(true);
+      expect(new Set(pinned.missions_detail.map(m => m.unit))).toEqual(new Set(['HMLA-167']));
+
+      // Least utilized prefers the unit with the larger free share of its aircraft
+      const least = await runSimulationWithPolicy(busy, settings, leastUtilizedPolicy());
+      expect(least.missions_detail.slice(0, 2).map(m => m.unit)).toEqual(['HMLA-167', 'HMLA-267']);
+
+      await expect(runSimulationWithPolicy(basicScenario, settings, { selectUnit: () => 'VMU-9' }))
+        .rejects.toThrow("assignment policy chose unknown unit 'VMU-9'");
+      await expect(runSimulationWithPolicy(basicScenario, settings, {}))
+        .rejects.toThrow('assignment policy must be an object with a selectUnit(ctx) function');
+    });
+
+    test('per_flight_hours adds a pilot crew for each started block of flight hours', async () => {
+      const scenario = {
+        ...basicScenario,
+        mission_types: [{
+          name: 'LONG',
+          flight_time: { type: 'deterministic', value_hours: 14, transit_in_hours: 0.5, transit_out_hours: 0.5 },
+          required_aircrew: { pilot: 1, per_flight_hours: 8 },
+          required_payload_types: ['SkyTower II']
+        }],
+        demand: [{ type: 'deterministic', mission_type: 'LONG', every_hours: 48, unit: 'HMLA-167' }],
+        unit_policy: {}
+      };
+      const pilotsOf = result => result.timeline.find(e => e.type === 'mission').crew.pilots.map(a => [a.start, a.end]);
+
+      // Legacy: both crews are held for the whole 16.5h mission
+      const legacy = await runSimulation(scenario, { state: mockState, missionDetail: true });
+      expect(legacy.missions.started).toBe(1);
+      expect(legacy.missions_detail[0].crew_composition).toEqual({ pilot: 2, so: 0 });
+      expect(legacy.missions_detail[0].crew_shifts).toBe(2);
+      expect(pilotsOf(legacy)).toEqual([[0, 16.5], [0, 16.5]]);
+
+      // per_resource: the second crew takes over 8h into the flight (flight starts at 1.5h)
+      const perResource = await runSimulation(scenario, { state: mockState, resourceHoldModel: 'per_resource' });
+      expect(pilotsOf(perResource)).toEqual([[0, 9.5], [9.5, 16.25]]);
+
+      // A unit with a single pilot cannot fly it
+      const short = { ...scenario, demand: [{ ...scenario.demand[0], unit: 'HMLA-267' }] };
+      const rejected = await runSimulation(short, { state: mockState });
+      expect(rejected.missions.rejected).toBe(1);
+      expect(rejected.rejections.pilot).toBe(1);
+
+      // per_resource: pilots off shift from 8h cannot take over at 9.5h, so the seat's chain fails
+      const offShift = {
+        ...scenario,
+        personnel_availability: { '7318': { daily_crew_rest_hours: 16, work_schedule: { days_on: 7, days_off: 0, daily_start_hour: 0 } } }
+      };
+      const noHandover = await runSimulation(offShift, { state: mockState, resourceHoldModel: 'per_resource' });
+      expect(noHandover.missions.started).toBe(0);
+      expect(noHandover.rejections.pilot).toBe(1);
+      expect(noHandover.utilization['HMLA-167'].pilot).toBe(0);
+      expect(noHandover.utilization['HMLA-167'].pilot_stats.allocations).toBe(0);
+
+      const bad = mt => ({ ...scenario, mission_types: [{ ...scenario.mission_types[0], ...mt }] });
+      await expect(runSimulation(bad({ required_aircrew: { pilot: 1, per_flight_hours: 0 } }), { state: mockState }))
+        .rejects.toThrow('per_flight_hours must be a positive number');
+      await expect(runSimulation(bad({ crew_rotation: { enabled: true, pilot_shifts: [8, 8] } }), { state: mockState }))
+        .rejects.toThrow('not both');
+    });
+
+    test('retried demand re-enters after its delay and flies once the blocker completes', async () => {
+      // HMLA-267 has one aircraft and one pilot: the t=0 mission holds both until t=4.5
+      const retryScenario = retry => ({
+        ...basicScenario,
+        demand: [
+          { type: 'deterministic', mission_type: 'ISR', every_hours: 48, unit: 'HMLA-267' },
+          { type: 'deterministic', mission_type: 'ISR', every_hours: 48, start_at_hours: 1, unit: 'HMLA-267', retry }
+        ],
+        unit_policy: {}
+      });
+      const delay = { type: 'deterministic', value_hours: 2 };
+
+      // Attempts at t=1 and t=3 are turned away; t=5 flies
+      const flown = await runSimulation(retryScenario({ max_attempts: 5, delay }), { state: mockState, missionDetail: true });
+      expect(flown.missions).toMatchObject({ requested: 2, started: 2, rejected: 0 });
+      expect(flown.retries).toBe(2);
+      const rejectionCount = r => Object.values(r.rejections).reduce((sum, n) => sum + n, 0);
+      expect(rejectionCount(flown)).toBe(0);
+      expect(flown.by_type.ISR.requested).toBe(2);
+      const retried = flown.missions_detail.find(m => m.attempts !== undefined && m.demand_time > 0);
+      expect(retried).toMatchObject({ outcome: 'started', attempts: 3, demand_time: 5, start_time: 5 });
+      expect(flown.missions_detail).toHaveLength(2);
+
+      // Out of attempts: one final rejection
+      const given = await runSimulation(retryScenario({ max_attempts: 2, delay }), { state: mockState, missionDetail: true });
+      expect(given.missions).toMatchObject({ requested: 2, started: 1, rejected: 1 });
+      expect(given.retries).toBe(1);
+      expect(rejectionCount(given)).toBe(1);
+      expect(given.missions_detail.find(m => m.outcome === 'rejected')).toMatchObject({ attempts: 2, demand_time: 3 });
+
+      // Without retry the counter is absent
+      expect((await runSimulation(basicScenario, { state: mockState })).retries).toBeUndefined();
+
+      await expect(runSimulation(retryScenario({ max_attempts: 0, delay }), { state: mockState }))
+        .rejects.toThrow('demand[1].retry.max_attempts must be a positive integer');
+      await expect(runSimulation(retryScenario({ max_attempts: 2 }), { state: mockState }))
+        .rejects.toThrow('demand[1].retry.delay must be a distribution');
+    });
+
+    test('rate_uncertainty scales a Poisson rate once per run and records the realized rate', async () => {
+      const withMultiplier = multiplier => ({
+        ...basicScenario,
+        demand: [{ name: 'surge', type: 'poisson', mission_type: 'ISR', rate_per_hour: 0.25, rate_uncertainty: multiplier }]
+      });
+      const doubled = await runSimulation(withMultiplier({ type: 'deterministic', value_hours: 2 }), { state: mockState, seed: 3 });
+      expect(doubled.demand_stats.realized_rates).toEqual({ surge: 0.5 });
+
+      // A zero multiplier switches the entry off for the run
+      const silent = await runSimulation(withMultiplier({ type: 'deterministic', value_hours: 0 }), { state: mockState, seed: 3 });
+      expect(silent.missions.requested).toBe(0);
+      expect(silent.demand_stats.realized_rates).toEqual({ surge: 0 });
+
+      // The preview's expected count uses the mean multiplier
+      const preview = previewDemand(withMultiplier({ type: 'triangular', a: 0.8, m: 1, b: 1.5 }), { seed: 3 });
+      expect(preview.expected_rate_check[0].expected).toBeCloseTo(0.25 * 1.1 * 24, 2);
+
+      expect((await runSimulation(basicScenario, { state: mockState })).demand_stats.realized_rates).toBeUndefined();
+      await expect(runSimulation(withMultiplier(1.2), { state: mockState }))
+        .rejects.toThrow('demand[0].rate_uncertainty must be a distribution');
+      const deterministic = { ...basicScenario, demand: [{ ...basicScenario.demand[0], rate_uncertainty: { type: 'deterministic', value_hours: 1 } }] };
+      await expect(runSimulation(deterministic, { state: mockState })).rejects.toThrow('needs a poisson entry');
+    });
+
+    test('resource level events track every pool between zero and its total', async () => {
+      const busy = { ...basicScenario, demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 1.5 }] };
+      const result = await runSimulation(busy, { state: mockState, seed: 2, emitResourceLevels: true });
+      const levels = result.timeline.filter(e => e.type === 'resource_level');
+      const series = new Map();
+      for (const e of levels) {
+        const key = `${e.unit}/${e.resource}/${e.payload_type || ''}`;
+        if (!series.has(key)) series.set(key, []);
+        series.get(key).push(e);
+      }
+      expect(series.has('HMLA-167/aircraft/')).toBe(true);
+      expect(series.has('HMLA-267/pilot/')).toBe(true);
+      for (const events of series.values()) {
+        expect(events[0].time).toBe(0);
+        events.forEach((e, i) => {
+          expect(e.available).toBeGreaterThanOrEqual(0);
+          expect(e.available).toBeLessThanOrEqual(e.total);
+          if (i > 0) {
+            expect(e.time).toBeGreaterThan(events[i - 1].time);
+            expect(e.available).not.toBe(events[i - 1].available);
+          }
+        });
+      }
+
+      // Each mission's aircraft is out from its demand time: the level right after is below the total
+      const aircraft = series.get('HMLA-267/aircraft/');
+      const levelAt = t => aircraft.filter(e => e.time <= t).pop().available;
+      for (const m of result.timeline.filter(e => e.type === 'mission' && e.unit === 'HMLA-267')) {
+        expect(levelAt(m.demand_time)).toBe(0);
+      }
+
+      // Off by default, and counted against maxTimelineEvents
+      expect((await runSimulation(busy, { state: mockState, seed: 2 })).timeline.some(e => e.type === 'resource_level')).toBe(false);
+      const plainCount = result.timeline.length - levels.length;
+      const error = await runSimulation(busy, { state: mockState, seed: 2, emitResourceLevels: true, limits: { maxTimelineEvents: plainCount } })
+        .catch(e => e);
+      expect(error.code).toBe('SIMULATION_LIMIT');
+      expect(error.limit).toBe('maxTimelineEvents');
+    });
+
+    test('timeline rejection sampling keeps a binomial share of rejections and exact counters', async () => {
+      // One aircraft, missions every 6 minutes: nearly every demand is rejected
+      const saturated = {
+        ...basicScenario,
+        horizon_hours: 200,
+        demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 0.1, start_at_hours: 0 }],
+        unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } }
+      };
+      const overrides = {
+        units: { 'HMLA-167': { aircraft: 1, pilot: 50, so: 50, intel: 50, payload_by_type: { 'SkyTower II': 5 } } }
+      };
+
+      const full = await runSimulation(saturated, { state: mockState, overrides, seed: 21 });
+      const sampled = await runSimulation(saturated, { state: mockState, overrides, seed: 21, timelineRejectionSampling: 0.1 });
+
+      const rejections = r => r.timeline.filter(e => e.type === 'rejection');
+      const n = rejections(full).length;
+      const kept = rejections(sampled).length;
+      const sd = Math.sqrt(n * 0.1 * 0.9);
+      expect(n).toBe(full.missions.rejected);
+      expect(Math.abs(kept - n * 0.1)).toBeLessThan(4 * sd);
+
+      // Counters and mission events are untouched; the rate is recorded for rescaling
+      expect(sampled.missions).toEqual(full.missions);
+      expect(sampled.rejections).toEqual(full.rejections);
+      expect(sampled.timeline.filter(e => e.type === 'mission')).toEqual(full.timeline.filter(e => e.type === 'mission'));
+      expect(sampled.timeline_rejection_sampling).toBe(0.1);
+      expect(full.timeline_rejection_sampling).toBeUndefined();
+
+      await expect(runSimulation(saturated, { state: mockState, timelineRejectionSampling: 0 })).rejects.toThrow('timelineRejectionSampling');
+    });
+
     test('mission split policy affects unit selection', async () => {
       const biasedScenario = {
         ...basicScenario,
@@ -564,6 +2057,46 @@ describe('DES Engine - Whitebox Tests', () => {
     });
   });
 
+  describe('EquipmentPool - Query Order', () => {
+    test('availability queries do not change the pool, so earlier times can be asked after later ones', () => {
+      const pool = new EquipmentPool('aircraft', 2);
+      expect(pool.tryAcquire(0, 4)).toBe(true);
+      expect(pool.tryAcquire(1, 8)).toBe(true);
+
+      expect(pool.availableAt(6)).toBe(1);
+      // The hold ending at 4 is still seen at t=2 after the query at t=6
+      expect(pool.availableAt(2)).toBe(0);
+      expect(pool.tryAcquire(2, 1)).toBe(false);
+      expect(pool.heldAt(5)).toEqual({ general: 1, reserved: 0 });
+      expect(pool.availableAt(9)).toBe(2);
+      expect(pool.availableAt(3.5)).toBe(0);
+    });
+
+    test('a step booked ahead of an earlier acquisition leaves that acquisition its true availability', () => {
+      const pool = new EquipmentPool('payload', 2);
+      expect(pool.tryAcquire(0, 1.8)).toBe(true);
+      // Mission A, long preflight: payload booked from t=2 (after the first hold ends) to t=3
+      expect(pool.tryAcquire(2, 1)).toBe(true);
+      // Mission B, processed later with a short preflight, wants a payload from t=1.3 while the first hold still runs
+      expect(pool.tryAcquire(1.3, 1)).toBe(false);
+      expect(pool.tryAcquire(3, 1)).toBe(true);
+      expect(pool.denials).toBe(1);
+      expect(pool.usedCount).toBe(1);
+    });
+
+    test('reserved holds are counted by time in any query order', () => {
+      const pool = new EquipmentPool('aircraft', 2);
+      pool.setReserved(1);
+      expect(pool.tryAcquire(0, 5, 1, true)).toBe(true);
+      expect(pool.heldAt(1)).toEqual({ general: 0, reserved: 1 });
+      expect(pool.availableAt(6, true)).toBe(2);
+      expect(pool.availableAt(1, true)).toBe(1);
+      expect(pool.tryAcquire(1, 1, 2, true)).toBe(false);
+      expect(pool.tryAcquire(1, 1, 1, true)).toBe(true);
+      expect(pool.peakReservedInUse).toBe(1);
+    });
+  });
+
   describe('Integration with State Derivation', () => {
     test('derived initial state is embedded in simulation results', async () => {
       const initial = loadState(mockState);
@@ -587,4 +2120,62 @@ describe('DES Engine - Whitebox Tests', () => {
       }
     });
   });
+
+  describe('applyScenarioPatch - Merge Semantics', () => {
+    test('null removes keys and nested objects merge without touching the baseline', () => {
+      const snapshot = JSON.stringify(basicScenario);
+      const patched = applyScenarioPatch(basicScenario, {
+        horizon_hours: 48,
+        unit_policy: null,
+        process_times: { preflight: { type: 'triangular', value_hours: null, a: 0.25, m: 0.5, b: 1 }, hold_crew_during_process_times: null }
+      });
+
+      expect(patched.horizon_hours).toBe(48);
+      expect(patched.unit_policy).toBeUndefined();
+      expect(patched.process_times.preflight).toEqual({ type: 'triangular', a: 0.25, m: 0.5, b: 1 });
+      expect(patched.process_times.postflight).toEqual(basicScenario.process_times.postflight);
+      expect('hold_crew_during_process_times' in patched.process_times).toBe(false);
+      expect(JSON.stringify(basicScenario)).toBe(snapshot);
+    });
+
+    test('merges mission_types by name and demand by mission_type', () => {
+      const patched = applyScenarioPatch(basicScenario, {
+        mission_types: [
+          { name: 'ISR', flight_time: { type: 'lognormal', mu: 1, sigma: 0.2, value_hours: null } },
+          { name: 'Strike', flight_time: { type: 'deterministic', value_hours: 1 }, required_aircrew: { pilot: 1 } }
+        ],
+        demand: [
+          { mission_type: 'ISR', every_hours: 4 },
+          { mission_type: 'Strike', type: 'poisson', rate_per_hour: 0.1 }
+        ]
+      });
+
+      expect(patched.mission_types.map(mt => mt.name)).toEqual(['ISR', 'Strike']);
+      expect(patched.mission_types[0].flight_time).toEqual({ type: 'lognormal', mu: 1, sigma: 0.2, transit_in_hours: 0.5, transit_out_hours: 0.5 });
+      expect(patched.mission_types[0].required_payload_types).toEqual(['SkyTower II']);
+      expect(patched.demand).toEqual([
+        { type: 'deterministic', mission_type: 'ISR', every_hours: 4, start_at_hours: 0 },
+        { mission_type: 'Strike', type: 'poisson', rate_per_hour: 0.1 }
+      ]);
+    });
+
+    test('removes named entries flagged with _remove and rejects unkeyed entries', () => {
+      const patched = applyScenarioPatch(basicScenario, {
+        mission_types: [{ name: 'ISR', _remove: true }],
+        demand: [{ mission_type: 'ISR', _remove: true }]
+      });
+      expect(patched.mission_types).toEqual([]);
+      expect(patched.demand).toEqual([]);
+
+      expect(() => applyScenarioPatch(basicScenario, { demand: [{ every_hours: 2 }] })).toThrow("'mission_type'");
+    });
+
+    test('patched scenario runs like the equivalent full copy', async () => {
+      const full = { ...basicScenario, horizon_hours: 12 };
+      const patched = applyScenarioPatch(basicScenario, { horizon_hours: 12 });
+      const a = await runSimulation(full, { state: mockState });
+      const b = await runSimulation(patched, { state: mockState });
+      expect(b.missions).toEqual(a.missions);
+    });
+  });
 });
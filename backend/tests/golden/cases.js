@@ -0,0 +1,156 @@
+// Golden regression cases for the DES Engine
+// Each case is a small scenario + state, either fully deterministic or run under a
+// fixed seed, whose serialized results are pinned in tests/golden/fixtures/<name>.json
+
+const { mockState, basicScenario } = require('../fixtures');
+
+// Long turnaround with scarce crew and payloads, run under both resource hold models
+const holdModelScenario = {
+  ...basicScenario,
+  horizon_hours: 24,
+  demand: [
+    { type: 'deterministic', mission_type: 'ISR', every_hours: 1.5, start_at_hours: 0 }
+  ],
+  process_times: {
+    ...basicScenario.process_times,
+    turnaround: { type: 'deterministic', value_hours: 3 }
+  },
+  unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } }
+};
+const holdModelOverrides = {
+  units: {
+    'HMLA-167': { aircraft: 6, pilot: 3, so: 3, payload_by_type: { 'SkyTower II': 3 } }
+  }
+};
+
+const cases = [
+  {
+    // Baseline fixture scenario: 60/40 split, no contention
+    name: 'basic-split',
+    scenario: basicScenario,
+    settings: { state: mockState }
+  },
+  {
+    // Hourly demand against a single aircraft per unit: aircraft is the bottleneck
+    name: 'aircraft-saturated',
+    scenario: {
+      ...basicScenario,
+      horizon_hours: 12,
+      demand: [
+        { type: 'deterministic', mission_type: 'ISR', every_hours: 1, start_at_hours: 0 }
+      ]
+    },
+    settings: {
+      state: mockState,
+      overrides: {
+        units: {
+          'HMLA-167': { aircraft: 1, pilot: 10, so: 10, payload_by_type: { 'SkyTower II': 10 } },
+          'HMLA-267': { aircraft: 1, pilot: 10, so: 10, payload_by_type: { 'SkyTower II': 10 } }
+        }
+      }
+    }
+  },
+  {
+    // Two payload types where only one unit carries the second payload
+    name: 'payload-shortage',
+    scenario: {
+      ...basicScenario,
+      horizon_hours: 24,
+      mission_types: [
+        {
+          name: 'Strike',
+          flight_time: { type: 'deterministic', value_hours: 3 },
+          required_aircrew: { pilot: 1, so: 1 },
+          required_payload_types: ['SkyTower II', 'Hellfire']
+        }
+      ],
+      demand: [
+        { type: 'deterministic', mission_type: 'Strike', every_hours: 4, start_at_hours: 1 }
+      ],
+      process_times: {
+        ...basicScenario.process_times,
+        mount_times: {
+          'SkyTower II': { type: 'deterministic', value_hours: 0.5 },
+          'Hellfire': { type: 'deterministic', value_hours: 0.25 }
+        }
+      },
+      unit_policy: { mission_split: { 'HMLA-167': 0.5, 'HMLA-267': 0.5 } }
+    },
+    settings: { state: mockState }
+  },
+  {
+    // Crew rotation, work schedules and a rotating duty with recovery time
+    name: 'rotation-and-duty',
+    scenario: {
+      ...basicScenario,
+      horizon_hours: 48,
+      mission_types: [
+        {
+          name: 'Long ISR',
+          flight_time: { type: 'deterministic', value_hours: 8, transit_in_hours: 0.5, transit_out_hours: 0.5 },
+          required_aircrew: { pilot: 2, so: 1 },
+          required_payload_types: ['SkyTower II'],
+          crew_rotation: { enabled: true, pilot_shifts: [4, 4], so_shifts: [9] }
+        }
+      ],
+      demand: [
+        { type: 'deterministic', mission_type: 'Long ISR', every_hours: 12, start_at_hours: 2 }
+      ],
+      personnel_availability: {
+        '7318': { daily_crew_rest_hours: 8, work_schedule: { days_on: 5, days_off: 2, daily_start_hour: 0 } },
+        '7314': { daily_crew_rest_hours: 8, work_schedule: { days_on: 7, days_off: 0, daily_start_hour: 0 } }
+      },
+      duty_requirements: {
+        sdo: { enabled: true, shifts_per_day: 1, hours_per_shift: 24, requires_pilot: 1, requires_so: 1, duty_recovery_hours: 8 }
+      },
+      unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } }
+    },
+    settings: {
+      state: mockState,
+      overrides: {
+        units: {
+          'HMLA-167': { aircraft: 2, pilot: 6, so: 4, payload_by_type: { 'SkyTower II': 2 } }
+        }
+      }
+    }
+  },
+  {
+    // Every resource held through turnaround
+    name: 'hold-model-legacy',
+    scenario: holdModelScenario,
+    settings: { state: mockState, overrides: holdModelOverrides, resourceHoldModel: 'legacy' }
+  },
+  {
+    // Crew released after postflight and payloads after transit out; only aircraft sit in turnaround
+    name: 'hold-model-per-resource',
+    scenario: holdModelScenario,
+    settings: { state: mockState, overrides: holdModelOverrides, resourceHoldModel: 'per_resource' }
+  },
+  {
+    // Seeded stochastic run: Poisson demand, triangular and lognormal times, random crew picks.
+    // Pins the random stream as well as the engine, so a change to draw order shows up here
+    name: 'seeded-stochastic',
+    scenario: {
+      ...basicScenario,
+      horizon_hours: 48,
+      mission_types: [
+        {
+          ...basicScenario.mission_types[0],
+          flight_time: { type: 'triangular', a: 2, m: 3, b: 5 },
+          crew_distribution: 'random'
+        }
+      ],
+      demand: [
+        { type: 'poisson', mission_type: 'ISR', rate_per_hour: 0.4 }
+      ],
+      process_times: {
+        ...basicScenario.process_times,
+        preflight: { type: 'triangular', a: 0.5, m: 1, b: 1.5 },
+        turnaround: { type: 'lognormal', mu: 0.5, sigma: 0.4 }
+      }
+    },
+    settings: { state: mockState, seed: 20250117 }
+  }
+];
+
+module.exports = { cases };
@@ -0,0 +1,61 @@
+// Golden Output Regression Tests for DES Engine
+// Re-runs each case (deterministic, or under its fixed seed) and diffs the full results against the pinned fixture
+//
+// To intentionally regenerate fixtures after a behavioral change, run:
+//   GOLDEN_BLESS=1 npx jest tests/golden
+// and review the fixture diff in the commit.
+
+const fs = require('fs');
+const path = require('path');
+
+const { runSimulation } = require('../../sim/des/engine');
+const { diffFields } = require('../../sim/monte/compare');
+const { cases } = require('./cases');
+
+const FIXTURE_DIR = path.join(__dirname, 'fixtures');
+const BLESS = process.env.GOLDEN_BLESS === '1';
+const TOLERANCE = 1e-9;
+
+/**
+ * Human-readable differences between a fixture and the current run
+ * @param {*} expected - Value from the fixture
+ * @param {*} actual - Value from the current run
+ * @returns {Array<string>} Differences found (at most 20)
+ */
+function diffValues(expected, actual) {
+  return diffFields(expected, actual, { tolerance: TOLERANCE }).map(d => {
+    if (d.kind === 'missing') return `${d.path}: missing from results`;
+    if (d.kind === 'added') return `${d.path}: not in fixture`;
+    if (d.kind === 'length') return `${d.path}: expected length ${d.expected}, got ${d.actual}`;
+    return `${d.path}: expected ${JSON.stringify(d.expected)}, got ${JSON.stringify(d.actual)}`;
+  });
+}
+
+describe('DES Engine - Golden Output', () => {
+  for (const c of cases) {
+    test(`${c.name} matches pinned results`, async () => {
+      const settings = { ...c.settings, logLevel: 'silent', sortedOutput: true };
+      // Round-trip through JSON so the comparison sees exactly what the API serves
+      const results = JSON.parse(JSON.stringify(await runSimulation(c.scenario, settings)));
+      const fixturePath = path.join(FIXTURE_DIR, `${c.name}.json`);
+
+      if (BLESS || !fs.existsSync(fixturePath)) {
+        fs.mkdirSync(FIXTURE_DIR, { recursive: true });
+        fs.writeFileSync(fixturePath, JSON.stringify(results, null, 2) + '\n');
+        if (!BLESS) throw new Error(`Fixture ${c.name}.json was missing and has been written; re-run to verify`);
+        return;
+      }
+
+      // Every started mission either completed or was still in progress at the horizon
+      for (const counts of [results.missions, ...Object.values(results.by_type), ...Object.values(results.by_demand_source)]) {
+        expect(counts.started).toBe(counts.completed + counts.in_progress_at_horizon);
+      }
+      const byUnit = Object.values(results.in_progress_by_unit).reduce((a, b) => a + b, 0);
+      expect(byUnit).toBe(results.missions.in_progress_at_horizon);
+
+      const expected = JSON.parse(fs.readFileSync(fixturePath, 'utf8'));
+      const diffs = diffValues(expected, results);
+      expect(diffs).toEqual([]);
+    });
+  }
+});
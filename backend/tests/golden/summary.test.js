@@ -0,0 +1,33 @@
+// Snapshot Tests for the Scenario Summary
+// Renders summarizeScenario for each golden case and diffs it against the pinned text
+//
+// To intentionally regenerate snapshots after a format change, run:
+//   GOLDEN_BLESS=1 npx jest tests/golden
+// and review the snapshot diff in the commit.
+
+const fs = require('fs');
+const path = require('path');
+
+const { summarizeScenario } = require('../../sim/des/engine');
+const { cases } = require('./cases');
+
+const FIXTURE_DIR = path.join(__dirname, 'fixtures');
+const BLESS = process.env.GOLDEN_BLESS === '1';
+
+describe('DES Engine - Scenario Summary Snapshots', () => {
+  for (const c of cases) {
+    test(`${c.name} summary matches pinned text`, () => {
+      const summary = summarizeScenario(c.scenario, { state: c.settings.state, overrides: c.settings.overrides });
+      const fixturePath = path.join(FIXTURE_DIR, `${c.name}.summary.txt`);
+
+      if (BLESS || !fs.existsSync(fixturePath)) {
+        fs.mkdirSync(FIXTURE_DIR, { recursive: true });
+        fs.writeFileSync(fixturePath, summary + '\n');
+        if (!BLESS) throw new Error(`Snapshot ${c.name}.summary.txt was missing and has been written; re-run to verify`);
+        return;
+      }
+
+      expect(summary + '\n').toBe(fs.readFileSync(fixturePath, 'utf8'));
+    });
+  }
+});
@@ -0,0 +1,951 @@
+// Monte Carlo Engine Tests
+// Runs small iteration counts against deterministic scenarios so aggregated values are exact
+
+const { runMonteCarlo, runMonteCarloWithExtractors, runMonteCarloSequence } = require('../sim/monte/engine');
+const { runSimulation } = require('../sim/des/engine');
+const { deriveSeed, deriveStreamSeed } = require('../sim/des/helpers/random');
+const { compileThresholds, thresholdProbabilities } = require('../sim/monte/thresholds');
+const { compareResults } = require('../sim/monte/compare');
+const { serializeResults, deserializeResults } = require('../sim/monte/persist');
+const { runSensitivity } = require('../sim/monte/sensitivity');
+const { compileInputUncertainty, latinHypercube, sampleInputs } = require('../sim/monte/uncertainty');
+const { distQuantile } = require('../sim/des/helpers/distributions');
+const { getEngineStatus } = require('../sim/monte/slots');
+const { compileInputOutputAnalysis, inputOutputAnalysis, ranks } = require('../sim/monte/analysis');
+const { mockState, basicScenario } = require('./fixtures');
+
+describe('Monte Carlo Engine', () => {
+  test('aggregates rejection breakdowns by mission type and unit', async () => {
+    const scenario = {
+      ...basicScenario,
+      horizon_hours: 12,
+      demand: [
+        { type: 'deterministic', mission_type: 'ISR', every_hours: 1, start_at_hours: 0 }
+      ]
+    };
+    const overrides = {
+      units: {
+        'HMLA-167': { aircraft: 1, pilot: 10, so: 10, payload_by_type: { 'SkyTower II': 10 } },
+        'HMLA-267': { aircraft: 1, pilot: 10, so: 10, payload_by_type: { 'SkyTower II': 10 } }
+      }
+    };
+
+    const mc = await runMonteCarlo(scenario, { state: mockState, overrides, iterations: 3, maxConcurrent: 2 });
+
+    const isr = mc.rejections_by_type.ISR;
+    expect(Object.keys(isr).sort()).toEqual(['aircraft', 'control_station', 'intel', 'payload', 'pilot', 'so']);
+    expect(isr.aircraft.mean).toBe(mc.rejections.aircraft.mean);
+    expect(isr.aircraft.stddev).toBe(0);
+    expect(isr.pilot.max).toBe(0);
+
+    const unitTotal = Object.values(mc.rejections_by_unit).reduce((acc, u) => acc + u.aircraft.mean, 0);
+    expect(unitTotal).toBeCloseTo(mc.rejections.aircraft.mean, 2);
+  }, 60000);
+
+  test('threshold probabilities report the fraction of satisfying iterations', async () => {
+    const mc = await runMonteCarlo(basicScenario, {
+      state: mockState,
+      iterations: 2,
+      maxConcurrent: 2,
+      thresholds: [
+        { metric: 'missions.completed / missions.requested', op: '>=', value: 0.5 },
+        { metric: 'by_type["ISR"].rejected', op: '>', value: 100 }
+      ]
+    });
+
+    expect(mc.threshold_probabilities).toEqual({
+      'missions.completed / missions.requested >= 0.5': 1,
+      'by_type["ISR"].rejected > 100': 0
+    });
+  }, 60000);
+
+  test('threshold expressions follow arithmetic precedence and treat 0 / 0 as unmet', () => {
+    const compiled = compileThresholds([
+      { metric: '(missions.started - missions.rejected) * 2 + 1', op: '==', value: 13 },
+      { metric: 'missions.completed / missions.requested', op: '<', value: 1 },
+      { metric: "utilization['HMLA-167'].aircraft_efficiency", op: '<=', value: 0.5 },
+      { metric: 'missions.requested-missions.rejected', op: '==', value: 8 }
+    ]);
+    const iterations = [
+      { missions: { requested: 10, started: 8, completed: 7, rejected: 2 }, utilization: { 'HMLA-167': { aircraft_efficiency: 0.4 } } },
+      { missions: { requested: 0, started: 0, completed: 0, rejected: 0 }, utilization: {} }
+    ];
+
+    expect(thresholdProbabilities(compiled, iterations)).toEqual({
+      '(missions.started - missions.rejected) * 2 + 1 == 13': 0.5,
+      'missions.completed / missions.requested < 1': 0.5,
+      "utilization['HMLA-167'].aircraft_efficiency <= 0.5": 1,
+      'missions.requested-missions.rejected == 8': 0.5
+    });
+  });
+
+  test('invalid thresholds fail before any iteration runs', async () => {
+    await expect(runMonteCarlo(basicScenario, {
+      state: mockState,
+      iterations: 1,
+      thresholds: [{ metric: 'missions.finished', op: '>=', value: 1 }]
+    })).rejects.toThrow('unknown metric path');
+
+    expect(() => compileThresholds([{ metric: 'missions.completed', op: '!=', value: 1 }])).toThrow('unsupported op');
+    expect(() => compileThresholds([{ metric: 'missions.completed +', value: 1 }])).toThrow('Unexpected end');
+    // '-' after a dotted name subtracts, so hyphenated names need brackets
+    expect(() => compileThresholds([{ metric: 'utilization.HMLA-167.aircraft', value: 1 }])).toThrow();
+  });
+
+  test('an iteration that trips a limit fails the run and names the iteration', async () => {
+    let error;
+    try {
+      await runMonteCarlo(basicScenario, { state: mockState, iterations: 3, maxConcurrent: 2, limits: { maxTimelineEvents: 2 } });
+    } catch (e) {
+      error = e;
+    }
+
+    expect(error.message).toMatch(/^Iteration 0 failed: Simulation limit exceeded/);
+    expect(error.iteration).toBe(0);
+    expect(error.code).toBe('SIMULATION_LIMIT');
+    expect(error.limit).toBe('maxTimelineEvents');
+    expect(error.counts.timelineEvents).toBe(3);
+  }, 60000);
+
+  test('continueOnIterationError records failures and aggregates the successes', async () => {
+    // Seeded Poisson demand: some iterations produce more timeline events than the limit allows
+    const stochastic = {
+      ...basicScenario,
+      demand: [{ type: 'poisson', mission_type: 'ISR', rate_per_hour: 0.25 }]
+    };
+    const options = { state: mockState, iterations: 8, maxConcurrent: 4, seed: 7 };
+    // Iteration i runs DES with sub-seed deriveSeed(seed, i), so its timeline length is known up front
+    const counts = [];
+    for (let i = 0; i < 8; i++) {
+      const run = await runSimulation(stochastic, { state: mockState, seed: deriveSeed(7, i), logLevel: 'silent' });
+      counts.push(run.timeline.length);
+    }
+    const limit = [...counts].sort((a, b) => a - b)[4];
+    const expectedFailures = counts.filter(c => c > limit).length;
+    expect(expectedFailures).toBeGreaterThan(0);
+
+    const mc = await runMonteCarlo(stochastic, {
+      ...options,
+      limits: { maxTimelineEvents: limit },
+      continueOnIterationError: true,
+      maxIterationErrorFraction: 0.5
+    });
+
+    expect(mc.iterations_failed).toBe(expectedFailures);
+    expect(mc.iterations_succeeded).toBe(8 - expectedFailures);
+    expect(mc.iteration_errors.reduce((acc, e) => acc + e.count, 0)).toBe(expectedFailures);
+    expect(mc.iteration_errors[0].message).toMatch(/^Simulation limit exceeded/);
+    expect(mc.iteration_errors[0].first_iteration).toBe(counts.findIndex(c => c > limit));
+    expect(mc.missions.requested.max).toBeLessThanOrEqual(limit);
+
+    // Too many failures still fail the run
+    await expect(runMonteCarlo(stochastic, {
+      ...options,
+      limits: { maxTimelineEvents: limit },
+      continueOnIterationError: true,
+      maxIterationErrorFraction: 0
+    })).rejects.toThrow('more than maxIterationErrorFraction');
+  }, 120000);
+
+  test('statistics report how many iterations contributed to them', async () => {
+    // A rare mission type only appears in iterations whose Poisson draw produced it
+    const scenario = {
+      ...basicScenario,
+      mission_types: [...basicScenario.mission_types, { ...basicScenario.mission_types[0], name: 'Rare' }],
+      demand: [...basicScenario.demand, { type: 'poisson', mission_type: 'Rare', rate_per_hour: 0.02 }]
+    };
+    const options = { state: mockState, iterations: 10, maxConcurrent: 4, seed: 11 };
+
+    const skipped = await runMonteCarlo(scenario, { ...options, byTypeMissingAsZero: false });
+    const zeroed = await runMonteCarlo(scenario, options);
+
+    expect(skipped.missions.requested.count).toBe(10);
+    expect(skipped.by_type.ISR.requested.count).toBe(10);
+    const seen = skipped.by_type.Rare.requested.count;
+    expect(seen).toBeGreaterThan(0);
+    expect(seen).toBeLessThan(10);
+
+    expect(zeroed.by_type.Rare.requested.count).toBe(10);
+    expect(zeroed.by_type.Rare.requested.min).toBe(0);
+    expect(zeroed.by_type.Rare.requested.mean).toBeCloseTo(skipped.by_type.Rare.requested.mean * seen / 10, 1);
+  }, 120000);
+
+  test('by_type counts declared mission types with no demand as zero', async () => {
+    // A very low-rate stream: most iterations request no Rare missions at all
+    const scenario = {
+      ...basicScenario,
+      mission_types: [...basicScenario.mission_types, { ...basicScenario.mission_types[0], name: 'Rare' }, { ...basicScenario.mission_types[0], name: 'Unused' }],
+      demand: [...basicScenario.demand, { type: 'poisson', mission_type: 'Rare', rate_per_hour: 0.01 }]
+    };
+    const mc = await runMonteCarlo(scenario, { state: mockState, iterations: 10, maxConcurrent: 4, seed: 5 });
+    const legacy = await runMonteCarlo(scenario, { state: mockState, iterations: 10, maxConcurrent: 4, seed: 5, byTypeMissingAsZero: false });
+
+    // Unused never appears in any iteration but is still reported
+    expect(mc.by_type.Unused.requested).toMatchObject({ count: 10, mean: 0, max: 0 });
+    expect(legacy.by_type.Unused).toBeUndefined();
+
+    // The Rare mean is taken over all iterations, not just the ones that saw a request
+    // (with seed 5 a single iteration requests one)
+    expect(legacy.by_type.Rare.requested).toMatchObject({ count: 1, mean: 1 });
+    expect(mc.by_type.Rare.requested).toMatchObject({ count: 10, mean: 0.1 });
+  }, 120000);
+
+  test('aggregates standby reservation usage per unit and resource', async () => {
+    const scenario = {
+      ...basicScenario,
+      mission_types: [...basicScenario.mission_types, { ...basicScenario.mission_types[0], name: 'Alert' }],
+      demand: [...basicScenario.demand, { type: 'deterministic', mission_type: 'Alert', every_hours: 12, start_at_hours: 1 }],
+      unit_policy: {
+        mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 },
+        reservations: { 'HMLA-167': { aircraft: 1, for_mission_types: ['Alert'] } }
+      }
+    };
+    const mc = await runMonteCarlo(scenario, { state: mockState, iterations: 3, maxConcurrent: 2 });
+
+    const aircraft = mc.reservations['HMLA-167'].aircraft;
+    expect(mc.reservations['HMLA-167'].for_mission_types).toEqual(['Alert']);
+    expect(aircraft.reserved).toBe(1);
+    // Each started alert takes the reserved aircraft (the crew is unreserved, so some alerts still wait on the SO)
+    expect(aircraft.allocations).toMatchObject({ count: 3, mean: mc.by_type.Alert.started.mean });
+    expect(aircraft.allocations.mean).toBeGreaterThan(0);
+    expect(aircraft.busy_hours.mean).toBeGreaterThan(0);
+  }, 120000);
+
+  test('aggregates the peak sliding-window sortie count', async () => {
+    const single = await runSimulation(basicScenario, { state: mockState, peakWindowHours: 12 });
+    const mc = await runMonteCarlo(basicScenario, { state: mockState, iterations: 3, maxConcurrent: 2, peakWindowHours: 12 });
+
+    expect(mc.peak_window.window_hours).toBe(12);
+    expect(mc.peak_window.max_starts).toMatchObject({ count: 3, mean: single.peak_window.max_starts, stddev: 0 });
+    expect(mc.peak_window.by_unit['HMLA-167'].max_starts.mean).toBe(single.peak_window.by_unit['HMLA-167'].max_starts);
+  }, 120000);
+
+  test('input uncertainty draws override values per iteration and records them', async () => {
+    // Hourly demand on HMLA-167 only: completions depend on nothing but the drawn fleet size
+    const scenario = {
+      ...basicScenario,
+      horizon_hours: 12,
+      demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 1, start_at_hours: 0 }],
+      unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } }
+    };
+    const overrides = { units: { 'HMLA-167': { aircraft: 1, pilot: 10, so: 10, payload_by_type: { 'SkyTower II': 10 } } } };
+    const inputUncertainty = [{ path: 'overrides.units.HMLA-167.aircraft', dist: { type: 'triangular', a: 1, m: 2, b: 4 } }];
+    const options = { state: mockState, overrides, iterations: 8, maxConcurrent: 4, seed: 5, keepIterations: true, inputUncertainty };
+
+    const mc = await runMonteCarlo(scenario, options);
+    const drawn = mc.iterations.map(it => it.inputs['overrides.units.HMLA-167.aircraft']);
+    expect(drawn.every(n => Number.isInteger(n) && n >= 1 && n <= 3)).toBe(true);
+    expect(new Set(drawn).size).toBeGreaterThan(1);
+    expect(mc.input_uncertainty['overrides.units.HMLA-167.aircraft'].count).toBe(8);
+
+    // Same draw, same outcome; more aircraft never completes fewer missions
+    const byDraw = new Map();
+    mc.iterations.forEach((it, i) => byDraw.set(drawn[i], [...(byDraw.get(drawn[i]) || []), it.missions.completed]));
+    for (const completed of byDraw.values()) expect(new Set(completed).size).toBe(1);
+    const ordered = [...byDraw.entries()].sort((a, b) => a[0] - b[0]).map(([, c]) => c[0]);
+    expect(ordered).toEqual([...ordered].sort((a, b) => a - b));
+
+    // Draws come from the iteration seed, so they repeat across runs
+    const again = await runMonteCarlo(scenario, { ...options, maxConcurrent: 2 });
+    expect(again.iterations.map(it => it.inputs)).toEqual(mc.iterations.map(it => it.inputs));
+
+    await expect(runMonteCarlo(scenario, { ...options, inputUncertainty: [{ path: 'overrides.units.HMLA-167.name', dist: { type: 'deterministic', value: 1 } }] }))
+      .rejects.toThrow('not a numeric override field');
+  }, 60000);
+
+  test('Latin hypercube input sampling uses every stratum exactly once', async () => {
+    const n = 20;
+    const dist = { type: 'triangular', a: 0, m: 200, b: 400 };
+    const compiled = compileInputUncertainty([
+      { path: 'overrides.units.HMLA-167.aircraft', dist },
+      { path: 'overrides.units.HMLA-167.pilot', dist: { type: 'lognormal', mu: 4, sigma: 0.5 } }
+    ]);
+    const design = latinHypercube(compiled, n, 11);
+    for (const c of compiled) {
+      expect([...design.strata[c.key]].sort((a, b) => a - b)).toEqual([...Array(n).keys()]);
+    }
+    expect(design.strata[compiled[0].key]).not.toEqual(design.strata[compiled[1].key]);
+
+    // Each draw falls inside its iteration's stratum (floored to a count)
+    for (let i = 0; i < n; i++) {
+      const drawn = sampleInputs(compiled, deriveSeed(11, i), design, i)[compiled[0].key];
+      const k = design.strata[compiled[0].key][i];
+      expect(drawn).toBeGreaterThanOrEqual(Math.floor(distQuantile(dist, k / n)));
+      expect(drawn).toBeLessThanOrEqual(distQuantile(dist, (k + 1) / n));
+    }
+
+    const scenario = { ...basicScenario, horizon_hours: 12 };
+    const options = {
+      state: mockState, iterations: 8, maxConcurrent: 4, seed: 5, keepIterations: true, inputSampling: 'lhs',
+      inputUncertainty: [{ path: 'overrides.units.HMLA-167.aircraft', dist: { type: 'triangular', a: 0, m: 4, b: 8 } }]
+    };
+    const mc = await runMonteCarlo(scenario, options);
+    expect(mc.input_sampling).toBe('lhs');
+    const again = await runMonteCarlo(scenario, { ...options, maxConcurrent: 2 });
+    expect(again.iterations.map(it => it.inputs)).toEqual(mc.iterations.map(it => it.inputs));
+    const random = await runMonteCarlo(scenario, { ...options, inputSampling: 'random' });
+    expect(random.iterations.map(it => it.inputs)).not.toEqual(mc.iterations.map(it => it.inputs));
+
+    await expect(runMonteCarlo(scenario, { ...options, inputSampling: 'sobol' })).rejects.toThrow('inputSampling must be one of');
+  }, 60000);
+
+  test('side-stream seeds never equal an iteration seed', () => {
+    // Latin hypercube shuffle (0x4C48) and keepSampleIterations pick (0x5349) streams
+    const streams = [0x4C48, 0x5349];
+    for (const base of [0, 1, 7, 42, 0x4C48, 2 ** 31 - 1]) {
+      const streamSeeds = new Set(streams.map(stream => deriveStreamSeed(base, stream)));
+      expect(streamSeeds.size).toBe(streams.length);
+      for (let i = 0; i < 60000; i++) expect(streamSeeds.has(deriveSeed(base, i))).toBe(false);
+    }
+  });
+
+  test('input/output analysis reports correlation and slope over iterations', async () => {
+    const inputs = [{ key: 'overrides.units.A.aircraft' }];
+    const pairs = compileInputOutputAnalysis([{ input: 'overrides.units.A.aircraft', output: 'missions.completed' }], inputs);
+    const iters = ys => [1, 2, 3, 4, 5].map((x, i) => ({ sampled_inputs: { 'overrides.units.A.aircraft': x }, missions: { completed: ys[i] } }));
+
+    // Linear: exact fit
+    const linear = inputOutputAnalysis(pairs, iters([3, 5, 7, 9, 11]))['overrides.units.A.aircraft ~ missions.completed'];
+    expect(linear).toMatchObject({ count: 5, pearson: 1, spearman: 1, slope: 2, intercept: 1 });
+
+    // Monotonic but non-linear: ranks agree perfectly, Pearson does not
+    const cubic = inputOutputAnalysis(pairs, iters([1, 8, 27, 64, 125]))['overrides.units.A.aircraft ~ missions.completed'];
+    expect(cubic.spearman).toBe(1);
+    expect(cubic.pearson).toBeGreaterThan(0.9);
+    expect(cubic.pearson).toBeLessThan(1);
+
+    // Decreasing with ties; a constant output has no defined correlation
+    expect(ranks([10, 20, 20, 5])).toEqual([2, 3.5, 3.5, 1]);
+    expect(inputOutputAnalysis(pairs, iters([5, 4, 4, 2, 1]))['overrides.units.A.aircraft ~ missions.completed'].spearman).toBeLessThan(-0.9);
+    expect(inputOutputAnalysis(pairs, iters([4, 4, 4, 4, 4]))['overrides.units.A.aircraft ~ missions.completed'])
+      .toMatchObject({ pearson: null, spearman: null, slope: 0 });
+
+    expect(() => compileInputOutputAnalysis([{ input: 'overrides.units.B.aircraft', output: 'missions.completed' }], inputs))
+      .toThrow('is not an inputUncertainty path');
+    expect(() => compileInputOutputAnalysis([{ input: 'overrides.units.A.aircraft', output: 'missions.flown' }], inputs))
+      .toThrow("unknown metric path 'missions.flown'");
+
+    // End to end: more drawn aircraft, more completions
+    const scenario = {
+      ...basicScenario,
+      horizon_hours: 12,
+      demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 1, start_at_hours: 0 }],
+      unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } }
+    };
+    const mc = await runMonteCarlo(scenario, {
+      state: mockState,
+      overrides: { units: { 'HMLA-167': { aircraft: 1, pilot: 10, so: 10, payload_by_type: { 'SkyTower II': 10 } } } },
+      iterations: 8,
+      maxConcurrent: 4,
+      seed: 5,
+      inputUncertainty: [{ path: 'overrides.units.HMLA-167.aircraft', dist: { type: 'triangular', a: 1, m: 2, b: 4 } }],
+      inputOutputAnalysis: [{ input: 'overrides.units.HMLA-167.aircraft', output: 'missions.completed' }]
+    });
+    const analysis = mc.input_output_analysis['overrides.units.HMLA-167.aircraft ~ missions.completed'];
+    expect(analysis.count).toBe(8);
+    expect(analysis.spearman).toBeGreaterThan(0.9);
+    expect(analysis.slope).toBeGreaterThan(0);
+  }, 60000);
+
+  test('sensitivity sweeps one factor at a time and ranks factors by swing', async () => {
+    const scenario = {
+      ...basicScenario,
+      horizon_hours: 12,
+      demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 1, start_at_hours: 0 }],
+      unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } }
+    };
+    const overrides = { units: { 'HMLA-167': { aircraft: 2, pilot: 10, so: 10, payload_by_type: { 'SkyTower II': 10 } } } };
+    const options = { state: mockState, overrides, iterations: 2, maxConcurrent: 2, seed: 3 };
+    const factors = [
+      { name: 'flight time', path: 'scenario.mission_types.ISR.flight_time.value_hours', low: 1.5, high: 2.5 },
+      { path: 'overrides.units.HMLA-167.aircraft', low: 1, high: 3 }
+    ];
+
+    const tornado = await runSensitivity(scenario, options, factors);
+    const direct = await runMonteCarlo(scenario, options);
+    expect(tornado.baseline).toBe(direct.missions.completed.mean);
+    expect(tornado.seed).toBe(3);
+    expect(tornado.factors.map(f => f.name)).toEqual(['overrides.units.HMLA-167.aircraft', 'flight time']);
+
+    const aircraft = tornado.factors[0];
+    expect(aircraft).toMatchObject({ low: 1, base: 2, high: 3 });
+    expect(aircraft.low_delta).toBeLessThan(0);
+    expect(aircraft.high_delta).toBeGreaterThan(0);
+    expect(aircraft.swing).toBe(aircraft.high_value - aircraft.low_value);
+    // Base comes from the scenario when not given
+    expect(tornado.factors[1].base).toBe(2);
+    expect(tornado.factors[0].swing).toBeGreaterThanOrEqual(tornado.factors[1].swing);
+
+    await expect(runSensitivity(scenario, options, [{ path: 'scenario.mission_types.Strike.priority', low: 1, high: 2 }]))
+      .rejects.toThrow('unknown scenario path');
+    await expect(runSensitivity(scenario, options, [{ path: 'overrides.units.HMLA-167.name', low: 1, high: 2 }]))
+      .rejects.toThrow('not a numeric override field');
+    await expect(runSensitivity(scenario, { ...options, metric: 'missions' }, factors))
+      .rejects.toThrow("metric 'missions' is not an aggregated statistic");
+  }, 120000);
+
+  test('iteration timeout aborts stragglers and records them without failing the run', async () => {
+    const quick = await runMonteCarlo(basicScenario, { state: mockState, iterations: 3, maxConcurrent: 3, iterationTimeoutMs: 30000 });
+    expect(quick.meta.iteration_wall_ms.count).toBe(3);
+    expect(quick.meta.iteration_wall_ms.p99).toBeLessThanOrEqual(quick.meta.iteration_wall_ms.max);
+    expect(quick.meta.timed_out).toBe(0);
+
+    // 4 million demand events: far past a 300ms budget
+    const heavy = {
+      ...basicScenario,
+      horizon_hours: 200000,
+      demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 0.05, start_at_hours: 0 }]
+    };
+    const mc = await runMonteCarlo(heavy, { state: mockState, iterations: 2, maxConcurrent: 2, iterationTimeoutMs: 300, maxIterationErrorFraction: 1 });
+    expect(mc.iterations_failed).toBe(2);
+    expect(mc.iteration_errors).toEqual([{ message: 'iteration timed out after 300ms', count: 2, first_iteration: 0 }]);
+    expect(mc.meta).toEqual({ iteration_wall_ms: null, stragglers: [], timed_out: 2 });
+
+    // Timeouts still count toward maxIterationErrorFraction
+    await expect(runMonteCarlo(heavy, { state: mockState, iterations: 2, maxConcurrent: 2, iterationTimeoutMs: 300 }))
+      .rejects.toThrow('more than maxIterationErrorFraction');
+    await expect(runMonteCarlo(basicScenario, { state: mockState, iterations: 1, iterationTimeoutMs: -1 }))
+      .rejects.toThrow('iterationTimeoutMs must be a positive number');
+  }, 120000);
+
+  test('expected_value run lands inside the Monte Carlo confidence interval', async () => {
+    const scenario = {
+      ...basicScenario,
+      horizon_hours: 240,
+      mission_types: [{ ...basicScenario.mission_types[0], flight_time: { type: 'triangular', a: 1, m: 2, b: 4, transit_in_hours: 0.5, transit_out_hours: 0.5 } }],
+      demand: [{ type: 'poisson', mission_type: 'ISR', rate_per_hour: 0.25 }]
+    };
+    // Ample resources, so completions follow demand
+    const unit = { aircraft: 10, pilot: 10, so: 10, payload_by_type: { 'SkyTower II': 10 } };
+    const overrides = { units: { 'HMLA-167': unit, 'HMLA-267': unit } };
+
+    const expected = await runSimulation(scenario, { state: mockState, overrides, mode: 'expected_value', logLevel: 'silent' });
+    const mc = await runMonteCarlo(scenario, { state: mockState, overrides, iterations: 40, maxConcurrent: 4, seed: 1 });
+    const { mean, stddev, count } = mc.missions.completed;
+    const half = 1.96 * stddev / Math.sqrt(count);
+    expect(expected.missions.completed).toBeGreaterThanOrEqual(mean - half);
+    expect(expected.missions.completed).toBeLessThanOrEqual(mean + half);
+  }, 120000);
+
+  test('rejection density is averaged bucket by bucket across iterations', async () => {
+    const scenario = {
+      ...basicScenario,
+      horizon_hours: 12,
+      demand: [
+        { mission_type: 'ISR', type: 'deterministic', every_hours: 0.5 },
+        { mission_type: 'ISR', type: 'poisson', rate_per_hour: 1 }
+      ],
+      unit_policy: { mission_split: { 'HMLA-167': 1, 'HMLA-267': 0 } }
+    };
+    // HMLA-267's aircraft keeps ISR feasible, so every demand is an aircraft rejection at HMLA-167
+    const mc = await runMonteCarlo(scenario, {
+      state: mockState, iterations: 6, seed: 9, rejectionDensityBucketHours: 2,
+      overrides: { units: { 'HMLA-167': { aircraft: 0 }, 'HMLA-267': { aircraft: 1 } } }
+    });
+    expect(mc.rejection_density.bucket_hours).toBe(2);
+    const buckets = mc.rejection_density.buckets.aircraft;
+    expect(buckets).toHaveLength(6);
+    // 4 deterministic rejections per bucket plus the Poisson ones (mean 2 per bucket)
+    buckets.forEach(mean => expect(mean).toBeGreaterThanOrEqual(4));
+    const total = buckets.reduce((a, b) => a + b, 0);
+    expect(total).toBeCloseTo(mc.rejections.aircraft.mean, 3);
+  }, 60000);
+
+  test('concurrent runs queue for the run slot or are rejected as busy', async () => {
+    const events = [];
+    const options = label => ({
+      state: mockState, iterations: 4, maxConcurrent: 2, seed: 1,
+      onProgress: ev => events.push({ label, ...ev })
+    });
+    const first = runMonteCarlo(basicScenario, options('first'));
+    expect(getEngineStatus()).toEqual({ running: 1, queued: 0, max_concurrent_runs: 1 });
+
+    const busy = await runMonteCarlo(basicScenario, { ...options('rejected'), onBusy: 'reject' }).catch(error => error);
+    expect(busy.code).toBe('ENGINE_BUSY');
+    expect(busy.message).toContain('Monte Carlo engine is busy (1 running, 0 queued');
+    const second = runMonteCarlo(basicScenario, options('second'));
+    expect(getEngineStatus()).toEqual({ running: 1, queued: 1, max_concurrent_runs: 1 });
+
+    const [a, b] = await Promise.all([first, second]);
+    expect(b.missions).toEqual(a.missions);
+    expect(getEngineStatus()).toEqual({ running: 0, queued: 0, max_concurrent_runs: 1 });
+
+    // The queued run waited, then ran only after the first run's iterations were done
+    expect(events.filter(ev => ev.label === 'rejected')).toEqual([]);
+    expect(events.find(ev => ev.label === 'second')).toEqual({ label: 'second', type: 'queued', position: 1 });
+    const lastFirst = events.map(ev => ev.label).lastIndexOf('first');
+    const firstSecondBatch = events.findIndex(ev => ev.label === 'second' && ev.type === 'iterations');
+    expect(lastFirst).toBeLessThan(firstSecondBatch);
+    expect(events.filter(ev => ev.label === 'first').pop()).toEqual({ label: 'first', type: 'iterations', finished: 4, failed: 0, total: 4 });
+
+    await expect(runMonteCarlo(basicScenario, { ...options('bad'), onBusy: 'wait' })).rejects.toThrow('onBusy must be one of queue, reject');
+  }, 60000);
+
+  test('a run killed midway resumes from its checkpoint to the uninterrupted result', async () => {
+    const fs = require('fs');
+    const os = require('os');
+    const path = require('path');
+    const file = path.join(fs.mkdtempSync(path.join(os.tmpdir(), 'mc-checkpoint-')), 'run.json');
+    const scenario = { ...basicScenario, demand: [{ mission_type: 'ISR', type: 'poisson', rate_per_hour: 0.5 }] };
+    const options = { state: mockState, iterations: 8, maxConcurrent: 2, seed: 77, keepIterations: true };
+    const withoutMeta = ({ meta, ...rest }) => rest;
+
+    const uninterrupted = await runMonteCarlo(scenario, options);
+
+    // Die after the third batch; the last checkpoint holds the first 4 iterations
+    const killed = await runMonteCarlo(scenario, {
+      ...options,
+      checkpoint: { path: file, everyIterations: 4 },
+      onProgress: ev => { if (ev.finished === 6) throw new Error('killed'); }
+    }).catch(error => error);
+    expect(killed.message).toBe('killed');
+    expect(JSON.parse(fs.readFileSync(file, 'utf8')).next_index).toBe(4);
+
+    const ran = [];
+    const resumed = await runMonteCarlo(scenario, {
+      ...options, maxConcurrent: 3, resumeFrom: file, onProgress: ev => ran.push(ev.finished)
+    });
+    expect(ran).toEqual([7, 8]);
+    expect(withoutMeta(resumed)).toEqual(withoutMeta(uninterrupted));
+
+    await expect(runMonteCarlo(scenario, { ...options, seed: 78, resumeFrom: file }))
+      .rejects.toThrow('resumeFrom: checkpoint was written by a run with a different scenario');
+    await expect(runMonteCarlo(scenario, { ...options, checkpoint: { path: file } }))
+      .rejects.toThrow('checkpoint.everyIterations must be a positive integer');
+  }, 60000);
+
+  test('rate_uncertainty gives each iteration its own demand rate, recorded per iteration', async () => {
+    const scenario = {
+      ...basicScenario,
+      demand: [{ name: 'tasking', type: 'poisson', mission_type: 'ISR', rate_per_hour: 0.25, rate_uncertainty: { type: 'triangular', a: 0.8, m: 1, b: 1.2 } }]
+    };
+    const mc = await runMonteCarlo(scenario, { state: mockState, iterations: 8, seed: 21, keepIterations: true });
+    const rates = mc.iterations.map(iter => iter.demand_rates.tasking);
+    rates.forEach(rate => {
+      expect(rate).toBeGreaterThanOrEqual(0.2);
+      expect(rate).toBeLessThanOrEqual(0.3);
+    });
+    expect(new Set(rates).size).toBe(rates.length);
+    expect(mc.demand_rates.tasking.min).toBe(Math.min(...rates));
+    expect(mc.demand_rates.tasking.max).toBe(Math.max(...rates));
+  }, 60000);
+
+  test('custom extractors summarize per-iteration metrics from functions and expressions', async () => {
+    const options = { state: mockState, iterations: 4, seed: 5 };
+    const mc = await runMonteCarloWithExtractors(basicScenario, options, {
+      completion: 'missions.completed / missions.requested',
+      hmla167_aircraft: "utilization['HMLA-167'].aircraft",
+      long_flights: result => result.timeline.filter(e => e.type === 'mission' && e.finish_time - e.demand_time > 4).length,
+      undefined_ratio: 'missions.rejected / missions.rejected'
+    });
+    const plain = await runMonteCarlo(basicScenario, options);
+    // Deterministic scenario: every iteration gives the same values
+    expect(mc.custom.completion.mean).toBeCloseTo(plain.missions.completed.mean / plain.missions.requested.mean, 2);
+    expect(mc.custom.hmla167_aircraft.mean).toBeCloseTo(plain.utilization['HMLA-167'].aircraft.mean, 3);
+    expect(mc.custom.long_flights.mean).toBe(plain.missions.started.mean);
+    // 0 / 0 in every iteration leaves nothing to summarize
+    expect(mc.custom.undefined_ratio).toBeNull();
+    expect(plain.custom).toBeUndefined();
+
+    await expect(runMonteCarlo(basicScenario, { ...options, extractors: { bad: 'missions.flown' } }))
+      .rejects.toThrow("extractors['bad']: unknown metric path 'missions.flown'");
+    await expect(runMonteCarlo(basicScenario, { ...options, extractors: { bad: 3 } }))
+      .rejects.toThrow('must be a function or a metric expression string');
+    await expect(runMonteCarlo(basicScenario, { ...options, extractors: { bad: () => { throw new Error('boom'); } } }))
+      .rejects.toThrow("extractors['bad'] failed: boom");
+  }, 60000);
+
+  test('demand bootstrap replays one library day per iteration, the same day for the same seed', async () => {
+    const day = (...at_hours) => [{ name: 'actual', mission_type: 'ISR', type: 'scheduled', at_hours }];
+    const library = [day(1), day(1, 9), day(1, 9, 17)];
+    const options = {
+      state: mockState,
+      iterations: 12,
+      seed: 31,
+      keepIterations: true,
+      demandBootstrap: { library },
+      extractors: { replay_mismatch: r => r.missions.requested - library[r.bootstrap_index][0].at_hours.length }
+    };
+
+    const mc = await runMonteCarlo(basicScenario, options);
+    expect(mc.custom.replay_mismatch).toMatchObject({ min: 0, max: 0 });
+    expect(mc.demand_bootstrap.library_size).toBe(3);
+    expect(mc.demand_bootstrap.seed_linked).toBe(true);
+    expect(mc.demand_bootstrap.counts.reduce((a, b) => a + b, 0)).toBe(12);
+    const picks = mc.iterations.map(iter => iter.bootstrap_index);
+    expect(new Set(picks).size).toBeGreaterThan(1);
+
+    // A what-if run with the same seed sees the same day in every iteration
+    const grounded = await runMonteCarlo(basicScenario, { ...options, overrides: { units: { 'HMLA-267': { aircraft: 0 } } } });
+    expect(grounded.iterations.map(iter => iter.bootstrap_index)).toEqual(picks);
+
+    const badDay = await runMonteCarlo(basicScenario, { ...options, demandBootstrap: { library: [day(1), [{ mission_type: 'CAS', type: 'scheduled', at_hours: [2] }]] } })
+      .catch(e => e);
+    expect(badDay.message).toContain('demandBootstrap.library[1]');
+    expect(badDay.message).toContain('demand[0].mission_type');
+  });
+
+  test('an unlinked demand bootstrap still reproduces under the same seed', async () => {
+    const day = (...at_hours) => [{ name: 'actual', mission_type: 'ISR', type: 'scheduled', at_hours }];
+    const library = [day(1), day(1, 9), day(1, 9, 17), day(3), day(3, 11)];
+    const options = { state: mockState, iterations: 16, seed: 31, keepIterations: true, demandBootstrap: { library, seedLinked: false } };
+
+    const first = await runMonteCarlo(basicScenario, options);
+    const second = await runMonteCarlo(basicScenario, options);
+    expect(first.demand_bootstrap.seed_linked).toBe(false);
+    const picks = first.iterations.map(iter => iter.bootstrap_index);
+    expect(second.iterations.map(iter => iter.bootstrap_index)).toEqual(picks);
+
+    // The unlinked picks do not follow the iteration seeds' bootstrap sub-stream
+    const linked = await runMonteCarlo(basicScenario, { ...options, demandBootstrap: { library } });
+    expect(linked.iterations.map(iter => iter.bootstrap_index)).not.toEqual(picks);
+  });
+
+  test('hourly profile averages arrivals, starts and rejections hour by hour', async () => {
+    const scenario = {
+      ...basicScenario,
+      horizon_hours: 5.5,
+      demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 1, start_at_hours: 0 }]
+    };
+    const des = await runSimulation(scenario, { state: mockState, hourlyProfile: true, logLevel: 'silent' });
+    // HMLA-167's only SO flies from t=0 to 4.5, so its demands at 1, 2 and 4 are rejected;
+    // HMLA-267 flies at 3 and still holds its one payload at 5
+    expect(des.hourly_profile).toEqual({
+      arrivals: [1, 1, 1, 1, 1, 1],
+      starts: [1, 0, 0, 1, 0, 0],
+      rejections: [0, 1, 1, 0, 1, 1]
+    });
+
+    const mc = await runMonteCarlo(scenario, { state: mockState, iterations: 3, seed: 4, hourlyProfile: true });
+    expect(mc.hourly_profile).toEqual({
+      arrivals_mean: [1, 1, 1, 1, 1, 1],
+      starts_mean: [1, 0, 0, 1, 0, 0],
+      rejections_mean: [0, 1, 1, 0, 1, 1]
+    });
+
+    const plain = await runMonteCarlo(scenario, { state: mockState, iterations: 2, seed: 4 });
+    expect(plain.hourly_profile).toBeUndefined();
+  }, 60000);
+
+  test('keepSampleIterations keeps whole seeded iterations, the same ones whatever the worker count', async () => {
+    const scenario = { ...basicScenario, demand: [{ type: 'poisson', mission_type: 'ISR', rate_per_hour: 0.5 }] };
+    const options = { state: mockState, iterations: 10, seed: 9, keepSampleIterations: 3 };
+    const serial = await runMonteCarlo(scenario, { ...options, maxConcurrent: 1 });
+    const parallel = await runMonteCarlo(scenario, { ...options, maxConcurrent: 4 });
+
+    const picked = serial.sample_iterations.map(s => s.iteration);
+    expect(picked).toHaveLength(3);
+    expect(picked).toEqual([...picked].sort((a, b) => a - b));
+    expect(parallel.sample_iterations.map(s => s.iteration)).toEqual(picked);
+    expect(serial.iterations).toBe(10);
+
+    // Each kept iteration is the full DES result for its sub-seed
+    for (const sample of serial.sample_iterations) {
+      expect(sample.seed).toBe(deriveSeed(9, sample.iteration));
+      const des = await runSimulation(scenario, { state: mockState, seed: sample.seed, logLevel: 'silent' });
+      expect(sample.results.missions).toEqual(des.missions);
+      expect(sample.results.timeline).toEqual(des.timeline);
+    }
+
+    const all = await runMonteCarlo(scenario, { ...options, iterations: 2, keepSampleIterations: 5 });
+    expect(all.sample_iterations.map(s => s.iteration)).toEqual([0, 1]);
+    const none = await runMonteCarlo(scenario, { ...options, iterations: 2, keepSampleIterations: undefined });
+    expect(none.sample_iterations).toBeUndefined();
+    await expect(runMonteCarlo(scenario, { ...options, keepSampleIterations: -1 }))
+      .rejects.toThrow('keepSampleIterations must be a non-negative integer');
+  }, 60000);
+
+  test('extremes name the iterations with the lowest and highest metric values', async () => {
+    const scenario = { ...basicScenario, demand: [{ type: 'poisson', mission_type: 'ISR', rate_per_hour: 0.5 }] };
+    const mc = await runMonteCarlo(scenario, {
+      state: mockState, iterations: 8, maxConcurrent: 4, seed: 31, keepIterations: true, keepSampleIterations: 8
+    });
+
+    const completed = mc.iterations.map(iter => iter.missions.completed);
+    const rejected = mc.sample_iterations.map(s => s.results.missions.rejected);
+    const first = (values, target) => values.indexOf(target);
+    const low = mc.extremes['missions.completed'].min;
+    expect(low.value).toBe(Math.min(...completed));
+    expect(low.iteration).toBe(first(completed, low.value));
+    expect(low.seed).toBe(deriveSeed(31, low.iteration));
+    expect(mc.extremes['missions.completed'].max.value).toBe(Math.max(...completed));
+    expect(mc.extremes['missions.rejected'].max.value).toBe(Math.max(...rejected));
+    expect(mc.extremes['missions.rejected'].max.iteration).toBe(first(rejected, Math.max(...rejected)));
+
+    // The worst iteration re-runs alone from its sub-seed
+    const replay = await runSimulation(scenario, { state: mockState, seed: low.seed, logLevel: 'silent' });
+    expect(replay.missions.completed).toBe(low.value);
+
+    const custom = await runMonteCarlo(scenario, { state: mockState, iterations: 2, seed: 31, extremeMetrics: ['missions.started - missions.completed'] });
+    expect(Object.keys(custom.extremes)).toEqual(['missions.started - missions.completed']);
+    await expect(runMonteCarlo(scenario, { state: mockState, iterations: 2, extremeMetrics: ['missions.nope'] }))
+      .rejects.toThrow("extremeMetrics[0]: unknown metric path 'missions.nope'");
+  }, 60000);
+
+  test('serialized results round-trip like JSON at a fraction of the size', async () => {
+    const scenario = { ...basicScenario, demand: [{ type: 'poisson', mission_type: 'ISR', rate_per_hour: 0.5 }] };
+    const mc = await runMonteCarlo(scenario, { state: mockState, iterations: 4, seed: 31, keepIterations: true });
+
+    const json = JSON.stringify(mc);
+    const bytes = serializeResults(mc);
+    expect(deserializeResults(bytes)).toEqual(JSON.parse(json));
+    expect(bytes.length).toBeLessThan(Buffer.byteLength(json) / 2);
+
+    // JSON semantics for values JSON cannot hold
+    expect(deserializeResults(serializeResults({ a: undefined, b: NaN, c: [undefined, -0, 2 ** 60, 'é'] })))
+      .toEqual({ b: null, c: [null, 0, 2 ** 60, 'é'] });
+
+    // A newer writer's value kind (length-prefixed extension tag) is skipped: { a: 1, b: <ext> } reads as { a: 1 }
+    const known = serializeResults({ a: 1, b: 2 });
+    const future = Buffer.concat([known.subarray(0, known.length - 2), Buffer.from([0x41, 2, 0xff, 0xff])]);
+    expect(deserializeResults(future)).toEqual({ a: 1 });
+
+    expect(() => deserializeResults(Buffer.from('{"a":1}'))).toThrow('bad header');
+    expect(() => deserializeResults(bytes.subarray(0, bytes.length - 3))).toThrow('truncated');
+  }, 60000);
+
+  test('collectTimeline false runs iterations without timelines', async () => {
+    const scenario = { ...basicScenario, demand: [{ type: 'poisson', mission_type: 'ISR', rate_per_hour: 0.5 }] };
+    const full = await runMonteCarlo(scenario, { state: mockState, iterations: 4, seed: 17, keepSampleIterations: 1 });
+    const lean = await runMonteCarlo(scenario, { state: mockState, iterations: 4, seed: 17, keepSampleIterations: 1, collectTimeline: false });
+
+    expect(lean.missions).toEqual(full.missions);
+    expect(lean.by_type).toEqual(full.by_type);
+    expect(full.percentile_timelines).toBeDefined();
+    expect(lean.percentile_timelines).toBeUndefined();
+    expect(lean.sample_iterations[0].results.timeline).toBeUndefined();
+  }, 60000);
+
+  test('stochastic crew absence varies between iterations', async () => {
+    const scenario = {
+      ...basicScenario,
+      horizon_hours: 72,
+      crew_policy: { availability_rate: { pilot: 0.5 }, availability_mode: 'stochastic' }
+    };
+    const mc = await runMonteCarlo(scenario, { state: mockState, iterations: 6, seed: 9 });
+
+    const pilots = mc.crew_availability.units['HMLA-167'].pilot;
+    expect(mc.crew_availability.mode).toBe('stochastic');
+    expect(pilots.rate).toBe(0.5);
+    expect(pilots.headcount.mean).toBe(2);
+    expect(pilots.realized_rate.stddev).toBeGreaterThan(0);
+    expect(mc.crew_availability.units['HMLA-167'].so).toBeUndefined();
+  }, 60000);
+
+  test('stratifyBy reports each iteration regime separately', async () => {
+    // A quiet day and a surge day that swamps both units
+    const quiet = [{ name: 'actual', mission_type: 'ISR', type: 'scheduled', at_hours: [1] }];
+    const surge = [{ name: 'actual', mission_type: 'ISR', type: 'scheduled', at_hours: Array(8).fill(1) }];
+    const options = { state: mockState, iterations: 12, seed: 31, demandBootstrap: { library: [quiet, surge] } };
+    const mc = await runMonteCarlo(basicScenario, { ...options, stratifyBy: 'bootstrap_index' });
+
+    expect(mc.stratified_by).toBe('bootstrap_index');
+    expect(Object.keys(mc.strata)).toEqual(['0', '1']);
+    expect(mc.strata['0'].iterations + mc.strata['1'].iterations).toBe(12);
+    expect(mc.strata['0'].value).toBe(0);
+    expect(mc.strata['0'].missions.requested.mean).toBe(1);
+    expect(mc.strata['0'].missions.rejected.mean).toBe(0);
+    expect(mc.strata['1'].missions.requested.mean).toBe(8);
+    expect(mc.strata['1'].missions.rejected.mean).toBeGreaterThan(0);
+    expect(mc.strata['1'].by_type.ISR.requested.mean).toBe(8);
+
+    // A continuous input draw is cut into quantile buckets
+    const byInput = await runMonteCarlo(basicScenario, {
+      state: mockState,
+      iterations: 8,
+      seed: 5,
+      inputUncertainty: [{ path: 'overrides.units.HMLA-167.aircraft', dist: { type: 'triangular', a: 0, m: 4, b: 8 } }],
+      stratifyBy: 'overrides.units.HMLA-167.aircraft',
+      stratifyBuckets: 2
+    });
+    const { q1, q2 } = byInput.strata;
+    expect(q1.iterations + q2.iterations).toBe(8);
+    expect(q1.range[1]).toBeLessThanOrEqual(q2.range[0]);
+
+    await expect(runMonteCarlo(basicScenario, { state: mockState, iterations: 2, stratifyBy: 'bootstrap_index' }))
+      .rejects.toThrow('stratifyBy bootstrap_index requires demandBootstrap');
+    await expect(runMonteCarlo(basicScenario, { state: mockState, iterations: 2, stratifyBy: 'missions.flown' }))
+      .rejects.toThrow("stratifyBy: unknown metric path 'missions.flown'");
+  }, 60000);
+
+  test('resolved_config reports the run options once and iterations carry none', async () => {
+    const results = await runMonteCarlo(basicScenario, { state: mockState, iterations: 3, seed: 21, keepSampleIterations: 1 });
+    expect(results.resolved_config.options).toMatchObject({
+      iterations: 3, seed: 21, algorithm: 'PERT', percentile_method: 'nearest_rank', simulate_settings: null
+    });
+    expect(results.resolved_config.scenario.horizon_hours).toBe(24);
+    expect(results.resolved_config.iteration_settings).toMatchObject({ collectTimeline: true, echoConfig: false });
+    expect(results.resolved_config.iteration_settings.state).toBeUndefined();
+    expect(results.sample_iterations[0].results.resolved_config).toBeUndefined();
+
+    const lean = await runMonteCarlo(basicScenario, { state: mockState, iterations: 2, seed: 21, echoConfig: false });
+    expect(lean.resolved_config).toBeUndefined();
+    await expect(runMonteCarlo(basicScenario, { state: mockState, iterations: 2, echoConfig: 1 }))
+      .rejects.toThrow('echoConfig must be a boolean');
+  }, 60000);
+
+  test('maxOutputBytes drops per-iteration data from an oversized aggregate', async () => {
+    const options = { state: mockState, iterations: 4, seed: 8, keepIterations: true, keepSampleIterations: 2 };
+    const full = await runMonteCarlo(basicScenario, options);
+    const capped = await runMonteCarlo(basicScenario, { ...options, maxOutputBytes: JSON.stringify(full).length - 100 });
+
+    expect(full.truncated).toBeUndefined();
+    expect(capped.truncated[0]).toBe('iterations');
+    expect(capped.iterations).toBeUndefined();
+    expect(capped.missions).toEqual(full.missions);
+    expect(capped.meta.output_size.estimated_bytes).toBeGreaterThan(capped.meta.output_size.max_bytes);
+    expect(capped.meta.output_size.actual_bytes).toBeLessThanOrEqual(capped.meta.output_size.max_bytes);
+    await expect(runMonteCarlo(basicScenario, { ...options, maxOutputBytes: 'big' }))
+      .rejects.toThrow('maxOutputBytes must be a positive number or null');
+  }, 60000);
+
+  test('weighted_completion_score is summarized over the iterations', async () => {
+    const scenario = {
+      ...basicScenario,
+      mission_types: [{ ...basicScenario.mission_types[0], priority: 2 }]
+    };
+    const single = await runSimulation(scenario, { state: mockState, priorityWeights: { 2: 4 } });
+    const results = await runMonteCarlo(scenario, {
+      state: mockState, iterations: 3, seed: 5, priorityWeights: { 2: 4 },
+      thresholds: [{ metric: 'weighted_completion_score', op: '>=', value: 0.5 }]
+    });
+
+    // One mission type, so its weight cancels: the score is the completion fraction
+    expect(single.weighted_completion_score).toBe(Number((single.missions.completed / single.missions.requested).toFixed(3)));
+    expect(results.weighted_completion_score).toMatchObject({ count: 3, min: single.weighted_completion_score, max: single.weighted_completion_score });
+    expect(results.threshold_probabilities['weighted_completion_score >= 0.5']).toBe(single.weighted_completion_score >= 0.5 ? 1 : 0);
+    await expect(runMonteCarlo(scenario, { state: mockState, iterations: 2, priorityWeights: { 2: -1 } }))
+      .rejects.toThrow("priorityWeights['2'] must be a non-negative number");
+  }, 60000);
+
+  test('infeasible demands are summarized apart from rejections', async () => {
+    const results = await runMonteCarlo(basicScenario, {
+      state: mockState, iterations: 3, seed: 2,
+      overrides: { units: { 'HMLA-167': { aircraft: 0 }, 'HMLA-267': { aircraft: 0 } } },
+      thresholds: [{ metric: 'missions.infeasible', op: '>', value: 0 }]
+    });
+    expect(results.missions.infeasible).toMatchObject({ count: 3, min: 3, max: 3 });
+    expect(results.missions.rejected.max).toBe(0);
+    expect(results.by_type.ISR.infeasible).toMatchObject({ count: 3, mean: 3 });
+    expect(results.infeasible_by_type).toEqual({ ISR: { iterations: 3, missions: expect.objectContaining({ mean: 3 }) } });
+    expect(results.threshold_probabilities['missions.infeasible > 0']).toBe(1);
+
+    const normal = await runMonteCarlo(basicScenario, { state: mockState, iterations: 2, seed: 2 });
+    expect(normal.infeasible_by_type).toBeUndefined();
+    expect(normal.by_type.ISR.infeasible).toBeUndefined();
+  }, 60000);
+
+  test('runMonteCarloSequence aggregates whole sequences and per-day counters', async () => {
+    const day = { ...basicScenario, demand: [{ type: 'deterministic', mission_type: 'ISR', every_hours: 8, start_at_hours: 6 }] };
+    const results = await runMonteCarloSequence([day, day], { state: mockState, iterations: 3, maxConcurrent: 2, seed: 9 });
+
+    expect(results.iterations).toBe(3);
+    expect(results.days).toHaveLength(2);
+    expect(results.days[1].missions.completed.mean).toBe(2);
+    // Day 1's late sortie finishes on day 2 as a carried hold
+    expect(results.summary.missions.completed.mean).toBe(5);
+    expect(results.summary.missions.in_progress_at_horizon.mean).toBe(1);
+    expect(results.summary.by_type.ISR.requested.mean).toBe(6);
+    expect(results.summary.flight_hours['HMLA-167'].mean).toBe(12);
+
+    await expect(runMonteCarloSequence([], { state: mockState })).rejects.toThrow('non-empty array');
+  }, 60000);
+
+  test('aggregates completion quality when the scenario sets quality penalties', async () => {
+    const mission = { ...basicScenario.mission_types[0], crew_flex: { allowed_compositions: [{ pilot: 1, so: 2 }, { pilot: 2, so: 0 }] } };
+    const scenario = { ...basicScenario, mission_types: [mission], quality_penalties: { crew_flex: 0.25 } };
+    const results = await runMonteCarlo(scenario, {
+      state: mockState, iterations: 3, maxConcurrent: 2, seed: 4,
+      thresholds: [{ metric: 'completion_quality.degraded', op: '>=', value: 2 }]
+    });
+
+    expect(results.completion_quality.mean.mean).toBe(0.83);
+    expect(results.completion_quality.degraded.mean).toBe(2);
+    expect(results.completion_quality.by_type.ISR.degraded.max).toBe(2);
+    expect(results.threshold_probabilities['completion_quality.degraded >= 2']).toBe(1);
+
+    const plain = await runMonteCarlo(basicScenario, { state: mockState, iterations: 2, maxConcurrent: 2, seed: 4 });
+    expect(plain.completion_quality).toBeUndefined();
+  }, 60000);
+
+  test('seeded runs give identical output regardless of worker count', async () => {
+    const stochastic = {
+      ...basicScenario,
+      mission_types: [{ ...basicScenario.mission_types[0], flight_time: { type: 'triangular', a: 1, m: 2, b: 4 }, crew_distribution: 'random' }],
+      demand: [{ type: 'poisson', mission_type: 'ISR', rate_per_hour: 0.5 }]
+    };
+    // PERT-sampled turnaround exercises the seeded main-thread draws too
+    const simulateSettings = [
+      { path: ['processTimes', 'turnaround'], pathString: 'turnaround', defaultValue: 1, min: 0, max: 4, step: 0.25 }
+    ];
+    const run = maxConcurrent => runMonteCarlo(stochastic, {
+      state: mockState, iterations: 6, maxConcurrent, seed: 1234, simulateSettings, keepIterations: true
+    });
+
+    const serial = await run(1);
+    const parallel = await run(4);
+    // Wall-time meta is the one part that is not seeded
+    const seeded = r => JSON.stringify({ ...r, meta: undefined });
+    expect(seeded(parallel)).toBe(seeded(serial));
+    expect(serial.seed).toBe(1234);
+
+    const other = await runMonteCarlo(stochastic, { state: mockState, iterations: 6, maxConcurrent: 4, seed: 99, simulateSettings });
+    expect(JSON.stringify(other.missions)).not.toBe(JSON.stringify(serial.missions));
+  }, 120000);
+
+  test('compareResults ranks movers, flags significant changes and reports added/removed keys', () => {
+    const stats = (mean, stddev = 1, count = 100) => ({ count, mean, p10: mean - 1, p50: mean, p90: mean + 1, stddev });
+    const before = {
+      missions: { completed: stats(50), rejected: stats(10) },
+      by_type: { ISR: { completed: stats(40) }, Strike: { completed: stats(10) } },
+      utilization: { 'HMLA-167': { aircraft: stats(0.5, 0.05) } },
+      threshold_probabilities: { 'missions.completed >= 45': 0.8 },
+      iterations: [{ missions: { completed: 50 } }]
+    };
+    const after = {
+      missions: { completed: stats(50.2), rejected: stats(5) },
+      by_type: { ISR: { completed: stats(45) }, Recon: { completed: stats(3) } },
+      utilization: { 'HMLA-167': { aircraft: stats(0.5, 0.05) } },
+      threshold_probabilities: { 'missions.completed >= 45': 0.95 }
+    };
+
+    const summary = compareResults(before, after, { relativeThreshold: 0.1 });
+
+    expect(summary.metrics_compared).toBe(5);
+    expect(summary.added).toEqual(['by_type.Recon.completed']);
+    expect(summary.removed).toEqual(['by_type.Strike.completed']);
+
+    // Sorted by relative mean change: rejected -50%, threshold +18.75%, ISR +12.5%, completed +0.4%
+    expect(summary.biggest_movers.map(c => c.metric)).toEqual([
+      'missions.rejected',
+      'threshold_probabilities.missions.completed >= 45',
+      'by_type.ISR.completed',
+      'missions.completed'
+    ]);
+    const rejected = summary.biggest_movers[0];
+    expect(rejected.delta).toEqual({ mean: -5, p10: -5, p50: -5, p90: -5 });
+    expect(rejected.relative.mean).toBe(-0.5);
+    expect(rejected.ci_overlap).toBe(false);
+
+    // A 0.4% move whose confidence intervals overlap is not significant
+    const completed = summary.biggest_movers.find(c => c.metric === 'missions.completed');
+    expect(completed.ci_overlap).toBe(true);
+    expect(completed.significant).toBe(false);
+    expect(summary.significant.map(c => c.metric)).toEqual([
+      'missions.rejected',
+      'threshold_probabilities.missions.completed >= 45',
+      'by_type.ISR.completed'
+    ]);
+
+    for (const [options, message] of [
+      [{ percentiles: 'p50' }, 'percentiles must be an array'],
+      [{ percentiles: ['median'] }, 'percentiles must be an array'],
+      [{ relativeThreshold: 'abc' }, 'relativeThreshold must be a non-negative number'],
+      [{ absoluteThreshold: -1 }, 'absoluteThreshold must be a non-negative number'],
+      [{ top: 'x' }, 'top must be a positive integer'],
+      [{ top: -1 }, 'top must be a positive integer']
+    ]) {
+      expect(() => compareResults(before, after, options)).toThrow(message);
+    }
+  });
+});
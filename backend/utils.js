@@ -124,9 +124,9 @@ function logPersonnelAvailability(personnelAvailability, staffingData = null) {
   const intelCrewRestHours = intelConfig.daily_crew_rest_hours || 0;
 
   // Extract work schedules
-  const pilotWorkSchedule = pilotConfig.work_schedule;
-  const soWorkSchedule = soConfig.work_schedule;
-  const intelWorkSchedule = intelConfig.work_schedule;
+  const pilotWorkSchedule = pilotConfig.work_schedule || {};
+  const soWorkSchedule = soConfig.work_schedule || {};
+  const intelWorkSchedule = intelConfig.work_schedule || {};
 
   logWithLocation(`=========================================`);
   logWithLocation(`===== Personnel Availability Config =====`);